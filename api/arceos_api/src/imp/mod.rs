@@ -38,5 +38,8 @@ pub use self::stdio::*;
 pub use self::task::*;
 
 pub use axhal::misc::terminate as ax_terminate;
-pub use axhal::time::{current_time as ax_current_time, TimeValue as AxTimeValue};
+pub use axhal::time::{
+    current_time as ax_current_time, wall_time as ax_wall_time, TimeValue as AxTimeValue,
+};
 pub use axio::PollState as AxPollState;
+pub use axrand::fill_bytes as ax_fill_random;
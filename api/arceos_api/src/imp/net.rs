@@ -2,6 +2,9 @@ use crate::io::AxPollState;
 use axerrno::AxResult;
 use axnet::{UdpSocket, TcpSocket};
 use core::net::{IpAddr, SocketAddr};
+use core::time::Duration;
+
+pub use axnet::UdpRecvBuf as AxUdpRecvBuf;
 
 /// A handle to a TCP socket.
 pub struct AxTcpSocketHandle(TcpSocket);
@@ -64,6 +67,67 @@ pub fn ax_tcp_shutdown(socket: &AxTcpSocketHandle) -> AxResult {
     socket.0.shutdown()
 }
 
+pub fn ax_tcp_nodelay(socket: &AxTcpSocketHandle) -> AxResult<bool> {
+    Ok(socket.0.nodelay())
+}
+
+pub fn ax_tcp_set_nodelay(socket: &AxTcpSocketHandle, nodelay: bool) -> AxResult {
+    socket.0.set_nodelay(nodelay);
+    Ok(())
+}
+
+pub fn ax_tcp_recv_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.recv_timeout())
+}
+
+pub fn ax_tcp_set_recv_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult {
+    socket.0.set_recv_timeout(timeout);
+    Ok(())
+}
+
+pub fn ax_tcp_send_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.send_timeout())
+}
+
+pub fn ax_tcp_set_send_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult {
+    socket.0.set_send_timeout(timeout);
+    Ok(())
+}
+
+pub fn ax_tcp_linger(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.linger())
+}
+
+pub fn ax_tcp_set_linger(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult {
+    socket.0.set_linger(timeout);
+    Ok(())
+}
+
+pub fn ax_tcp_keep_alive(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.keep_alive())
+}
+
+pub fn ax_tcp_set_keep_alive(socket: &AxTcpSocketHandle, interval: Option<Duration>) -> AxResult {
+    socket.0.set_keep_alive(interval);
+    Ok(())
+}
+
+pub fn ax_tcp_recv_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize> {
+    Ok(socket.0.recv_buffer_size())
+}
+
+pub fn ax_tcp_set_recv_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult {
+    socket.0.set_recv_buffer_size(size)
+}
+
+pub fn ax_tcp_send_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize> {
+    Ok(socket.0.send_buffer_size())
+}
+
+pub fn ax_tcp_set_send_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult {
+    socket.0.set_send_buffer_size(size)
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // UDP socket
 ////////////////////////////////////////////////////////////////////////////////
@@ -97,6 +161,10 @@ pub fn ax_udp_peek_from(socket: &AxUdpSocketHandle, buf: &mut [u8]) -> AxResult<
     socket.0.peek_from(buf)
 }
 
+pub fn ax_udp_recv_buf_from(socket: &AxUdpSocketHandle) -> AxResult<(AxUdpRecvBuf, SocketAddr)> {
+    socket.0.recv_buf_from()
+}
+
 pub fn ax_udp_send_to(socket: &AxUdpSocketHandle, buf: &[u8], addr: SocketAddr) -> AxResult<usize> {
     socket.0.send_to(buf, addr)
 }
@@ -113,6 +181,10 @@ pub fn ax_udp_recv(socket: &AxUdpSocketHandle, buf: &mut [u8]) -> AxResult<usize
     socket.0.recv(buf)
 }
 
+pub fn ax_udp_recv_buf(socket: &AxUdpSocketHandle) -> AxResult<AxUdpRecvBuf> {
+    socket.0.recv_buf()
+}
+
 pub fn ax_udp_poll(socket: &AxUdpSocketHandle) -> AxResult<AxPollState> {
     socket.0.poll()
 }
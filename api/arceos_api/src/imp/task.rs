@@ -67,6 +67,29 @@ cfg_task! {
         }
     }
 
+    pub fn ax_spawn_with_affinity<F>(
+        f: F,
+        name: alloc::string::String,
+        stack_size: usize,
+        cpumask: usize,
+    ) -> crate::AxResult<AxTaskHandle>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if cpumask.trailing_zeros() as usize >= axconfig::SMP {
+            return axerrno::ax_err!(InvalidInput, "ax_spawn_with_affinity: no CPU in the mask");
+        }
+        let inner = axtask::TaskBuilder::new()
+            .name(name)
+            .stack_size(stack_size)
+            .affinity(cpumask)
+            .spawn(f);
+        Ok(AxTaskHandle {
+            id: inner.id().as_u64(),
+            inner,
+        })
+    }
+
     pub fn ax_wait_for_exit(task: AxTaskHandle) -> Option<i32> {
         task.inner.join()
     }
@@ -34,6 +34,9 @@ pub mod sys {
     define_api! {
         /// Shutdown the whole system and all CPUs.
         pub fn ax_terminate() -> !;
+        /// Fills `buf` with random bytes from the kernel random number
+        /// generator.
+        pub fn ax_fill_random(buf: &mut [u8]);
     }
 }
 
@@ -46,6 +49,8 @@ pub mod time {
     define_api! {
         /// Returns the current clock time.
         pub fn ax_current_time() -> AxTimeValue;
+        /// Returns the current wall clock time since the Unix epoch.
+        pub fn ax_wall_time() -> AxTimeValue;
     }
 }
 
@@ -114,6 +119,14 @@ pub mod task {
             name: alloc::string::String,
             stack_size: usize
         ) -> AxTaskHandle;
+        /// Spawns a new task which can only run on the CPUs in `cpumask`, bit
+        /// `i` of the mask stands for CPU `i`.
+        pub fn ax_spawn_with_affinity(
+            f: impl FnOnce() + Send + 'static,
+            name: alloc::string::String,
+            stack_size: usize,
+            cpumask: usize
+        ) -> crate::AxResult<AxTaskHandle>;
         /// Waits for the given task to exit, and returns its exit code (the
         /// argument of [`ax_exit`]).
         pub fn ax_wait_for_exit(task: AxTaskHandle) -> Option<i32>;
@@ -241,11 +254,13 @@ pub mod fs {
 pub mod net {
     use crate::{io::AxPollState, AxResult};
     use core::net::{IpAddr, SocketAddr};
+    use core::time::Duration;
 
     define_api_type! {
         @cfg "net";
         pub type AxTcpSocketHandle;
         pub type AxUdpSocketHandle;
+        pub type AxUdpRecvBuf;
     }
 
     define_api! {
@@ -284,6 +299,40 @@ pub mod net {
         /// Closes the connection on the TCP socket.
         pub fn ax_tcp_shutdown(socket: &AxTcpSocketHandle) -> AxResult;
 
+        /// Returns whether Nagle's algorithm is disabled on the TCP socket.
+        pub fn ax_tcp_nodelay(socket: &AxTcpSocketHandle) -> AxResult<bool>;
+        /// Disables or enables Nagle's algorithm on the TCP socket.
+        pub fn ax_tcp_set_nodelay(socket: &AxTcpSocketHandle, nodelay: bool) -> AxResult;
+        /// Returns the receive timeout of the TCP socket.
+        pub fn ax_tcp_recv_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the receive timeout of the TCP socket. `None` means blocking
+        /// indefinitely.
+        pub fn ax_tcp_set_recv_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the send timeout of the TCP socket.
+        pub fn ax_tcp_send_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the send timeout of the TCP socket. `None` means blocking
+        /// indefinitely.
+        pub fn ax_tcp_set_send_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the linger timeout of the TCP socket.
+        pub fn ax_tcp_linger(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the linger timeout used when the TCP socket is closed.
+        pub fn ax_tcp_set_linger(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the keep-alive interval of the TCP socket.
+        pub fn ax_tcp_keep_alive(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the keep-alive interval of the TCP socket. `None` disables
+        /// keep-alive packets.
+        pub fn ax_tcp_set_keep_alive(socket: &AxTcpSocketHandle, interval: Option<Duration>) -> AxResult;
+        /// Returns the receive buffer size of the TCP socket.
+        pub fn ax_tcp_recv_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize>;
+        /// Sets the receive buffer size of the TCP socket. It must be called
+        /// before the socket is connected or listening.
+        pub fn ax_tcp_set_recv_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult;
+        /// Returns the send buffer size of the TCP socket.
+        pub fn ax_tcp_send_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize>;
+        /// Sets the send buffer size of the TCP socket. It must be called
+        /// before the socket is connected or listening.
+        pub fn ax_tcp_set_send_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult;
+
         // UDP socket
 
         /// Creates a new UDP socket.
@@ -302,6 +351,9 @@ pub mod net {
         /// Receives a single datagram message on the UDP socket, without
         /// removing it from the queue.
         pub fn ax_udp_peek_from(socket: &AxUdpSocketHandle, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)>;
+        /// Receives a single datagram message on the UDP socket, lent in place
+        /// in the NIC's receive buffer if possible.
+        pub fn ax_udp_recv_buf_from(socket: &AxUdpSocketHandle) -> AxResult<(AxUdpRecvBuf, SocketAddr)>;
         /// Sends data on the UDP socket to the given address. On success,
         /// returns the number of bytes written.
         pub fn ax_udp_send_to(socket: &AxUdpSocketHandle, buf: &[u8], addr: SocketAddr) -> AxResult<usize>;
@@ -317,6 +369,10 @@ pub mod net {
         /// address to which it is connected. On success, returns the number of
         /// bytes read.
         pub fn ax_udp_recv(socket: &AxUdpSocketHandle, buf: &mut [u8]) -> AxResult<usize>;
+        /// Receives a single datagram message on the UDP socket from the remote
+        /// address to which it is connected, lent in place in the NIC's
+        /// receive buffer if possible.
+        pub fn ax_udp_recv_buf(socket: &AxUdpSocketHandle) -> AxResult<AxUdpRecvBuf>;
         /// Returns whether the UDP socket is readable or writable.
         pub fn ax_udp_poll(socket: &AxUdpSocketHandle) -> AxResult<AxPollState>;
 
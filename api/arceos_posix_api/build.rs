@@ -41,32 +41,91 @@ typedef struct {{
             "ssize_t",
             "off_t",
             "mode_t",
+            "flock",
             "sock.*",
             "fd_set",
+            "pollfd",
+            "nfds_t",
+            "sigset_t",
+            "sigaction",
+            "siginfo_t",
+            "itimerval",
+            "itimerspec",
             "timeval",
             "pthread_t",
+            "cpu_set_t",
+            "sched_param",
             "pthread_attr_t",
             "pthread_mutex_t",
             "pthread_mutexattr_t",
+            "pthread_cond_t",
+            "pthread_condattr_t",
+            "pthread_rwlock_t",
+            "pthread_rwlockattr_t",
+            "pthread_barrier_t",
+            "pthread_barrierattr_t",
             "epoll_event",
             "iovec",
+            "msghdr",
             "clockid_t",
             "rlimit",
+            "rusage",
             "aibuf",
+            "linger",
+            "termios",
+            "winsize",
         ];
         let allow_vars = [
             "O_.*",
             "AF_.*",
             "SOCK_.*",
+            "SOL_.*",
+            "SO_.*",
+            "MSG_.*",
+            "TCP_.*",
             "IPPROTO_.*",
             "FD_.*",
             "F_.*",
+            "LOCK_.*",
+            "[RWX]_OK",
+            "AT_.*",
             "_SC_.*",
+            "POLL.*",
             "EPOLL_CTL_.*",
             "EPOLL.*",
             "RLIMIT_.*",
+            "RUSAGE_.*",
+            "SCHED_.*",
+            "PROT_.*",
+            "MAP_.*",
+            "MREMAP_.*",
+            "SIG.*",
+            "SA_.*",
+            "SI_.*",
+            "ITIMER_.*",
+            "CLOCK_.*",
+            "EFD_.*",
+            "TFD_.*",
+            "TC.*",
+            "TIOC.*",
+            "FIO.*",
+            "GRND_.*",
+            "V(INTR|ERASE|KILL|EOF|EOL|TIME|MIN)",
+            "I(GNCR|CRNL|NLCR|XON)",
+            "O(POST|NLCR)",
+            "B38400",
+            "CS8",
+            "CREAD",
+            "HUPCL",
+            "ISIG",
+            "ICANON",
+            "ECHO.*",
+            "NOFLSH",
+            "IEXTEN",
             "EAI_.*",
             "MAXADDRS",
+            "PTHREAD_BARRIER_SERIAL_THREAD",
+            "WNOHANG",
         ];
 
         #[derive(Debug)]
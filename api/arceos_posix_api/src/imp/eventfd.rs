@@ -0,0 +1,152 @@
+use alloc::sync::Arc;
+use core::ffi::{c_int, c_uint};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use axerrno::{LinuxError, LinuxResult};
+use axio::PollState;
+use axsync::spin::SpinNoIrq;
+use axtask::WaitQueue;
+
+use super::fd_ops::{add_file_like, notify_pollers, FileLike};
+use crate::ctypes;
+
+/// The largest value the counter can hold.
+const MAX_COUNT: u64 = u64::MAX - 1;
+
+/// A file descriptor for event notification, which holds a 64-bit counter.
+///
+/// A write adds to the counter, and a read returns and resets it, or only
+/// decrements it by one in the semaphore mode.
+///
+/// The counter is checked with the wait queues locked, so it is protected by
+/// [`SpinNoIrq`] rather than a sleeping mutex.
+pub struct EventFd {
+    count: SpinNoIrq<u64>,
+    semaphore: bool,
+    nonblocking: AtomicBool,
+    /// Readers blocked until the counter is not zero.
+    readers: WaitQueue,
+    /// Writers blocked until the counter has room for their values.
+    writers: WaitQueue,
+}
+
+impl EventFd {
+    fn new(initval: u64, flags: u32) -> Self {
+        Self {
+            count: SpinNoIrq::new(initval),
+            semaphore: flags & ctypes::EFD_SEMAPHORE != 0,
+            nonblocking: AtomicBool::new(flags & ctypes::EFD_NONBLOCK != 0),
+            readers: WaitQueue::new(),
+            writers: WaitQueue::new(),
+        }
+    }
+
+    /// Blocks on `queue` until `ready` returns true, or fails with `EAGAIN`
+    /// in the non-blocking mode.
+    fn wait<F>(&self, queue: &WaitQueue, ready: F) -> LinuxResult
+    where
+        F: Fn(u64) -> bool,
+    {
+        if self.nonblocking.load(Ordering::Relaxed) {
+            return Err(LinuxError::EAGAIN);
+        }
+        #[cfg(feature = "signal")]
+        queue
+            .wait_until_interruptible(|| ready(*self.count.lock()))
+            .map_err(|_| LinuxError::EINTR)?;
+        #[cfg(not(feature = "signal"))]
+        queue.wait_until(|| ready(*self.count.lock()));
+        Ok(())
+    }
+}
+
+impl FileLike for EventFd {
+    fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        if buf.len() < 8 {
+            return Err(LinuxError::EINVAL);
+        }
+        loop {
+            let mut count = self.count.lock();
+            if *count > 0 {
+                let value = if self.semaphore { 1 } else { *count };
+                *count -= value;
+                drop(count);
+                self.writers.notify_all(true);
+                notify_pollers();
+                buf[..8].copy_from_slice(&value.to_ne_bytes());
+                return Ok(8);
+            }
+            drop(count);
+            self.wait(&self.readers, |count| count > 0)?;
+        }
+    }
+
+    fn write(&self, buf: &[u8]) -> LinuxResult<usize> {
+        if buf.len() < 8 {
+            return Err(LinuxError::EINVAL);
+        }
+        let value = u64::from_ne_bytes(buf[..8].try_into().unwrap());
+        if value == u64::MAX {
+            return Err(LinuxError::EINVAL);
+        }
+        loop {
+            let mut count = self.count.lock();
+            if MAX_COUNT - *count >= value {
+                *count += value;
+                drop(count);
+                self.readers.notify_all(true);
+                notify_pollers();
+                return Ok(8);
+            }
+            drop(count);
+            self.wait(&self.writers, |count| MAX_COUNT - count >= value)?;
+        }
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        Ok(ctypes::stat {
+            st_ino: 1,
+            st_nlink: 1,
+            st_mode: 0o600u32, // rw-------
+            st_uid: 1000,
+            st_gid: 1000,
+            st_blksize: 4096,
+            ..Default::default()
+        })
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        let count = *self.count.lock();
+        Ok(PollState::new(count > 0, count < MAX_COUNT))
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult {
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn notifies_pollers(&self) -> bool {
+        true
+    }
+}
+
+/// Create a file descriptor for event notification, with the counter
+/// initialized to `initval`.
+///
+/// `EFD_CLOEXEC` is accepted but has no effect.
+///
+/// Return the new file descriptor.
+pub fn sys_eventfd(initval: c_uint, flags: c_int) -> c_int {
+    debug!("sys_eventfd <= {} {:#x}", initval, flags);
+    syscall_body!(sys_eventfd, {
+        let flags = flags as u32;
+        if flags & !(ctypes::EFD_SEMAPHORE | ctypes::EFD_NONBLOCK | ctypes::EFD_CLOEXEC) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        add_file_like(Arc::new(EventFd::new(initval as u64, flags)))
+    })
+}
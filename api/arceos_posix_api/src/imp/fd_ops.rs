@@ -1,5 +1,7 @@
 use alloc::sync::Arc;
 use core::ffi::c_int;
+use core::ops::Deref;
+use core::sync::atomic::{AtomicU64, Ordering};
 
 use axerrno::{LinuxError, LinuxResult};
 use axio::PollState;
@@ -18,32 +20,128 @@ pub trait FileLike: Send + Sync {
     fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync>;
     fn poll(&self) -> LinuxResult<PollState>;
     fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult;
+
+    /// Whether every change of the [`poll`](Self::poll) results is announced
+    /// by [`notify_pollers`], so that `poll` can sleep until then.
+    fn notifies_pollers(&self) -> bool {
+        false
+    }
+
+    /// Handles a device-specific request, `ENOTTY` if it is not supported.
+    fn ioctl(&self, _cmd: u32, _arg: usize) -> LinuxResult<c_int> {
+        Err(LinuxError::ENOTTY)
+    }
+}
+
+/// Increased every time the poll results of a file may have changed.
+static POLL_SEQ: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "multitask")]
+static POLL_QUEUE: axtask::WaitQueue = axtask::WaitQueue::new();
+
+/// Wakes up the tasks in `poll`, after the poll results of a file may have
+/// changed.
+#[cfg_attr(
+    not(any(
+        feature = "pipe",
+        feature = "eventfd",
+        feature = "timerfd",
+        feature = "net"
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn notify_pollers() {
+    POLL_SEQ.fetch_add(1, Ordering::AcqRel);
+    #[cfg(feature = "multitask")]
+    POLL_QUEUE.notify_all(true);
+}
+
+/// Returns the current value of the counter increased by [`notify_pollers`].
+#[cfg(feature = "poll")]
+pub(crate) fn poll_seq() -> u64 {
+    POLL_SEQ.load(Ordering::Acquire)
+}
+
+/// Sleeps until [`notify_pollers`] is called after [`poll_seq`] returned
+/// `seq`, or until `deadline`.
+///
+/// Returns `EINTR` if an unblocked signal is pending.
+#[cfg(all(feature = "poll", feature = "multitask", feature = "irq"))]
+pub(crate) fn wait_pollers(seq: u64, deadline: Option<axhal::time::TimeValue>) -> LinuxResult {
+    let changed = || poll_seq() != seq;
+    match deadline {
+        #[cfg(feature = "signal")]
+        Some(ddl) => {
+            let dur = ddl.saturating_sub(axhal::time::current_time());
+            POLL_QUEUE
+                .wait_timeout_until_interruptible(dur, changed)
+                .map_err(|_| LinuxError::EINTR)?;
+        }
+        #[cfg(feature = "signal")]
+        None => POLL_QUEUE
+            .wait_until_interruptible(changed)
+            .map_err(|_| LinuxError::EINTR)?,
+        #[cfg(not(feature = "signal"))]
+        Some(ddl) => {
+            let dur = ddl.saturating_sub(axhal::time::current_time());
+            POLL_QUEUE.wait_timeout_until(dur, changed);
+        }
+        #[cfg(not(feature = "signal"))]
+        None => POLL_QUEUE.wait_until(changed),
+    }
+    Ok(())
 }
 
+pub(crate) type FdTable = FlattenObjects<Arc<dyn FileLike>, AX_FILE_LIMIT>;
+
+/// Creates a file descriptor table with only stdin, stdout and stderr.
+pub(crate) fn new_fd_table() -> FdTable {
+    let mut fd_table = FlattenObjects::new();
+    fd_table.add_at(0, Arc::new(stdin()) as _).unwrap(); // stdin
+    fd_table.add_at(1, Arc::new(stdout()) as _).unwrap(); // stdout
+    fd_table.add_at(2, Arc::new(stdout()) as _).unwrap(); // stderr
+    fd_table
+}
+
+#[cfg(not(feature = "process"))]
 lazy_static::lazy_static! {
-    static ref FD_TABLE: RwLock<FlattenObjects<Arc<dyn FileLike>, AX_FILE_LIMIT>> = {
-        let mut fd_table = FlattenObjects::new();
-        fd_table.add_at(0, Arc::new(stdin()) as _).unwrap(); // stdin
-        fd_table.add_at(1, Arc::new(stdout()) as _).unwrap(); // stdout
-        fd_table.add_at(2, Arc::new(stdout()) as _).unwrap(); // stderr
-        RwLock::new(fd_table)
-    };
+    static ref FD_TABLE: RwLock<FdTable> = RwLock::new(new_fd_table());
+}
+
+/// Returns the file descriptor table of the current process.
+fn fd_table() -> impl Deref<Target = RwLock<FdTable>> {
+    #[cfg(feature = "process")]
+    {
+        super::process::current().fd_table.clone()
+    }
+    #[cfg(not(feature = "process"))]
+    {
+        &*FD_TABLE
+    }
 }
 
 pub fn get_file_like(fd: c_int) -> LinuxResult<Arc<dyn FileLike>> {
-    FD_TABLE
+    fd_table()
         .read()
         .get(fd as usize)
         .cloned()
         .ok_or(LinuxError::EBADF)
 }
 
+/// Adds a file to the lowest free file descriptor, which must be less than
+/// the `RLIMIT_NOFILE` limit.
 pub fn add_file_like(f: Arc<dyn FileLike>) -> LinuxResult<c_int> {
-    Ok(FD_TABLE.write().add(f).ok_or(LinuxError::EMFILE)? as c_int)
+    let mut fd_table = fd_table().write();
+    let fd = fd_table.add(f).ok_or(LinuxError::EMFILE)?;
+    if fd >= super::resources::nofile_limit() {
+        fd_table.remove(fd);
+        return Err(LinuxError::EMFILE);
+    }
+    Ok(fd as c_int)
 }
 
 pub fn close_file_like(fd: c_int) -> LinuxResult {
-    let f = FD_TABLE
+    let f = fd_table()
         .write()
         .remove(fd as usize)
         .ok_or(LinuxError::EBADF)?;
@@ -86,12 +184,12 @@ pub fn sys_dup2(old_fd: c_int, new_fd: c_int) -> c_int {
                 return Ok(r);
             }
         }
-        if new_fd as usize >= AX_FILE_LIMIT {
+        if new_fd < 0 || new_fd as usize >= super::resources::nofile_limit() {
             return Err(LinuxError::EBADF);
         }
 
         let f = get_file_like(old_fd)?;
-        FD_TABLE
+        fd_table()
             .write()
             .add_at(new_fd as usize, f)
             .ok_or(LinuxError::EMFILE)?;
@@ -119,6 +217,10 @@ pub fn sys_fcntl(fd: c_int, cmd: c_int, arg: usize) -> c_int {
                 get_file_like(fd)?.set_nonblocking(arg & (ctypes::O_NONBLOCK as usize) > 0)?;
                 Ok(0)
             }
+            #[cfg(feature = "fs")]
+            ctypes::F_GETLK | ctypes::F_SETLK | ctypes::F_SETLKW => {
+                super::fs::fcntl_lock(fd, cmd as u32, arg as *mut ctypes::flock)
+            }
             _ => {
                 warn!("unsupported fcntl parameters: cmd {}", cmd);
                 Ok(0)
@@ -126,3 +228,26 @@ pub fn sys_fcntl(fd: c_int, cmd: c_int, arg: usize) -> c_int {
         }
     })
 }
+
+/// Manipulate the underlying device of a file descriptor.
+///
+/// `FIONBIO` is supported by all file descriptors, and other requests are
+/// passed to the file itself, e.g. the terminal requests of the console.
+pub fn sys_ioctl(fd: c_int, request: c_int, arg: usize) -> c_int {
+    debug!(
+        "sys_ioctl <= fd: {} request: {:#x} arg: {:#x}",
+        fd, request, arg
+    );
+    syscall_body!(sys_ioctl, {
+        let f = get_file_like(fd)?;
+        match request as u32 {
+            ctypes::FIONBIO => {
+                let nonblocking =
+                    unsafe { (arg as *const c_int).as_ref() }.ok_or(LinuxError::EFAULT)?;
+                f.set_nonblocking(*nonblocking != 0)?;
+                Ok(0)
+            }
+            cmd => f.ioctl(cmd, arg),
+        }
+    })
+}
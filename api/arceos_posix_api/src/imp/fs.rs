@@ -1,8 +1,8 @@
-use alloc::sync::Arc;
-use core::ffi::{c_char, c_int};
+use alloc::{format, string::String, sync::Arc};
+use core::ffi::{c_char, c_int, c_void};
 
-use axerrno::{LinuxError, LinuxResult};
-use axfs::fops::OpenOptions;
+use axerrno::{AxError, LinuxError, LinuxResult};
+use axfs::fops::{DirEntry, FileAttr, LockKind, OpenOptions};
 use axio::{PollState, SeekFrom};
 use axsync::Mutex;
 
@@ -24,12 +24,35 @@ impl File {
         super::fd_ops::add_file_like(Arc::new(self))
     }
 
-    fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+    pub(crate) fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
         let f = super::fd_ops::get_file_like(fd)?;
         f.into_any()
             .downcast::<Self>()
             .map_err(|_| LinuxError::EINVAL)
     }
+
+    /// Reads the file at the given offset, without moving the file cursor.
+    pub(crate) fn read_at(&self, offset: u64, buf: &mut [u8]) -> LinuxResult<usize> {
+        Ok(self.inner.lock().read_at(offset, buf)?)
+    }
+
+    /// Writes the file at the given offset, without moving the file cursor.
+    fn write_at(&self, offset: u64, buf: &[u8]) -> LinuxResult<usize> {
+        Ok(self.inner.lock().write_at(offset, buf)?)
+    }
+
+    /// Like [`File::from_fd`], for reads and writes at given offsets, which
+    /// need a regular file rather than a pipe or socket.
+    fn from_fd_at(fd: c_int, offset: ctypes::off_t) -> LinuxResult<Arc<Self>> {
+        let file = Self::from_fd(fd).map_err(|e| match e {
+            LinuxError::EINVAL => LinuxError::ESPIPE,
+            e => e,
+        })?;
+        if offset < 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        Ok(file)
+    }
 }
 
 impl FileLike for File {
@@ -42,21 +65,7 @@ impl FileLike for File {
     }
 
     fn stat(&self) -> LinuxResult<ctypes::stat> {
-        let metadata = self.inner.lock().get_attr()?;
-        let ty = metadata.file_type() as u8;
-        let perm = metadata.perm().bits() as u32;
-        let st_mode = ((ty as u32) << 12) | perm;
-        Ok(ctypes::stat {
-            st_ino: 1,
-            st_nlink: 1,
-            st_mode,
-            st_uid: 1000,
-            st_gid: 1000,
-            st_size: metadata.size() as _,
-            st_blocks: metadata.blocks() as _,
-            st_blksize: 512,
-            ..Default::default()
-        })
+        Ok(attr_to_stat(self.inner.lock().get_attr()?))
     }
 
     fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
@@ -64,17 +73,146 @@ impl FileLike for File {
     }
 
     fn poll(&self) -> LinuxResult<PollState> {
-        Ok(PollState {
-            readable: true,
-            writable: true,
+        Ok(PollState::new(true, true))
+    }
+
+    fn notifies_pollers(&self) -> bool {
+        true // never changes
+    }
+
+    fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
+        Ok(())
+    }
+}
+
+/// A directory opened by [`sys_open`], which can be iterated with
+/// [`sys_getdents64`] or used as the `dirfd` of the `*at` functions.
+pub struct Directory {
+    inner: Mutex<axfs::fops::Directory>,
+    /// The absolute path, to resolve paths relative to this directory.
+    path: String,
+}
+
+impl Directory {
+    fn open(path: &str) -> LinuxResult<Self> {
+        let mut options = OpenOptions::new();
+        options.read(true);
+        let inner = axfs::fops::Directory::open_dir(path, &options)?;
+        Ok(Self {
+            inner: Mutex::new(inner),
+            path: axfs::api::canonicalize(path)?,
         })
     }
 
+    fn add_to_fd_table(self) -> LinuxResult<c_int> {
+        super::fd_ops::add_file_like(Arc::new(self))
+    }
+
+    /// Returns `ENOTDIR` if `fd` is not a directory.
+    pub(crate) fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+        let f = super::fd_ops::get_file_like(fd)?;
+        f.into_any()
+            .downcast::<Self>()
+            .map_err(|_| LinuxError::ENOTDIR)
+    }
+
+    /// Only `SEEK_SET` is supported, the offset is the index of an entry.
+    fn seek(&self, pos: SeekFrom) -> LinuxResult<u64> {
+        match pos {
+            SeekFrom::Start(idx) => {
+                self.inner.lock().seek_dir(idx as _);
+                Ok(idx)
+            }
+            _ => Err(LinuxError::EINVAL),
+        }
+    }
+
+    /// Fills `buf` with `struct linux_dirent64` records, returns the number
+    /// of bytes filled.
+    fn read_dirents(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        // d_ino (8 bytes), d_off (8 bytes), d_reclen (2 bytes), d_type (1 byte)
+        const NAME_OFFSET: usize = 19;
+
+        let mut dir = self.inner.lock();
+        let mut entry = [DirEntry::default()];
+        let mut len = 0;
+        loop {
+            let idx = dir.tell_dir();
+            if dir.read_dir(&mut entry)? == 0 {
+                break;
+            }
+            let name = entry[0].name_as_bytes();
+            let reclen = (NAME_OFFSET + name.len() + 1).next_multiple_of(8);
+            if len + reclen > buf.len() {
+                // Leave the entry for the next call.
+                dir.seek_dir(idx);
+                if len == 0 {
+                    return Err(LinuxError::EINVAL);
+                }
+                break;
+            }
+
+            let rec = &mut buf[len..len + reclen];
+            rec[0..8].copy_from_slice(&1u64.to_ne_bytes());
+            rec[8..16].copy_from_slice(&(idx as i64 + 1).to_ne_bytes());
+            rec[16..18].copy_from_slice(&(reclen as u16).to_ne_bytes());
+            // `VfsNodeType` has the same values as `DT_*`.
+            rec[18] = entry[0].entry_type() as u8;
+            rec[NAME_OFFSET..NAME_OFFSET + name.len()].copy_from_slice(name);
+            rec[NAME_OFFSET + name.len()..].fill(0);
+            len += reclen;
+        }
+        Ok(len)
+    }
+}
+
+impl FileLike for Directory {
+    fn read(&self, _buf: &mut [u8]) -> LinuxResult<usize> {
+        Err(LinuxError::EISDIR)
+    }
+
+    fn write(&self, _buf: &[u8]) -> LinuxResult<usize> {
+        Err(LinuxError::EBADF)
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        Ok(attr_to_stat(self.inner.lock().get_attr()?))
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        Ok(PollState::new(true, false))
+    }
+
+    fn notifies_pollers(&self) -> bool {
+        true // never changes
+    }
+
     fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
         Ok(())
     }
 }
 
+fn attr_to_stat(metadata: FileAttr) -> ctypes::stat {
+    let ty = metadata.file_type() as u8;
+    let perm = metadata.perm().bits() as u32;
+    let st_mode = ((ty as u32) << 12) | perm;
+    ctypes::stat {
+        st_ino: 1,
+        st_nlink: 1,
+        st_mode,
+        st_uid: 1000,
+        st_gid: 1000,
+        st_size: metadata.size() as _,
+        st_blocks: metadata.blocks() as _,
+        st_blksize: 512,
+        ..Default::default()
+    }
+}
+
 /// Convert open flags to [`OpenOptions`].
 fn flags_to_options(flags: c_int, _mode: ctypes::mode_t) -> OpenOptions {
     let flags = flags as u32;
@@ -102,6 +240,88 @@ fn flags_to_options(flags: c_int, _mode: ctypes::mode_t) -> OpenOptions {
     options
 }
 
+/// Resolves `path` relative to the directory `dirfd`, or the current directory
+/// if `dirfd` is `AT_FDCWD`. Absolute paths are returned as is.
+pub(crate) fn path_at(dirfd: c_int, path: &str) -> LinuxResult<String> {
+    if path.is_empty() {
+        return Err(LinuxError::ENOENT);
+    }
+    if path.starts_with('/') {
+        return Ok(path.into());
+    }
+    if dirfd == ctypes::AT_FDCWD {
+        // Each process has its own current directory, which `axfs` knows
+        // nothing about.
+        #[cfg(feature = "process")]
+        return Ok(format!("{}{}", super::process::current().cwd.lock(), path));
+        #[cfg(not(feature = "process"))]
+        return Ok(path.into());
+    }
+    let dir = Directory::from_fd(dirfd)?;
+    Ok(format!("{}/{}", dir.path.trim_end_matches('/'), path))
+}
+
+fn open_path(path: &str, flags: c_int, mode: ctypes::mode_t) -> LinuxResult<c_int> {
+    let is_dir = match axfs::api::metadata(path) {
+        Ok(metadata) => metadata.is_dir(),
+        Err(_) if flags as u32 & ctypes::O_DIRECTORY == 0 => false,
+        Err(e) => return Err(e.into()),
+    };
+    if is_dir {
+        // Directories can only be opened for reading.
+        if flags as u32 & 0b11 != ctypes::O_RDONLY {
+            return Err(LinuxError::EISDIR);
+        }
+        Directory::open(path)?.add_to_fd_table()
+    } else if flags as u32 & ctypes::O_DIRECTORY != 0 {
+        Err(LinuxError::ENOTDIR)
+    } else {
+        let options = flags_to_options(flags, mode);
+        let file = axfs::fops::File::open(path, &options)?;
+        File::new(file).add_to_fd_table()
+    }
+}
+
+/// Changes the current directory to `path`.
+fn set_current_dir(path: &str) -> LinuxResult {
+    #[cfg(feature = "process")]
+    {
+        let mut path = axfs::api::canonicalize(path)?;
+        if !axfs::api::metadata(&path)?.is_dir() {
+            return Err(LinuxError::ENOTDIR);
+        }
+        if !path.ends_with('/') {
+            path.push('/');
+        }
+        *super::process::current().cwd.lock() = path;
+    }
+    #[cfg(not(feature = "process"))]
+    axfs::api::set_current_dir(path)?;
+    Ok(())
+}
+
+fn stat_path(path: &str) -> LinuxResult<ctypes::stat> {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    let file = axfs::fops::File::open(path, &options)?;
+    File::new(file).stat()
+}
+
+fn access_path(path: &str, mode: c_int) -> LinuxResult<c_int> {
+    let mode = mode as u32;
+    if mode & !(ctypes::R_OK | ctypes::W_OK | ctypes::X_OK) != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let perm = axfs::api::metadata(path)?.permissions();
+    if (mode & ctypes::R_OK != 0 && !perm.owner_readable())
+        || (mode & ctypes::W_OK != 0 && !perm.owner_writable())
+        || (mode & ctypes::X_OK != 0 && !perm.owner_executable())
+    {
+        return Err(LinuxError::EACCES);
+    }
+    Ok(0)
+}
+
 /// Open a file by `filename` and insert it into the file descriptor table.
 ///
 /// Return its index in the file table (`fd`). Return `EMFILE` if it already
@@ -110,9 +330,27 @@ pub fn sys_open(filename: *const c_char, flags: c_int, mode: ctypes::mode_t) ->
     let filename = char_ptr_to_str(filename);
     debug!("sys_open <= {:?} {:#o} {:#o}", filename, flags, mode);
     syscall_body!(sys_open, {
-        let options = flags_to_options(flags, mode);
-        let file = axfs::fops::File::open(filename?, &options)?;
-        File::new(file).add_to_fd_table()
+        let path = path_at(ctypes::AT_FDCWD, filename?)?;
+        open_path(&path, flags, mode)
+    })
+}
+
+/// Like [`sys_open`], but a relative `filename` is resolved relative to the
+/// directory `dirfd` (or the current directory if it is `AT_FDCWD`).
+pub fn sys_openat(
+    dirfd: c_int,
+    filename: *const c_char,
+    flags: c_int,
+    mode: ctypes::mode_t,
+) -> c_int {
+    let filename = char_ptr_to_str(filename);
+    debug!(
+        "sys_openat <= {} {:?} {:#o} {:#o}",
+        dirfd, filename, flags, mode
+    );
+    syscall_body!(sys_openat, {
+        let path = path_at(dirfd, filename?)?;
+        open_path(&path, flags, mode)
     })
 }
 
@@ -128,11 +366,96 @@ pub fn sys_lseek(fd: c_int, offset: ctypes::off_t, whence: c_int) -> ctypes::off
             2 => SeekFrom::End(offset as _),
             _ => return Err(LinuxError::EINVAL),
         };
+        if let Ok(dir) = Directory::from_fd(fd) {
+            return dir.seek(pos);
+        }
         let off = File::from_fd(fd)?.inner.lock().seek(pos)?;
         Ok(off)
     })
 }
 
+/// Read from the file `fd` at `offset`, the file position is not changed.
+///
+/// Return the read size if success.
+pub unsafe fn sys_pread64(
+    fd: c_int,
+    buf: *mut c_void,
+    count: usize,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    debug!(
+        "sys_pread64 <= {} {:#x} {} {}",
+        fd, buf as usize, count, offset
+    );
+    syscall_body!(sys_pread64, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let dst = unsafe { core::slice::from_raw_parts_mut(buf as *mut u8, count) };
+        let n = File::from_fd_at(fd, offset)?.read_at(offset as u64, dst)?;
+        Ok(n as ctypes::ssize_t)
+    })
+}
+
+/// Write to the file `fd` at `offset`, the file position is not changed.
+///
+/// Return the written size if success.
+pub unsafe fn sys_pwrite64(
+    fd: c_int,
+    buf: *const c_void,
+    count: usize,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    debug!(
+        "sys_pwrite64 <= {} {:#x} {} {}",
+        fd, buf as usize, count, offset
+    );
+    syscall_body!(sys_pwrite64, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let src = unsafe { core::slice::from_raw_parts(buf as *const u8, count) };
+        let n = File::from_fd_at(fd, offset)?.write_at(offset as u64, src)?;
+        Ok(n as ctypes::ssize_t)
+    })
+}
+
+/// Like [`sys_pread64`], but reads into multiple buffers.
+pub unsafe fn sys_preadv(
+    fd: c_int,
+    iov: *const ctypes::iovec,
+    iocnt: c_int,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    debug!("sys_preadv <= {} {} {}", fd, iocnt, offset);
+    syscall_body!(sys_preadv, {
+        let iovs = unsafe { super::io::iovecs(iov, iocnt)? };
+        let file = File::from_fd_at(fd, offset)?;
+        let n = unsafe { super::io::read_iovecs(iovs, |buf| file.read_at(offset as u64, buf))? };
+        Ok(n as ctypes::ssize_t)
+    })
+}
+
+/// Like [`sys_pwrite64`], but writes from multiple buffers.
+pub unsafe fn sys_pwritev(
+    fd: c_int,
+    iov: *const ctypes::iovec,
+    iocnt: c_int,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    debug!("sys_pwritev <= {} {} {}", fd, iocnt, offset);
+    syscall_body!(sys_pwritev, {
+        let iovs = unsafe { super::io::iovecs(iov, iocnt)? };
+        let file = File::from_fd_at(fd, offset)?;
+        let n = unsafe {
+            super::io::write_iovecs(iovs, |buf, pos| {
+                file.write_at(offset as u64 + pos as u64, buf)
+            })?
+        };
+        Ok(n as ctypes::ssize_t)
+    })
+}
+
 /// Get the file metadata by `path` and write into `buf`.
 ///
 /// Return 0 if success.
@@ -143,11 +466,7 @@ pub unsafe fn sys_stat(path: *const c_char, buf: *mut ctypes::stat) -> c_int {
         if buf.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        let mut options = OpenOptions::new();
-        options.read(true);
-        let file = axfs::fops::File::open(path?, &options)?;
-        let st = File::new(file).stat()?;
-        unsafe { *buf = st };
+        unsafe { *buf = stat_path(&path_at(ctypes::AT_FDCWD, path?)?)? };
         Ok(0)
     })
 }
@@ -167,6 +486,42 @@ pub unsafe fn sys_fstat(fd: c_int, buf: *mut ctypes::stat) -> c_int {
     })
 }
 
+/// Get the file metadata by `path` relative to the directory `dirfd`, and
+/// write into `buf`.
+///
+/// If `flags` contains `AT_EMPTY_PATH` and `path` is empty, it gets the
+/// metadata of `dirfd` itself. Symbolic links are not supported, so
+/// `AT_SYMLINK_NOFOLLOW` makes no difference.
+pub unsafe fn sys_fstatat(
+    dirfd: c_int,
+    path: *const c_char,
+    buf: *mut ctypes::stat,
+    flags: c_int,
+) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!(
+        "sys_fstatat <= {} {:?} {:#x} {:#x}",
+        dirfd, path, buf as usize, flags
+    );
+    syscall_body!(sys_fstatat, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let flags = flags as u32;
+        if flags & !(ctypes::AT_EMPTY_PATH | ctypes::AT_SYMLINK_NOFOLLOW) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let path = path?;
+        let st = if path.is_empty() && flags & ctypes::AT_EMPTY_PATH != 0 {
+            get_file_like(dirfd)?.stat()?
+        } else {
+            stat_path(&path_at(dirfd, path)?)?
+        };
+        unsafe { *buf = st };
+        Ok(0)
+    })
+}
+
 /// Get the metadata of the symbolic link and write into `buf`.
 ///
 /// Return 0 if success.
@@ -190,6 +545,9 @@ pub fn sys_getcwd(buf: *mut c_char, size: usize) -> *mut c_char {
             return Ok(core::ptr::null::<c_char>() as _);
         }
         let dst = unsafe { core::slice::from_raw_parts_mut(buf as *mut u8, size as _) };
+        #[cfg(feature = "process")]
+        let cwd = super::process::current().cwd.lock().clone();
+        #[cfg(not(feature = "process"))]
         let cwd = axfs::api::current_dir()?;
         let cwd = cwd.as_bytes();
         if cwd.len() < size {
@@ -211,7 +569,294 @@ pub fn sys_rename(old: *const c_char, new: *const c_char) -> c_int {
         let old_path = char_ptr_to_str(old)?;
         let new_path = char_ptr_to_str(new)?;
         debug!("sys_rename <= old: {:?}, new: {:?}", old_path, new_path);
-        axfs::api::rename(old_path, new_path)?;
+        axfs::api::rename(
+            &path_at(ctypes::AT_FDCWD, old_path)?,
+            &path_at(ctypes::AT_FDCWD, new_path)?,
+        )?;
+        Ok(0)
+    })
+}
+
+/// Like [`sys_rename`], but relative paths are resolved relative to the
+/// directories `olddirfd` and `newdirfd` respectively.
+pub fn sys_renameat(
+    olddirfd: c_int,
+    old: *const c_char,
+    newdirfd: c_int,
+    new: *const c_char,
+) -> c_int {
+    syscall_body!(sys_renameat, {
+        let old_path = char_ptr_to_str(old)?;
+        let new_path = char_ptr_to_str(new)?;
+        debug!(
+            "sys_renameat <= old: {} {:?}, new: {} {:?}",
+            olddirfd, old_path, newdirfd, new_path
+        );
+        axfs::api::rename(&path_at(olddirfd, old_path)?, &path_at(newdirfd, new_path)?)?;
+        Ok(0)
+    })
+}
+
+/// Remove a file.
+pub fn sys_unlink(path: *const c_char) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_unlink <= {:?}", path);
+    syscall_body!(sys_unlink, {
+        axfs::api::remove_file(&path_at(ctypes::AT_FDCWD, path?)?)?;
+        Ok(0)
+    })
+}
+
+/// Remove a file, or an empty directory if `flags` contains `AT_REMOVEDIR`.
+///
+/// A relative `path` is resolved relative to the directory `dirfd`.
+pub fn sys_unlinkat(dirfd: c_int, path: *const c_char, flags: c_int) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_unlinkat <= {} {:?} {:#x}", dirfd, path, flags);
+    syscall_body!(sys_unlinkat, {
+        let path = path_at(dirfd, path?)?;
+        match flags as u32 {
+            0 => axfs::api::remove_file(&path)?,
+            ctypes::AT_REMOVEDIR => axfs::api::remove_dir(&path)?,
+            _ => return Err(LinuxError::EINVAL),
+        }
+        Ok(0)
+    })
+}
+
+/// Remove an empty directory.
+pub fn sys_rmdir(path: *const c_char) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_rmdir <= {:?}", path);
+    syscall_body!(sys_rmdir, {
+        axfs::api::remove_dir(&path_at(ctypes::AT_FDCWD, path?)?)?;
+        Ok(0)
+    })
+}
+
+/// Create a directory.
+///
+/// File permissions are not supported, so `mode` is ignored.
+pub fn sys_mkdir(path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_mkdir <= {:?} {:#o}", path, mode);
+    syscall_body!(sys_mkdir, {
+        axfs::api::create_dir(&path_at(ctypes::AT_FDCWD, path?)?)?;
+        Ok(0)
+    })
+}
+
+/// Like [`sys_mkdir`], but a relative `path` is resolved relative to the
+/// directory `dirfd`.
+pub fn sys_mkdirat(dirfd: c_int, path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_mkdirat <= {} {:?} {:#o}", dirfd, path, mode);
+    syscall_body!(sys_mkdirat, {
+        axfs::api::create_dir(&path_at(dirfd, path?)?)?;
         Ok(0)
     })
 }
+
+/// Truncate or extend the file indicated by `fd` to `length` bytes.
+pub fn sys_ftruncate(fd: c_int, length: ctypes::off_t) -> c_int {
+    debug!("sys_ftruncate <= {} {}", fd, length);
+    syscall_body!(sys_ftruncate, {
+        if length < 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        File::from_fd(fd)?.inner.lock().truncate(length as _)?;
+        Ok(0)
+    })
+}
+
+/// Truncate or extend the file at `path` to `length` bytes.
+pub fn sys_truncate(path: *const c_char, length: ctypes::off_t) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_truncate <= {:?} {}", path, length);
+    syscall_body!(sys_truncate, {
+        if length < 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let mut options = OpenOptions::new();
+        options.write(true);
+        let path = path_at(ctypes::AT_FDCWD, path?)?;
+        axfs::fops::File::open(&path, &options)?.truncate(length as _)?;
+        Ok(0)
+    })
+}
+
+/// Write the buffered data of the file indicated by `fd` to the device.
+pub fn sys_fsync(fd: c_int) -> c_int {
+    debug!("sys_fsync <= {}", fd);
+    syscall_body!(sys_fsync, {
+        match File::from_fd(fd)?.inner.lock().flush() {
+            // Files opened read-only have nothing to write back.
+            Ok(()) | Err(AxError::PermissionDenied) => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Like [`sys_fsync`], the metadata is always written back as well.
+pub fn sys_fdatasync(fd: c_int) -> c_int {
+    debug!("sys_fdatasync <= {}", fd);
+    sys_fsync(fd)
+}
+
+/// Change the current directory.
+pub fn sys_chdir(path: *const c_char) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_chdir <= {:?}", path);
+    syscall_body!(sys_chdir, {
+        set_current_dir(&path_at(ctypes::AT_FDCWD, path?)?)?;
+        Ok(0)
+    })
+}
+
+/// Apply or remove an advisory lock on the whole file `fd`.
+///
+/// `flock` locks are the same as `fcntl` locks that cover the whole file,
+/// they can convert or split each other.
+pub fn sys_flock(fd: c_int, operation: c_int) -> c_int {
+    debug!("sys_flock <= {} {:#x}", fd, operation);
+    syscall_body!(sys_flock, {
+        // Keep the file alive until the lock is placed, so that its locks are
+        // released even if it is closed meanwhile, but do not hold its inner
+        // mutex while waiting, or other operations on it block.
+        let file = File::from_fd(fd)?;
+        let locker = file.inner.lock().locker()?;
+        let operation = operation as u32;
+        let wait = operation & ctypes::LOCK_NB == 0;
+        match operation & !ctypes::LOCK_NB {
+            ctypes::LOCK_SH => locker.lock(LockKind::Shared, 0..u64::MAX, wait)?,
+            ctypes::LOCK_EX => locker.lock(LockKind::Exclusive, 0..u64::MAX, wait)?,
+            ctypes::LOCK_UN => locker.unlock(0..u64::MAX),
+            _ => return Err(LinuxError::EINVAL),
+        }
+        drop(file);
+        Ok(0)
+    })
+}
+
+/// Handles the `F_GETLK`, `F_SETLK` and `F_SETLKW` commands of `fcntl`.
+///
+/// Locks are owned by the opened file rather than the process, like the open
+/// file description locks of Linux. So threads exclude each other as long as
+/// they use different `open` calls, and `l_pid` is always -1. The locks are
+/// released when the last file descriptor of the opened file is closed.
+pub(crate) fn fcntl_lock(fd: c_int, cmd: u32, lock: *mut ctypes::flock) -> LinuxResult<c_int> {
+    if lock.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    let lock = unsafe { &mut *lock };
+    let file = File::from_fd(fd)?;
+    let mut inner = file.inner.lock();
+
+    let base = match lock.l_whence {
+        0 => 0,
+        1 => inner.seek(SeekFrom::Current(0))?,
+        2 => inner.get_attr()?.size(),
+        _ => return Err(LinuxError::EINVAL),
+    };
+    let start = base
+        .checked_add_signed(lock.l_start)
+        .ok_or(LinuxError::EINVAL)?;
+    let range = match lock.l_len {
+        0 => start..u64::MAX,
+        len if len > 0 => {
+            let end = start.checked_add(len as u64);
+            start..end.ok_or(LinuxError::EOVERFLOW)?
+        }
+        len => {
+            let begin = start.checked_sub(len.unsigned_abs());
+            begin.ok_or(LinuxError::EINVAL)?..start
+        }
+    };
+    let kind = match lock.l_type as u32 {
+        ctypes::F_RDLCK => Some(LockKind::Shared),
+        ctypes::F_WRLCK => Some(LockKind::Exclusive),
+        ctypes::F_UNLCK => None,
+        _ => return Err(LinuxError::EINVAL),
+    };
+    // Keep the file alive but do not hold its inner mutex while waiting, or
+    // other operations on it block.
+    let locker = inner.locker()?;
+    drop(inner);
+
+    match (cmd, kind) {
+        (ctypes::F_GETLK, None) => return Err(LinuxError::EINVAL),
+        (ctypes::F_GETLK, Some(kind)) => match locker.test_lock(kind, range) {
+            Some(l) => {
+                lock.l_type = match l.kind {
+                    LockKind::Shared => ctypes::F_RDLCK,
+                    LockKind::Exclusive => ctypes::F_WRLCK,
+                } as _;
+                lock.l_whence = 0;
+                lock.l_start = l.range.start as _;
+                lock.l_len = match l.range.end {
+                    u64::MAX => 0,
+                    end => (end - l.range.start) as _,
+                };
+                lock.l_pid = -1;
+            }
+            None => lock.l_type = ctypes::F_UNLCK as _,
+        },
+        (_, Some(kind)) => locker.lock(kind, range, cmd == ctypes::F_SETLKW)?,
+        (_, None) => locker.unlock(range),
+    }
+    Ok(0)
+}
+
+/// Change the current directory to the directory `fd`.
+pub fn sys_fchdir(fd: c_int) -> c_int {
+    debug!("sys_fchdir <= {}", fd);
+    syscall_body!(sys_fchdir, {
+        set_current_dir(&Directory::from_fd(fd)?.path)?;
+        Ok(0)
+    })
+}
+
+/// Read entries of the directory `fd` into `dirp`, as `struct linux_dirent64`
+/// records.
+///
+/// Return the number of bytes read, or 0 at the end of the directory.
+pub unsafe fn sys_getdents64(fd: c_int, dirp: *mut c_void, count: usize) -> ctypes::ssize_t {
+    debug!("sys_getdents64 <= {} {:#x} {}", fd, dirp as usize, count);
+    syscall_body!(sys_getdents64, {
+        if dirp.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let buf = unsafe { core::slice::from_raw_parts_mut(dirp as *mut u8, count) };
+        Ok(Directory::from_fd(fd)?.read_dirents(buf)? as ctypes::ssize_t)
+    })
+}
+
+/// Check whether the file at `path` exists (`F_OK`), or can be accessed
+/// with the permissions in `mode` (a mask of `R_OK`, `W_OK` and `X_OK`).
+pub fn sys_access(path: *const c_char, mode: c_int) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_access <= {:?} {:#o}", path, mode);
+    syscall_body!(
+        sys_access,
+        access_path(&path_at(ctypes::AT_FDCWD, path?)?, mode)
+    )
+}
+
+/// Like [`sys_access`], but a relative `path` is resolved relative to the
+/// directory `dirfd`.
+///
+/// There are no users or symbolic links, so `AT_EACCESS` and
+/// `AT_SYMLINK_NOFOLLOW` in `flags` make no difference.
+pub fn sys_faccessat(dirfd: c_int, path: *const c_char, mode: c_int, flags: c_int) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!(
+        "sys_faccessat <= {} {:?} {:#o} {:#x}",
+        dirfd, path, mode, flags
+    );
+    syscall_body!(sys_faccessat, {
+        if flags as u32 & !(ctypes::AT_EACCESS | ctypes::AT_SYMLINK_NOFOLLOW) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        access_path(&path_at(dirfd, path?)?, mode)
+    })
+}
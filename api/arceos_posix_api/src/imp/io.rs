@@ -1,5 +1,5 @@
 use crate::ctypes;
-use axerrno::LinuxError;
+use axerrno::{LinuxError, LinuxResult};
 use core::ffi::{c_int, c_void};
 
 #[cfg(feature = "fd")]
@@ -7,6 +7,135 @@ use crate::imp::fd_ops::get_file_like;
 #[cfg(not(feature = "fd"))]
 use axio::prelude::*;
 
+/// The maximum number of buffers in an `iovec` array, the same as Linux.
+const IOV_MAX: c_int = 1024;
+
+fn read_fd(fd: c_int, dst: &mut [u8]) -> LinuxResult<usize> {
+    #[cfg(feature = "fd")]
+    {
+        get_file_like(fd)?.read(dst)
+    }
+    #[cfg(not(feature = "fd"))]
+    match fd {
+        0 => Ok(super::stdio::stdin().read(dst)?),
+        1 | 2 => Err(LinuxError::EPERM),
+        _ => Err(LinuxError::EBADF),
+    }
+}
+
+fn write_fd(fd: c_int, src: &[u8]) -> LinuxResult<usize> {
+    #[cfg(feature = "fd")]
+    {
+        get_file_like(fd)?.write(src)
+    }
+    #[cfg(not(feature = "fd"))]
+    match fd {
+        0 => Err(LinuxError::EPERM),
+        1 | 2 => Ok(super::stdio::stdout().write(src)?),
+        _ => Err(LinuxError::EBADF),
+    }
+}
+
+/// Checks and converts an `iovec` array from the user.
+pub(crate) unsafe fn iovecs<'a>(
+    iov: *const ctypes::iovec,
+    iocnt: c_int,
+) -> LinuxResult<&'a [ctypes::iovec]> {
+    if !(0..=IOV_MAX).contains(&iocnt) {
+        return Err(LinuxError::EINVAL);
+    }
+    if iocnt == 0 {
+        return Ok(&[]);
+    }
+    if iov.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    let iovs = unsafe { core::slice::from_raw_parts(iov, iocnt as usize) };
+    if iovs
+        .iter()
+        .any(|iov| iov.iov_base.is_null() && iov.iov_len > 0)
+    {
+        return Err(LinuxError::EFAULT);
+    }
+    // The total length must fit in the returned `ssize_t`.
+    iovs.iter()
+        .try_fold(0usize, |total, iov| total.checked_add(iov.iov_len))
+        .filter(|&total| total <= isize::MAX as usize)
+        .ok_or(LinuxError::EINVAL)?;
+    Ok(iovs)
+}
+
+/// Reads once with `read`, and scatters the data into the buffers of `iovs`
+/// in order, so that it blocks at most once like a single `read`.
+///
+/// The data is read into a temporary buffer if there are several buffers, or
+/// only into the first one without the `alloc` feature.
+pub(crate) unsafe fn read_iovecs(
+    iovs: &[ctypes::iovec],
+    read: impl FnOnce(&mut [u8]) -> LinuxResult<usize>,
+) -> LinuxResult<usize> {
+    let Some(first) = iovs.iter().find(|iov| iov.iov_len > 0) else {
+        return Ok(0);
+    };
+    #[cfg(feature = "alloc")]
+    {
+        let total: usize = iovs.iter().map(|iov| iov.iov_len).sum();
+        if total > first.iov_len {
+            let mut buf = alloc::vec::Vec::new();
+            buf.try_reserve_exact(total)
+                .map_err(|_| LinuxError::ENOMEM)?;
+            buf.resize(total, 0);
+            let n = read(&mut buf)?;
+            return Ok(unsafe { scatter(iovs, &buf[..n]) });
+        }
+    }
+    let buf = unsafe { core::slice::from_raw_parts_mut(first.iov_base as *mut u8, first.iov_len) };
+    read(buf)
+}
+
+/// Copies `data` into the buffers of `iovs` in order, and returns the number
+/// of bytes copied.
+pub(crate) unsafe fn scatter(iovs: &[ctypes::iovec], mut data: &[u8]) -> usize {
+    let mut total = 0;
+    for iov in iovs.iter().filter(|iov| iov.iov_len > 0) {
+        if data.is_empty() {
+            break;
+        }
+        let n = iov.iov_len.min(data.len());
+        unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), iov.iov_base as *mut u8, n) };
+        data = &data[n..];
+        total += n;
+    }
+    total
+}
+
+/// Writes the buffers of `iovs` in order with `write`, which is given the
+/// buffer and the number of bytes written before it.
+///
+/// It stops at the first short write. An error is only returned if nothing
+/// has been written, otherwise the number of bytes written so far is
+/// returned.
+pub(crate) unsafe fn write_iovecs(
+    iovs: &[ctypes::iovec],
+    mut write: impl FnMut(&[u8], usize) -> LinuxResult<usize>,
+) -> LinuxResult<usize> {
+    let mut total = 0;
+    for iov in iovs.iter().filter(|iov| iov.iov_len > 0) {
+        let buf = unsafe { core::slice::from_raw_parts(iov.iov_base as *const u8, iov.iov_len) };
+        match write(buf, total) {
+            Ok(n) => {
+                total += n;
+                if n < buf.len() {
+                    break;
+                }
+            }
+            Err(e) if total == 0 => return Err(e),
+            Err(_) => break,
+        }
+    }
+    Ok(total)
+}
+
 /// Read data from the file indicated by `fd`.
 ///
 /// Return the read size if success.
@@ -17,16 +146,7 @@ pub fn sys_read(fd: c_int, buf: *mut c_void, count: usize) -> ctypes::ssize_t {
             return Err(LinuxError::EFAULT);
         }
         let dst = unsafe { core::slice::from_raw_parts_mut(buf as *mut u8, count) };
-        #[cfg(feature = "fd")]
-        {
-            Ok(get_file_like(fd)?.read(dst)? as ctypes::ssize_t)
-        }
-        #[cfg(not(feature = "fd"))]
-        match fd {
-            0 => Ok(super::stdio::stdin().read(dst)? as ctypes::ssize_t),
-            1 | 2 => Err(LinuxError::EPERM),
-            _ => Err(LinuxError::EBADF),
-        }
+        Ok(read_fd(fd, dst)? as ctypes::ssize_t)
     })
 }
 
@@ -40,33 +160,30 @@ pub fn sys_write(fd: c_int, buf: *const c_void, count: usize) -> ctypes::ssize_t
             return Err(LinuxError::EFAULT);
         }
         let src = unsafe { core::slice::from_raw_parts(buf as *const u8, count) };
-        #[cfg(feature = "fd")]
-        {
-            Ok(get_file_like(fd)?.write(src)? as ctypes::ssize_t)
-        }
-        #[cfg(not(feature = "fd"))]
-        match fd {
-            0 => Err(LinuxError::EPERM),
-            1 | 2 => Ok(super::stdio::stdout().write(src)? as ctypes::ssize_t),
-            _ => Err(LinuxError::EBADF),
-        }
+        Ok(write_fd(fd, src)? as ctypes::ssize_t)
+    })
+}
+
+/// Read data from the file indicated by `fd` into multiple buffers.
+///
+/// Return the total read size if success.
+pub unsafe fn sys_readv(fd: c_int, iov: *const ctypes::iovec, iocnt: c_int) -> ctypes::ssize_t {
+    debug!("sys_readv <= fd: {}, iocnt: {}", fd, iocnt);
+    syscall_body!(sys_readv, {
+        let iovs = unsafe { iovecs(iov, iocnt)? };
+        let n = unsafe { read_iovecs(iovs, |buf| read_fd(fd, buf))? };
+        Ok(n as ctypes::ssize_t)
     })
 }
 
 /// Write a vector.
+///
+/// Return the total written size if success.
 pub unsafe fn sys_writev(fd: c_int, iov: *const ctypes::iovec, iocnt: c_int) -> ctypes::ssize_t {
-    debug!("sys_writev <= fd: {}", fd);
+    debug!("sys_writev <= fd: {}, iocnt: {}", fd, iocnt);
     syscall_body!(sys_writev, {
-        if !(0..=1024).contains(&iocnt) {
-            return Err(LinuxError::EINVAL);
-        }
-
-        let iovs = unsafe { core::slice::from_raw_parts(iov, iocnt as usize) };
-        let mut ret = 0;
-        for iov in iovs.iter() {
-            ret += sys_write(fd, iov.iov_base, iov.iov_len);
-        }
-
-        Ok(ret)
+        let iovs = unsafe { iovecs(iov, iocnt)? };
+        let n = unsafe { write_iovecs(iovs, |buf, _| write_fd(fd, buf))? };
+        Ok(n as ctypes::ssize_t)
     })
 }
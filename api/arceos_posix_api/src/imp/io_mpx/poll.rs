@@ -0,0 +1,161 @@
+use core::ffi::c_int;
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{current_time, TimeValue};
+
+use crate::ctypes;
+use crate::imp::fd_ops::{get_file_like, poll_seq};
+use crate::imp::resources::nofile_limit;
+
+/// Fills `revents` of the entry, returns whether any event is reported.
+fn poll_one(pfd: &mut ctypes::pollfd) -> bool {
+    pfd.revents = 0;
+    if pfd.fd < 0 {
+        return false;
+    }
+    let events = pfd.events as u32;
+    let revents = match get_file_like(pfd.fd) {
+        Err(_) => ctypes::POLLNVAL,
+        Ok(f) => match f.poll() {
+            Err(e) => {
+                debug!("    error: {} {:?}", pfd.fd, e);
+                ctypes::POLLERR
+            }
+            Ok(state) => {
+                let mut revents = 0;
+                if state.readable {
+                    revents |= ctypes::POLLIN & events;
+                }
+                if state.writable {
+                    revents |= ctypes::POLLOUT & events;
+                }
+                // `POLLHUP` is always reported, even if it is not requested.
+                if state.hangup {
+                    revents |= ctypes::POLLHUP;
+                }
+                revents
+            }
+        },
+    };
+    pfd.revents = revents as _;
+    revents != 0
+}
+
+fn poll_all(fds: &mut [ctypes::pollfd]) -> usize {
+    fds.iter_mut().filter(|pfd| poll_one(pfd)).count()
+}
+
+/// Whether changes of the entry are announced by `notify_pollers`.
+#[cfg(all(feature = "multitask", feature = "irq"))]
+fn is_notified(pfd: &ctypes::pollfd) -> bool {
+    // Closed files are reported without waiting.
+    pfd.fd < 0 || get_file_like(pfd.fd).map_or(true, |f| f.notifies_pollers())
+}
+
+/// Sleeps until the state of files may have changed after [`poll_seq`]
+/// returned `seq`, or until `deadline`.
+///
+/// It only yields if some of the files do not announce their changes.
+fn wait(fds: &[ctypes::pollfd], seq: u64, deadline: Option<TimeValue>) -> LinuxResult {
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    if fds.iter().all(is_notified) {
+        return crate::imp::fd_ops::wait_pollers(seq, deadline);
+    }
+    let _ = (fds, seq, deadline);
+    #[cfg(feature = "signal")]
+    if axtask::current().has_unblocked_signals() {
+        return Err(LinuxError::EINTR);
+    }
+    crate::sys_sched_yield();
+    Ok(())
+}
+
+fn do_poll(fds: &mut [ctypes::pollfd], deadline: Option<TimeValue>) -> LinuxResult<usize> {
+    loop {
+        #[cfg(feature = "net")]
+        axnet::poll_interfaces();
+        let seq = poll_seq();
+        let res = poll_all(fds);
+        if res > 0 {
+            return Ok(res);
+        }
+
+        if deadline.map_or(false, |ddl| current_time() >= ddl) {
+            debug!("    timeout!");
+            return Ok(0);
+        }
+        wait(fds, seq, deadline)?;
+    }
+}
+
+unsafe fn pollfds<'a>(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+) -> LinuxResult<&'a mut [ctypes::pollfd]> {
+    if nfds == 0 {
+        return Ok(&mut []);
+    }
+    if fds.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    let nfds = nfds as usize;
+    if nfds > nofile_limit() {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(core::slice::from_raw_parts_mut(fds, nfds))
+}
+
+/// Wait for some event on a set of file descriptors.
+///
+/// `timeout` is in milliseconds, a negative value means an infinite timeout.
+///
+/// Returns the number of entries with non-zero `revents`, or 0 on timeout.
+pub unsafe fn sys_poll(fds: *mut ctypes::pollfd, nfds: ctypes::nfds_t, timeout: c_int) -> c_int {
+    debug!(
+        "sys_poll <= fds: {:#x}, nfds: {}, timeout: {}",
+        fds as usize, nfds, timeout
+    );
+    syscall_body!(sys_poll, {
+        let fds = unsafe { pollfds(fds, nfds)? };
+        let deadline = (!timeout.is_negative())
+            .then(|| current_time() + Duration::from_millis(timeout as u64));
+        do_poll(fds, deadline)
+    })
+}
+
+/// Like [`sys_poll`], but the timeout is given as a `timespec`, and `NULL`
+/// means an infinite timeout.
+///
+/// The blocked signals are replaced by `sigmask` during the wait if it is not
+/// `NULL`. Without the `signal` feature, there are no signals to block, so it
+/// is ignored.
+pub unsafe fn sys_ppoll(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+    timeout: *const ctypes::timespec,
+    sigmask: *const ctypes::sigset_t,
+) -> c_int {
+    debug!(
+        "sys_ppoll <= fds: {:#x}, nfds: {}, timeout: {:#x}",
+        fds as usize, nfds, timeout as usize
+    );
+    syscall_body!(sys_ppoll, {
+        let fds = unsafe { pollfds(fds, nfds)? };
+        let deadline = match unsafe { timeout.as_ref() } {
+            Some(ts) if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) => {
+                return Err(LinuxError::EINVAL);
+            }
+            Some(ts) => Some(current_time() + Duration::from(*ts)),
+            None => None,
+        };
+        let sigmask = unsafe { sigmask.as_ref() };
+        #[cfg(feature = "signal")]
+        return crate::imp::signal::with_sigmask(sigmask, || do_poll(fds, deadline));
+        #[cfg(not(feature = "signal"))]
+        {
+            let _ = sigmask;
+            do_poll(fds, deadline)
+        }
+    })
+}
@@ -0,0 +1,530 @@
+//! Memory mappings in the kernel address space.
+//!
+//! ArceOS runs everything in a single address space, so mappings are created
+//! in the kernel page table, inside a dedicated window above the linear
+//! mapping of physical memory. Every page is backed by a frame from `axalloc`
+//! as soon as it is mapped.
+//!
+//! The page table is shared by all CPUs, so changes to existing mappings are
+//! shot down on all of them before the old frames are freed. Configurations
+//! that can not do that (`smp` and `multitask` without `irq`) refuse to map
+//! anything.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::ffi::{c_int, c_void};
+
+use axalloc::global_allocator;
+use axerrno::{LinuxError, LinuxResult};
+use axhal::mem::{phys_to_virt, virt_to_phys, PhysAddr, VirtAddr, PAGE_SIZE_4K};
+use axhal::paging::MappingFlags;
+use axruntime::{flush_tlb_range, kernel_page_table, TLB_SHOOTDOWN_SUPPORTED};
+use axsync::Mutex;
+
+use crate::ctypes;
+
+/// Start of the virtual address window for memory mappings.
+const MMAP_BASE: usize = axconfig::PHYS_VIRT_OFFSET + 0x20_0000_0000;
+/// End of the virtual address window for memory mappings.
+const MMAP_END: usize = MMAP_BASE + 0x10_0000_0000;
+
+/// A mapped area, keyed by its start address in [`MMAP_AREAS`].
+struct MmapArea {
+    flags: MappingFlags,
+    /// Physical frames backing each page of the area.
+    frames: Vec<PhysAddr>,
+}
+
+static MMAP_AREAS: Mutex<BTreeMap<usize, MmapArea>> = Mutex::new(BTreeMap::new());
+
+impl MmapArea {
+    fn size(&self) -> usize {
+        self.frames.len() * PAGE_SIZE_4K
+    }
+
+    /// Maps the area at `start` in the kernel page table. On failure, the
+    /// pages mapped so far are unmapped again.
+    fn map(&self, start: usize) -> LinuxResult {
+        // `PROT_NONE` pages stay out of the page table.
+        if self.flags.is_empty() {
+            return Ok(());
+        }
+        let mut pt = kernel_page_table().lock();
+        for (i, &paddr) in self.frames.iter().enumerate() {
+            let vaddr = VirtAddr::from(start + i * PAGE_SIZE_4K);
+            if pt
+                .map_region(vaddr, paddr, PAGE_SIZE_4K, self.flags, false)
+                .is_err()
+            {
+                drop(pt);
+                unmap_pages(start, i);
+                return Err(LinuxError::ENOMEM);
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes the mapping flags of the area at `start`.
+    ///
+    /// Only mapping a `PROT_NONE` area may fail, which leaves it unchanged.
+    fn protect(&mut self, start: usize, flags: MappingFlags) -> LinuxResult {
+        if self.flags.is_empty() || flags.is_empty() {
+            unmap_pages(start, self.frames.len());
+            let old_flags = core::mem::replace(&mut self.flags, flags);
+            return self.map(start).inspect_err(|_| self.flags = old_flags);
+        }
+        let mut pt = kernel_page_table().lock();
+        let res = (0..self.frames.len()).try_for_each(|i| {
+            let vaddr = VirtAddr::from(start + i * PAGE_SIZE_4K);
+            pt.update(vaddr, None, Some(flags))
+                .map(|_| ())
+                .map_err(|_| LinuxError::EFAULT)
+        });
+        drop(pt);
+        flush_tlb_range(start.into(), self.size());
+        res?;
+        self.flags = flags;
+        Ok(())
+    }
+}
+
+/// Removes `num_pages` pages starting at `start` from the kernel page table,
+/// skipping those that are not mapped.
+///
+/// When it returns, no CPU can access the pages anymore.
+fn unmap_pages(start: usize, num_pages: usize) {
+    let mut pt = kernel_page_table().lock();
+    for i in 0..num_pages {
+        pt.unmap(VirtAddr::from(start + i * PAGE_SIZE_4K)).ok();
+    }
+    drop(pt);
+    flush_tlb_range(start.into(), num_pages * PAGE_SIZE_4K);
+}
+
+/// Allocates `num_pages` zeroed frames, which are not necessarily contiguous.
+///
+/// The frames are charged against the `RLIMIT_DATA` and `RLIMIT_AS` limits.
+fn alloc_frames(num_pages: usize) -> LinuxResult<Vec<PhysAddr>> {
+    axalloc::app_charge(num_pages * PAGE_SIZE_4K).map_err(|_| LinuxError::ENOMEM)?;
+    let mut frames = Vec::with_capacity(num_pages);
+    for _ in 0..num_pages {
+        match global_allocator().alloc_pages(1, PAGE_SIZE_4K) {
+            Ok(vaddr) => {
+                unsafe { core::ptr::write_bytes(vaddr as *mut u8, 0, PAGE_SIZE_4K) };
+                frames.push(virt_to_phys(vaddr.into()));
+            }
+            Err(_) => {
+                axalloc::app_uncharge((num_pages - frames.len()) * PAGE_SIZE_4K);
+                dealloc_frames(&frames);
+                return Err(LinuxError::ENOMEM);
+            }
+        }
+    }
+    Ok(frames)
+}
+
+fn dealloc_frames(frames: &[PhysAddr]) {
+    for &paddr in frames {
+        global_allocator().dealloc_pages(phys_to_virt(paddr).as_usize(), 1);
+    }
+    axalloc::app_uncharge(frames.len() * PAGE_SIZE_4K);
+}
+
+/// Fills the frames with the content of the file `fd` starting at `offset`.
+/// Bytes beyond the end of the file are left zeroed.
+#[cfg(feature = "fs")]
+fn read_file(fd: c_int, offset: u64, frames: &[PhysAddr]) -> LinuxResult {
+    let file = super::fs::File::from_fd(fd)?;
+    let mut offset = offset;
+    for &paddr in frames {
+        let buf = unsafe {
+            core::slice::from_raw_parts_mut(phys_to_virt(paddr).as_mut_ptr(), PAGE_SIZE_4K)
+        };
+        let mut pos = 0;
+        while pos < buf.len() {
+            let n = file.read_at(offset + pos as u64, &mut buf[pos..])?;
+            if n == 0 {
+                return Ok(());
+            }
+            pos += n;
+        }
+        offset += PAGE_SIZE_4K as u64;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "fs"))]
+fn read_file(_fd: c_int, _offset: u64, _frames: &[PhysAddr]) -> LinuxResult {
+    Err(LinuxError::EBADF)
+}
+
+fn page_align_up(len: usize) -> LinuxResult<usize> {
+    len.checked_add(PAGE_SIZE_4K - 1)
+        .map(|len| len & !(PAGE_SIZE_4K - 1))
+        .ok_or(LinuxError::ENOMEM)
+}
+
+fn prot_to_flags(prot: c_int) -> LinuxResult<MappingFlags> {
+    let prot = prot as u32;
+    if prot & !(ctypes::PROT_READ | ctypes::PROT_WRITE | ctypes::PROT_EXEC) != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let mut flags = MappingFlags::empty();
+    if prot & ctypes::PROT_READ != 0 {
+        flags |= MappingFlags::READ;
+    }
+    // Write-only pages can not be expressed on all architectures.
+    if prot & ctypes::PROT_WRITE != 0 {
+        flags |= MappingFlags::READ | MappingFlags::WRITE;
+    }
+    if prot & ctypes::PROT_EXEC != 0 {
+        flags |= MappingFlags::EXECUTE;
+    }
+    Ok(flags)
+}
+
+/// Returns whether `[start, start + size)` is inside the window.
+fn in_window(start: usize, size: usize) -> bool {
+    start >= MMAP_BASE && start.checked_add(size).is_some_and(|end| end <= MMAP_END)
+}
+
+/// Returns whether `[start, start + size)` is inside the window and does not
+/// overlap any area.
+fn is_free(areas: &BTreeMap<usize, MmapArea>, start: usize, size: usize) -> bool {
+    let end = start.wrapping_add(size);
+    in_window(start, size)
+        && areas
+            .range(..end)
+            .next_back()
+            .map_or(true, |(&s, area)| s + area.size() <= start)
+}
+
+/// Finds a free range of `size` bytes, `hint` is used if it is available.
+fn find_free(areas: &BTreeMap<usize, MmapArea>, hint: usize, size: usize) -> Option<usize> {
+    if hint != 0 && is_free(areas, hint, size) {
+        return Some(hint);
+    }
+    let mut start = MMAP_BASE;
+    for (&area_start, area) in areas {
+        if area_start - start >= size {
+            return Some(start);
+        }
+        start = area_start + area.size();
+    }
+    (MMAP_END - start >= size).then_some(start)
+}
+
+/// Splits the area containing `addr`, so that `addr` becomes the start of an
+/// area.
+fn split_at(areas: &mut BTreeMap<usize, MmapArea>, addr: usize) {
+    if let Some((&start, area)) = areas.range_mut(..addr).next_back() {
+        if start + area.size() > addr {
+            let frames = area.frames.split_off((addr - start) / PAGE_SIZE_4K);
+            let flags = area.flags;
+            areas.insert(addr, MmapArea { flags, frames });
+        }
+    }
+}
+
+/// Takes the areas in `[start, end)` out of `areas` and unmaps them, but
+/// keeps their frames, so that they can still be put back with [`restore`].
+fn take_range(
+    areas: &mut BTreeMap<usize, MmapArea>,
+    start: usize,
+    end: usize,
+) -> BTreeMap<usize, MmapArea> {
+    split_at(areas, start);
+    split_at(areas, end);
+    let mut taken = areas.split_off(&start);
+    areas.append(&mut taken.split_off(&end));
+    for (&start, area) in &taken {
+        unmap_pages(start, area.frames.len());
+    }
+    taken
+}
+
+/// Maps the areas taken by [`take_range`] again.
+///
+/// The page table entries were only cleared, so this does not allocate and
+/// can not fail.
+fn restore(areas: &mut BTreeMap<usize, MmapArea>, taken: BTreeMap<usize, MmapArea>) {
+    for (start, area) in taken {
+        area.map(start).ok();
+        areas.insert(start, area);
+    }
+}
+
+/// Unmaps all pages in `[start, end)`.
+fn unmap_range(areas: &mut BTreeMap<usize, MmapArea>, start: usize, end: usize) {
+    for (_, area) in take_range(areas, start, end) {
+        dealloc_frames(&area.frames);
+    }
+}
+
+/// Creates a new mapping of `len` bytes.
+///
+/// Anonymous mappings are filled with zeros. For file mappings, the content
+/// of the file `fd` starting at `off` is copied into the mapping, so changes
+/// are never written back to the file. Thus shared file mappings can only be
+/// read-only.
+///
+/// Returns the start address of the mapping, or a negative error number.
+pub unsafe fn sys_mmap(
+    addr: *mut c_void,
+    len: ctypes::size_t,
+    prot: c_int,
+    flags: c_int,
+    fd: c_int,
+    off: ctypes::off_t,
+) -> *mut c_void {
+    debug!(
+        "sys_mmap <= addr: {:#x}, len: {:#x}, prot: {:#x}, flags: {:#x}, fd: {}, off: {:#x}",
+        addr as usize, len, prot, flags, fd, off
+    );
+    syscall_body!(sys_mmap, {
+        let addr = addr as usize;
+        let flags = flags as u32;
+        let map_flags = prot_to_flags(prot)?;
+        if len == 0 || off < 0 || off as usize % PAGE_SIZE_4K != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let shared = match flags & ctypes::MAP_TYPE {
+            ctypes::MAP_PRIVATE => false,
+            ctypes::MAP_SHARED | ctypes::MAP_SHARED_VALIDATE => true,
+            _ => return Err(LinuxError::EINVAL),
+        };
+        let fixed = flags & ctypes::MAP_FIXED != 0;
+        if fixed && addr % PAGE_SIZE_4K != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let anonymous = flags & ctypes::MAP_ANONYMOUS != 0;
+        if !anonymous && shared && map_flags.contains(MappingFlags::WRITE) {
+            return Err(LinuxError::ENODEV);
+        }
+        if !TLB_SHOOTDOWN_SUPPORTED {
+            return Err(LinuxError::ENOSYS);
+        }
+
+        let size = page_align_up(len as usize)?;
+        if fixed && !in_window(addr, size) {
+            return Err(LinuxError::ENOMEM);
+        }
+        let frames = alloc_frames(size / PAGE_SIZE_4K)?;
+        if !anonymous {
+            if let Err(e) = read_file(fd, off as u64, &frames) {
+                dealloc_frames(&frames);
+                return Err(e);
+            }
+        }
+        let area = MmapArea {
+            flags: map_flags,
+            frames,
+        };
+
+        let mut areas = MMAP_AREAS.lock();
+        // Everything that can fail is done before the old mappings are
+        // replaced, which are only freed once the new one is in place.
+        let (start, replaced) = if fixed {
+            (addr, take_range(&mut areas, addr, addr + size))
+        } else {
+            match find_free(&areas, addr & !(PAGE_SIZE_4K - 1), size) {
+                Some(start) => (start, BTreeMap::new()),
+                None => {
+                    dealloc_frames(&area.frames);
+                    return Err(LinuxError::ENOMEM);
+                }
+            }
+        };
+        if let Err(e) = area.map(start) {
+            dealloc_frames(&area.frames);
+            restore(&mut areas, replaced);
+            return Err(e);
+        }
+        for (_, old) in replaced {
+            dealloc_frames(&old.frames);
+        }
+        areas.insert(start, area);
+        Ok(start)
+    })
+}
+
+/// Removes the mappings of all pages in `[addr, addr + len)`.
+pub unsafe fn sys_munmap(addr: *mut c_void, len: ctypes::size_t) -> c_int {
+    debug!("sys_munmap <= addr: {:#x}, len: {:#x}", addr as usize, len);
+    syscall_body!(sys_munmap, {
+        let addr = addr as usize;
+        if len == 0 || addr % PAGE_SIZE_4K != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let end = addr
+            .checked_add(page_align_up(len as usize)?)
+            .ok_or(LinuxError::EINVAL)?;
+        unmap_range(&mut MMAP_AREAS.lock(), addr, end);
+        Ok(0)
+    })
+}
+
+/// Changes the access protections of all pages in `[addr, addr + len)`.
+///
+/// Fails with `ENOMEM` if some of the pages are not mapped, or page tables
+/// can not be allocated for them. Nothing is changed on failure.
+pub unsafe fn sys_mprotect(addr: *mut c_void, len: ctypes::size_t, prot: c_int) -> c_int {
+    debug!(
+        "sys_mprotect <= addr: {:#x}, len: {:#x}, prot: {:#x}",
+        addr as usize, len, prot
+    );
+    syscall_body!(sys_mprotect, {
+        let addr = addr as usize;
+        let flags = prot_to_flags(prot)?;
+        if addr % PAGE_SIZE_4K != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let end = addr
+            .checked_add(page_align_up(len as usize)?)
+            .ok_or(LinuxError::ENOMEM)?;
+
+        let mut areas = MMAP_AREAS.lock();
+        // Check that the whole range is mapped before changing anything.
+        let mut covered = addr;
+        if let Some((&start, area)) = areas.range(..=addr).next_back() {
+            if start + area.size() > addr {
+                covered = start + area.size();
+            }
+        }
+        while covered < end {
+            match areas.get(&covered) {
+                Some(area) => covered += area.size(),
+                None => return Err(LinuxError::ENOMEM),
+            }
+        }
+
+        split_at(&mut areas, addr);
+        split_at(&mut areas, end);
+        // Only mapping `PROT_NONE` areas may fail, so do that first, and undo
+        // it on failure to leave the whole range unchanged.
+        let (none, mapped): (Vec<usize>, Vec<usize>) = areas
+            .range(addr..end)
+            .map(|(&start, _)| start)
+            .partition(|start| areas[start].flags.is_empty());
+        for (i, &start) in none.iter().enumerate() {
+            if let Err(e) = areas.get_mut(&start).unwrap().protect(start, flags) {
+                for &start in &none[..i] {
+                    let area = areas.get_mut(&start).unwrap();
+                    area.protect(start, MappingFlags::empty()).ok();
+                }
+                return Err(e);
+            }
+        }
+        for start in mapped {
+            areas.get_mut(&start).unwrap().protect(start, flags)?;
+        }
+        Ok(0)
+    })
+}
+
+/// Expands or shrinks the mapping at `[old_addr, old_addr + old_size)` to
+/// `new_size` bytes, and moves it if needed and allowed by `flags`.
+///
+/// `new_addr` is only used if `flags` contains `MREMAP_FIXED`.
+///
+/// Returns the start address of the new mapping, or a negative error number.
+pub unsafe fn sys_mremap(
+    old_addr: *mut c_void,
+    old_size: ctypes::size_t,
+    new_size: ctypes::size_t,
+    flags: c_int,
+    new_addr: *mut c_void,
+) -> *mut c_void {
+    debug!(
+        "sys_mremap <= old_addr: {:#x}, old_size: {:#x}, new_size: {:#x}, flags: {:#x}, new_addr: {:#x}",
+        old_addr as usize, old_size, new_size, flags, new_addr as usize
+    );
+    syscall_body!(sys_mremap, {
+        let old_addr = old_addr as usize;
+        let new_addr = new_addr as usize;
+        let flags = flags as u32;
+        let may_move = flags & ctypes::MREMAP_MAYMOVE != 0;
+        let fixed = flags & ctypes::MREMAP_FIXED != 0;
+        if old_addr % PAGE_SIZE_4K != 0
+            || old_size == 0
+            || new_size == 0
+            || flags & !(ctypes::MREMAP_MAYMOVE | ctypes::MREMAP_FIXED) != 0
+            || (fixed && (!may_move || new_addr % PAGE_SIZE_4K != 0))
+        {
+            return Err(LinuxError::EINVAL);
+        }
+        let old_size = page_align_up(old_size as usize)?;
+        let new_size = page_align_up(new_size as usize)?;
+        let old_end = old_addr.checked_add(old_size).ok_or(LinuxError::EFAULT)?;
+
+        let mut areas = MMAP_AREAS.lock();
+        // The old range must be inside a single area.
+        match areas.range(..=old_addr).next_back() {
+            Some((&start, area)) if start + area.size() >= old_end => {}
+            _ => return Err(LinuxError::EFAULT),
+        }
+        if fixed && new_addr < old_end && old_addr < new_addr.saturating_add(new_size) {
+            return Err(LinuxError::EINVAL);
+        }
+        split_at(&mut areas, old_addr);
+        split_at(&mut areas, old_end);
+
+        let extra_pages = new_size.saturating_sub(old_size) / PAGE_SIZE_4K;
+        let target = if fixed {
+            if !in_window(new_addr, new_size) {
+                return Err(LinuxError::ENOMEM);
+            }
+            new_addr
+        } else if new_size <= old_size {
+            unmap_range(&mut areas, old_addr + new_size, old_end);
+            return Ok(old_addr);
+        } else if is_free(&areas, old_end, new_size - old_size) {
+            // Expand in place.
+            let extra = MmapArea {
+                flags: areas[&old_addr].flags,
+                frames: alloc_frames(extra_pages)?,
+            };
+            if let Err(e) = extra.map(old_end) {
+                dealloc_frames(&extra.frames);
+                return Err(e);
+            }
+            let area = areas.get_mut(&old_addr).unwrap();
+            area.frames.extend(extra.frames);
+            return Ok(old_addr);
+        } else if may_move {
+            find_free(&areas, 0, new_size).ok_or(LinuxError::ENOMEM)?
+        } else {
+            return Err(LinuxError::ENOMEM);
+        };
+
+        // Move the frames to the new place, so the content is preserved. As
+        // in `sys_mmap`, nothing is freed before the new mapping is in place.
+        let extra = alloc_frames(extra_pages)?;
+        let replaced = if fixed {
+            take_range(&mut areas, target, target + new_size)
+        } else {
+            BTreeMap::new()
+        };
+        let mut area = areas.remove(&old_addr).unwrap();
+        unmap_pages(old_addr, area.frames.len());
+        let tail = area
+            .frames
+            .split_off(area.frames.len().min(new_size / PAGE_SIZE_4K));
+        let old_len = area.frames.len();
+        area.frames.extend(extra);
+        if let Err(e) = area.map(target) {
+            dealloc_frames(&area.frames[old_len..]);
+            area.frames.truncate(old_len);
+            area.frames.extend(tail);
+            area.map(old_addr).ok();
+            areas.insert(old_addr, area);
+            restore(&mut areas, replaced);
+            return Err(e);
+        }
+        dealloc_frames(&tail);
+        for (_, old) in replaced {
+            dealloc_frames(&old.frames);
+        }
+        areas.insert(target, area);
+        Ok(target)
+    })
+}
@@ -6,15 +6,25 @@ pub mod sys;
 pub mod task;
 pub mod time;
 
+#[cfg(feature = "eventfd")]
+pub mod eventfd;
 #[cfg(feature = "fd")]
 pub mod fd_ops;
 #[cfg(feature = "fs")]
 pub mod fs;
-#[cfg(any(feature = "select", feature = "epoll"))]
+#[cfg(any(feature = "select", feature = "poll", feature = "epoll"))]
 pub mod io_mpx;
+#[cfg(feature = "mmap")]
+pub mod mman;
 #[cfg(feature = "net")]
 pub mod net;
 #[cfg(feature = "pipe")]
 pub mod pipe;
+#[cfg(feature = "process")]
+pub mod process;
 #[cfg(feature = "multitask")]
 pub mod pthread;
+#[cfg(feature = "signal")]
+pub mod signal;
+#[cfg(feature = "timerfd")]
+pub mod timerfd;
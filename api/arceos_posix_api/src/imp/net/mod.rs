@@ -0,0 +1,1135 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::ffi::{c_char, c_int, c_void};
+use core::mem::size_of;
+use core::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axio::PollState;
+use axnet::{TcpSocket, UdpSocket};
+use axsync::Mutex;
+
+use self::unix::{UnixAddr, UnixSocket};
+use super::fd_ops::{add_file_like, close_file_like, FileLike};
+use super::io::{iovecs, read_iovecs, scatter, write_iovecs};
+use crate::ctypes;
+use crate::utils::char_ptr_to_str;
+
+mod unix;
+
+pub enum Socket {
+    Udp(Mutex<UdpSocket>),
+    Tcp(Mutex<TcpSocket>),
+    Unix(UnixSocket),
+}
+
+/// Keep-alive interval used when `SO_KEEPALIVE` is enabled, the same as the
+/// Linux default `tcp_keepalive_intvl`.
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(75);
+
+/// Socket options supported by [`sys_setsockopt`] and [`sys_getsockopt`].
+#[derive(Debug, Clone, Copy)]
+enum SockOpt {
+    ReuseAddr,
+    RecvBuf,
+    SendBuf,
+    KeepAlive,
+    RecvTimeout,
+    SendTimeout,
+    Linger,
+    Type,
+    Error,
+    NoDelay,
+    KeepInterval,
+}
+
+/// Value of a socket option, in the C representation.
+enum SockOptValue {
+    Int(c_int),
+    Timeval(ctypes::timeval),
+    Linger(ctypes::linger),
+}
+
+impl SockOpt {
+    fn from_raw(level: c_int, optname: c_int) -> LinuxResult<Self> {
+        let opt = match (level as u32, optname as u32) {
+            (ctypes::SOL_SOCKET, ctypes::SO_REUSEADDR) => Self::ReuseAddr,
+            (ctypes::SOL_SOCKET, ctypes::SO_RCVBUF) => Self::RecvBuf,
+            (ctypes::SOL_SOCKET, ctypes::SO_SNDBUF) => Self::SendBuf,
+            (ctypes::SOL_SOCKET, ctypes::SO_KEEPALIVE) => Self::KeepAlive,
+            (ctypes::SOL_SOCKET, ctypes::SO_RCVTIMEO) => Self::RecvTimeout,
+            (ctypes::SOL_SOCKET, ctypes::SO_SNDTIMEO) => Self::SendTimeout,
+            (ctypes::SOL_SOCKET, ctypes::SO_LINGER) => Self::Linger,
+            (ctypes::SOL_SOCKET, ctypes::SO_TYPE) => Self::Type,
+            (ctypes::SOL_SOCKET, ctypes::SO_ERROR) => Self::Error,
+            (ctypes::IPPROTO_TCP, ctypes::TCP_NODELAY) => Self::NoDelay,
+            // diff: smoltcp only has a single keep-alive interval
+            (ctypes::IPPROTO_TCP, ctypes::TCP_KEEPIDLE | ctypes::TCP_KEEPINTVL) => {
+                Self::KeepInterval
+            }
+            _ => return Err(LinuxError::ENOPROTOOPT),
+        };
+        Ok(opt)
+    }
+
+    /// Reads the option value from the user buffer.
+    unsafe fn read_value(
+        self,
+        optval: *const c_void,
+        optlen: ctypes::socklen_t,
+    ) -> LinuxResult<SockOptValue> {
+        unsafe fn read<T: Copy>(
+            optval: *const c_void,
+            optlen: ctypes::socklen_t,
+        ) -> LinuxResult<T> {
+            if optval.is_null() {
+                return Err(LinuxError::EFAULT);
+            }
+            if (optlen as usize) < size_of::<T>() {
+                return Err(LinuxError::EINVAL);
+            }
+            Ok((optval as *const T).read_unaligned())
+        }
+        Ok(match self {
+            Self::RecvTimeout | Self::SendTimeout => SockOptValue::Timeval(read(optval, optlen)?),
+            Self::Linger => SockOptValue::Linger(read(optval, optlen)?),
+            _ => SockOptValue::Int(read(optval, optlen)?),
+        })
+    }
+}
+
+impl SockOptValue {
+    /// Writes the option value to the user buffer, truncating it if the buffer
+    /// is too small.
+    unsafe fn write(self, optval: *mut c_void, optlen: *mut ctypes::socklen_t) -> LinuxResult {
+        unsafe fn write<T>(val: T, optval: *mut c_void, optlen: *mut ctypes::socklen_t) {
+            let len = (*optlen as usize).min(size_of::<T>());
+            core::ptr::copy_nonoverlapping(&val as *const T as *const u8, optval as *mut u8, len);
+            *optlen = len as _;
+        }
+        if optval.is_null() || optlen.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        match self {
+            Self::Int(val) => write(val, optval, optlen),
+            Self::Timeval(val) => write(val, optval, optlen),
+            Self::Linger(val) => write(val, optval, optlen),
+        }
+        Ok(())
+    }
+
+    fn as_int(&self) -> LinuxResult<c_int> {
+        match self {
+            Self::Int(val) => Ok(*val),
+            _ => Err(LinuxError::EINVAL),
+        }
+    }
+
+    fn as_timeout(&self) -> LinuxResult<Option<Duration>> {
+        match self {
+            Self::Timeval(tv) if tv.tv_sec < 0 || !(0..1_000_000).contains(&tv.tv_usec) => {
+                Err(LinuxError::EDOM)
+            }
+            Self::Timeval(tv) => Ok(Some(Duration::from(*tv)).filter(|d| !d.is_zero())),
+            _ => Err(LinuxError::EINVAL),
+        }
+    }
+
+    fn from_timeout(timeout: Option<Duration>) -> Self {
+        Self::Timeval(timeout.unwrap_or_default().into())
+    }
+}
+
+impl Socket {
+    fn add_to_fd_table(self) -> LinuxResult<c_int> {
+        super::fd_ops::add_file_like(Arc::new(self))
+    }
+
+    fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+        let f = super::fd_ops::get_file_like(fd)?;
+        f.into_any()
+            .downcast::<Self>()
+            .map_err(|_| LinuxError::EINVAL)
+    }
+
+    fn send(&self, buf: &[u8]) -> LinuxResult<usize> {
+        match self {
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().send(buf)?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().send(buf)?),
+            Socket::Unix(unixsocket) => unixsocket.send(buf),
+        }
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        match self {
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().recv_from(buf).map(|e| e.0)?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().recv(buf)?),
+            Socket::Unix(unixsocket) => unixsocket.recv(buf),
+        }
+    }
+
+    pub fn poll(&self) -> LinuxResult<PollState> {
+        match self {
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().poll()?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().poll()?),
+            Socket::Unix(unixsocket) => unixsocket.poll(),
+        }
+    }
+
+    fn local_addr(&self) -> LinuxResult<SocketAddr> {
+        match self {
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().local_addr()?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().local_addr()?),
+            // Addresses of Unix sockets are handled by the callers.
+            Socket::Unix(_) => Err(LinuxError::EAFNOSUPPORT),
+        }
+    }
+
+    fn peer_addr(&self) -> LinuxResult<SocketAddr> {
+        match self {
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().peer_addr()?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().peer_addr()?),
+            // Addresses of Unix sockets are handled by the callers.
+            Socket::Unix(_) => Err(LinuxError::EAFNOSUPPORT),
+        }
+    }
+
+    fn bind(&self, addr: SocketAddr) -> LinuxResult {
+        match self {
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().bind(addr)?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().bind(addr)?),
+            // Addresses of Unix sockets are handled by the callers.
+            Socket::Unix(_) => Err(LinuxError::EAFNOSUPPORT),
+        }
+    }
+
+    fn connect(&self, addr: SocketAddr) -> LinuxResult {
+        match self {
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().connect(addr)?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().connect(addr)?),
+            // Addresses of Unix sockets are handled by the callers.
+            Socket::Unix(_) => Err(LinuxError::EAFNOSUPPORT),
+        }
+    }
+
+    fn sendto(&self, buf: &[u8], addr: SocketAddr) -> LinuxResult<usize> {
+        match self {
+            // diff: must bind before sendto
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().send_to(buf, addr)?),
+            Socket::Tcp(_) => Err(LinuxError::EISCONN),
+            Socket::Unix(_) => Err(LinuxError::EAFNOSUPPORT),
+        }
+    }
+
+    fn recvfrom(&self, buf: &mut [u8]) -> LinuxResult<(usize, Option<SocketAddr>)> {
+        match self {
+            // diff: must bind before recvfrom
+            Socket::Udp(udpsocket) => Ok(udpsocket
+                .lock()
+                .recv_from(buf)
+                .map(|res| (res.0, Some(res.1)))?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().recv(buf).map(|res| (res, None))?),
+            Socket::Unix(_) => Err(LinuxError::EAFNOSUPPORT),
+        }
+    }
+
+    fn listen(&self) -> LinuxResult {
+        match self {
+            Socket::Udp(_) => Err(LinuxError::EOPNOTSUPP),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().listen()?),
+            Socket::Unix(unixsocket) => unixsocket.listen(),
+        }
+    }
+
+    fn accept(&self) -> LinuxResult<Socket> {
+        match self {
+            Socket::Udp(_) => Err(LinuxError::EOPNOTSUPP),
+            Socket::Tcp(tcpsocket) => Ok(Socket::Tcp(Mutex::new(tcpsocket.lock().accept()?))),
+            Socket::Unix(unixsocket) => Ok(Socket::Unix(unixsocket.accept()?)),
+        }
+    }
+
+    fn set_option(&self, opt: SockOpt, val: SockOptValue) -> LinuxResult {
+        match (self, opt) {
+            (Socket::Udp(udpsocket), SockOpt::ReuseAddr) => {
+                udpsocket.lock().set_reuse_address(val.as_int()? != 0)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::ReuseAddr) => {
+                tcpsocket.lock().set_reuse_address(val.as_int()? != 0)
+            }
+            (_, SockOpt::RecvBuf | SockOpt::SendBuf) => {
+                let size = val.as_int()?.max(0) as usize;
+                match (self, opt) {
+                    (Socket::Udp(udpsocket), SockOpt::RecvBuf) => {
+                        udpsocket.lock().set_recv_buffer_size(size)?
+                    }
+                    (Socket::Udp(udpsocket), _) => udpsocket.lock().set_send_buffer_size(size)?,
+                    (Socket::Tcp(tcpsocket), SockOpt::RecvBuf) => {
+                        tcpsocket.lock().set_recv_buffer_size(size)?
+                    }
+                    (Socket::Tcp(tcpsocket), _) => tcpsocket.lock().set_send_buffer_size(size)?,
+                    // diff: buffers of Unix sockets have fixed sizes
+                    (Socket::Unix(_), _) => {}
+                }
+            }
+            (Socket::Udp(udpsocket), SockOpt::RecvTimeout) => {
+                udpsocket.lock().set_recv_timeout(val.as_timeout()?)
+            }
+            (Socket::Udp(udpsocket), SockOpt::SendTimeout) => {
+                udpsocket.lock().set_send_timeout(val.as_timeout()?)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::RecvTimeout) => {
+                tcpsocket.lock().set_recv_timeout(val.as_timeout()?)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::SendTimeout) => {
+                tcpsocket.lock().set_send_timeout(val.as_timeout()?)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::KeepAlive) => {
+                let tcpsocket = tcpsocket.lock();
+                // keep the interval set by `TCP_KEEPINTVL` if already enabled
+                let interval = (val.as_int()? != 0)
+                    .then(|| tcpsocket.keep_alive().unwrap_or(DEFAULT_KEEP_ALIVE));
+                tcpsocket.set_keep_alive(interval)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::KeepInterval) => {
+                let secs = val.as_int()?;
+                if secs < 1 {
+                    return Err(LinuxError::EINVAL);
+                }
+                // diff: setting the interval also enables keep-alive
+                tcpsocket
+                    .lock()
+                    .set_keep_alive(Some(Duration::from_secs(secs as u64)))
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::Linger) => {
+                let linger = match val {
+                    SockOptValue::Linger(l) if l.l_onoff != 0 => {
+                        Some(Duration::from_secs(l.l_linger.max(0) as u64))
+                    }
+                    SockOptValue::Linger(_) => None,
+                    _ => return Err(LinuxError::EINVAL),
+                };
+                tcpsocket.lock().set_linger(linger)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::NoDelay) => {
+                tcpsocket.lock().set_nodelay(val.as_int()? != 0)
+            }
+            (_, SockOpt::Type | SockOpt::Error) => return Err(LinuxError::ENOPROTOOPT),
+            (Socket::Udp(_) | Socket::Unix(_), _) => return Err(LinuxError::ENOPROTOOPT),
+        }
+        Ok(())
+    }
+
+    fn get_option(&self, opt: SockOpt) -> LinuxResult<SockOptValue> {
+        let val = match (self, opt) {
+            (Socket::Udp(udpsocket), SockOpt::ReuseAddr) => {
+                SockOptValue::Int(udpsocket.lock().reuse_address() as _)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::ReuseAddr) => {
+                SockOptValue::Int(tcpsocket.lock().reuse_address() as _)
+            }
+            (Socket::Udp(udpsocket), SockOpt::RecvBuf) => {
+                SockOptValue::Int(udpsocket.lock().recv_buffer_size() as _)
+            }
+            (Socket::Udp(udpsocket), SockOpt::SendBuf) => {
+                SockOptValue::Int(udpsocket.lock().send_buffer_size() as _)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::RecvBuf) => {
+                SockOptValue::Int(tcpsocket.lock().recv_buffer_size() as _)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::SendBuf) => {
+                SockOptValue::Int(tcpsocket.lock().send_buffer_size() as _)
+            }
+            (Socket::Udp(udpsocket), SockOpt::RecvTimeout) => {
+                SockOptValue::from_timeout(udpsocket.lock().recv_timeout())
+            }
+            (Socket::Udp(udpsocket), SockOpt::SendTimeout) => {
+                SockOptValue::from_timeout(udpsocket.lock().send_timeout())
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::RecvTimeout) => {
+                SockOptValue::from_timeout(tcpsocket.lock().recv_timeout())
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::SendTimeout) => {
+                SockOptValue::from_timeout(tcpsocket.lock().send_timeout())
+            }
+            (Socket::Udp(_), SockOpt::Type) => SockOptValue::Int(ctypes::SOCK_DGRAM as _),
+            (Socket::Tcp(_), SockOpt::Type) => SockOptValue::Int(ctypes::SOCK_STREAM as _),
+            (Socket::Unix(unixsocket), SockOpt::Type) => {
+                SockOptValue::Int(unixsocket.socktype() as _)
+            }
+            (Socket::Unix(unixsocket), SockOpt::RecvBuf | SockOpt::SendBuf) => {
+                SockOptValue::Int(unixsocket.buffer_size() as _)
+            }
+            (_, SockOpt::Error) => SockOptValue::Int(0),
+            (Socket::Tcp(tcpsocket), SockOpt::KeepAlive) => {
+                SockOptValue::Int(tcpsocket.lock().keep_alive().is_some() as _)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::KeepInterval) => {
+                let interval = tcpsocket.lock().keep_alive().unwrap_or(DEFAULT_KEEP_ALIVE);
+                SockOptValue::Int(interval.as_secs() as _)
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::Linger) => {
+                let linger = tcpsocket.lock().linger();
+                SockOptValue::Linger(ctypes::linger {
+                    l_onoff: linger.is_some() as _,
+                    l_linger: linger.map_or(0, |l| l.as_secs() as _),
+                })
+            }
+            (Socket::Tcp(tcpsocket), SockOpt::NoDelay) => {
+                SockOptValue::Int(tcpsocket.lock().nodelay() as _)
+            }
+            (Socket::Udp(_) | Socket::Unix(_), _) => return Err(LinuxError::ENOPROTOOPT),
+        };
+        Ok(val)
+    }
+
+    fn shutdown(&self) -> LinuxResult {
+        match self {
+            Socket::Udp(udpsocket) => {
+                let udpsocket = udpsocket.lock();
+                udpsocket.peer_addr()?;
+                udpsocket.shutdown()?;
+                Ok(())
+            }
+
+            Socket::Tcp(tcpsocket) => {
+                let tcpsocket = tcpsocket.lock();
+                tcpsocket.peer_addr()?;
+                tcpsocket.shutdown()?;
+                Ok(())
+            }
+
+            Socket::Unix(unixsocket) => unixsocket.shutdown(),
+        }
+    }
+}
+
+impl FileLike for Socket {
+    fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        self.recv(buf)
+    }
+
+    fn write(&self, buf: &[u8]) -> LinuxResult<usize> {
+        self.send(buf)
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        // not really implemented
+        let st_mode = 0o140000 | 0o777u32; // S_IFSOCK | rwxrwxrwx
+        Ok(ctypes::stat {
+            st_ino: 1,
+            st_nlink: 1,
+            st_mode,
+            st_uid: 1000,
+            st_gid: 1000,
+            st_blksize: 4096,
+            ..Default::default()
+        })
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        self.poll()
+    }
+
+    fn set_nonblocking(&self, nonblock: bool) -> LinuxResult {
+        match self {
+            Socket::Udp(udpsocket) => udpsocket.lock().set_nonblocking(nonblock),
+            Socket::Tcp(tcpsocket) => tcpsocket.lock().set_nonblocking(nonblock),
+            Socket::Unix(unixsocket) => unixsocket.set_nonblocking(nonblock),
+        }
+        Ok(())
+    }
+
+    fn notifies_pollers(&self) -> bool {
+        match self {
+            Socket::Unix(_) => true,
+            // Otherwise, the sockets change only when the interfaces are polled.
+            #[cfg(all(feature = "multitask", feature = "irq"))]
+            _ => axnet::is_irq_driven(),
+            #[cfg(not(all(feature = "multitask", feature = "irq")))]
+            _ => false,
+        }
+    }
+}
+
+impl From<SocketAddrV4> for ctypes::sockaddr_in {
+    fn from(addr: SocketAddrV4) -> ctypes::sockaddr_in {
+        ctypes::sockaddr_in {
+            sin_family: ctypes::AF_INET as u16,
+            sin_port: addr.port().to_be(),
+            sin_addr: ctypes::in_addr {
+                // `s_addr` is stored as BE on all machines and the array is in BE order.
+                // So the native endian conversion method is used so that it's never swapped.
+                s_addr: u32::from_ne_bytes(addr.ip().octets()),
+            },
+            sin_zero: [0; 8],
+        }
+    }
+}
+
+impl From<ctypes::sockaddr_in> for SocketAddrV4 {
+    fn from(addr: ctypes::sockaddr_in) -> SocketAddrV4 {
+        SocketAddrV4::new(
+            Ipv4Addr::from(addr.sin_addr.s_addr.to_ne_bytes()),
+            u16::from_be(addr.sin_port),
+        )
+    }
+}
+
+fn into_sockaddr(addr: SocketAddr) -> (ctypes::sockaddr, ctypes::socklen_t) {
+    debug!("    Sockaddr: {}", addr);
+    match addr {
+        SocketAddr::V4(addr) => (
+            unsafe { *(&ctypes::sockaddr_in::from(addr) as *const _ as *const ctypes::sockaddr) },
+            size_of::<ctypes::sockaddr>() as _,
+        ),
+        SocketAddr::V6(_) => panic!("IPv6 is not supported"),
+    }
+}
+
+fn from_sockaddr(
+    addr: *const ctypes::sockaddr,
+    addrlen: ctypes::socklen_t,
+) -> LinuxResult<SocketAddr> {
+    if addr.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    if addrlen != size_of::<ctypes::sockaddr>() as _ {
+        return Err(LinuxError::EINVAL);
+    }
+
+    let mid = unsafe { *(addr as *const ctypes::sockaddr_in) };
+    if mid.sin_family != ctypes::AF_INET as u16 {
+        return Err(LinuxError::EINVAL);
+    }
+
+    let res = SocketAddr::V4(mid.into());
+    debug!("    load sockaddr:{:#x} => {:?}", addr as usize, res);
+    Ok(res)
+}
+
+/// Lets the network stack wake up the tasks in `poll` when the state of
+/// sockets changes. Installed when the first network socket is created.
+#[cfg(all(feature = "multitask", feature = "irq"))]
+fn install_event_hook() {
+    use core::sync::atomic::{AtomicBool, Ordering};
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+    if !INSTALLED.swap(true, Ordering::AcqRel) {
+        axnet::set_event_hook(super::fd_ops::notify_pollers);
+    }
+}
+
+/// Create an socket for communication.
+///
+/// `AF_INET` and `AF_UNIX` sockets are supported. `SOCK_NONBLOCK` can be
+/// or'ed to `socktype`, and `SOCK_CLOEXEC` is accepted but has no effect.
+///
+/// Return the socket file descriptor.
+pub fn sys_socket(domain: c_int, socktype: c_int, protocol: c_int) -> c_int {
+    debug!("sys_socket <= {} {} {}", domain, socktype, protocol);
+    let (domain, socktype, protocol) = (domain as u32, socktype as u32, protocol as u32);
+    syscall_body!(sys_socket, {
+        let flags = socktype & (ctypes::SOCK_NONBLOCK | ctypes::SOCK_CLOEXEC);
+        let socket = match (domain, socktype & !flags, protocol) {
+            (ctypes::AF_INET, ctypes::SOCK_STREAM, ctypes::IPPROTO_TCP)
+            | (ctypes::AF_INET, ctypes::SOCK_STREAM, 0) => {
+                Socket::Tcp(Mutex::new(TcpSocket::new()))
+            }
+            (ctypes::AF_INET, ctypes::SOCK_DGRAM, ctypes::IPPROTO_UDP)
+            | (ctypes::AF_INET, ctypes::SOCK_DGRAM, 0) => Socket::Udp(Mutex::new(UdpSocket::new())),
+            (ctypes::AF_UNIX, ctypes::SOCK_STREAM, 0) => Socket::Unix(UnixSocket::new_stream()),
+            (ctypes::AF_UNIX, ctypes::SOCK_DGRAM, 0) => Socket::Unix(UnixSocket::new_dgram()),
+            _ => return Err(LinuxError::EINVAL),
+        };
+        #[cfg(all(feature = "multitask", feature = "irq"))]
+        if matches!(socket, Socket::Tcp(_) | Socket::Udp(_)) {
+            install_event_hook();
+        }
+        if flags & ctypes::SOCK_NONBLOCK != 0 {
+            socket.set_nonblocking(true)?;
+        }
+        socket.add_to_fd_table()
+    })
+}
+
+/// Create a pair of connected `AF_UNIX` sockets, and store their file
+/// descriptors in `fds`.
+///
+/// The flags of `socktype` are the same as [`sys_socket`].
+///
+/// Return 0 if success.
+pub fn sys_socketpair(domain: c_int, socktype: c_int, protocol: c_int, fds: &mut [c_int]) -> c_int {
+    debug!(
+        "sys_socketpair <= {} {} {} {:#x}",
+        domain,
+        socktype,
+        protocol,
+        fds.as_ptr() as usize
+    );
+    let (domain, socktype, protocol) = (domain as u32, socktype as u32, protocol as u32);
+    syscall_body!(sys_socketpair, {
+        if fds.len() != 2 {
+            return Err(LinuxError::EFAULT);
+        }
+        if domain != ctypes::AF_UNIX {
+            return Err(LinuxError::EOPNOTSUPP);
+        }
+        let flags = socktype & (ctypes::SOCK_NONBLOCK | ctypes::SOCK_CLOEXEC);
+        let (a, b) = match (socktype & !flags, protocol) {
+            (ctypes::SOCK_STREAM, 0) => UnixSocket::pair(true),
+            (ctypes::SOCK_DGRAM, 0) => UnixSocket::pair(false),
+            _ => return Err(LinuxError::EINVAL),
+        };
+        #[cfg(all(feature = "multitask", feature = "irq"))]
+        if matches!(socket, Socket::Tcp(_) | Socket::Udp(_)) {
+            install_event_hook();
+        }
+        if flags & ctypes::SOCK_NONBLOCK != 0 {
+            a.set_nonblocking(true);
+            b.set_nonblocking(true);
+        }
+
+        let fd0 = add_file_like(Arc::new(Socket::Unix(a)))?;
+        let fd1 = add_file_like(Arc::new(Socket::Unix(b))).inspect_err(|_| {
+            close_file_like(fd0).ok();
+        })?;
+        fds[0] = fd0;
+        fds[1] = fd1;
+        Ok(0)
+    })
+}
+
+/// Bind a address to a socket.
+///
+/// Return 0 if success.
+pub fn sys_bind(
+    socket_fd: c_int,
+    socket_addr: *const ctypes::sockaddr,
+    addrlen: ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_bind <= {} {:#x} {}",
+        socket_fd, socket_addr as usize, addrlen
+    );
+    syscall_body!(sys_bind, {
+        let socket = Socket::from_fd(socket_fd)?;
+        match &*socket {
+            Socket::Unix(unixsocket) => {
+                unixsocket.bind(unsafe { UnixAddr::read(socket_addr, addrlen)? })?
+            }
+            _ => socket.bind(from_sockaddr(socket_addr, addrlen)?)?,
+        }
+        Ok(0)
+    })
+}
+
+/// Connects the socket to the address specified.
+///
+/// Return 0 if success.
+pub fn sys_connect(
+    socket_fd: c_int,
+    socket_addr: *const ctypes::sockaddr,
+    addrlen: ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_connect <= {} {:#x} {}",
+        socket_fd, socket_addr as usize, addrlen
+    );
+    syscall_body!(sys_connect, {
+        let socket = Socket::from_fd(socket_fd)?;
+        match &*socket {
+            Socket::Unix(unixsocket) => {
+                unixsocket.connect(unsafe { UnixAddr::read(socket_addr, addrlen)? })?
+            }
+            _ => socket.connect(from_sockaddr(socket_addr, addrlen)?)?,
+        }
+        Ok(0)
+    })
+}
+
+/// Send a message on a socket to the address specified.
+///
+/// Return the number of bytes sent if success.
+pub fn sys_sendto(
+    socket_fd: c_int,
+    buf_ptr: *const c_void,
+    len: ctypes::size_t,
+    flag: c_int, // currently not used
+    socket_addr: *const ctypes::sockaddr,
+    addrlen: ctypes::socklen_t,
+) -> ctypes::ssize_t {
+    debug!(
+        "sys_sendto <= {} {:#x} {} {} {:#x} {}",
+        socket_fd, buf_ptr as usize, len, flag, socket_addr as usize, addrlen
+    );
+    syscall_body!(sys_sendto, {
+        if buf_ptr.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let socket = Socket::from_fd(socket_fd)?;
+        let buf = unsafe { core::slice::from_raw_parts(buf_ptr as *const u8, len) };
+        match &*socket {
+            Socket::Unix(unixsocket) => {
+                unixsocket.sendto(buf, unsafe { UnixAddr::read(socket_addr, addrlen)? })
+            }
+            _ => socket.sendto(buf, from_sockaddr(socket_addr, addrlen)?),
+        }
+    })
+}
+
+/// Send a message on a socket to the address connected.
+///
+/// Return the number of bytes sent if success.
+pub fn sys_send(
+    socket_fd: c_int,
+    buf_ptr: *const c_void,
+    len: ctypes::size_t,
+    flag: c_int, // currently not used
+) -> ctypes::ssize_t {
+    debug!(
+        "sys_sendto <= {} {:#x} {} {}",
+        socket_fd, buf_ptr as usize, len, flag
+    );
+    syscall_body!(sys_send, {
+        if buf_ptr.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let buf = unsafe { core::slice::from_raw_parts(buf_ptr as *const u8, len) };
+        Socket::from_fd(socket_fd)?.send(buf)
+    })
+}
+
+/// Receive a message on a socket and get its source address.
+///
+/// Return the number of bytes received if success.
+pub unsafe fn sys_recvfrom(
+    socket_fd: c_int,
+    buf_ptr: *mut c_void,
+    len: ctypes::size_t,
+    flag: c_int, // currently not used
+    socket_addr: *mut ctypes::sockaddr,
+    addrlen: *mut ctypes::socklen_t,
+) -> ctypes::ssize_t {
+    debug!(
+        "sys_recvfrom <= {} {:#x} {} {} {:#x} {:#x}",
+        socket_fd, buf_ptr as usize, len, flag, socket_addr as usize, addrlen as usize
+    );
+    syscall_body!(sys_recvfrom, {
+        if buf_ptr.is_null() || socket_addr.is_null() || addrlen.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let socket = Socket::from_fd(socket_fd)?;
+        let buf = unsafe { core::slice::from_raw_parts_mut(buf_ptr as *mut u8, len) };
+
+        if let Socket::Unix(unixsocket) = &*socket {
+            let (len, addr) = unixsocket.recvfrom(buf)?;
+            if let Some(addr) = addr {
+                unsafe { addr.write(socket_addr, addrlen)? };
+            }
+            return Ok(len);
+        }
+        let res = socket.recvfrom(buf)?;
+        if let Some(addr) = res.1 {
+            unsafe {
+                (*socket_addr, *addrlen) = into_sockaddr(addr);
+            }
+        }
+        Ok(res.0)
+    })
+}
+
+/// Receive a message on a socket.
+///
+/// Return the number of bytes received if success.
+pub fn sys_recv(
+    socket_fd: c_int,
+    buf_ptr: *mut c_void,
+    len: ctypes::size_t,
+    flag: c_int, // currently not used
+) -> ctypes::ssize_t {
+    debug!(
+        "sys_recv <= {} {:#x} {} {}",
+        socket_fd, buf_ptr as usize, len, flag
+    );
+    syscall_body!(sys_recv, {
+        if buf_ptr.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let buf = unsafe { core::slice::from_raw_parts_mut(buf_ptr as *mut u8, len) };
+        Socket::from_fd(socket_fd)?.recv(buf)
+    })
+}
+
+/// Send a message gathered from the buffers of `msg.msg_iov` on a socket.
+///
+/// If `msg.msg_name` is not null, the message is sent to that address.
+/// Ancillary data in `msg.msg_control` is not supported and ignored.
+///
+/// Return the number of bytes sent if success.
+pub unsafe fn sys_sendmsg(
+    socket_fd: c_int,
+    msg: *const ctypes::msghdr,
+    flags: c_int, // currently not used
+) -> ctypes::ssize_t {
+    debug!("sys_sendmsg <= {} {:#x} {}", socket_fd, msg as usize, flags);
+    syscall_body!(sys_sendmsg, {
+        if msg.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let msg = unsafe { &*msg };
+        let iovs = unsafe { iovecs(msg.msg_iov, msg.msg_iovlen)? };
+        // A datagram must be sent at once, so gather the buffers first.
+        let mut buf = Vec::with_capacity(iovs.iter().map(|iov| iov.iov_len).sum());
+        unsafe {
+            write_iovecs(iovs, |data, _| {
+                buf.extend_from_slice(data);
+                Ok(data.len())
+            })?
+        };
+
+        let socket = Socket::from_fd(socket_fd)?;
+        let name = msg.msg_name as *const ctypes::sockaddr;
+        match &*socket {
+            _ if name.is_null() => socket.send(&buf),
+            Socket::Unix(unixsocket) => {
+                unixsocket.sendto(&buf, unsafe { UnixAddr::read(name, msg.msg_namelen)? })
+            }
+            _ => socket.sendto(&buf, from_sockaddr(name, msg.msg_namelen)?),
+        }
+    })
+}
+
+/// Receive a message on a socket, scattered into the buffers of
+/// `msg.msg_iov`.
+///
+/// If `msg.msg_name` is not null, the source address is stored there, and
+/// `msg.msg_namelen` is set to its length (0 for stream sockets). No ancillary
+/// data is received, so `msg.msg_controllen` is set to 0. `msg.msg_flags` is
+/// set to `MSG_TRUNC` if the datagram is longer than the buffers, or 0.
+///
+/// Return the number of bytes received if success.
+pub unsafe fn sys_recvmsg(
+    socket_fd: c_int,
+    msg: *mut ctypes::msghdr,
+    flags: c_int, // currently not used
+) -> ctypes::ssize_t {
+    debug!("sys_recvmsg <= {} {:#x} {}", socket_fd, msg as usize, flags);
+    syscall_body!(sys_recvmsg, {
+        if msg.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let msg = unsafe { &mut *msg };
+        let iovs = unsafe { iovecs(msg.msg_iov, msg.msg_iovlen)? };
+        let socket = Socket::from_fd(socket_fd)?;
+        // A datagram must be received at once, so it is scattered afterwards.
+        let (len, full_len, addr, unix_addr) = match &*socket {
+            Socket::Udp(udpsocket) => {
+                let (dgram, addr) = udpsocket.lock().recv_buf_from()?;
+                let len = unsafe { scatter(iovs, &dgram) };
+                (len, dgram.len(), Some(addr), None)
+            }
+            Socket::Tcp(tcpsocket) => {
+                let len = unsafe { read_iovecs(iovs, |buf| Ok(tcpsocket.lock().recv(buf)?))? };
+                (len, len, None, None)
+            }
+            Socket::Unix(unixsocket) => {
+                let (mut full_len, mut addr) = (0, None);
+                let len = unsafe {
+                    read_iovecs(iovs, |buf| {
+                        let (len, dgram_len, source) = unixsocket.recvmsg(buf)?;
+                        (full_len, addr) = (dgram_len, source);
+                        Ok(len)
+                    })?
+                };
+                (len, full_len, None, addr)
+            }
+        };
+
+        if !msg.msg_name.is_null() {
+            msg.msg_namelen = match (addr, unix_addr) {
+                (Some(addr), _) => {
+                    let (sockaddr, addrlen) = into_sockaddr(addr);
+                    let n = addrlen.min(msg.msg_namelen) as usize;
+                    unsafe {
+                        let src = &sockaddr as *const ctypes::sockaddr as *const u8;
+                        core::ptr::copy_nonoverlapping(src, msg.msg_name as *mut u8, n);
+                    }
+                    addrlen
+                }
+                (_, Some(addr)) => {
+                    unsafe { addr.write(msg.msg_name as *mut _, &mut msg.msg_namelen)? };
+                    msg.msg_namelen
+                }
+                (None, None) => 0,
+            };
+        }
+        msg.msg_controllen = 0;
+        msg.msg_flags = if full_len > len {
+            ctypes::MSG_TRUNC as _
+        } else {
+            0
+        };
+        Ok(len)
+    })
+}
+
+/// Listen for connections on a socket
+///
+/// Return 0 if success.
+pub fn sys_listen(
+    socket_fd: c_int,
+    backlog: c_int, // currently not used
+) -> c_int {
+    debug!("sys_listen <= {} {}", socket_fd, backlog);
+    syscall_body!(sys_listen, {
+        Socket::from_fd(socket_fd)?.listen()?;
+        Ok(0)
+    })
+}
+
+/// Accept for connections on a socket
+///
+/// Return file descriptor for the accepted socket if success.
+pub unsafe fn sys_accept(
+    socket_fd: c_int,
+    socket_addr: *mut ctypes::sockaddr,
+    socket_len: *mut ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_accept <= {} {:#x} {:#x}",
+        socket_fd, socket_addr as usize, socket_len as usize
+    );
+    syscall_body!(sys_accept, {
+        if socket_addr.is_null() || socket_len.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let socket = Socket::from_fd(socket_fd)?;
+        let new_socket = socket.accept()?;
+        match &new_socket {
+            Socket::Unix(unixsocket) => unsafe {
+                unixsocket.peer_addr()?.write(socket_addr, socket_len)?
+            },
+            _ => unsafe { (*socket_addr, *socket_len) = into_sockaddr(new_socket.peer_addr()?) },
+        }
+        new_socket.add_to_fd_table()
+    })
+}
+
+/// Shut down a full-duplex connection.
+///
+/// Return 0 if success.
+pub fn sys_shutdown(
+    socket_fd: c_int,
+    flag: c_int, // currently not used
+) -> c_int {
+    debug!("sys_shutdown <= {} {}", socket_fd, flag);
+    syscall_body!(sys_shutdown, {
+        Socket::from_fd(socket_fd)?.shutdown()?;
+        Ok(0)
+    })
+}
+
+/// Set options on a socket.
+///
+/// Supports `SO_REUSEADDR`, `SO_RCVBUF`, `SO_SNDBUF`, `SO_KEEPALIVE`,
+/// `SO_RCVTIMEO`, `SO_SNDTIMEO` and `SO_LINGER` at the `SOL_SOCKET` level, and
+/// `TCP_NODELAY`, `TCP_KEEPIDLE` and `TCP_KEEPINTVL` at the `IPPROTO_TCP` level.
+///
+/// Return 0 if success.
+pub unsafe fn sys_setsockopt(
+    socket_fd: c_int,
+    level: c_int,
+    optname: c_int,
+    optval: *const c_void,
+    optlen: ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_setsockopt <= {} {} {} {:#x} {}",
+        socket_fd, level, optname, optval as usize, optlen
+    );
+    syscall_body!(sys_setsockopt, {
+        let socket = Socket::from_fd(socket_fd)?;
+        let opt = SockOpt::from_raw(level, optname)?;
+        let val = unsafe { opt.read_value(optval, optlen)? };
+        socket.set_option(opt, val)?;
+        Ok(0)
+    })
+}
+
+/// Get options on a socket.
+///
+/// See [`sys_setsockopt`] for supported options. In addition, `SO_TYPE` and
+/// `SO_ERROR` can be read.
+///
+/// Return 0 if success.
+pub unsafe fn sys_getsockopt(
+    socket_fd: c_int,
+    level: c_int,
+    optname: c_int,
+    optval: *mut c_void,
+    optlen: *mut ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_getsockopt <= {} {} {} {:#x} {:#x}",
+        socket_fd, level, optname, optval as usize, optlen as usize
+    );
+    syscall_body!(sys_getsockopt, {
+        let socket = Socket::from_fd(socket_fd)?;
+        let opt = SockOpt::from_raw(level, optname)?;
+        unsafe { socket.get_option(opt)?.write(optval, optlen)? };
+        Ok(0)
+    })
+}
+
+/// Query addresses for a domain name.
+///
+/// Only IPv4. Ports are always 0. Ignore servname and hint.
+/// Results' ai_flags and ai_canonname are 0 or NULL.
+///
+/// Return address number if success.
+pub unsafe fn sys_getaddrinfo(
+    nodename: *const c_char,
+    servname: *const c_char,
+    _hints: *const ctypes::addrinfo,
+    res: *mut *mut ctypes::addrinfo,
+) -> c_int {
+    let name = char_ptr_to_str(nodename);
+    let port = char_ptr_to_str(servname);
+    debug!("sys_getaddrinfo <= {:?} {:?}", name, port);
+    syscall_body!(sys_getaddrinfo, {
+        if nodename.is_null() && servname.is_null() {
+            return Ok(0);
+        }
+        if res.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+
+        let port = port.map_or(0, |p| p.parse::<u16>().unwrap_or(0));
+        let ip_addrs = if let Ok(domain) = name {
+            if let Ok(a) = domain.parse::<IpAddr>() {
+                vec![a]
+            } else {
+                axnet::dns_query(domain)?
+            }
+        } else {
+            vec![Ipv4Addr::LOCALHOST.into()]
+        };
+
+        let len = ip_addrs.len().min(ctypes::MAXADDRS as usize);
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let mut out: Vec<ctypes::aibuf> = Vec::with_capacity(len);
+        for (i, &ip) in ip_addrs.iter().enumerate().take(len) {
+            let buf = match ip {
+                IpAddr::V4(ip) => ctypes::aibuf {
+                    ai: ctypes::addrinfo {
+                        ai_family: ctypes::AF_INET as _,
+                        // TODO: This is a hard-code part, only return TCP parameters
+                        ai_socktype: ctypes::SOCK_STREAM as _,
+                        ai_protocol: ctypes::IPPROTO_TCP as _,
+                        ai_addrlen: size_of::<ctypes::sockaddr_in>() as _,
+                        ai_addr: core::ptr::null_mut(),
+                        ai_canonname: core::ptr::null_mut(),
+                        ai_next: core::ptr::null_mut(),
+                        ai_flags: 0,
+                    },
+                    sa: ctypes::aibuf_sa {
+                        sin: SocketAddrV4::new(ip, port).into(),
+                    },
+                    slot: i as i16,
+                    lock: [0],
+                    ref_: 0,
+                },
+                _ => panic!("IPv6 is not supported"),
+            };
+            out.push(buf);
+            out[i].ai.ai_addr =
+                unsafe { core::ptr::addr_of_mut!(out[i].sa.sin) as *mut ctypes::sockaddr };
+            if i > 0 {
+                out[i - 1].ai.ai_next = core::ptr::addr_of_mut!(out[i].ai);
+            }
+        }
+
+        out[0].ref_ = len as i16;
+        unsafe { *res = core::ptr::addr_of_mut!(out[0].ai) };
+        core::mem::forget(out); // drop in `sys_freeaddrinfo`
+        Ok(len)
+    })
+}
+
+/// Free queried `addrinfo` struct
+pub unsafe fn sys_freeaddrinfo(res: *mut ctypes::addrinfo) {
+    if res.is_null() {
+        return;
+    }
+    let aibuf_ptr = res as *mut ctypes::aibuf;
+    let len = (*aibuf_ptr).ref_ as usize;
+    assert!((*aibuf_ptr).slot == 0);
+    assert!(len > 0);
+    let vec = Vec::from_raw_parts(aibuf_ptr, len, len); // TODO: lock
+    drop(vec);
+}
+
+/// Get current address to which the socket sockfd is bound.
+pub unsafe fn sys_getsockname(
+    sock_fd: c_int,
+    addr: *mut ctypes::sockaddr,
+    addrlen: *mut ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_getsockname <= {} {:#x} {:#x}",
+        sock_fd, addr as usize, addrlen as usize
+    );
+    syscall_body!(sys_getsockname, {
+        if addr.is_null() || addrlen.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let socket = Socket::from_fd(sock_fd)?;
+        if let Socket::Unix(unixsocket) = &*socket {
+            unsafe { unixsocket.local_addr().write(addr, addrlen)? };
+            return Ok(0);
+        }
+        if unsafe { *addrlen } < size_of::<ctypes::sockaddr>() as u32 {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe {
+            (*addr, *addrlen) = into_sockaddr(socket.local_addr()?);
+        }
+        Ok(0)
+    })
+}
+
+/// Get peer address to which the socket sockfd is connected.
+pub unsafe fn sys_getpeername(
+    sock_fd: c_int,
+    addr: *mut ctypes::sockaddr,
+    addrlen: *mut ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_getpeername <= {} {:#x} {:#x}",
+        sock_fd, addr as usize, addrlen as usize
+    );
+    syscall_body!(sys_getpeername, {
+        if addr.is_null() || addrlen.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let socket = Socket::from_fd(sock_fd)?;
+        if let Socket::Unix(unixsocket) = &*socket {
+            unsafe { unixsocket.peer_addr()?.write(addr, addrlen)? };
+            return Ok(0);
+        }
+        if unsafe { *addrlen } < size_of::<ctypes::sockaddr>() as u32 {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe {
+            (*addr, *addrlen) = into_sockaddr(socket.peer_addr()?);
+        }
+        Ok(0)
+    })
+}
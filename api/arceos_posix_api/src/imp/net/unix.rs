@@ -0,0 +1,665 @@
+//! Unix domain sockets, which connect tasks in the same system without going
+//! through the network stack.
+//!
+//! Sockets can be bound to paths in the file system, where an empty file is
+//! created to reserve the name, or to names in the abstract namespace.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::{format, string::String, sync::Arc, sync::Weak, vec::Vec};
+use core::mem::size_of;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use axerrno::{LinuxError, LinuxResult};
+use axio::PollState;
+use axsync::Mutex;
+#[cfg(feature = "multitask")]
+use axtask::WaitQueue;
+
+use crate::ctypes;
+use crate::imp::fd_ops::notify_pollers;
+
+/// The capacity of each direction of a stream connection.
+const STREAM_BUF_SIZE: usize = 64 * 1024;
+/// The capacity of the receive queue of a datagram socket.
+const DGRAM_BUF_SIZE: usize = 64 * 1024;
+/// The maximum number of connections waiting to be accepted.
+const MAX_BACKLOG: usize = 128;
+
+/// The address of a Unix domain socket.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UnixAddr {
+    /// Not bound to any name.
+    Unnamed,
+    /// A path in the file system.
+    Path(String),
+    /// A name in the abstract namespace, which starts with a null byte in
+    /// `sun_path`.
+    Abstract(Vec<u8>),
+}
+
+impl UnixAddr {
+    /// Reads an address from a `sockaddr_un` of `addrlen` bytes.
+    pub unsafe fn read(
+        addr: *const ctypes::sockaddr,
+        addrlen: ctypes::socklen_t,
+    ) -> LinuxResult<Self> {
+        if addr.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let addrlen = addrlen as usize;
+        let family_len = size_of::<ctypes::sa_family_t>();
+        if addrlen < family_len || addrlen > size_of::<ctypes::sockaddr_un>() {
+            return Err(LinuxError::EINVAL);
+        }
+        let bytes = unsafe { core::slice::from_raw_parts(addr as *const u8, addrlen) };
+        let (family, path) = bytes.split_at(family_len);
+        if u16::from_ne_bytes([family[0], family[1]]) != ctypes::AF_UNIX as u16 {
+            return Err(LinuxError::EINVAL);
+        }
+        Ok(match path {
+            [] => Self::Unnamed,
+            [0, name @ ..] => Self::Abstract(name.to_vec()),
+            _ => {
+                let end = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+                let path = core::str::from_utf8(&path[..end]).map_err(|_| LinuxError::EINVAL)?;
+                Self::Path(path.into())
+            }
+        })
+    }
+
+    /// Writes the address to a `sockaddr_un`, truncating it if `addrlen` is
+    /// too small, and sets `addrlen` to the length of the full address.
+    pub unsafe fn write(
+        &self,
+        addr: *mut ctypes::sockaddr,
+        addrlen: *mut ctypes::socklen_t,
+    ) -> LinuxResult {
+        if addr.is_null() || addrlen.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let mut buf = Vec::with_capacity(size_of::<ctypes::sockaddr_un>());
+        buf.extend_from_slice(&(ctypes::AF_UNIX as u16).to_ne_bytes());
+        match self {
+            Self::Unnamed => {}
+            Self::Path(path) => {
+                buf.extend_from_slice(path.as_bytes());
+                buf.push(0);
+            }
+            Self::Abstract(name) => {
+                buf.push(0);
+                buf.extend_from_slice(name);
+            }
+        }
+        unsafe {
+            let len = (*addrlen as usize).min(buf.len());
+            core::ptr::copy_nonoverlapping(buf.as_ptr(), addr as *mut u8, len);
+            *addrlen = buf.len() as _;
+        }
+        Ok(())
+    }
+
+    /// Returns the key of the address in the name table, where paths are
+    /// absolute.
+    fn resolve(self) -> LinuxResult<Self> {
+        match self {
+            #[cfg(feature = "fs")]
+            Self::Path(path) => {
+                let path = crate::imp::fs::path_at(ctypes::AT_FDCWD, &path)?;
+                Ok(Self::Path(axfs::api::canonicalize(&path)?))
+            }
+            addr => Ok(addr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SockType {
+    Stream,
+    Dgram,
+}
+
+/// Received datagrams with their source addresses.
+#[derive(Default)]
+struct DgramQueue {
+    msgs: VecDeque<(Vec<u8>, UnixAddr)>,
+    /// The total size of the datagrams.
+    size: usize,
+}
+
+/// Wakes up the tasks blocked on a connection, or on the backlog or the
+/// received datagrams of an endpoint, when another task changes them.
+struct Waiter {
+    /// Increased on every change.
+    seq: AtomicU64,
+    #[cfg(feature = "multitask")]
+    queue: WaitQueue,
+}
+
+impl Waiter {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            #[cfg(feature = "multitask")]
+            queue: WaitQueue::new(),
+        }
+    }
+
+    fn seq(&self) -> u64 {
+        self.seq.load(Ordering::Acquire)
+    }
+
+    /// Wakes up the blocked tasks and pollers.
+    fn notify(&self) {
+        self.seq.fetch_add(1, Ordering::AcqRel);
+        #[cfg(feature = "multitask")]
+        self.queue.notify_all(true);
+        notify_pollers();
+    }
+
+    /// Sleeps until [`notify`](Self::notify) is called after [`seq`](Self::seq)
+    /// returned `seq`, or fails with `EINTR` if a signal arrives. It only
+    /// yields without the `multitask` feature.
+    fn wait(&self, seq: u64) -> LinuxResult {
+        let changed = || self.seq() != seq;
+        #[cfg(feature = "signal")]
+        self.queue
+            .wait_until_interruptible(changed)
+            .map_err(|_| LinuxError::EINTR)?;
+        #[cfg(all(feature = "multitask", not(feature = "signal")))]
+        self.queue.wait_until(changed);
+        #[cfg(not(feature = "multitask"))]
+        {
+            let _ = changed;
+            crate::sys_sched_yield();
+        }
+        Ok(())
+    }
+}
+
+/// The part of a socket that other sockets can find by its address.
+struct Endpoint {
+    ty: SockType,
+    /// Connections waiting to be accepted, `None` if it is not listening.
+    backlog: Mutex<Option<VecDeque<UnixSocket>>>,
+    /// Received datagrams, only used by datagram sockets.
+    dgrams: Mutex<DgramQueue>,
+    /// Notified when `backlog` or `dgrams` changes.
+    waiter: Waiter,
+}
+
+/// Bound addresses, resolved by [`UnixAddr::resolve`].
+static NAMES: Mutex<BTreeMap<UnixAddr, Weak<Endpoint>>> = Mutex::new(BTreeMap::new());
+/// Names for sockets bound to an unnamed address.
+static NEXT_AUTOBIND: AtomicUsize = AtomicUsize::new(0);
+
+/// Finds the socket bound to `addr`.
+fn lookup(addr: &UnixAddr) -> LinuxResult<Arc<Endpoint>> {
+    let endpoint = NAMES.lock().get(addr).and_then(Weak::upgrade);
+    if let Some(endpoint) = endpoint {
+        return Ok(endpoint);
+    }
+    match addr {
+        // The file is left behind by a closed socket.
+        #[cfg(feature = "fs")]
+        UnixAddr::Path(path) if axfs::api::metadata(path).is_ok() => Err(LinuxError::ECONNREFUSED),
+        UnixAddr::Path(_) => Err(LinuxError::ENOENT),
+        _ => Err(LinuxError::ECONNREFUSED),
+    }
+}
+
+/// One direction of a stream connection.
+#[derive(Default)]
+struct StreamBuffer {
+    data: VecDeque<u8>,
+    /// The writer has shut down, readers get EOF after the remaining data.
+    write_closed: bool,
+    /// The reader has shut down, writers get `EPIPE`.
+    read_closed: bool,
+}
+
+/// A buffer shared by both ends of a stream connection.
+type StreamRef = Arc<Mutex<StreamBuffer>>;
+
+/// One end of a stream connection.
+struct Connection {
+    rx: StreamRef,
+    tx: StreamRef,
+    peer_addr: UnixAddr,
+    /// Shared by both ends, notified when either buffer changes.
+    waiter: Arc<Waiter>,
+}
+
+impl Connection {
+    /// Creates both ends of a connection, `addr1` and `addr2` are the
+    /// addresses of the sockets owning the ends.
+    fn pair(addr1: UnixAddr, addr2: UnixAddr) -> (Self, Self) {
+        let buf1 = Arc::new(Mutex::new(StreamBuffer::default()));
+        let buf2 = Arc::new(Mutex::new(StreamBuffer::default()));
+        let waiter = Arc::new(Waiter::new());
+        let end1 = Self {
+            rx: buf1.clone(),
+            tx: buf2.clone(),
+            peer_addr: addr2,
+            waiter: waiter.clone(),
+        };
+        let end2 = Self {
+            rx: buf2,
+            tx: buf1,
+            peer_addr: addr1,
+            waiter,
+        };
+        (end1, end2)
+    }
+
+    fn shutdown(&self) {
+        self.rx.lock().read_closed = true;
+        self.tx.lock().write_closed = true;
+        self.waiter.notify();
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+enum Peer {
+    None,
+    Stream(Connection),
+    /// The default destination of a datagram socket.
+    Dgram(UnixAddr, Weak<Endpoint>),
+}
+
+/// A Unix domain socket.
+pub struct UnixSocket {
+    endpoint: Arc<Endpoint>,
+    /// The bound address, resolved by [`UnixAddr::resolve`].
+    addr: Mutex<UnixAddr>,
+    peer: Mutex<Peer>,
+    nonblocking: AtomicBool,
+}
+
+impl UnixSocket {
+    fn new(ty: SockType) -> Self {
+        Self {
+            endpoint: Arc::new(Endpoint {
+                ty,
+                backlog: Mutex::new(None),
+                dgrams: Mutex::new(DgramQueue::default()),
+                waiter: Waiter::new(),
+            }),
+            addr: Mutex::new(UnixAddr::Unnamed),
+            peer: Mutex::new(Peer::None),
+            nonblocking: AtomicBool::new(false),
+        }
+    }
+
+    /// Creates a new `SOCK_STREAM` socket.
+    pub fn new_stream() -> Self {
+        Self::new(SockType::Stream)
+    }
+
+    /// Creates a new `SOCK_DGRAM` socket.
+    pub fn new_dgram() -> Self {
+        Self::new(SockType::Dgram)
+    }
+
+    /// Creates a pair of connected sockets, of `SOCK_STREAM` if `stream` is
+    /// true, or `SOCK_DGRAM` otherwise.
+    pub fn pair(stream: bool) -> (Self, Self) {
+        if stream {
+            let (a, b) = (Self::new_stream(), Self::new_stream());
+            let (conn_a, conn_b) = Connection::pair(UnixAddr::Unnamed, UnixAddr::Unnamed);
+            *a.peer.lock() = Peer::Stream(conn_a);
+            *b.peer.lock() = Peer::Stream(conn_b);
+            (a, b)
+        } else {
+            let (a, b) = (Self::new_dgram(), Self::new_dgram());
+            *a.peer.lock() = Peer::Dgram(UnixAddr::Unnamed, Arc::downgrade(&b.endpoint));
+            *b.peer.lock() = Peer::Dgram(UnixAddr::Unnamed, Arc::downgrade(&a.endpoint));
+            (a, b)
+        }
+    }
+
+    fn is_stream(&self) -> bool {
+        self.endpoint.ty == SockType::Stream
+    }
+
+    /// Returns `SOCK_STREAM` or `SOCK_DGRAM`.
+    pub fn socktype(&self) -> u32 {
+        match self.endpoint.ty {
+            SockType::Stream => ctypes::SOCK_STREAM,
+            SockType::Dgram => ctypes::SOCK_DGRAM,
+        }
+    }
+
+    /// Returns the size of the buffer of each direction.
+    pub fn buffer_size(&self) -> usize {
+        match self.endpoint.ty {
+            SockType::Stream => STREAM_BUF_SIZE,
+            SockType::Dgram => DGRAM_BUF_SIZE,
+        }
+    }
+
+    /// Runs `f` again after `waiter` is notified while it returns `EAGAIN`,
+    /// unless the socket is nonblocking.
+    ///
+    /// `f` changes what `waiter` guards when it succeeds, so `waiter` is
+    /// notified then.
+    fn block_on<T, F>(&self, waiter: &Waiter, mut f: F) -> LinuxResult<T>
+    where
+        F: FnMut() -> LinuxResult<T>,
+    {
+        loop {
+            let seq = waiter.seq();
+            match f() {
+                Err(LinuxError::EAGAIN) if !self.nonblocking.load(Ordering::Relaxed) => {
+                    waiter.wait(seq)?;
+                }
+                Ok(res) => {
+                    waiter.notify();
+                    return Ok(res);
+                }
+                res => return res,
+            }
+        }
+    }
+
+    pub fn local_addr(&self) -> UnixAddr {
+        self.addr.lock().clone()
+    }
+
+    pub fn peer_addr(&self) -> LinuxResult<UnixAddr> {
+        match &*self.peer.lock() {
+            Peer::None => Err(LinuxError::ENOTCONN),
+            Peer::Stream(conn) => Ok(conn.peer_addr.clone()),
+            Peer::Dgram(addr, _) => Ok(addr.clone()),
+        }
+    }
+
+    /// Binds the socket to `addr`, or to a unique name in the abstract
+    /// namespace if `addr` is [`UnixAddr::Unnamed`].
+    pub fn bind(&self, addr: UnixAddr) -> LinuxResult {
+        let mut bound = self.addr.lock();
+        if *bound != UnixAddr::Unnamed {
+            return Err(LinuxError::EINVAL);
+        }
+        let mut names = NAMES.lock();
+        let addr = match addr {
+            UnixAddr::Unnamed => loop {
+                let n = NEXT_AUTOBIND.fetch_add(1, Ordering::Relaxed);
+                let addr = UnixAddr::Abstract(format!("{:05x}", n).into_bytes());
+                if !names.contains_key(&addr) {
+                    break addr;
+                }
+            },
+            addr => addr.resolve()?,
+        };
+        if names.get(&addr).is_some_and(|e| e.strong_count() > 0) {
+            return Err(LinuxError::EADDRINUSE);
+        }
+        #[cfg(feature = "fs")]
+        if let UnixAddr::Path(path) = &addr {
+            axfs::api::File::create_new(path).map_err(|e| match LinuxError::from(e) {
+                LinuxError::EEXIST => LinuxError::EADDRINUSE,
+                e => e,
+            })?;
+        }
+        names.insert(addr.clone(), Arc::downgrade(&self.endpoint));
+        *bound = addr;
+        Ok(())
+    }
+
+    pub fn listen(&self) -> LinuxResult {
+        if !self.is_stream() {
+            return Err(LinuxError::EOPNOTSUPP);
+        }
+        if *self.addr.lock() == UnixAddr::Unnamed || !matches!(*self.peer.lock(), Peer::None) {
+            return Err(LinuxError::EINVAL);
+        }
+        self.endpoint
+            .backlog
+            .lock()
+            .get_or_insert_with(VecDeque::new);
+        Ok(())
+    }
+
+    /// Connects a stream socket to the listening socket at `addr`, or sets
+    /// the default destination of a datagram socket.
+    pub fn connect(&self, addr: UnixAddr) -> LinuxResult {
+        if addr == UnixAddr::Unnamed {
+            return Err(LinuxError::EINVAL);
+        }
+        let addr = addr.resolve()?;
+        let endpoint = lookup(&addr)?;
+        if endpoint.ty != self.endpoint.ty {
+            return Err(LinuxError::EPROTOTYPE);
+        }
+        if !self.is_stream() {
+            *self.peer.lock() = Peer::Dgram(addr, Arc::downgrade(&endpoint));
+            return Ok(());
+        }
+
+        if !matches!(*self.peer.lock(), Peer::None) {
+            return Err(LinuxError::EISCONN);
+        }
+        if self.endpoint.backlog.lock().is_some() {
+            return Err(LinuxError::EINVAL);
+        }
+        let (client, server) = Connection::pair(self.local_addr(), addr.clone());
+        let accepted = UnixSocket::new_stream();
+        *accepted.addr.lock() = addr;
+        *accepted.peer.lock() = Peer::Stream(server);
+
+        let mut accepted = Some(accepted);
+        self.block_on(&endpoint.waiter, || {
+            let mut backlog = endpoint.backlog.lock();
+            let backlog = backlog.as_mut().ok_or(LinuxError::ECONNREFUSED)?;
+            if backlog.len() >= MAX_BACKLOG {
+                return Err(LinuxError::EAGAIN);
+            }
+            backlog.push_back(accepted.take().unwrap());
+            Ok(())
+        })?;
+        *self.peer.lock() = Peer::Stream(client);
+        Ok(())
+    }
+
+    pub fn accept(&self) -> LinuxResult<UnixSocket> {
+        if !self.is_stream() {
+            return Err(LinuxError::EOPNOTSUPP);
+        }
+        self.block_on(&self.endpoint.waiter, || {
+            let mut backlog = self.endpoint.backlog.lock();
+            let backlog = backlog.as_mut().ok_or(LinuxError::EINVAL)?;
+            backlog.pop_front().ok_or(LinuxError::EAGAIN)
+        })
+    }
+
+    /// Returns the receive and send buffers, and the waiter of the
+    /// connection.
+    fn stream_buffers(&self) -> LinuxResult<(StreamRef, StreamRef, Arc<Waiter>)> {
+        match &*self.peer.lock() {
+            Peer::Stream(conn) => Ok((conn.rx.clone(), conn.tx.clone(), conn.waiter.clone())),
+            _ => Err(LinuxError::ENOTCONN),
+        }
+    }
+
+    fn send_dgram(&self, buf: &[u8], dest: &Endpoint) -> LinuxResult<usize> {
+        if buf.len() > DGRAM_BUF_SIZE {
+            return Err(LinuxError::EMSGSIZE);
+        }
+        let source = self.local_addr();
+        self.block_on(&dest.waiter, || {
+            let mut queue = dest.dgrams.lock();
+            if queue.size + buf.len() > DGRAM_BUF_SIZE {
+                return Err(LinuxError::EAGAIN);
+            }
+            queue.size += buf.len();
+            queue.msgs.push_back((buf.to_vec(), source.clone()));
+            Ok(buf.len())
+        })
+    }
+
+    pub fn send(&self, buf: &[u8]) -> LinuxResult<usize> {
+        if !self.is_stream() {
+            let dest = match &*self.peer.lock() {
+                Peer::Dgram(_, dest) => dest.clone(),
+                _ => return Err(LinuxError::ENOTCONN),
+            };
+            let dest = dest.upgrade().ok_or(LinuxError::ECONNREFUSED)?;
+            return self.send_dgram(buf, &dest);
+        }
+
+        let (_, tx, waiter) = self.stream_buffers()?;
+        let nonblocking = self.nonblocking.load(Ordering::Relaxed);
+        let mut written = 0;
+        self.block_on(&waiter, || {
+            let mut tx = tx.lock();
+            if tx.read_closed || tx.write_closed {
+                return if written > 0 {
+                    Ok(written)
+                } else {
+                    Err(LinuxError::EPIPE)
+                };
+            }
+            let n = (STREAM_BUF_SIZE - tx.data.len()).min(buf.len() - written);
+            tx.data.extend(&buf[written..written + n]);
+            written += n;
+            if n > 0 {
+                // The reader may be blocked while this blocks for more room.
+                waiter.notify();
+            }
+            // Blocking writes return after all data is written.
+            if written < buf.len() && (written == 0 || !nonblocking) {
+                Err(LinuxError::EAGAIN)
+            } else {
+                Ok(written)
+            }
+        })
+    }
+
+    /// Sends a datagram to `addr`, which is not supported by stream sockets.
+    pub fn sendto(&self, buf: &[u8], addr: UnixAddr) -> LinuxResult<usize> {
+        if self.is_stream() {
+            return Err(LinuxError::EISCONN);
+        }
+        let dest = lookup(&addr.resolve()?)?;
+        if dest.ty != SockType::Dgram {
+            return Err(LinuxError::EPROTOTYPE);
+        }
+        self.send_dgram(buf, &dest)
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        self.recvfrom(buf).map(|res| res.0)
+    }
+
+    /// Receives data, and the source address of a datagram. A datagram
+    /// longer than `buf` is truncated.
+    pub fn recvfrom(&self, buf: &mut [u8]) -> LinuxResult<(usize, Option<UnixAddr>)> {
+        self.recvmsg(buf).map(|(n, _, source)| (n, source))
+    }
+
+    /// Like [`recvfrom`](Self::recvfrom), but also returns the length of the
+    /// datagram before it is truncated, or the number of bytes received for
+    /// stream sockets.
+    pub fn recvmsg(&self, buf: &mut [u8]) -> LinuxResult<(usize, usize, Option<UnixAddr>)> {
+        if !self.is_stream() {
+            return self.block_on(&self.endpoint.waiter, || {
+                let mut queue = self.endpoint.dgrams.lock();
+                let (msg, source) = queue.msgs.pop_front().ok_or(LinuxError::EAGAIN)?;
+                queue.size -= msg.len();
+                let n = buf.len().min(msg.len());
+                buf[..n].copy_from_slice(&msg[..n]);
+                Ok((n, msg.len(), Some(source)))
+            });
+        }
+
+        let (rx, _, waiter) = self.stream_buffers()?;
+        self.block_on(&waiter, || {
+            let mut rx = rx.lock();
+            if rx.read_closed || buf.is_empty() {
+                return Ok((0, 0, None));
+            }
+            if rx.data.is_empty() {
+                return if rx.write_closed {
+                    Ok((0, 0, None))
+                } else {
+                    Err(LinuxError::EAGAIN)
+                };
+            }
+            let n = buf.len().min(rx.data.len());
+            for (dst, src) in buf.iter_mut().zip(rx.data.drain(..n)) {
+                *dst = src;
+            }
+            Ok((n, n, None))
+        })
+    }
+
+    /// Shuts down both directions of the connection.
+    pub fn shutdown(&self) -> LinuxResult {
+        match &*self.peer.lock() {
+            Peer::None => Err(LinuxError::ENOTCONN),
+            Peer::Stream(conn) => {
+                conn.shutdown();
+                Ok(())
+            }
+            Peer::Dgram(..) => Ok(()),
+        }
+    }
+
+    pub fn poll(&self) -> LinuxResult<PollState> {
+        if let Some(backlog) = self.endpoint.backlog.lock().as_ref() {
+            return Ok(PollState::new(!backlog.is_empty(), false));
+        }
+        if !self.is_stream() {
+            let readable = !self.endpoint.dgrams.lock().msgs.is_empty();
+            return Ok(PollState::new(readable, true));
+        }
+        let Ok((rx, tx, _)) = self.stream_buffers() else {
+            return Ok(PollState::default());
+        };
+        // Never hold both locks, the peer locks them in the reverse order.
+        let (readable, peer_write_closed) = {
+            let rx = rx.lock();
+            (
+                !rx.data.is_empty() || rx.write_closed || rx.read_closed,
+                rx.write_closed,
+            )
+        };
+        let (writable, peer_read_closed) = {
+            let tx = tx.lock();
+            (
+                tx.data.len() < STREAM_BUF_SIZE || tx.read_closed,
+                tx.read_closed,
+            )
+        };
+        Ok(PollState::new(readable, writable).with_hangup(peer_write_closed && peer_read_closed))
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) {
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
+    }
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        // Refuse the connections not accepted yet.
+        let pending = self.endpoint.backlog.lock().take();
+        drop(pending);
+        self.endpoint.waiter.notify();
+
+        let addr = self.addr.get_mut();
+        if *addr != UnixAddr::Unnamed {
+            let mut names = NAMES.lock();
+            if names
+                .get(addr)
+                .is_some_and(|e| e.as_ptr() == Arc::as_ptr(&self.endpoint))
+            {
+                names.remove(addr);
+            }
+        }
+    }
+}
@@ -5,7 +5,7 @@ use axerrno::{LinuxError, LinuxResult};
 use axio::PollState;
 use axsync::Mutex;
 
-use super::fd_ops::{add_file_like, close_file_like, FileLike};
+use super::fd_ops::{add_file_like, close_file_like, notify_pollers, FileLike};
 use crate::ctypes;
 
 #[derive(Copy, Clone, PartialEq)]
@@ -106,6 +106,13 @@ impl Pipe {
     }
 }
 
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        // The other end may see the hangup.
+        notify_pollers();
+    }
+}
+
 impl FileLike for Pipe {
     fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
         if !self.readable() {
@@ -127,11 +134,15 @@ impl FileLike for Pipe {
             }
             for _ in 0..loop_read {
                 if read_size == max_len {
+                    drop(ring_buffer);
+                    notify_pollers();
                     return Ok(read_size);
                 }
                 buf[read_size] = ring_buffer.read_byte();
                 read_size += 1;
             }
+            drop(ring_buffer);
+            notify_pollers();
         }
     }
 
@@ -152,11 +163,15 @@ impl FileLike for Pipe {
             }
             for _ in 0..loop_write {
                 if write_size == max_len {
+                    drop(ring_buffer);
+                    notify_pollers();
                     return Ok(write_size);
                 }
                 ring_buffer.write_byte(buf[write_size]);
                 write_size += 1;
             }
+            drop(ring_buffer);
+            notify_pollers();
         }
     }
 
@@ -179,15 +194,30 @@ impl FileLike for Pipe {
 
     fn poll(&self) -> LinuxResult<PollState> {
         let buf = self.buffer.lock();
-        Ok(PollState {
-            readable: self.readable() && buf.available_read() > 0,
-            writable: self.writable() && buf.available_write() > 0,
-        })
+        let write_end_closed = self.readable() && self.write_end_close();
+        let readable = self.readable() && (buf.available_read() > 0 || write_end_closed);
+        let writable = self.writable() && buf.available_write() > 0;
+        Ok(PollState::new(readable, writable).with_hangup(write_end_closed))
     }
 
     fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
         Ok(())
     }
+
+    fn notifies_pollers(&self) -> bool {
+        true
+    }
+
+    fn ioctl(&self, cmd: u32, arg: usize) -> LinuxResult<c_int> {
+        match cmd {
+            ctypes::FIONREAD if self.readable() => {
+                let count = unsafe { (arg as *mut c_int).as_mut() }.ok_or(LinuxError::EFAULT)?;
+                *count = self.buffer.lock().available_read() as c_int;
+                Ok(0)
+            }
+            _ => Err(LinuxError::ENOTTY),
+        }
+    }
 }
 
 /// Create a pipe
@@ -0,0 +1,273 @@
+//! Emulated processes.
+//!
+//! A process is a group of tasks with its own file descriptor table, current
+//! directory and exit status. There is no address space isolation, a new
+//! process just runs an entry function linked into the image.
+
+use alloc::{
+    collections::BTreeMap,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::ffi::{c_int, c_void};
+use core::sync::atomic::{AtomicI32, Ordering};
+
+use axerrno::LinuxError;
+use axsync::spin::SpinNoIrq;
+use axtask::{AxTaskRef, WaitQueue};
+use spin::{Mutex, RwLock};
+
+use super::fd_ops::FdTable;
+use crate::ctypes;
+
+#[cfg(feature = "fs")]
+use alloc::string::String;
+
+pub struct Process {
+    pid: c_int,
+    parent: Weak<Process>,
+    pub(crate) fd_table: Arc<RwLock<FdTable>>,
+    /// The current directory, ends with a slash.
+    #[cfg(feature = "fs")]
+    pub(crate) cwd: Mutex<String>,
+    main_task: Mutex<Option<AxTaskRef>>,
+    /// The wait status, set when the process exits.
+    exit_status: SpinNoIrq<Option<c_int>>,
+    /// Children that have not been waited for.
+    children: SpinNoIrq<Vec<Arc<Process>>>,
+    /// Tasks waiting for children to exit.
+    child_exited: WaitQueue,
+    /// The `ITIMER_REAL` timer.
+    #[cfg(feature = "signal")]
+    pub(crate) real_timer: Arc<axtask::RealTimer>,
+}
+
+impl Process {
+    pub(crate) fn pid(&self) -> c_int {
+        self.pid
+    }
+
+    pub(crate) fn ppid(&self) -> c_int {
+        self.parent.upgrade().map_or(0, |p| p.pid)
+    }
+
+    pub(crate) fn is_init(&self) -> bool {
+        self.pid == INIT_PID
+    }
+
+    /// Returns the task running the `main` function of the process.
+    pub(crate) fn main_task(&self) -> Option<AxTaskRef> {
+        self.main_task.lock().clone()
+    }
+
+    /// Whether `waitpid(pid)` can return now, either a matching child has
+    /// exited or there is no matching child at all.
+    fn can_wait(&self, pid: c_int) -> bool {
+        let children = self.children.lock();
+        let mut matched = children
+            .iter()
+            .filter(|c| pid <= 0 || c.pid == pid)
+            .peekable();
+        matched.peek().is_none() || matched.any(|c| c.exit_status.lock().is_some())
+    }
+
+    /// Removes an exited child matching `pid`, returns its PID and wait status.
+    fn reap_child(&self, pid: c_int) -> Result<Option<(c_int, c_int)>, LinuxError> {
+        let mut children = self.children.lock();
+        let matches = |c: &Arc<Process>| pid <= 0 || c.pid == pid;
+        if !children.iter().any(matches) {
+            return Err(LinuxError::ECHILD);
+        }
+        let exited = |c: &Arc<Process>| matches(c) && c.exit_status.lock().is_some();
+        Ok(children.iter().position(exited).map(|idx| {
+            let child = children.remove(idx);
+            let status = child.exit_status.lock().unwrap();
+            (child.pid, status)
+        }))
+    }
+}
+
+const INIT_PID: c_int = 1;
+static NEXT_PID: AtomicI32 = AtomicI32::new(INIT_PID + 1);
+
+lazy_static::lazy_static! {
+    /// The process of `main`, which all tasks belong to by default.
+    static ref INIT: Arc<Process> = Arc::new(Process {
+        pid: INIT_PID,
+        parent: Weak::new(),
+        fd_table: Arc::new(RwLock::new(super::fd_ops::new_fd_table())),
+        #[cfg(feature = "fs")]
+        cwd: Mutex::new(axfs::api::current_dir().unwrap_or_else(|_| "/".into())),
+        main_task: Mutex::new(Some(axtask::main_task())),
+        exit_status: SpinNoIrq::new(None),
+        children: SpinNoIrq::new(Vec::new()),
+        child_exited: WaitQueue::new(),
+        #[cfg(feature = "signal")]
+        real_timer: Arc::new(axtask::RealTimer::new()),
+    });
+
+    /// Tasks of processes other than [`INIT`], keyed by task ID.
+    static ref TASK_PROCESS: RwLock<BTreeMap<u64, Arc<Process>>> = RwLock::new(BTreeMap::new());
+}
+
+/// Returns the process of the current task.
+pub(crate) fn current() -> Arc<Process> {
+    let tid = axtask::current().id().as_u64();
+    match TASK_PROCESS.read().get(&tid) {
+        Some(process) => process.clone(),
+        None => INIT.clone(),
+    }
+}
+
+/// Adds the current task to `process`, new threads call it before running.
+pub(crate) fn enter(process: Arc<Process>) {
+    if !process.is_init() {
+        let tid = axtask::current().id().as_u64();
+        TASK_PROCESS.write().insert(tid, process);
+    }
+}
+
+/// Removes the current task from its process, exiting threads call it.
+pub(crate) fn leave() {
+    let tid = axtask::current().id().as_u64();
+    TASK_PROCESS.write().remove(&tid);
+}
+
+/// Returns the process with the given PID, which is the current process, or
+/// one of its descendants.
+pub(crate) fn find(pid: c_int) -> Option<Arc<Process>> {
+    fn find_in(process: &Arc<Process>, pid: c_int) -> Option<Arc<Process>> {
+        if process.pid == pid {
+            return Some(process.clone());
+        }
+        let children = process.children.lock().clone();
+        children.iter().find_map(|c| find_in(c, pid))
+    }
+    find_in(&INIT, pid)
+}
+
+/// Exits the current process with the wait `status`, closing all its files
+/// and waking up the parent in `waitpid`.
+///
+/// Other threads of the process are not stopped, but they can no longer use
+/// its files.
+pub(crate) fn exit_current(status: c_int) -> ! {
+    let process = current();
+    let files: Vec<_> = {
+        let mut fd_table = process.fd_table.write();
+        let capacity = fd_table.capacity();
+        (0..capacity).filter_map(|fd| fd_table.remove(fd)).collect()
+    };
+    drop(files);
+    #[cfg(feature = "signal")]
+    process.real_timer.set(None, axhal::time::TimeValue::ZERO);
+    let tid = axtask::current().id().as_u64();
+    if process.main_task().is_some_and(|t| t.id().as_u64() == tid) {
+        super::pthread::unregister_current();
+    }
+    *process.exit_status.lock() = Some(status);
+    leave();
+    if let Some(parent) = process.parent.upgrade() {
+        parent.child_exited.notify_all(false);
+    }
+    axtask::exit(status)
+}
+
+/// Creates a new process as a child of the current process, and runs
+/// `entry(arg)` in its main thread. The return value of `entry` is the exit
+/// code of the process.
+///
+/// The child gets a copy of the file descriptor table and the current
+/// directory. Signal actions are shared by all processes.
+///
+/// Return the PID of the child.
+pub fn sys_spawn(entry: extern "C" fn(arg: *mut c_void) -> c_int, arg: *mut c_void) -> c_int {
+    debug!("sys_spawn <= {:#x} {:#x}", entry as usize, arg as usize);
+    syscall_body!(sys_spawn, {
+        let parent = current();
+        let child = Arc::new(Process {
+            pid: NEXT_PID.fetch_add(1, Ordering::Relaxed),
+            parent: Arc::downgrade(&parent),
+            fd_table: {
+                let parent_table = parent.fd_table.read();
+                let mut fd_table = FdTable::new();
+                for fd in 0..parent_table.capacity() {
+                    if let Some(f) = parent_table.get(fd) {
+                        fd_table.add_at(fd, f.clone());
+                    }
+                }
+                Arc::new(RwLock::new(fd_table))
+            },
+            #[cfg(feature = "fs")]
+            cwd: Mutex::new(parent.cwd.lock().clone()),
+            main_task: Mutex::new(None),
+            exit_status: SpinNoIrq::new(None),
+            children: SpinNoIrq::new(Vec::new()),
+            child_exited: WaitQueue::new(),
+            #[cfg(feature = "signal")]
+            real_timer: Arc::new(axtask::RealTimer::new()),
+        });
+
+        let arg = arg as usize;
+        // The child is set up before its task runs, which may exit at once.
+        axtask::TaskBuilder::new().spawn_prepared(
+            move || {
+                super::pthread::register_current();
+                let code = entry(arg as *mut c_void);
+                exit_current((code & 0xff) << 8)
+            },
+            |task| {
+                *child.main_task.lock() = Some(task.clone());
+                TASK_PROCESS
+                    .write()
+                    .insert(task.id().as_u64(), child.clone());
+                parent.children.lock().push(child.clone());
+            },
+        );
+        Ok(child.pid)
+    })
+}
+
+/// Get the process ID of the parent process.
+///
+/// The process of `main` has no parent, so 0 is returned for it.
+pub fn sys_getppid() -> c_int {
+    syscall_body!(sys_getppid, Ok(current().ppid()))
+}
+
+/// Wait for a child process to exit, and store its wait status in `status`.
+///
+/// If `pid` is greater than 0, it waits for the child with that PID,
+/// otherwise for any child. Returns 0 immediately if `WNOHANG` is set in
+/// `options` and no child has exited. With the `signal` feature, the wait is
+/// interrupted by signals, then `EINTR` is returned.
+///
+/// Return the PID of the exited child.
+pub unsafe fn sys_waitpid(pid: c_int, status: *mut c_int, options: c_int) -> c_int {
+    debug!(
+        "sys_waitpid <= {} {:#x} {:#x}",
+        pid, status as usize, options
+    );
+    syscall_body!(sys_waitpid, {
+        let process = current();
+        let (pid, wstatus) = loop {
+            if let Some(exited) = process.reap_child(pid)? {
+                break exited;
+            }
+            if options as u32 & ctypes::WNOHANG != 0 {
+                return Ok(0);
+            }
+            #[cfg(feature = "signal")]
+            process
+                .child_exited
+                .wait_until_interruptible(|| process.can_wait(pid))
+                .map_err(|_| LinuxError::EINTR)?;
+            #[cfg(not(feature = "signal"))]
+            process.child_exited.wait_until(|| process.can_wait(pid));
+        };
+        if !status.is_null() {
+            unsafe { *status = wstatus };
+        }
+        Ok(pid)
+    })
+}
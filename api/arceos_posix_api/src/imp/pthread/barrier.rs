@@ -0,0 +1,104 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::LinuxError;
+
+use core::ffi::{c_int, c_uint};
+use core::mem::size_of;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::LazyWaitQueue;
+
+static_assertions::const_assert!(
+    size_of::<PthreadBarrier>() <= size_of::<ctypes::pthread_barrier_t>()
+);
+
+#[repr(C)]
+pub struct PthreadBarrier {
+    /// Number of threads required to pass the barrier.
+    count: u32,
+    /// Number of threads arrived in the current round.
+    arrived: AtomicU32,
+    /// Increased every time all threads have arrived.
+    generation: AtomicU32,
+    queue: LazyWaitQueue,
+}
+
+impl PthreadBarrier {
+    const fn new(count: u32) -> Self {
+        Self {
+            count,
+            arrived: AtomicU32::new(0),
+            generation: AtomicU32::new(0),
+            queue: LazyWaitQueue::new(),
+        }
+    }
+
+    /// Returns whether the current thread is the last one to arrive.
+    fn wait(&self) -> bool {
+        let generation = self.generation.load(Ordering::Acquire);
+        if self.arrived.fetch_add(1, Ordering::AcqRel) + 1 == self.count {
+            self.arrived.store(0, Ordering::Relaxed);
+            self.generation.fetch_add(1, Ordering::Release);
+            self.queue.get().notify_all(true);
+            true
+        } else {
+            self.queue
+                .get()
+                .wait_until(|| self.generation.load(Ordering::Acquire) != generation);
+            false
+        }
+    }
+}
+
+/// Initialize a barrier that `count` threads must wait on before any of them
+/// can proceed.
+pub fn sys_pthread_barrier_init(
+    barrier: *mut ctypes::pthread_barrier_t,
+    _attr: *const ctypes::pthread_barrierattr_t,
+    count: c_uint,
+) -> c_int {
+    debug!(
+        "sys_pthread_barrier_init <= {:#x}, {}",
+        barrier as usize, count
+    );
+    syscall_body!(sys_pthread_barrier_init, {
+        check_null_mut_ptr(barrier)?;
+        if count == 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe {
+            barrier
+                .cast::<PthreadBarrier>()
+                .write(PthreadBarrier::new(count));
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy a barrier.
+pub fn sys_pthread_barrier_destroy(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    debug!("sys_pthread_barrier_destroy <= {:#x}", barrier as usize);
+    syscall_body!(sys_pthread_barrier_destroy, {
+        check_null_mut_ptr(barrier)?;
+        unsafe {
+            barrier.cast::<PthreadBarrier>().drop_in_place();
+        }
+        Ok(0)
+    })
+}
+
+/// Wait until enough threads have reached the barrier.
+///
+/// Returns 1 in one of the threads and 0 in the others. The C wrapper should
+/// translate 1 to `PTHREAD_BARRIER_SERIAL_THREAD`, which is negative.
+pub fn sys_pthread_barrier_wait(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    debug!("sys_pthread_barrier_wait <= {:#x}", barrier as usize);
+    syscall_body!(sys_pthread_barrier_wait, {
+        check_null_mut_ptr(barrier)?;
+        if unsafe { (*barrier.cast::<PthreadBarrier>()).wait() } {
+            Ok(1)
+        } else {
+            Ok(0)
+        }
+    })
+}
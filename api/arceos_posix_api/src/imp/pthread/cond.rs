@@ -0,0 +1,194 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{current_time, TimeValue};
+
+use core::ffi::c_int;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::mutex::PthreadMutex;
+use super::LazyWaitQueue;
+
+static_assertions::const_assert!(size_of::<PthreadCond>() <= size_of::<ctypes::pthread_cond_t>());
+
+#[repr(C)]
+pub struct PthreadCond {
+    /// Increased on every signal or broadcast, waiters sleep until it changes.
+    seq: AtomicU32,
+    queue: LazyWaitQueue,
+}
+
+impl PthreadCond {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicU32::new(0),
+            queue: LazyWaitQueue::new(),
+        }
+    }
+
+    /// Unlocks `mutex` and waits for a signal, or until `deadline` is reached,
+    /// then locks `mutex` again.
+    fn wait(&self, mutex: &PthreadMutex, deadline: Option<TimeValue>) -> LinuxResult {
+        let seq = self.seq.load(Ordering::Acquire);
+        mutex.unlock()?;
+        let signaled = || self.seq.load(Ordering::Acquire) != seq;
+        let timed_out = match deadline {
+            Some(deadline) => self.wait_timeout_until(deadline, signaled),
+            None => {
+                self.queue.get().wait_until(signaled);
+                false
+            }
+        };
+        mutex.lock()?;
+        if timed_out {
+            Err(LinuxError::ETIMEDOUT)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns whether `deadline` is reached before `condition` becomes true.
+    #[cfg(feature = "irq")]
+    fn wait_timeout_until<F>(&self, deadline: TimeValue, condition: F) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        match deadline.checked_sub(current_time()) {
+            Some(dur) if !dur.is_zero() => self.queue.get().wait_timeout_until(dur, condition),
+            _ => !condition(),
+        }
+    }
+
+    /// Returns whether `deadline` is reached before `condition` becomes true.
+    ///
+    /// Without timer interrupts, the waiter can only yield and check again.
+    #[cfg(not(feature = "irq"))]
+    fn wait_timeout_until<F>(&self, deadline: TimeValue, condition: F) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        loop {
+            if condition() {
+                return false;
+            }
+            if current_time() >= deadline {
+                return true;
+            }
+            axtask::yield_now();
+        }
+    }
+
+    fn signal(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        self.queue.get().notify_one(true);
+    }
+
+    fn broadcast(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        self.queue.get().notify_all(true);
+    }
+}
+
+/// Initialize a condition variable.
+pub fn sys_pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    _attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    debug!("sys_pthread_cond_init <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_init, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            cond.cast::<PthreadCond>().write(PthreadCond::new());
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy a condition variable.
+pub fn sys_pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_destroy <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_destroy, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            cond.cast::<PthreadCond>().drop_in_place();
+        }
+        Ok(0)
+    })
+}
+
+/// Unlock the mutex and wait on the condition variable, the mutex is locked
+/// again before returning.
+pub fn sys_pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_wait <= {:#x}, {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_wait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).wait(&*mutex.cast::<PthreadMutex>(), None)?;
+        }
+        Ok(0)
+    })
+}
+
+/// Like [`sys_pthread_cond_wait`], but fails with `ETIMEDOUT` if the
+/// condition variable is not signaled before the absolute time `abstime`,
+/// which is measured by `CLOCK_REALTIME`.
+pub unsafe fn sys_pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_timedwait <= {:#x}, {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_timedwait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        if abstime.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let abstime = unsafe { *abstime };
+        if abstime.tv_sec < 0 || !(0..1_000_000_000).contains(&abstime.tv_nsec) {
+            return Err(LinuxError::EINVAL);
+        }
+        // Convert the wall clock time to the monotonic clock time.
+        let offset = TimeValue::from_nanos(axhal::time::epochoffset_nanos());
+        let deadline = TimeValue::from(abstime).saturating_sub(offset);
+        unsafe {
+            (*cond.cast::<PthreadCond>()).wait(&*mutex.cast::<PthreadMutex>(), Some(deadline))?;
+        }
+        Ok(0)
+    })
+}
+
+/// Wake up at least one thread waiting on the condition variable.
+pub fn sys_pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_signal <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_signal, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).signal();
+        }
+        Ok(0)
+    })
+}
+
+/// Wake up all threads waiting on the condition variable.
+pub fn sys_pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_broadcast <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_broadcast, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).broadcast();
+        }
+        Ok(0)
+    })
+}
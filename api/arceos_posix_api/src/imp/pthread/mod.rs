@@ -1,22 +1,29 @@
 use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
 use core::cell::UnsafeCell;
 use core::ffi::{c_int, c_void};
+use core::sync::atomic::{AtomicPtr, Ordering};
 
 use axerrno::{LinuxError, LinuxResult};
-use axtask::AxTaskRef;
+use axtask::{AxTaskRef, WaitQueue};
 use spin::RwLock;
 
 use crate::ctypes;
 
+pub mod barrier;
+pub mod cond;
 pub mod mutex;
+pub mod rwlock;
+
+/// The minimum stack size of threads, stack sizes are also rounded up to it.
+const MIN_STACK_SIZE: usize = 0x1000;
 
 lazy_static::lazy_static! {
     static ref TID_TO_PTHREAD: RwLock<BTreeMap<u64, ForceSendSync<ctypes::pthread_t>>> = {
         let mut map = BTreeMap::new();
-        let main_task = axtask::current();
+        let main_task = axtask::main_task();
         let main_tid = main_task.id().as_u64();
         let main_thread = Pthread {
-            inner: main_task.as_task_ref().clone(),
+            inner: main_task,
             retval: Arc::new(Packet {
                 result: UnsafeCell::new(core::ptr::null_mut()),
             }),
@@ -41,7 +48,7 @@ pub struct Pthread {
 
 impl Pthread {
     fn create(
-        _attr: *const ctypes::pthread_attr_t,
+        attr: *const ctypes::pthread_attr_t,
         start_routine: extern "C" fn(arg: *mut c_void) -> *mut c_void,
         arg: *mut c_void,
     ) -> LinuxResult<ctypes::pthread_t> {
@@ -51,15 +58,21 @@ impl Pthread {
             result: UnsafeCell::new(core::ptr::null_mut()),
         });
         let their_packet = my_packet.clone();
+        #[cfg(feature = "process")]
+        let process = super::process::current();
 
         let main = move || {
+            #[cfg(feature = "process")]
+            super::process::enter(process);
             let arg = arg_wrapper;
             let ret = start_routine(arg.0);
             unsafe { *their_packet.result.get() = ret };
             drop(their_packet);
+            #[cfg(feature = "process")]
+            super::process::leave();
         };
 
-        let task_inner = axtask::spawn(main);
+        let task_inner = axtask::spawn_raw(main, "".into(), stack_size(attr));
         let tid = task_inner.id().as_u64();
         let thread = Pthread {
             inner: task_inner,
@@ -85,6 +98,8 @@ impl Pthread {
     fn exit_current(retval: *mut c_void) -> ! {
         let thread = Self::current().expect("fail to get current thread");
         unsafe { *thread.retval.result.get() = retval };
+        #[cfg(feature = "process")]
+        super::process::leave();
         axtask::exit(0);
     }
 
@@ -103,6 +118,64 @@ impl Pthread {
     }
 }
 
+/// Returns the stack size set by `pthread_attr_setstacksize`, or the
+/// `RLIMIT_STACK` limit if there are no attributes.
+fn stack_size(attr: *const ctypes::pthread_attr_t) -> usize {
+    // `_a_stacksize` in the C library.
+    let size = match unsafe { attr.as_ref() } {
+        Some(attr) => unsafe { attr.__u.__s[0] as usize },
+        None => 0,
+    };
+    let size = if size == 0 {
+        super::resources::stack_limit()
+    } else {
+        size
+    };
+    (size.max(MIN_STACK_SIZE) + MIN_STACK_SIZE - 1) & !(MIN_STACK_SIZE - 1)
+}
+
+/// Makes the current task a thread that nobody joins, e.g. the main thread of
+/// a new process.
+#[cfg(feature = "process")]
+pub(crate) fn register_current() {
+    let curr = axtask::current();
+    let thread = Pthread {
+        inner: curr.as_task_ref().clone(),
+        retval: Arc::new(Packet {
+            result: UnsafeCell::new(core::ptr::null_mut()),
+        }),
+    };
+    let ptr = Box::into_raw(Box::new(thread)) as *mut c_void;
+    TID_TO_PTHREAD
+        .write()
+        .insert(curr.id().as_u64(), ForceSendSync(ptr));
+}
+
+/// Frees the thread registered by [`register_current`] before the task exits.
+#[cfg(feature = "process")]
+pub(crate) fn unregister_current() {
+    let tid = axtask::current().id().as_u64();
+    if let Some(ptr) = TID_TO_PTHREAD.write().remove(&tid) {
+        drop(unsafe { Box::from_raw(ptr.0 as *mut Pthread) });
+    }
+}
+
+/// Returns the task of the thread with the given ID.
+#[cfg(not(feature = "process"))]
+pub(crate) fn find_task(tid: u64) -> Option<AxTaskRef> {
+    let map = TID_TO_PTHREAD.read();
+    let thread = map.get(&tid)?.0 as *const Pthread;
+    Some(unsafe { &*thread }.inner.clone())
+}
+
+/// Returns the task of the given `pthread` struct, or [`None`] if it is not a
+/// live thread.
+pub(crate) fn pthread_to_task(ptr: ctypes::pthread_t) -> Option<AxTaskRef> {
+    let map = TID_TO_PTHREAD.read();
+    let thread = map.values().find(|p| core::ptr::eq(p.0, ptr))?.0 as *const Pthread;
+    Some(unsafe { &*thread }.inner.clone())
+}
+
 /// Returns the `pthread` struct of current thread.
 pub fn sys_pthread_self() -> ctypes::pthread_t {
     Pthread::current().expect("fail to get current thread") as *const Pthread as _
@@ -147,6 +220,82 @@ pub unsafe fn sys_pthread_join(thread: ctypes::pthread_t, retval: *mut *mut c_vo
     })
 }
 
+/// Sets the CPU affinity mask of the given thread to the CPU set `cpuset` of
+/// `cpusetsize` bytes.
+pub unsafe fn sys_pthread_setaffinity_np(
+    thread: ctypes::pthread_t,
+    cpusetsize: usize,
+    cpuset: *const ctypes::cpu_set_t,
+) -> c_int {
+    debug!(
+        "sys_pthread_setaffinity_np <= thread: {:#x}, cpusetsize: {}",
+        thread as usize, cpusetsize
+    );
+    syscall_body!(sys_pthread_setaffinity_np, {
+        let cpumask = super::task::cpu_set_to_mask(cpusetsize, cpuset)?;
+        let task = pthread_to_task(thread).ok_or(LinuxError::ESRCH)?;
+        axtask::set_affinity(&task, cpumask);
+        Ok(0)
+    })
+}
+
+/// Gets the CPU affinity mask of the given thread, and stores it into the CPU
+/// set `cpuset` of `cpusetsize` bytes.
+pub unsafe fn sys_pthread_getaffinity_np(
+    thread: ctypes::pthread_t,
+    cpusetsize: usize,
+    cpuset: *mut ctypes::cpu_set_t,
+) -> c_int {
+    debug!(
+        "sys_pthread_getaffinity_np <= thread: {:#x}, cpusetsize: {}",
+        thread as usize, cpusetsize
+    );
+    syscall_body!(sys_pthread_getaffinity_np, {
+        let task = pthread_to_task(thread).ok_or(LinuxError::ESRCH)?;
+        super::task::mask_to_cpu_set(task.affinity(), cpusetsize, cpuset)?;
+        Ok(0)
+    })
+}
+
+/// A [`WaitQueue`] allocated on first use, so that all-zero pthread objects
+/// (e.g. `PTHREAD_COND_INITIALIZER`) are valid.
+struct LazyWaitQueue(AtomicPtr<WaitQueue>);
+
+impl LazyWaitQueue {
+    const fn new() -> Self {
+        Self(AtomicPtr::new(core::ptr::null_mut()))
+    }
+
+    fn get(&self) -> &WaitQueue {
+        let mut ptr = self.0.load(Ordering::Acquire);
+        if ptr.is_null() {
+            let new = Box::into_raw(Box::new(WaitQueue::new()));
+            ptr = match self.0.compare_exchange(
+                core::ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => new,
+                Err(old) => {
+                    drop(unsafe { Box::from_raw(new) });
+                    old
+                }
+            };
+        }
+        unsafe { &*ptr }
+    }
+}
+
+impl Drop for LazyWaitQueue {
+    fn drop(&mut self) {
+        let ptr = *self.0.get_mut();
+        if !ptr.is_null() {
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 struct ForceSendSync<T>(T);
 
@@ -1,6 +1,6 @@
 use crate::{ctypes, utils::check_null_mut_ptr};
 
-use axerrno::LinuxResult;
+use axerrno::{LinuxError, LinuxResult};
 use axsync::Mutex;
 
 use core::ffi::c_int;
@@ -19,12 +19,22 @@ impl PthreadMutex {
         Self(Mutex::new(()))
     }
 
-    fn lock(&self) -> LinuxResult {
+    pub(super) fn lock(&self) -> LinuxResult {
         let _guard = ManuallyDrop::new(self.0.lock());
         Ok(())
     }
 
-    fn unlock(&self) -> LinuxResult {
+    fn try_lock(&self) -> LinuxResult {
+        match self.0.try_lock() {
+            Some(guard) => {
+                let _guard = ManuallyDrop::new(guard);
+                Ok(())
+            }
+            None => Err(LinuxError::EBUSY),
+        }
+    }
+
+    pub(super) fn unlock(&self) -> LinuxResult {
         unsafe { self.0.force_unlock() };
         Ok(())
     }
@@ -57,6 +67,18 @@ pub fn sys_pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     })
 }
 
+/// Try to lock the given mutex, fails with `EBUSY` if it is already locked.
+pub fn sys_pthread_mutex_trylock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+    debug!("sys_pthread_mutex_trylock <= {:#x}", mutex as usize);
+    syscall_body!(sys_pthread_mutex_trylock, {
+        check_null_mut_ptr(mutex)?;
+        unsafe {
+            (*mutex.cast::<PthreadMutex>()).try_lock()?;
+        }
+        Ok(0)
+    })
+}
+
 /// Unlock the given mutex.
 pub fn sys_pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     debug!("sys_pthread_mutex_unlock <= {:#x}", mutex as usize);
@@ -0,0 +1,210 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+
+use alloc::collections::BTreeMap;
+use core::ffi::c_int;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicU32, Ordering};
+use spin::Mutex;
+
+use super::LazyWaitQueue;
+
+static_assertions::const_assert!(
+    size_of::<PthreadRwLock>() <= size_of::<ctypes::pthread_rwlock_t>()
+);
+
+/// The lock is held by a writer.
+const WRITER: u32 = 1 << 31;
+
+/// How many times each thread holds each lock for reading, keyed by the
+/// address of the lock and the thread ID, so that a thread holding a read
+/// lock can take it again while writers are waiting.
+static READ_HOLDS: Mutex<BTreeMap<(usize, u64), u32>> = Mutex::new(BTreeMap::new());
+
+fn read_hold_key(lock: &PthreadRwLock) -> (usize, u64) {
+    (lock as *const _ as usize, axtask::current().id().as_u64())
+}
+
+#[repr(C)]
+pub struct PthreadRwLock {
+    /// `WRITER` if locked for writing, otherwise the number of readers.
+    state: AtomicU32,
+    /// The number of blocked writers, readers that do not hold the lock yet
+    /// are blocked while there are any, so that writers are not starved.
+    waiting_writers: AtomicU32,
+    queue: LazyWaitQueue,
+}
+
+impl PthreadRwLock {
+    const fn new() -> Self {
+        Self {
+            state: AtomicU32::new(0),
+            waiting_writers: AtomicU32::new(0),
+            queue: LazyWaitQueue::new(),
+        }
+    }
+
+    fn try_read(&self) -> bool {
+        let key = read_hold_key(self);
+        let mut holds = READ_HOLDS.lock();
+        let held = holds.contains_key(&key);
+        if !held && self.waiting_writers.load(Ordering::Acquire) != 0 {
+            return false;
+        }
+        let ok = self
+            .state
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |state| {
+                (state & WRITER == 0 && state + 1 < WRITER).then_some(state + 1)
+            })
+            .is_ok();
+        if ok {
+            *holds.entry(key).or_insert(0) += 1;
+        }
+        ok
+    }
+
+    fn try_write(&self) -> bool {
+        self.state
+            .compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    fn read(&self) {
+        if !self.try_read() {
+            self.queue.get().wait_until(|| self.try_read());
+        }
+    }
+
+    fn write(&self) {
+        if !self.try_write() {
+            self.waiting_writers.fetch_add(1, Ordering::AcqRel);
+            self.queue.get().wait_until(|| self.try_write());
+            self.waiting_writers.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    fn unlock(&self) -> LinuxResult {
+        let prev = self
+            .state
+            .fetch_update(Ordering::Release, Ordering::Relaxed, |state| match state {
+                0 => None,
+                WRITER => Some(0),
+                readers => Some(readers - 1),
+            })
+            .map_err(|_| LinuxError::EPERM)?;
+        if prev != WRITER {
+            let key = read_hold_key(self);
+            let mut holds = READ_HOLDS.lock();
+            if let Some(count) = holds.get_mut(&key) {
+                *count -= 1;
+                if *count == 0 {
+                    holds.remove(&key);
+                }
+            }
+        }
+        if prev == WRITER || prev == 1 {
+            self.queue.get().notify_all(true);
+        }
+        Ok(())
+    }
+}
+
+/// Initialize a read-write lock.
+pub fn sys_pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    _attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    debug!("sys_pthread_rwlock_init <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_init, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            rwlock.cast::<PthreadRwLock>().write(PthreadRwLock::new());
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy a read-write lock.
+pub fn sys_pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_destroy <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_destroy, {
+        check_null_mut_ptr(rwlock)?;
+        READ_HOLDS
+            .lock()
+            .retain(|&(addr, _), _| addr != rwlock as usize);
+        unsafe {
+            rwlock.cast::<PthreadRwLock>().drop_in_place();
+        }
+        Ok(0)
+    })
+}
+
+/// Lock the given read-write lock for reading.
+///
+/// Writers are preferred, so it blocks while writers are waiting even if the
+/// lock is held by other readers, unless the calling thread already holds it
+/// for reading.
+pub fn sys_pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_rdlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_rdlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).read();
+        }
+        Ok(0)
+    })
+}
+
+/// Try to lock the given read-write lock for reading, fails with `EBUSY` if
+/// it is locked by a writer, or writers are waiting for it and the calling
+/// thread does not hold it for reading.
+pub fn sys_pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_tryrdlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_tryrdlock, {
+        check_null_mut_ptr(rwlock)?;
+        if unsafe { (*rwlock.cast::<PthreadRwLock>()).try_read() } {
+            Ok(0)
+        } else {
+            Err(LinuxError::EBUSY)
+        }
+    })
+}
+
+/// Lock the given read-write lock for writing.
+pub fn sys_pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_wrlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_wrlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).write();
+        }
+        Ok(0)
+    })
+}
+
+/// Try to lock the given read-write lock for writing, fails with `EBUSY` if
+/// it is already locked.
+pub fn sys_pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_trywrlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_trywrlock, {
+        check_null_mut_ptr(rwlock)?;
+        if unsafe { (*rwlock.cast::<PthreadRwLock>()).try_write() } {
+            Ok(0)
+        } else {
+            Err(LinuxError::EBUSY)
+        }
+    })
+}
+
+/// Unlock the given read-write lock.
+pub fn sys_pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_unlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_unlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).unlock()?;
+        }
+        Ok(0)
+    })
+}
@@ -1,6 +1,91 @@
 use crate::ctypes;
-use axerrno::LinuxError;
+use axerrno::{LinuxError, LinuxResult};
 use core::ffi::c_int;
+use spin::RwLock;
+
+const RLIM_INFINITY: ctypes::rlim_t = ctypes::rlim_t::MAX;
+
+/// The hard limit of `RLIMIT_NOFILE`, i.e. the capacity of the fd table.
+#[cfg(feature = "fd")]
+const NOFILE_MAX: ctypes::rlim_t = super::fd_ops::AX_FILE_LIMIT as _;
+#[cfg(not(feature = "fd"))]
+const NOFILE_MAX: ctypes::rlim_t = 0;
+
+const fn rlimit(cur: ctypes::rlim_t, max: ctypes::rlim_t) -> ctypes::rlimit {
+    ctypes::rlimit {
+        rlim_cur: cur,
+        rlim_max: max,
+    }
+}
+
+/// Resource limits, indexed by `RLIMIT_*`.
+///
+/// diff: the limits are shared by all processes with the `process` feature.
+static RLIMITS: RwLock<[ctypes::rlimit; ctypes::RLIMIT_NLIMITS as usize]> = {
+    let mut limits = [rlimit(RLIM_INFINITY, RLIM_INFINITY); ctypes::RLIMIT_NLIMITS as usize];
+    limits[ctypes::RLIMIT_STACK as usize] = rlimit(axconfig::TASK_STACK_SIZE as _, RLIM_INFINITY);
+    limits[ctypes::RLIMIT_NOFILE as usize] = rlimit(NOFILE_MAX, NOFILE_MAX);
+    RwLock::new(limits)
+};
+
+fn get_rlimit(resource: u32) -> ctypes::rlimit {
+    RLIMITS.read()[resource as usize]
+}
+
+/// Returns the maximum number of open files, i.e. the soft limit of
+/// `RLIMIT_NOFILE`. File descriptors must be less than it.
+#[cfg(feature = "fd")]
+pub(crate) fn nofile_limit() -> usize {
+    get_rlimit(ctypes::RLIMIT_NOFILE).rlim_cur.min(NOFILE_MAX) as usize
+}
+
+/// Returns the stack size of new threads, i.e. the soft limit of
+/// `RLIMIT_STACK`, or [`axconfig::TASK_STACK_SIZE`] if it is unlimited.
+#[cfg(feature = "multitask")]
+pub(crate) fn stack_limit() -> usize {
+    match get_rlimit(ctypes::RLIMIT_STACK).rlim_cur {
+        RLIM_INFINITY => axconfig::TASK_STACK_SIZE,
+        size => size as usize,
+    }
+}
+
+fn set_rlimit(resource: u32, new: ctypes::rlimit) -> LinuxResult {
+    if new.rlim_cur > new.rlim_max {
+        return Err(LinuxError::EINVAL);
+    }
+    if resource == ctypes::RLIMIT_NOFILE && new.rlim_max > NOFILE_MAX {
+        return Err(LinuxError::EPERM);
+    }
+    let mut limits = RLIMITS.write();
+    limits[resource as usize] = new;
+
+    // The heap and the mappings are all in one address space, so both limits
+    // apply to the memory allocated by the application.
+    #[cfg(feature = "alloc")]
+    if matches!(resource, ctypes::RLIMIT_DATA | ctypes::RLIMIT_AS) {
+        let data = limits[ctypes::RLIMIT_DATA as usize].rlim_cur;
+        let as_ = limits[ctypes::RLIMIT_AS as usize].rlim_cur;
+        axalloc::set_app_quota(data.min(as_).try_into().unwrap_or(usize::MAX));
+    }
+    Ok(())
+}
+
+/// Charges `size` bytes allocated by the application (e.g. by `malloc`)
+/// against the `RLIMIT_DATA` and `RLIMIT_AS` limits.
+///
+/// Returns [`LinuxError::ENOMEM`] if that exceeds the limits. Unlike system
+/// calls, signals are not delivered here, since it is called by the allocator
+/// which signal handlers may use.
+#[cfg(feature = "alloc")]
+pub fn charge_memory(size: usize) -> LinuxResult {
+    axalloc::app_charge(size).map_err(|_| LinuxError::ENOMEM)
+}
+
+/// Gives back `size` bytes charged by [`charge_memory`].
+#[cfg(feature = "alloc")]
+pub fn uncharge_memory(size: usize) {
+    axalloc::app_uncharge(size)
+}
 
 /// Get resource limitations
 ///
@@ -12,29 +97,23 @@ pub unsafe fn sys_getrlimit(resource: c_int, rlimits: *mut ctypes::rlimit) -> c_
             ctypes::RLIMIT_DATA => {}
             ctypes::RLIMIT_STACK => {}
             ctypes::RLIMIT_NOFILE => {}
+            ctypes::RLIMIT_AS => {}
             _ => return Err(LinuxError::EINVAL),
         }
         if rlimits.is_null() {
             return Ok(0);
         }
-        match resource as u32 {
-            ctypes::RLIMIT_STACK => unsafe {
-                (*rlimits).rlim_cur = axconfig::TASK_STACK_SIZE as _;
-                (*rlimits).rlim_max = axconfig::TASK_STACK_SIZE as _;
-            },
-            #[cfg(feature = "fd")]
-            ctypes::RLIMIT_NOFILE => unsafe {
-                (*rlimits).rlim_cur = super::fd_ops::AX_FILE_LIMIT as _;
-                (*rlimits).rlim_max = super::fd_ops::AX_FILE_LIMIT as _;
-            },
-            _ => {}
-        }
+        unsafe { *rlimits = get_rlimit(resource as u32) };
         Ok(0)
     })
 }
 
 /// Set resource limitations
 ///
+/// `RLIMIT_NOFILE` limits the file descriptors, `RLIMIT_STACK` the stack
+/// size of new threads, while `RLIMIT_DATA` and `RLIMIT_AS` both limit the
+/// memory allocated by `malloc` and `mmap`.
+///
 /// TODO: support more resource types
 pub unsafe fn sys_setrlimit(resource: c_int, rlimits: *mut crate::ctypes::rlimit) -> c_int {
     debug!("sys_setrlimit <= {} {:#x}", resource, rlimits as usize);
@@ -43,9 +122,41 @@ pub unsafe fn sys_setrlimit(resource: c_int, rlimits: *mut crate::ctypes::rlimit
             crate::ctypes::RLIMIT_DATA => {}
             crate::ctypes::RLIMIT_STACK => {}
             crate::ctypes::RLIMIT_NOFILE => {}
+            crate::ctypes::RLIMIT_AS => {}
             _ => return Err(LinuxError::EINVAL),
         }
-        // Currently do not support set resources
+        if rlimits.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        set_rlimit(resource as u32, unsafe { *rlimits })?;
+        Ok(0)
+    })
+}
+
+/// Get resource usage
+///
+/// Only the CPU time and the maximum memory usage are reported.
+///
+/// diff: the CPU time of the process is that of the whole system, and there
+/// is no system time, as the application runs in the kernel.
+pub unsafe fn sys_getrusage(who: c_int, usage: *mut ctypes::rusage) -> c_int {
+    debug!("sys_getrusage <= {} {:#x}", who, usage as usize);
+    syscall_body!(sys_getrusage, {
+        if usage.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let mut ru: ctypes::rusage = unsafe { core::mem::zeroed() };
+        if who == ctypes::RUSAGE_SELF as c_int || who == ctypes::RUSAGE_THREAD as c_int {
+            ru.ru_utime = super::time::cpu_time(who == ctypes::RUSAGE_THREAD as c_int).into();
+            // In kilobytes.
+            #[cfg(feature = "alloc")]
+            {
+                ru.ru_maxrss = (axalloc::app_peak_bytes() / 1024) as _;
+            }
+        } else if who != ctypes::RUSAGE_CHILDREN as c_int {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe { *usage = ru };
         Ok(0)
     })
 }
@@ -0,0 +1,407 @@
+use alloc::sync::Arc;
+use core::ffi::{c_int, c_uint, c_void};
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{current_time, TimeValue};
+use axtask::{AxTaskRef, RealTimer, WaitQueue, NSIG};
+use spin::RwLock;
+
+use crate::ctypes;
+use crate::imp::pthread;
+
+const SIG_DFL: usize = 0;
+const SIG_IGN: usize = 1;
+
+/// `SIGKILL` and `SIGSTOP` can not be blocked.
+const UNBLOCKABLE: u64 = sig_bit(ctypes::SIGKILL as usize) | sig_bit(ctypes::SIGSTOP as usize);
+
+#[derive(Clone, Copy)]
+struct SigAction {
+    handler: usize,
+    mask: u64,
+    flags: u32,
+}
+
+impl SigAction {
+    const DEFAULT: Self = Self {
+        handler: SIG_DFL,
+        mask: 0,
+        flags: 0,
+    };
+}
+
+impl From<&ctypes::sigaction> for SigAction {
+    fn from(act: &ctypes::sigaction) -> Self {
+        let handler = unsafe { act.__sa_handler.sa_handler };
+        Self {
+            handler: handler.map_or(SIG_DFL, |f| f as usize),
+            mask: act.sa_mask.__bits[0] as u64,
+            flags: act.sa_flags as u32,
+        }
+    }
+}
+
+impl From<SigAction> for ctypes::sigaction {
+    fn from(act: SigAction) -> Self {
+        let mut res: ctypes::sigaction = unsafe { core::mem::zeroed() };
+        res.__sa_handler.sa_handler = unsafe { core::mem::transmute(act.handler) };
+        res.sa_mask.__bits[0] = act.mask as _;
+        res.sa_flags = act.flags as _;
+        res
+    }
+}
+
+static SIG_ACTIONS: RwLock<[SigAction; NSIG]> = RwLock::new([SigAction::DEFAULT; NSIG]);
+
+/// `pause()` waits here until it is interrupted by a signal.
+static PAUSE_QUEUE: WaitQueue = WaitQueue::new();
+
+#[cfg(not(feature = "process"))]
+lazy_static::lazy_static! {
+    static ref REAL_TIMER: Arc<RealTimer> = Arc::new(RealTimer::new());
+}
+
+const fn sig_bit(sig: usize) -> u64 {
+    1 << (sig - 1)
+}
+
+fn check_signal(sig: c_int) -> LinuxResult<usize> {
+    if (1..NSIG as c_int).contains(&sig) {
+        Ok(sig as usize)
+    } else {
+        Err(LinuxError::EINVAL)
+    }
+}
+
+/// Whether the default action of the signal is to ignore it.
+fn ignored_by_default(sig: usize) -> bool {
+    // Stopping and continuing are not supported, these signals are ignored.
+    matches!(
+        sig as u32,
+        ctypes::SIGCHLD
+            | ctypes::SIGCONT
+            | ctypes::SIGURG
+            | ctypes::SIGWINCH
+            | ctypes::SIGSTOP
+            | ctypes::SIGTSTP
+            | ctypes::SIGTTIN
+            | ctypes::SIGTTOU
+    )
+}
+
+fn is_ignored(sig: usize) -> bool {
+    match SIG_ACTIONS.read()[sig].handler {
+        SIG_IGN => true,
+        SIG_DFL => ignored_by_default(sig),
+        _ => false,
+    }
+}
+
+fn send_signal(task: &AxTaskRef, sig: c_int) -> LinuxResult<c_int> {
+    // Signal 0 only checks the existence of the target.
+    if sig == 0 {
+        return Ok(0);
+    }
+    let sig = check_signal(sig)?;
+    // Ignored signals are discarded, so that they never interrupt sleeps.
+    if !is_ignored(sig) {
+        axtask::send_signal(task, sig);
+    }
+    Ok(0)
+}
+
+fn run_handler(sig: usize, act: SigAction) {
+    let curr = axtask::current();
+    let mut mask = curr.blocked_signals() | act.mask;
+    if act.flags & ctypes::SA_NODEFER == 0 {
+        mask |= sig_bit(sig);
+    }
+    let old_mask = curr.set_blocked_signals(mask & !UNBLOCKABLE);
+    unsafe {
+        if act.flags & ctypes::SA_SIGINFO != 0 {
+            let handler: unsafe extern "C" fn(c_int, *mut ctypes::siginfo_t, *mut c_void) =
+                core::mem::transmute(act.handler);
+            let mut info: ctypes::siginfo_t = core::mem::zeroed();
+            info.si_signo = sig as _;
+            info.si_code = ctypes::SI_USER as _;
+            handler(sig as _, &mut info, core::ptr::null_mut());
+        } else {
+            let handler: unsafe extern "C" fn(c_int) = core::mem::transmute(act.handler);
+            handler(sig as _);
+        }
+    }
+    curr.set_blocked_signals(old_mask);
+}
+
+/// Runs `f` with the blocked signals of the current thread replaced by
+/// `mask` if it is not `None`, like `ppoll` and `pselect`.
+///
+/// If `f` is interrupted, the pending signals are delivered before the old
+/// mask is restored, which may block them again.
+#[cfg_attr(not(feature = "poll"), allow(dead_code))]
+pub(crate) fn with_sigmask<T>(
+    mask: Option<&ctypes::sigset_t>,
+    f: impl FnOnce() -> LinuxResult<T>,
+) -> LinuxResult<T> {
+    let Some(mask) = mask else {
+        return f();
+    };
+    let curr = axtask::current();
+    let old_mask = curr.set_blocked_signals(mask.__bits[0] as u64 & !UNBLOCKABLE);
+    let res = f();
+    if res.as_ref().is_err_and(|&e| e == LinuxError::EINTR) {
+        axtask::handle_pending_signals();
+    }
+    curr.set_blocked_signals(old_mask);
+    res
+}
+
+/// Terminates the current process by the signal `sig`.
+fn terminate(sig: usize) -> ! {
+    #[cfg(feature = "process")]
+    if !super::process::current().is_init() {
+        super::process::exit_current(sig as c_int);
+    }
+    warn!("terminated by signal {}", sig);
+    // The system stops when the main task exits, which it does when `SIGKILL`
+    // is delivered, at its next system call or interrupt.
+    let main_task = axtask::main_task();
+    if !Arc::ptr_eq(axtask::current().as_task_ref(), &main_task) {
+        axtask::send_signal(&main_task, ctypes::SIGKILL as usize);
+    }
+    axtask::exit(128 + sig as c_int)
+}
+
+/// Returns the `ITIMER_REAL` timer of the current process.
+fn real_timer() -> Arc<RealTimer> {
+    #[cfg(feature = "process")]
+    return super::process::current().real_timer.clone();
+    #[cfg(not(feature = "process"))]
+    REAL_TIMER.clone()
+}
+
+struct SignalIfImpl;
+
+#[crate_interface::impl_interface]
+impl axtask::SignalIf for SignalIfImpl {
+    fn handle_signal(sig: usize) {
+        let act = {
+            let mut actions = SIG_ACTIONS.write();
+            let act = actions[sig];
+            if act.handler > SIG_IGN && act.flags & ctypes::SA_RESETHAND != 0 {
+                actions[sig] = SigAction::DEFAULT;
+            }
+            act
+        };
+        debug!("handle signal {} with handler {:#x}", sig, act.handler);
+        match act.handler {
+            SIG_IGN => {}
+            SIG_DFL if ignored_by_default(sig) => {}
+            SIG_DFL => terminate(sig),
+            _ => run_handler(sig, act),
+        }
+    }
+}
+
+/// Examine and change the action of a signal.
+pub unsafe fn sys_sigaction(
+    sig: c_int,
+    act: *const ctypes::sigaction,
+    oldact: *mut ctypes::sigaction,
+) -> c_int {
+    debug!(
+        "sys_sigaction <= sig: {}, act: {:#x}, oldact: {:#x}",
+        sig, act as usize, oldact as usize
+    );
+    syscall_body!(sys_sigaction, {
+        let sig = check_signal(sig)?;
+        let act = unsafe { act.as_ref() };
+        if act.is_some() && matches!(sig as u32, ctypes::SIGKILL | ctypes::SIGSTOP) {
+            return Err(LinuxError::EINVAL);
+        }
+        let mut actions = SIG_ACTIONS.write();
+        if let Some(oldact) = unsafe { oldact.as_mut() } {
+            *oldact = actions[sig].into();
+        }
+        if let Some(act) = act {
+            actions[sig] = act.into();
+        }
+        Ok(0)
+    })
+}
+
+/// Examine and change the blocked signals of the current thread.
+///
+/// Pending signals that become unblocked are delivered before returning, as
+/// all signals are on returning from system calls.
+pub unsafe fn sys_sigprocmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    debug!(
+        "sys_sigprocmask <= how: {}, set: {:#x}, oldset: {:#x}",
+        how, set as usize, oldset as usize
+    );
+    syscall_body!(sys_sigprocmask, {
+        let curr = axtask::current();
+        let old = curr.blocked_signals();
+        let new = match unsafe { set.as_ref() } {
+            Some(set) => {
+                let set = set.__bits[0] as u64;
+                match how as u32 {
+                    ctypes::SIG_BLOCK => Some(old | set),
+                    ctypes::SIG_UNBLOCK => Some(old & !set),
+                    ctypes::SIG_SETMASK => Some(set),
+                    _ => return Err(LinuxError::EINVAL),
+                }
+            }
+            None => None,
+        };
+        if let Some(oldset) = unsafe { oldset.as_mut() } {
+            *oldset = Default::default();
+            oldset.__bits[0] = old as _;
+        }
+        if let Some(new) = new {
+            curr.set_blocked_signals(new & !UNBLOCKABLE);
+        }
+        Ok(0)
+    })
+}
+
+/// Send a signal to a thread.
+///
+/// A `pid` not greater than 0 or equal to `getpid()` refers to the current
+/// thread. Otherwise, it is the thread ID returned by `getpid()` in the target
+/// thread, or the process ID with the `process` feature, in which case the
+/// signal goes to the main thread of that process.
+pub fn sys_kill(pid: c_int, sig: c_int) -> c_int {
+    debug!("sys_kill <= pid: {}, sig: {}", pid, sig);
+    syscall_body!(sys_kill, {
+        let curr = axtask::current();
+        if pid <= 0 || pid == super::task::sys_getpid() {
+            send_signal(curr.as_task_ref(), sig)
+        } else {
+            #[cfg(feature = "process")]
+            let task = super::process::find(pid).and_then(|p| p.main_task());
+            #[cfg(not(feature = "process"))]
+            let task = pthread::find_task(pid as u64);
+            send_signal(&task.ok_or(LinuxError::ESRCH)?, sig)
+        }
+    })
+}
+
+/// Send a signal to the current thread, the handler is run before returning.
+pub fn sys_raise(sig: c_int) -> c_int {
+    debug!("sys_raise <= sig: {}", sig);
+    syscall_body!(sys_raise, send_signal(axtask::current().as_task_ref(), sig))
+}
+
+/// Send a signal to the given thread.
+pub fn sys_pthread_kill(thread: ctypes::pthread_t, sig: c_int) -> c_int {
+    debug!(
+        "sys_pthread_kill <= thread: {:#x}, sig: {}",
+        thread as usize, sig
+    );
+    syscall_body!(sys_pthread_kill, {
+        let task = pthread::pthread_to_task(thread).ok_or(LinuxError::ESRCH)?;
+        send_signal(&task, sig)
+    })
+}
+
+/// Wait until a signal is caught by a handler, always returns `EINTR`.
+pub fn sys_pause() -> c_int {
+    debug!("sys_pause <=");
+    syscall_body!(sys_pause, {
+        // Nothing notifies the queue, only unblocked signals end the wait.
+        let _ = PAUSE_QUEUE.wait_until_interruptible(|| false);
+        Err::<c_int, _>(LinuxError::EINTR)
+    })
+}
+
+fn remaining(deadline: Option<TimeValue>) -> Duration {
+    deadline.map_or(Duration::ZERO, |ddl| ddl.saturating_sub(current_time()))
+}
+
+fn check_itimer(which: c_int) -> LinuxResult {
+    // Only the real-time timer is supported.
+    if which as u32 == ctypes::ITIMER_REAL {
+        Ok(())
+    } else {
+        Err(LinuxError::EINVAL)
+    }
+}
+
+/// Get the value of an interval timer, only `ITIMER_REAL` is supported.
+pub unsafe fn sys_getitimer(which: c_int, curr_value: *mut ctypes::itimerval) -> c_int {
+    debug!(
+        "sys_getitimer <= which: {}, curr_value: {:#x}",
+        which, curr_value as usize
+    );
+    syscall_body!(sys_getitimer, {
+        check_itimer(which)?;
+        let curr_value = unsafe { curr_value.as_mut() }.ok_or(LinuxError::EFAULT)?;
+        let (deadline, interval) = real_timer().get();
+        curr_value.it_value = remaining(deadline).into();
+        curr_value.it_interval = interval.into();
+        Ok(0)
+    })
+}
+
+/// Set the value of an interval timer, only `ITIMER_REAL` is supported.
+///
+/// Each process has its own timer, which sends `SIGALRM` to the thread that
+/// set it when it expires.
+pub unsafe fn sys_setitimer(
+    which: c_int,
+    new_value: *const ctypes::itimerval,
+    old_value: *mut ctypes::itimerval,
+) -> c_int {
+    debug!(
+        "sys_setitimer <= which: {}, new_value: {:#x}, old_value: {:#x}",
+        which, new_value as usize, old_value as usize
+    );
+    syscall_body!(sys_setitimer, {
+        check_itimer(which)?;
+        let new_value = unsafe { new_value.as_ref() }.ok_or(LinuxError::EFAULT)?;
+        for tv in [new_value.it_value, new_value.it_interval] {
+            if tv.tv_sec < 0 || !(0..1_000_000).contains(&tv.tv_usec) {
+                return Err(LinuxError::EINVAL);
+            }
+        }
+        let value = Duration::from(new_value.it_value);
+        let (deadline, interval) = if value.is_zero() {
+            (None, Duration::ZERO)
+        } else {
+            (
+                Some(current_time() + value),
+                Duration::from(new_value.it_interval),
+            )
+        };
+        let (old_deadline, old_interval) = real_timer().set(deadline, interval);
+        if let Some(old_value) = unsafe { old_value.as_mut() } {
+            old_value.it_value = remaining(old_deadline).into();
+            old_value.it_interval = old_interval.into();
+        }
+        Ok(0)
+    })
+}
+
+/// Send `SIGALRM` to the calling thread after `seconds` seconds, or cancel
+/// the pending alarm if `seconds` is 0.
+///
+/// Returns the number of seconds remaining of the previous alarm, or 0 if
+/// there was none.
+pub fn sys_alarm(seconds: c_uint) -> c_uint {
+    debug!("sys_alarm <= {}", seconds);
+    let deadline = (seconds != 0).then(|| current_time() + Duration::from_secs(seconds as u64));
+    let (old_deadline, _) = real_timer().set(deadline, Duration::ZERO);
+    if old_deadline.is_none() {
+        return 0;
+    }
+    // Round to the nearest second, but never return 0 for an armed alarm.
+    let rem = remaining(old_deadline);
+    let secs = rem.as_secs() + (rem.subsec_micros() >= 500_000) as u64;
+    secs.max(1) as c_uint
+}
@@ -1,38 +1,317 @@
-use axerrno::AxResult;
-use axio::{prelude::*, BufReader};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use axerrno::{AxError, AxResult};
+use axio::prelude::*;
 use axsync::Mutex;
 
+use crate::ctypes;
+
 #[cfg(feature = "fd")]
-use {alloc::sync::Arc, axerrno::LinuxError, axerrno::LinuxResult, axio::PollState};
+use {
+    alloc::sync::Arc, axerrno::LinuxError, axerrno::LinuxResult, axio::PollState, core::ffi::c_int,
+};
 
-fn console_read_bytes() -> Option<u8> {
-    axhal::console::getchar().map(|c| if c == b'\r' { b'\n' } else { c })
-}
+/// The size of the input buffer of the console.
+const TTY_BUF_SIZE: usize = 4096;
 
 fn console_write_bytes(buf: &[u8]) -> AxResult<usize> {
     axhal::console::write_bytes(buf);
     Ok(buf.len())
 }
 
-struct StdinRaw;
-struct StdoutRaw;
+/// The line discipline of the console.
+///
+/// Input is fetched from the console when the terminal is read or polled, and
+/// is processed according to the terminal attributes: carriage returns are
+/// translated, typed bytes are echoed, and in the canonical mode, the input
+/// is edited line by line with `VERASE`, `VKILL` and `VEOF`.
+///
+/// diff: output is never post-processed, the console drivers already
+/// translate newlines.
+#[cfg_attr(not(feature = "fd"), allow(dead_code))]
+struct Tty {
+    termios: ctypes::termios,
+    winsize: ctypes::winsize,
+    /// The input, of which the first `ready` bytes can be read, and the rest
+    /// is the line being edited in the canonical mode.
+    buf: [u8; TTY_BUF_SIZE],
+    len: usize,
+    ready: usize,
+    /// Whether an end-of-file follows the ready bytes.
+    eof: bool,
+}
 
-impl Read for StdinRaw {
-    // Non-blocking read, returns number of bytes read.
-    fn read(&mut self, buf: &mut [u8]) -> AxResult<usize> {
-        let mut read_len = 0;
-        while read_len < buf.len() {
-            if let Some(c) = console_read_bytes() {
-                buf[read_len] = c;
-                read_len += 1;
+#[cfg_attr(not(feature = "fd"), allow(dead_code))]
+impl Tty {
+    const fn new() -> Self {
+        let mut c_cc = [0; 32];
+        c_cc[ctypes::VINTR as usize] = 0x03; // ^C
+        c_cc[ctypes::VERASE as usize] = 0x7f; // DEL
+        c_cc[ctypes::VKILL as usize] = 0x15; // ^U
+        c_cc[ctypes::VEOF as usize] = 0x04; // ^D
+        c_cc[ctypes::VMIN as usize] = 1;
+        Self {
+            termios: ctypes::termios {
+                c_iflag: ctypes::ICRNL | ctypes::IXON,
+                c_oflag: ctypes::OPOST | ctypes::ONLCR,
+                c_cflag: ctypes::B38400 | ctypes::CS8 | ctypes::CREAD | ctypes::HUPCL,
+                c_lflag: ctypes::ISIG
+                    | ctypes::ICANON
+                    | ctypes::ECHO
+                    | ctypes::ECHOE
+                    | ctypes::ECHOK
+                    | ctypes::ECHOCTL
+                    | ctypes::ECHOKE
+                    | ctypes::IEXTEN,
+                c_line: 0,
+                c_cc,
+                __c_ispeed: ctypes::B38400,
+                __c_ospeed: ctypes::B38400,
+            },
+            winsize: ctypes::winsize {
+                ws_row: 24,
+                ws_col: 80,
+                ws_xpixel: 0,
+                ws_ypixel: 0,
+            },
+            buf: [0; TTY_BUF_SIZE],
+            len: 0,
+            ready: 0,
+            eof: false,
+        }
+    }
+
+    fn lflag(&self, flag: u32) -> bool {
+        self.termios.c_lflag & flag != 0
+    }
+
+    fn cc(&self, idx: u32) -> u8 {
+        self.termios.c_cc[idx as usize]
+    }
+
+    fn is_ctrl_echoed(&self, c: u8) -> bool {
+        c.is_ascii_control() && c != b'\n' && c != b'\t' && self.lflag(ctypes::ECHOCTL)
+    }
+
+    fn echo(&self, c: u8) {
+        if self.is_ctrl_echoed(c) {
+            console_write_bytes(&[b'^', c ^ 0x40]).ok();
+        } else {
+            console_write_bytes(&[c]).ok();
+        }
+    }
+
+    fn push(&mut self, c: u8) {
+        self.buf[self.len] = c;
+        self.len += 1;
+    }
+
+    /// Erases the last byte of the line being edited, returns `false` if the
+    /// line is empty.
+    fn erase(&mut self) -> bool {
+        if self.len == self.ready {
+            return false;
+        }
+        self.len -= 1;
+        if self.lflag(ctypes::ECHO) && self.lflag(ctypes::ECHOE) {
+            let width = if self.is_ctrl_echoed(self.buf[self.len]) {
+                2
             } else {
-                break;
+                1
+            };
+            for _ in 0..width {
+                console_write_bytes(b"\x08 \x08").ok();
             }
         }
-        Ok(read_len)
+        true
+    }
+
+    fn is_eol(&self, c: u8) -> bool {
+        c == b'\n' || (c != 0 && c == self.cc(ctypes::VEOL))
+    }
+
+    /// Processes a byte of input, returns `true` if it is `VINTR`.
+    fn receive(&mut self, mut c: u8) -> bool {
+        let iflag = self.termios.c_iflag;
+        if c == b'\r' {
+            if iflag & ctypes::IGNCR != 0 {
+                return false;
+            }
+            if iflag & ctypes::ICRNL != 0 {
+                c = b'\n';
+            }
+        } else if c == b'\n' && iflag & ctypes::INLCR != 0 {
+            c = b'\r';
+        }
+
+        if self.lflag(ctypes::ISIG) && c == self.cc(ctypes::VINTR) {
+            if !self.lflag(ctypes::NOFLSH) {
+                self.flush();
+            }
+            if self.lflag(ctypes::ECHO) {
+                self.echo(c);
+            }
+            return true;
+        }
+
+        if !self.lflag(ctypes::ICANON) {
+            if self.len < TTY_BUF_SIZE {
+                self.push(c);
+                self.ready = self.len;
+                if self.lflag(ctypes::ECHO) {
+                    self.echo(c);
+                }
+            }
+        } else if c == self.cc(ctypes::VERASE) {
+            self.erase();
+        } else if c == self.cc(ctypes::VKILL) {
+            while self.erase() {}
+        } else if c == self.cc(ctypes::VEOF) {
+            // Commit the line without the `VEOF`, or end the file if it is empty.
+            if self.len == self.ready {
+                self.eof = true;
+            }
+            self.ready = self.len;
+        } else if self.is_eol(c) {
+            // There is always room for the end of a line.
+            self.push(c);
+            self.ready = self.len;
+            if self.lflag(ctypes::ECHO) || self.lflag(ctypes::ECHONL) {
+                self.echo(c);
+            }
+        } else if self.len + 1 < TTY_BUF_SIZE {
+            self.push(c);
+            if self.lflag(ctypes::ECHO) {
+                self.echo(c);
+            }
+        }
+        false
+    }
+
+    /// Processes all available input of the console, returns `true` if
+    /// `VINTR` is received.
+    fn fill(&mut self) -> bool {
+        let mut interrupted = false;
+        while let Some(c) = axhal::console::getchar() {
+            interrupted |= self.receive(c);
+        }
+        interrupted
     }
+
+    /// Reads the ready input, at most one line in the canonical mode.
+    ///
+    /// Returns `None` if the reader should wait for more input.
+    fn read(&mut self, buf: &mut [u8]) -> Option<usize> {
+        if self.ready > 0 {
+            let mut n = buf.len().min(self.ready);
+            if self.lflag(ctypes::ICANON) {
+                if let Some(pos) = self.buf[..n].iter().position(|&c| self.is_eol(c)) {
+                    n = pos + 1;
+                }
+            }
+            buf[..n].copy_from_slice(&self.buf[..n]);
+            self.buf.copy_within(n..self.len, 0);
+            self.len -= n;
+            self.ready -= n;
+            Some(n)
+        } else if self.eof {
+            self.eof = false;
+            Some(0)
+        } else if !self.lflag(ctypes::ICANON) && self.cc(ctypes::VMIN) == 0 {
+            // diff: `VTIME` is ignored, such reads never wait.
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    fn is_readable(&self) -> bool {
+        self.ready > 0 || self.eof
+    }
+
+    /// Discards all input.
+    fn flush(&mut self) {
+        self.len = 0;
+        self.ready = 0;
+        self.eof = false;
+    }
+
+    fn set_termios(&mut self, termios: ctypes::termios) {
+        self.termios = termios;
+        // The line being edited becomes readable out of the canonical mode.
+        if !self.lflag(ctypes::ICANON) {
+            self.ready = self.len;
+        }
+    }
+}
+
+static TTY: Mutex<Tty> = Mutex::new(Tty::new());
+
+/// Fetches input of the console and calls `f` on the terminal, and then
+/// interrupts the caller if `VINTR` is received.
+fn with_input<R>(f: impl FnOnce(&mut Tty) -> R) -> R {
+    let mut tty = TTY.lock();
+    let interrupted = tty.fill();
+    let ret = f(&mut tty);
+    drop(tty);
+    if interrupted {
+        interrupt();
+    }
+    ret
+}
+
+/// Sends `SIGINT` to the current thread, as the console has no foreground
+/// process group. Without signals, `VINTR` only discards the input.
+fn interrupt() {
+    #[cfg(feature = "signal")]
+    crate::sys_raise(ctypes::SIGINT as _);
+}
+
+#[cfg(feature = "fd")]
+fn tty_ioctl(cmd: u32, arg: usize) -> LinuxResult<c_int> {
+    match cmd {
+        ctypes::TCGETS => {
+            let termios =
+                unsafe { (arg as *mut ctypes::termios).as_mut() }.ok_or(LinuxError::EFAULT)?;
+            *termios = TTY.lock().termios;
+        }
+        ctypes::TCSETS | ctypes::TCSETSW | ctypes::TCSETSF => {
+            let termios =
+                unsafe { (arg as *const ctypes::termios).as_ref() }.ok_or(LinuxError::EFAULT)?;
+            let mut tty = TTY.lock();
+            if cmd == ctypes::TCSETSF {
+                tty.flush();
+            }
+            tty.set_termios(*termios);
+        }
+        ctypes::TIOCGWINSZ => {
+            let winsize =
+                unsafe { (arg as *mut ctypes::winsize).as_mut() }.ok_or(LinuxError::EFAULT)?;
+            *winsize = TTY.lock().winsize;
+        }
+        ctypes::TIOCSWINSZ => {
+            let winsize =
+                unsafe { (arg as *const ctypes::winsize).as_ref() }.ok_or(LinuxError::EFAULT)?;
+            TTY.lock().winsize = *winsize;
+        }
+        ctypes::FIONREAD => {
+            let count = unsafe { (arg as *mut c_int).as_mut() }.ok_or(LinuxError::EFAULT)?;
+            *count = with_input(|tty| tty.ready) as c_int;
+        }
+        ctypes::TCFLSH => match arg as u32 {
+            ctypes::TCIFLUSH | ctypes::TCIOFLUSH => TTY.lock().flush(),
+            ctypes::TCOFLUSH => {}
+            _ => return Err(LinuxError::EINVAL),
+        },
+        // Output is written to the console immediately, there is nothing to drain.
+        ctypes::TCSBRK => {}
+        _ => return Err(LinuxError::ENOTTY),
+    }
+    Ok(0)
 }
 
+struct StdoutRaw;
+
 impl Write for StdoutRaw {
     fn write(&mut self, buf: &[u8]) -> AxResult<usize> {
         console_write_bytes(buf)
@@ -44,22 +323,22 @@ impl Write for StdoutRaw {
 }
 
 pub struct Stdin {
-    inner: &'static Mutex<BufReader<StdinRaw>>,
+    nonblocking: AtomicBool,
 }
 
 impl Stdin {
-    // Block until at least one byte is read.
+    // Block until at least one byte is read, or the end of file.
     fn read_blocked(&self, buf: &mut [u8]) -> AxResult<usize> {
-        let read_len = self.inner.lock().read(buf)?;
-        if buf.is_empty() || read_len > 0 {
-            return Ok(read_len);
+        if buf.is_empty() {
+            return Ok(0);
         }
-        // try again until we get something
         loop {
-            let read_len = self.inner.lock().read(buf)?;
-            if read_len > 0 {
+            if let Some(read_len) = with_input(|tty| tty.read(buf)) {
                 return Ok(read_len);
             }
+            if self.nonblocking.load(Ordering::Relaxed) {
+                return Err(AxError::WouldBlock);
+            }
             crate::sys_sched_yield();
         }
     }
@@ -87,8 +366,9 @@ impl Write for Stdout {
 
 /// Constructs a new handle to the standard input of the current process.
 pub fn stdin() -> Stdin {
-    static INSTANCE: Mutex<BufReader<StdinRaw>> = Mutex::new(BufReader::new(StdinRaw));
-    Stdin { inner: &INSTANCE }
+    Stdin {
+        nonblocking: AtomicBool::new(false),
+    }
 }
 
 /// Constructs a new handle to the standard output of the current process.
@@ -122,15 +402,17 @@ impl super::fd_ops::FileLike for Stdin {
     }
 
     fn poll(&self) -> LinuxResult<PollState> {
-        Ok(PollState {
-            readable: true,
-            writable: true,
-        })
+        Ok(PollState::new(with_input(|tty| tty.is_readable()), true))
     }
 
-    fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
+    fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult {
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
         Ok(())
     }
+
+    fn ioctl(&self, cmd: u32, arg: usize) -> LinuxResult<c_int> {
+        tty_ioctl(cmd, arg)
+    }
 }
 
 #[cfg(feature = "fd")]
@@ -158,13 +440,18 @@ impl super::fd_ops::FileLike for Stdout {
     }
 
     fn poll(&self) -> LinuxResult<PollState> {
-        Ok(PollState {
-            readable: true,
-            writable: true,
-        })
+        Ok(PollState::new(true, true))
+    }
+
+    fn notifies_pollers(&self) -> bool {
+        true // never changes
     }
 
     fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
         Ok(())
     }
+
+    fn ioctl(&self, cmd: u32, arg: usize) -> LinuxResult<c_int> {
+        tty_ioctl(cmd, arg)
+    }
 }
@@ -1,4 +1,6 @@
-use core::ffi::{c_int, c_long};
+use core::ffi::{c_int, c_long, c_uint, c_void};
+
+use axerrno::LinuxError;
 
 use crate::ctypes;
 
@@ -22,8 +24,33 @@ pub fn sys_sysconf(name: c_int) -> c_long {
             ctypes::_SC_AVPHYS_PAGES => Ok(axalloc::global_allocator().available_pages()),
             // Maximum number of files per process
             #[cfg(feature = "fd")]
-            ctypes::_SC_OPEN_MAX => Ok(super::fd_ops::AX_FILE_LIMIT),
+            ctypes::_SC_OPEN_MAX => Ok(super::resources::nofile_limit()),
             _ => Ok(0),
         }
     })
 }
+
+/// Fill a buffer with random bytes from the kernel random number generator
+///
+/// diff: it never blocks, and `GRND_RANDOM` draws from the same source as
+/// `/dev/urandom`, as the generator is seeded during boot.
+pub fn sys_getrandom(buf: *mut c_void, buflen: usize, flags: c_uint) -> ctypes::ssize_t {
+    debug!(
+        "sys_getrandom <= {:#x} {} {:#x}",
+        buf as usize, buflen, flags
+    );
+    syscall_body!(sys_getrandom, {
+        if flags & !(ctypes::GRND_NONBLOCK | ctypes::GRND_RANDOM | ctypes::GRND_INSECURE) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        if buflen == 0 {
+            return Ok(0);
+        }
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let dst = unsafe { core::slice::from_raw_parts_mut(buf as *mut u8, buflen) };
+        axrand::fill_bytes(dst);
+        Ok(buflen as ctypes::ssize_t)
+    })
+}
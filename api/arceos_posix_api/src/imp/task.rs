@@ -1,5 +1,13 @@
 use core::ffi::c_int;
 
+#[cfg(feature = "multitask")]
+use axerrno::{LinuxError, LinuxResult};
+#[cfg(feature = "multitask")]
+use axtask::{AxTaskRef, SchedPolicy};
+
+#[cfg(feature = "multitask")]
+use crate::ctypes;
+
 /// Relinquish the CPU, and switches to another task.
 ///
 /// For single-threaded configuration (`multitask` feature is disabled), we just
@@ -16,10 +24,23 @@ pub fn sys_sched_yield() -> c_int {
     0
 }
 
-/// Get current thread ID.
+/// Get the CPU that the current thread is running on.
+///
+/// The thread may be moved to another CPU right after it returns, unless its
+/// affinity mask contains only one CPU.
+pub fn sys_sched_getcpu() -> c_int {
+    axhal::cpu::this_cpu_id() as c_int
+}
+
+/// Get current thread ID, or the process ID if the `process` feature is
+/// enabled.
 pub fn sys_getpid() -> c_int {
     syscall_body!(sys_getpid,
-        #[cfg(feature = "multitask")]
+        #[cfg(feature = "process")]
+        {
+            Ok(super::process::current().pid())
+        }
+        #[cfg(all(feature = "multitask", not(feature = "process")))]
         {
             Ok(axtask::current().id().as_u64() as c_int)
         }
@@ -33,8 +54,255 @@ pub fn sys_getpid() -> c_int {
 /// Exit current task
 pub fn sys_exit(exit_code: c_int) -> ! {
     debug!("sys_exit <= {}", exit_code);
+    #[cfg(feature = "process")]
+    if !super::process::current().is_init() {
+        super::process::exit_current((exit_code & 0xff) << 8);
+    }
     #[cfg(feature = "multitask")]
     axtask::exit(exit_code);
     #[cfg(not(feature = "multitask"))]
     axhal::misc::terminate();
 }
+
+/// Returns the task of the thread `pid`, or the main thread of the process
+/// `pid` if the `process` feature is enabled. Zero means the current thread.
+#[cfg(feature = "multitask")]
+fn find_task(pid: c_int) -> LinuxResult<AxTaskRef> {
+    if pid == 0 || pid == sys_getpid() {
+        return Ok(axtask::current().as_task_ref().clone());
+    }
+    #[cfg(feature = "process")]
+    let task = super::process::find(pid).and_then(|p| p.main_task());
+    #[cfg(not(feature = "process"))]
+    let task = super::pthread::find_task(pid as u64);
+    task.ok_or(LinuxError::ESRCH)
+}
+
+/// Converts the CPU set of `cpusetsize` bytes at `set` to a mask of CPUs, bit
+/// `i` of the mask stands for CPU `i`.
+///
+/// CPUs not less than `axconfig::SMP` are ignored.
+#[cfg(feature = "multitask")]
+pub(crate) unsafe fn cpu_set_to_mask(
+    cpusetsize: usize,
+    set: *const ctypes::cpu_set_t,
+) -> LinuxResult<usize> {
+    if set.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    let bytes = core::slice::from_raw_parts(set as *const u8, cpusetsize);
+    let mask = (0..axconfig::SMP)
+        .filter(|&cpu| cpu / 8 < cpusetsize && bytes[cpu / 8] & (1 << (cpu % 8)) != 0)
+        .fold(0, |mask, cpu| mask | (1 << cpu));
+    if mask == 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(mask)
+}
+
+/// Stores the mask of CPUs into the CPU set of `cpusetsize` bytes at `set`.
+#[cfg(feature = "multitask")]
+pub(crate) unsafe fn mask_to_cpu_set(
+    mask: usize,
+    cpusetsize: usize,
+    set: *mut ctypes::cpu_set_t,
+) -> LinuxResult {
+    if set.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    if cpusetsize * 8 < axconfig::SMP {
+        return Err(LinuxError::EINVAL);
+    }
+    let bytes = core::slice::from_raw_parts_mut(set as *mut u8, cpusetsize);
+    bytes.fill(0);
+    for cpu in (0..axconfig::SMP).filter(|&cpu| mask & (1 << cpu) != 0) {
+        bytes[cpu / 8] |= 1 << (cpu % 8);
+    }
+    Ok(())
+}
+
+/// Set the CPU affinity mask of the thread `pid` (the current thread if it is
+/// zero) to the CPU set `mask` of `cpusetsize` bytes.
+///
+/// The thread is migrated to one of the CPUs in the set if it is running on
+/// other CPUs.
+///
+/// diff: `pid` refers to the main thread of the process if the `process`
+/// feature is enabled, rather than any thread.
+#[cfg(feature = "multitask")]
+pub unsafe fn sys_sched_setaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *const ctypes::cpu_set_t,
+) -> c_int {
+    debug!(
+        "sys_sched_setaffinity <= pid: {}, cpusetsize: {}, mask: {:#x}",
+        pid, cpusetsize, mask as usize
+    );
+    syscall_body!(sys_sched_setaffinity, {
+        let cpumask = cpu_set_to_mask(cpusetsize, mask)?;
+        axtask::set_affinity(&find_task(pid)?, cpumask);
+        Ok(0)
+    })
+}
+
+/// Get the CPU affinity mask of the thread `pid` (the current thread if it is
+/// zero), and store it into the CPU set `mask` of `cpusetsize` bytes.
+///
+/// diff: returns 0 on success like the glibc wrapper, rather than the size of
+/// the copied set.
+#[cfg(feature = "multitask")]
+pub unsafe fn sys_sched_getaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *mut ctypes::cpu_set_t,
+) -> c_int {
+    debug!(
+        "sys_sched_getaffinity <= pid: {}, cpusetsize: {}, mask: {:#x}",
+        pid, cpusetsize, mask as usize
+    );
+    syscall_body!(sys_sched_getaffinity, {
+        let task = find_task(pid)?;
+        mask_to_cpu_set(task.affinity(), cpusetsize, mask)?;
+        Ok(0)
+    })
+}
+
+/// Converts a duration in `timespec` to scheduler ticks, rounding up.
+#[cfg(feature = "multitask")]
+fn timespec_to_ticks(ts: ctypes::timespec) -> LinuxResult<usize> {
+    if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+        return Err(LinuxError::EINVAL);
+    }
+    let nanos = core::time::Duration::from(ts).as_nanos();
+    let ticks = (nanos * axconfig::TICKS_PER_SEC as u128).div_ceil(1_000_000_000);
+    usize::try_from(ticks).map_err(|_| LinuxError::EINVAL)
+}
+
+/// Converts scheduler ticks to a duration in `timespec`.
+#[cfg(feature = "multitask")]
+fn ticks_to_timespec(ticks: usize) -> ctypes::timespec {
+    let nanos = ticks as u64 * 1_000_000_000 / axconfig::TICKS_PER_SEC as u64;
+    core::time::Duration::from_nanos(nanos).into()
+}
+
+/// Set the scheduling policy and parameters of the thread `pid` (the current
+/// thread if it is zero).
+///
+/// Real-time policies take effect only if the `sched_rt` feature of `axtask`
+/// is enabled, otherwise only `SCHED_OTHER` is accepted.
+///
+/// diff: `SCHED_BATCH` and `SCHED_IDLE` are the same as `SCHED_OTHER`. The
+/// runtime and period of `SCHED_DEADLINE` are taken from
+/// `sched_ss_init_budget` and `sched_ss_repl_period` of `param`, and are
+/// rounded up to timer ticks.
+#[cfg(feature = "multitask")]
+pub unsafe fn sys_sched_setscheduler(
+    pid: c_int,
+    policy: c_int,
+    param: *const ctypes::sched_param,
+) -> c_int {
+    debug!(
+        "sys_sched_setscheduler <= pid: {}, policy: {}, param: {:#x}",
+        pid, policy, param as usize
+    );
+    syscall_body!(sys_sched_setscheduler, {
+        if param.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let param = &*param;
+        let prio = usize::try_from(param.sched_priority).map_err(|_| LinuxError::EINVAL)?;
+        let policy = match policy as u32 {
+            ctypes::SCHED_OTHER | ctypes::SCHED_BATCH | ctypes::SCHED_IDLE if prio == 0 => {
+                SchedPolicy::Normal
+            }
+            ctypes::SCHED_FIFO => SchedPolicy::Fifo(prio),
+            ctypes::SCHED_RR => SchedPolicy::RoundRobin(prio),
+            ctypes::SCHED_DEADLINE if prio == 0 => SchedPolicy::Deadline {
+                budget: timespec_to_ticks(param.sched_ss_init_budget)?,
+                period: timespec_to_ticks(param.sched_ss_repl_period)?,
+            },
+            _ => return Err(LinuxError::EINVAL),
+        };
+        if !axtask::set_sched_policy(&find_task(pid)?, policy) {
+            return Err(LinuxError::EINVAL);
+        }
+        Ok(0)
+    })
+}
+
+/// Get the scheduling policy of the thread `pid` (the current thread if it is
+/// zero).
+#[cfg(feature = "multitask")]
+pub fn sys_sched_getscheduler(pid: c_int) -> c_int {
+    debug!("sys_sched_getscheduler <= pid: {}", pid);
+    syscall_body!(sys_sched_getscheduler, {
+        let policy = match axtask::sched_policy(&find_task(pid)?) {
+            SchedPolicy::Normal => ctypes::SCHED_OTHER,
+            SchedPolicy::Fifo(_) => ctypes::SCHED_FIFO,
+            SchedPolicy::RoundRobin(_) => ctypes::SCHED_RR,
+            SchedPolicy::Deadline { .. } => ctypes::SCHED_DEADLINE,
+        };
+        Ok(policy as c_int)
+    })
+}
+
+/// Get the scheduling parameters of the thread `pid` (the current thread if
+/// it is zero).
+#[cfg(feature = "multitask")]
+pub unsafe fn sys_sched_getparam(pid: c_int, param: *mut ctypes::sched_param) -> c_int {
+    debug!(
+        "sys_sched_getparam <= pid: {}, param: {:#x}",
+        pid, param as usize
+    );
+    syscall_body!(sys_sched_getparam, {
+        if param.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let mut res: ctypes::sched_param = core::mem::zeroed();
+        match axtask::sched_policy(&find_task(pid)?) {
+            SchedPolicy::Normal => {}
+            SchedPolicy::Fifo(prio) | SchedPolicy::RoundRobin(prio) => {
+                res.sched_priority = prio as c_int;
+            }
+            SchedPolicy::Deadline { budget, period } => {
+                res.sched_ss_init_budget = ticks_to_timespec(budget);
+                res.sched_ss_repl_period = ticks_to_timespec(period);
+            }
+        }
+        *param = res;
+        Ok(0)
+    })
+}
+
+/// Returns the priority range of the scheduling policy `policy`.
+#[cfg(feature = "multitask")]
+fn sched_priority_range(policy: c_int) -> LinuxResult<(c_int, c_int)> {
+    match policy as u32 {
+        ctypes::SCHED_FIFO | ctypes::SCHED_RR => {
+            Ok((axtask::RT_PRIO_MIN as c_int, axtask::RT_PRIO_MAX as c_int))
+        }
+        ctypes::SCHED_OTHER | ctypes::SCHED_BATCH | ctypes::SCHED_IDLE | ctypes::SCHED_DEADLINE => {
+            Ok((0, 0))
+        }
+        _ => Err(LinuxError::EINVAL),
+    }
+}
+
+/// Get the maximum priority of the scheduling policy `policy`.
+#[cfg(feature = "multitask")]
+pub fn sys_sched_get_priority_max(policy: c_int) -> c_int {
+    debug!("sys_sched_get_priority_max <= policy: {}", policy);
+    syscall_body!(sys_sched_get_priority_max, {
+        sched_priority_range(policy).map(|(_, max)| max)
+    })
+}
+
+/// Get the minimum priority of the scheduling policy `policy`.
+#[cfg(feature = "multitask")]
+pub fn sys_sched_get_priority_min(policy: c_int) -> c_int {
+    debug!("sys_sched_get_priority_min <= policy: {}", policy);
+    syscall_body!(sys_sched_get_priority_min, {
+        sched_priority_range(policy).map(|(min, _)| min)
+    })
+}
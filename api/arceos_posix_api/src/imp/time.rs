@@ -1,4 +1,4 @@
-use axerrno::LinuxError;
+use axerrno::{LinuxError, LinuxResult};
 use core::ffi::{c_int, c_long};
 use core::time::Duration;
 
@@ -34,22 +34,93 @@ impl From<Duration> for ctypes::timeval {
     }
 }
 
-/// Get clock time since booting
-pub unsafe fn sys_clock_gettime(_clk: ctypes::clockid_t, ts: *mut ctypes::timespec) -> c_int {
+/// Returns the CPU time consumed by the current thread, or by the process if
+/// `thread` is false.
+///
+/// diff: the process is the whole system, i.e. all tasks are counted, even
+/// with the `process` feature.
+pub(crate) fn cpu_time(thread: bool) -> Duration {
+    #[cfg(feature = "multitask")]
+    {
+        if thread {
+            axtask::current().cpu_time()
+        } else {
+            axtask::total_cpu_time()
+        }
+    }
+    // The only task is always running.
+    #[cfg(not(feature = "multitask"))]
+    {
+        let _ = thread;
+        axhal::time::current_time()
+    }
+}
+
+fn clock_time(clk: ctypes::clockid_t) -> LinuxResult<Duration> {
+    Ok(match clk as u32 {
+        ctypes::CLOCK_REALTIME | ctypes::CLOCK_REALTIME_COARSE => axhal::time::wall_time(),
+        ctypes::CLOCK_MONOTONIC
+        | ctypes::CLOCK_MONOTONIC_RAW
+        | ctypes::CLOCK_MONOTONIC_COARSE
+        | ctypes::CLOCK_BOOTTIME => axhal::time::current_time(),
+        ctypes::CLOCK_PROCESS_CPUTIME_ID => cpu_time(false),
+        ctypes::CLOCK_THREAD_CPUTIME_ID => cpu_time(true),
+        _ => return Err(LinuxError::EINVAL),
+    })
+}
+
+/// Get the time of the clock `clk`.
+///
+/// `CLOCK_REALTIME` is the wall clock time since the epoch, the monotonic
+/// clocks count the time since booting, and the CPU-time clocks count the
+/// time the current thread or process has been running.
+pub unsafe fn sys_clock_gettime(clk: ctypes::clockid_t, ts: *mut ctypes::timespec) -> c_int {
     syscall_body!(sys_clock_gettime, {
         if ts.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        let now = axhal::time::current_time().into();
+        let now = clock_time(clk)?.into();
         unsafe { *ts = now };
         debug!("sys_clock_gettime: {}.{:09}s", now.tv_sec, now.tv_nsec);
         Ok(0)
     })
 }
 
+/// Set the time of the clock `clk`, only `CLOCK_REALTIME` can be set.
+pub unsafe fn sys_clock_settime(clk: ctypes::clockid_t, ts: *const ctypes::timespec) -> c_int {
+    syscall_body!(sys_clock_settime, {
+        let ts = unsafe { ts.as_ref() }.ok_or(LinuxError::EFAULT)?;
+        debug!(
+            "sys_clock_settime <= {} {}.{:09}s",
+            clk, ts.tv_sec, ts.tv_nsec
+        );
+        if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+            return Err(LinuxError::EINVAL);
+        }
+        match clk as u32 {
+            ctypes::CLOCK_REALTIME => axhal::time::set_wall_time(Duration::from(*ts)),
+            _ => return Err(LinuxError::EINVAL),
+        }
+        Ok(0)
+    })
+}
+
+/// Get the resolution of the clock `clk`, which is one nanosecond for all
+/// clocks.
+pub unsafe fn sys_clock_getres(clk: ctypes::clockid_t, res: *mut ctypes::timespec) -> c_int {
+    syscall_body!(sys_clock_getres, {
+        clock_time(clk)?;
+        if let Some(res) = unsafe { res.as_mut() } {
+            *res = Duration::from_nanos(1).into();
+        }
+        Ok(0)
+    })
+}
+
 /// Sleep some nanoseconds
 ///
-/// TODO: should be woken by signals, and set errno
+/// If the sleep is interrupted (e.g. by a signal), the remaining time is
+/// stored in `rem` and `EINTR` is returned.
 pub unsafe fn sys_nanosleep(req: *const ctypes::timespec, rem: *mut ctypes::timespec) -> c_int {
     syscall_body!(sys_nanosleep, {
         unsafe {
@@ -0,0 +1,292 @@
+use alloc::sync::Arc;
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{current_time, TimeValue};
+use axio::PollState;
+use axsync::spin::SpinNoIrq;
+use axtask::WaitQueue;
+
+use super::fd_ops::{add_file_like, get_file_like, notify_pollers, FileLike};
+use crate::ctypes;
+
+struct TimerState {
+    /// The next expiration time, `None` if the timer is disarmed.
+    deadline: Option<TimeValue>,
+    /// The period of the timer, zero for a one-shot timer.
+    interval: TimeValue,
+    /// The number of expirations since the last read.
+    expirations: u64,
+    /// Increased every time the timer is set, to ignore stale timer events.
+    generation: u64,
+}
+
+/// The state of a timerfd shared with its timer events.
+struct Timer {
+    state: SpinNoIrq<TimerState>,
+    /// Readers blocked until the timer expires.
+    expired: WaitQueue,
+}
+
+/// Keys of timer events in `axtask`, one for each timerfd.
+static NEXT_KEY: AtomicUsize = AtomicUsize::new(0);
+
+/// A file descriptor that counts expirations of a timer.
+///
+/// The timer is driven by the `axtask` timer list, and is updated in the
+/// timer interrupt handler, so its state is protected by [`SpinNoIrq`].
+/// Blocked readers are woken up there on every expiration.
+pub struct TimerFd {
+    key: usize,
+    realtime: bool,
+    timer: Arc<Timer>,
+    nonblocking: AtomicBool,
+}
+
+impl TimerFd {
+    fn new(realtime: bool, nonblocking: bool) -> Self {
+        Self {
+            key: NEXT_KEY.fetch_add(1, Ordering::Relaxed),
+            realtime,
+            timer: Arc::new(Timer {
+                state: SpinNoIrq::new(TimerState {
+                    deadline: None,
+                    interval: Duration::ZERO,
+                    expirations: 0,
+                    generation: 0,
+                }),
+                expired: WaitQueue::new(),
+            }),
+            nonblocking: AtomicBool::new(nonblocking),
+        }
+    }
+
+    fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+        get_file_like(fd)?
+            .into_any()
+            .downcast::<TimerFd>()
+            .map_err(|_| LinuxError::EINVAL)
+    }
+
+    /// Returns the time until the next expiration and the interval.
+    fn get(&self) -> (Duration, Duration) {
+        let state = self.timer.state.lock();
+        let remaining = state
+            .deadline
+            .map_or(Duration::ZERO, |ddl| ddl.saturating_sub(current_time()));
+        (remaining, state.interval)
+    }
+
+    /// Arms the timer to expire at `deadline`, and then every `interval` if
+    /// it is not zero, or disarms it if `deadline` is `None`.
+    fn set(&self, deadline: Option<TimeValue>, interval: Duration) {
+        let mut state = self.timer.state.lock();
+        state.generation += 1;
+        state.deadline = deadline;
+        state.interval = interval;
+        state.expirations = 0;
+        axtask::cancel_timer(self.key);
+        if let Some(deadline) = deadline {
+            arm(self.key, &self.timer, deadline, state.generation);
+        }
+    }
+}
+
+fn arm(key: usize, timer: &Arc<Timer>, deadline: TimeValue, generation: u64) {
+    let timer = Arc::downgrade(timer);
+    axtask::set_timer(deadline, key, move |now| {
+        if let Some(timer) = timer.upgrade() {
+            expired(key, &timer, generation, now);
+        }
+    });
+}
+
+fn expired(key: usize, timer: &Arc<Timer>, generation: u64, now: TimeValue) {
+    let mut st = timer.state.lock();
+    if st.generation != generation {
+        return;
+    }
+    let Some(deadline) = st.deadline else {
+        return;
+    };
+    if st.interval.is_zero() {
+        st.expirations += 1;
+        st.deadline = None;
+    } else {
+        // Also count the periods missed, if the timer interrupt came late.
+        let interval = st.interval.as_nanos();
+        let periods = (now.saturating_sub(deadline).as_nanos() / interval) as u64 + 1;
+        let next = deadline + Duration::from_nanos((interval * periods as u128) as u64);
+        st.expirations += periods;
+        st.deadline = Some(next);
+        arm(key, timer, next, generation);
+    }
+    drop(st);
+    timer.expired.notify_all(true);
+    notify_pollers();
+}
+
+impl Drop for TimerFd {
+    fn drop(&mut self) {
+        axtask::cancel_timer(self.key);
+    }
+}
+
+impl FileLike for TimerFd {
+    fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        if buf.len() < 8 {
+            return Err(LinuxError::EINVAL);
+        }
+        loop {
+            let mut state = self.timer.state.lock();
+            if state.expirations > 0 {
+                buf[..8].copy_from_slice(&state.expirations.to_ne_bytes());
+                state.expirations = 0;
+                return Ok(8);
+            }
+            drop(state);
+            if self.nonblocking.load(Ordering::Relaxed) {
+                return Err(LinuxError::EAGAIN);
+            }
+            let expired = || self.timer.state.lock().expirations > 0;
+            #[cfg(feature = "signal")]
+            self.timer
+                .expired
+                .wait_until_interruptible(expired)
+                .map_err(|_| LinuxError::EINTR)?;
+            #[cfg(not(feature = "signal"))]
+            self.timer.expired.wait_until(expired);
+        }
+    }
+
+    fn write(&self, _buf: &[u8]) -> LinuxResult<usize> {
+        Err(LinuxError::EINVAL)
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        Ok(ctypes::stat {
+            st_ino: 1,
+            st_nlink: 1,
+            st_mode: 0o600u32, // rw-------
+            st_uid: 1000,
+            st_gid: 1000,
+            st_blksize: 4096,
+            ..Default::default()
+        })
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        let readable = self.timer.state.lock().expirations > 0;
+        Ok(PollState::new(readable, false))
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult {
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn notifies_pollers(&self) -> bool {
+        true
+    }
+}
+
+fn check_timespec(ts: &ctypes::timespec) -> LinuxResult {
+    if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(())
+}
+
+/// Create a timer that notifies via a file descriptor.
+///
+/// `CLOCK_REALTIME` and `CLOCK_MONOTONIC` are supported, they only differ in
+/// absolute times. `TFD_CLOEXEC` is accepted but has no effect.
+///
+/// diff: a `CLOCK_REALTIME` timer is not adjusted when the clock is set.
+///
+/// Return the new file descriptor.
+pub fn sys_timerfd_create(clockid: ctypes::clockid_t, flags: c_int) -> c_int {
+    debug!("sys_timerfd_create <= {} {:#x}", clockid, flags);
+    syscall_body!(sys_timerfd_create, {
+        if !matches!(
+            clockid as u32,
+            ctypes::CLOCK_REALTIME | ctypes::CLOCK_MONOTONIC
+        ) {
+            return Err(LinuxError::EINVAL);
+        }
+        let flags = flags as u32;
+        if flags & !(ctypes::TFD_NONBLOCK | ctypes::TFD_CLOEXEC) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let realtime = clockid as u32 == ctypes::CLOCK_REALTIME;
+        let nonblocking = flags & ctypes::TFD_NONBLOCK != 0;
+        add_file_like(Arc::new(TimerFd::new(realtime, nonblocking)))
+    })
+}
+
+/// Arm or disarm the timer of a timerfd.
+///
+/// The timer expires after `it_value`, or at `it_value` if `TFD_TIMER_ABSTIME`
+/// is set, and then every `it_interval` if it is not zero. A zero `it_value`
+/// disarms the timer. The previous setting is stored in `old_value` if it is
+/// not null.
+pub unsafe fn sys_timerfd_settime(
+    fd: c_int,
+    flags: c_int,
+    new_value: *const ctypes::itimerspec,
+    old_value: *mut ctypes::itimerspec,
+) -> c_int {
+    debug!(
+        "sys_timerfd_settime <= {} {:#x} {:#x} {:#x}",
+        fd, flags, new_value as usize, old_value as usize
+    );
+    syscall_body!(sys_timerfd_settime, {
+        let timer = TimerFd::from_fd(fd)?;
+        let new_value = unsafe { new_value.as_ref() }.ok_or(LinuxError::EFAULT)?;
+        if flags as u32 & !ctypes::TFD_TIMER_ABSTIME != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        check_timespec(&new_value.it_value)?;
+        check_timespec(&new_value.it_interval)?;
+
+        if let Some(old_value) = unsafe { old_value.as_mut() } {
+            let (remaining, interval) = timer.get();
+            old_value.it_value = remaining.into();
+            old_value.it_interval = interval.into();
+        }
+        let value = Duration::from(new_value.it_value);
+        let deadline = if value.is_zero() {
+            None
+        } else if flags as u32 & ctypes::TFD_TIMER_ABSTIME != 0 {
+            if timer.realtime {
+                let offset = Duration::from_nanos(axhal::time::epochoffset_nanos());
+                Some(value.saturating_sub(offset))
+            } else {
+                Some(value)
+            }
+        } else {
+            Some(current_time() + value)
+        };
+        timer.set(deadline, Duration::from(new_value.it_interval));
+        Ok(0)
+    })
+}
+
+/// Get the time until the next expiration of a timerfd, and its interval.
+pub unsafe fn sys_timerfd_gettime(fd: c_int, curr_value: *mut ctypes::itimerspec) -> c_int {
+    debug!("sys_timerfd_gettime <= {} {:#x}", fd, curr_value as usize);
+    syscall_body!(sys_timerfd_gettime, {
+        let timer = TimerFd::from_fd(fd)?;
+        let curr_value = unsafe { curr_value.as_mut() }.ok_or(LinuxError::EFAULT)?;
+        let (remaining, interval) = timer.get();
+        curr_value.it_value = remaining.into();
+        curr_value.it_interval = interval.into();
+        Ok(0)
+    })
+}
@@ -32,31 +32,79 @@ pub mod config {
 #[allow(dead_code, non_snake_case, non_camel_case_types, non_upper_case_globals, clippy::upper_case_acronyms, missing_docs)]
 pub mod ctypes;
 
-pub use imp::io::{sys_read, sys_write, sys_writev};
-pub use imp::resources::{sys_getrlimit, sys_setrlimit};
-pub use imp::sys::sys_sysconf;
-pub use imp::task::{sys_exit, sys_getpid, sys_sched_yield};
-pub use imp::time::{sys_clock_gettime, sys_nanosleep};
+pub use imp::io::{sys_read, sys_readv, sys_write, sys_writev};
+pub use imp::resources::{sys_getrlimit, sys_getrusage, sys_setrlimit};
+pub use imp::sys::{sys_getrandom, sys_sysconf};
+pub use imp::task::{sys_exit, sys_getpid, sys_sched_getcpu, sys_sched_yield};
+#[cfg(feature = "multitask")]
+pub use imp::task::{sys_sched_get_priority_max, sys_sched_get_priority_min};
+#[cfg(feature = "multitask")]
+pub use imp::task::{sys_sched_getaffinity, sys_sched_setaffinity};
+#[cfg(feature = "multitask")]
+pub use imp::task::{sys_sched_getparam, sys_sched_getscheduler, sys_sched_setscheduler};
+pub use imp::time::{sys_clock_getres, sys_clock_gettime, sys_clock_settime, sys_nanosleep};
 
+#[cfg(feature = "eventfd")]
+pub use imp::eventfd::sys_eventfd;
 #[cfg(feature = "fd")]
-pub use imp::fd_ops::{sys_close, sys_dup, sys_dup2, sys_fcntl};
+pub use imp::fd_ops::{sys_close, sys_dup, sys_dup2, sys_fcntl, sys_ioctl};
 #[cfg(feature = "fs")]
-pub use imp::fs::{sys_fstat, sys_getcwd, sys_lseek, sys_lstat, sys_open, sys_rename, sys_stat};
+pub use imp::fs::{
+    sys_access, sys_chdir, sys_faccessat, sys_fchdir, sys_fdatasync, sys_flock, sys_fstat,
+    sys_fstatat, sys_fsync, sys_ftruncate, sys_getcwd, sys_getdents64, sys_lseek, sys_lstat,
+    sys_mkdir, sys_mkdirat, sys_open, sys_openat, sys_pread64, sys_preadv, sys_pwrite64,
+    sys_pwritev, sys_rename, sys_renameat, sys_rmdir, sys_stat, sys_truncate, sys_unlink,
+    sys_unlinkat,
+};
 #[cfg(feature = "select")]
 pub use imp::io_mpx::sys_select;
 #[cfg(feature = "epoll")]
 pub use imp::io_mpx::{sys_epoll_create, sys_epoll_ctl, sys_epoll_wait};
+#[cfg(feature = "poll")]
+pub use imp::io_mpx::{sys_poll, sys_ppoll};
+#[cfg(feature = "mmap")]
+pub use imp::mman::{sys_mmap, sys_mprotect, sys_mremap, sys_munmap};
 #[cfg(feature = "net")]
 pub use imp::net::{
     sys_accept, sys_bind, sys_connect, sys_freeaddrinfo, sys_getaddrinfo, sys_getpeername,
-    sys_getsockname, sys_listen, sys_recv, sys_recvfrom, sys_send, sys_sendto, sys_shutdown,
-    sys_socket,
+    sys_getsockname, sys_getsockopt, sys_listen, sys_recv, sys_recvfrom, sys_recvmsg, sys_send,
+    sys_sendmsg, sys_sendto, sys_setsockopt, sys_shutdown, sys_socket, sys_socketpair,
 };
 #[cfg(feature = "pipe")]
 pub use imp::pipe::sys_pipe;
+#[cfg(feature = "process")]
+pub use imp::process::{sys_getppid, sys_spawn, sys_waitpid};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::barrier::{
+    sys_pthread_barrier_destroy, sys_pthread_barrier_init, sys_pthread_barrier_wait,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::cond::{
+    sys_pthread_cond_broadcast, sys_pthread_cond_destroy, sys_pthread_cond_init,
+    sys_pthread_cond_signal, sys_pthread_cond_timedwait, sys_pthread_cond_wait,
+};
 #[cfg(feature = "multitask")]
 pub use imp::pthread::mutex::{
-    sys_pthread_mutex_init, sys_pthread_mutex_lock, sys_pthread_mutex_unlock,
+    sys_pthread_mutex_init, sys_pthread_mutex_lock, sys_pthread_mutex_trylock,
+    sys_pthread_mutex_unlock,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::rwlock::{
+    sys_pthread_rwlock_destroy, sys_pthread_rwlock_init, sys_pthread_rwlock_rdlock,
+    sys_pthread_rwlock_tryrdlock, sys_pthread_rwlock_trywrlock, sys_pthread_rwlock_unlock,
+    sys_pthread_rwlock_wrlock,
 };
 #[cfg(feature = "multitask")]
-pub use imp::pthread::{sys_pthread_create, sys_pthread_exit, sys_pthread_join, sys_pthread_self};
+pub use imp::pthread::{
+    sys_pthread_create, sys_pthread_exit, sys_pthread_getaffinity_np, sys_pthread_join,
+    sys_pthread_self, sys_pthread_setaffinity_np,
+};
+#[cfg(feature = "alloc")]
+pub use imp::resources::{charge_memory, uncharge_memory};
+#[cfg(feature = "signal")]
+pub use imp::signal::{
+    sys_alarm, sys_getitimer, sys_kill, sys_pause, sys_pthread_kill, sys_raise, sys_setitimer,
+    sys_sigaction, sys_sigprocmask,
+};
+#[cfg(feature = "timerfd")]
+pub use imp::timerfd::{sys_timerfd_create, sys_timerfd_gettime, sys_timerfd_settime};
@@ -38,6 +38,9 @@ macro_rules! syscall_body {
             Ok(_) | Err(axerrno::LinuxError::EAGAIN) => debug!(concat!(stringify!($fn), " => {:?}"),  res),
             Err(_) => info!(concat!(stringify!($fn), " => {:?}"), res),
         }
+        // Signals are delivered on returning from system calls.
+        #[cfg(feature = "signal")]
+        axtask::handle_pending_signals();
         match res {
             Ok(v) => v as _,
             Err(e) => {
@@ -51,6 +54,8 @@ macro_rules! syscall_body_no_debug {
     ($($stmt: tt)*) => {{
         #[allow(clippy::redundant_closure_call)]
         let res = (|| -> axerrno::LinuxResult<_> { $($stmt)* })();
+        #[cfg(feature = "signal")]
+        axtask::handle_pending_signals();
         match res {
             Ok(v) => v as _,
             Err(e) => {
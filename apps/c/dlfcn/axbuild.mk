@@ -0,0 +1 @@
+app-objs := main.o plugin.o
@@ -0,0 +1,68 @@
+#include <assert.h>
+#include <dlfcn.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef int (*add_fn)(int, int);
+typedef int (*square_fn)(int);
+
+void test_dlopen()
+{
+    void *plugin = dlopen("libplugin.so", RTLD_NOW);
+    assert(plugin != NULL);
+    assert(dlopen("/usr/lib/libplugin.so", RTLD_LAZY) == plugin);
+
+    add_fn add = (add_fn)dlsym(plugin, "plugin_add");
+    assert(add != NULL && add(1, 2) == 3);
+    int *version = dlsym(plugin, "plugin_version");
+    assert(version != NULL && *version == 3);
+
+    // Symbols of other libraries are not visible through this handle.
+    assert(dlsym(plugin, "square") == NULL);
+    assert(strstr(dlerror(), "square") != NULL);
+    assert(dlerror() == NULL);
+    assert(dlclose(plugin) == 0);
+
+    assert(dlopen("libnone.so", RTLD_NOW) == NULL);
+    assert(strstr(dlerror(), "libnone.so") != NULL);
+    puts("test_dlopen: OK");
+}
+
+void test_global()
+{
+    void *self = dlopen(NULL, RTLD_NOW);
+    assert(self != NULL);
+    square_fn square = (square_fn)dlsym(self, "square");
+    assert(square != NULL && square(5) == 25);
+    assert(dlsym(RTLD_DEFAULT, "plugin_add") != NULL);
+    assert(dlsym(RTLD_DEFAULT, "main") == NULL);
+    assert(dlclose(self) == 0);
+    puts("test_global: OK");
+}
+
+int main();
+
+void test_dladdr()
+{
+    Dl_info info;
+    void *add = dlsym(RTLD_DEFAULT, "plugin_add");
+    assert(dladdr((char *)add + 1, &info) != 0);
+    assert(strcmp(info.dli_fname, "libplugin.so") == 0);
+    assert(strcmp(info.dli_sname, "plugin_add") == 0);
+    assert(info.dli_saddr == add && info.dli_fbase != NULL);
+    assert(dladdr(NULL, &info) == 0);
+    // `main` is not exported, but found in the functions of the image
+    assert(dladdr((char *)main + 1, &info) != 0);
+    assert(strcmp(info.dli_sname, "main") == 0);
+    assert(info.dli_saddr == (void *)main && info.dli_fname != NULL);
+    puts("test_dladdr: OK");
+}
+
+int main()
+{
+    test_dlopen();
+    test_global();
+    test_dladdr();
+    puts("(C)dlfcn tests run OK!");
+    return 0;
+}
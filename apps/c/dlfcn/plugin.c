@@ -0,0 +1,13 @@
+// Functions of the "plugins", which are linked into the image as usual.
+
+int plugin_version = 3;
+
+int plugin_add(int a, int b)
+{
+    return a + b;
+}
+
+int square(int x)
+{
+    return x * x;
+}
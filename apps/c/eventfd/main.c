@@ -0,0 +1,174 @@
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <sys/epoll.h>
+#include <sys/eventfd.h>
+#include <sys/select.h>
+#include <sys/timerfd.h>
+#include <time.h>
+#include <unistd.h>
+
+void test_eventfd()
+{
+    int fd = eventfd(3, EFD_NONBLOCK);
+    assert(fd >= 0);
+
+    uint64_t value;
+    assert(read(fd, &value, sizeof(value)) == 8 && value == 3);
+    assert(read(fd, &value, sizeof(value)) == -1 && errno == EAGAIN);
+
+    assert(eventfd_write(fd, 1) == 0 && eventfd_write(fd, 2) == 0);
+    assert(eventfd_read(fd, &value) == 0 && value == 3);
+
+    // Too short buffers and the reserved value are rejected.
+    assert(read(fd, &value, 4) == -1 && errno == EINVAL);
+    value = UINT64_MAX;
+    assert(write(fd, &value, sizeof(value)) == -1 && errno == EINVAL);
+
+    // The counter can not exceed `UINT64_MAX - 1`.
+    assert(eventfd_write(fd, UINT64_MAX - 1) == 0);
+    assert(eventfd_write(fd, 1) == -1 && errno == EAGAIN);
+    close(fd);
+    puts("test_eventfd: OK");
+}
+
+void test_semaphore()
+{
+    int fd = eventfd(2, EFD_SEMAPHORE | EFD_NONBLOCK);
+    assert(fd >= 0);
+    eventfd_t value;
+    assert(eventfd_read(fd, &value) == 0 && value == 1);
+    assert(eventfd_read(fd, &value) == 0 && value == 1);
+    assert(eventfd_read(fd, &value) == -1 && errno == EAGAIN);
+    close(fd);
+    puts("test_semaphore: OK");
+}
+
+static void *notifier(void *arg)
+{
+    int fd = (int)(intptr_t)arg;
+    struct timespec ts = {0, 50 * 1000000};
+    nanosleep(&ts, NULL);
+    assert(eventfd_write(fd, 42) == 0);
+    return NULL;
+}
+
+void test_eventfd_epoll()
+{
+    int fd = eventfd(0, 0);
+    int epfd = epoll_create(1);
+    assert(fd >= 0 && epfd >= 0);
+    struct epoll_event ev = {.events = EPOLLIN, .data.fd = fd};
+    assert(epoll_ctl(epfd, EPOLL_CTL_ADD, fd, &ev) == 0);
+    assert(epoll_wait(epfd, &ev, 1, 0) == 0);
+
+    pthread_t t;
+    assert(pthread_create(&t, NULL, notifier, (void *)(intptr_t)fd) == 0);
+    assert(epoll_wait(epfd, &ev, 1, 1000) == 1);
+    assert(ev.data.fd == fd && (ev.events & EPOLLIN));
+
+    eventfd_t value;
+    assert(eventfd_read(fd, &value) == 0 && value == 42);
+    assert(epoll_wait(epfd, &ev, 1, 0) == 0);
+    pthread_join(t, NULL);
+    close(epfd);
+    close(fd);
+    puts("test_eventfd_epoll: OK");
+}
+
+void test_timerfd()
+{
+    int fd = timerfd_create(CLOCK_MONOTONIC, TFD_NONBLOCK);
+    assert(fd >= 0);
+    assert(timerfd_create(100, 0) == -1 && errno == EINVAL);
+
+    uint64_t expirations;
+    assert(read(fd, &expirations, sizeof(expirations)) == -1 && errno == EAGAIN);
+
+    struct itimerspec its = {.it_value = {0, 100 * 1000000}};
+    assert(timerfd_settime(fd, 0, &its, NULL) == 0);
+    struct itimerspec curr;
+    assert(timerfd_gettime(fd, &curr) == 0);
+    assert(curr.it_value.tv_sec == 0 && curr.it_value.tv_nsec > 0);
+    assert(curr.it_interval.tv_sec == 0 && curr.it_interval.tv_nsec == 0);
+
+    // Blocks until the timer expires.
+    int flags = fcntl(fd, F_GETFL);
+    assert(fcntl(fd, F_SETFL, flags & ~O_NONBLOCK) == 0);
+    assert(read(fd, &expirations, sizeof(expirations)) == 8 && expirations == 1);
+    assert(timerfd_gettime(fd, &curr) == 0);
+    assert(curr.it_value.tv_sec == 0 && curr.it_value.tv_nsec == 0);
+
+    // Disarm a timer before it expires.
+    its.it_value.tv_sec = 1;
+    assert(timerfd_settime(fd, 0, &its, NULL) == 0);
+    struct itimerspec old, zero = {0};
+    assert(timerfd_settime(fd, 0, &zero, &old) == 0);
+    assert(old.it_value.tv_sec > 0 || old.it_value.tv_nsec > 0);
+    close(fd);
+    puts("test_timerfd: OK");
+}
+
+void test_timerfd_periodic()
+{
+    int fd = timerfd_create(CLOCK_MONOTONIC, 0);
+    assert(fd >= 0);
+
+    struct timespec now;
+    clock_gettime(CLOCK_MONOTONIC, &now);
+    struct itimerspec its = {
+        .it_interval = {0, 50 * 1000000},
+        .it_value = now,
+    };
+    assert(timerfd_settime(fd, TFD_TIMER_ABSTIME, &its, NULL) == 0);
+
+    uint64_t expirations, total = 0;
+    while (total < 4) {
+        assert(read(fd, &expirations, sizeof(expirations)) == 8 && expirations > 0);
+        total += expirations;
+    }
+
+    // Missed periods are counted.
+    struct timespec ts = {0, 220 * 1000000};
+    nanosleep(&ts, NULL);
+    assert(read(fd, &expirations, sizeof(expirations)) == 8 && expirations >= 4);
+    close(fd);
+    puts("test_timerfd_periodic: OK");
+}
+
+void test_timerfd_select()
+{
+    int fd = timerfd_create(CLOCK_REALTIME, 0);
+    assert(fd >= 0);
+    struct itimerspec its = {.it_value = {0, 100 * 1000000}};
+    assert(timerfd_settime(fd, 0, &its, NULL) == 0);
+
+    fd_set rfds;
+    FD_ZERO(&rfds);
+    FD_SET(fd, &rfds);
+    struct timeval tv = {0, 10 * 1000};
+    assert(select(fd + 1, &rfds, NULL, NULL, &tv) == 0);
+
+    FD_SET(fd, &rfds);
+    tv.tv_sec = 1;
+    assert(select(fd + 1, &rfds, NULL, NULL, &tv) == 1 && FD_ISSET(fd, &rfds));
+    uint64_t expirations;
+    assert(read(fd, &expirations, sizeof(expirations)) == 8 && expirations == 1);
+    close(fd);
+    puts("test_timerfd_select: OK");
+}
+
+int main()
+{
+    test_eventfd();
+    test_semaphore();
+    test_eventfd_epoll();
+    test_timerfd();
+    test_timerfd_periodic();
+    test_timerfd_select();
+    puts("(C)Eventfd tests run OK!");
+    return 0;
+}
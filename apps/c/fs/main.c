@@ -0,0 +1,258 @@
+#include <assert.h>
+#include <dirent.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <pthread.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/file.h>
+#include <sys/stat.h>
+#include <sys/uio.h>
+#include <unistd.h>
+
+void test_dir()
+{
+    char cwd[256];
+    assert(mkdir("/fs_test", 0755) == 0);
+    assert(mkdir("/fs_test", 0755) == -1 && errno == EEXIST);
+    assert(chdir("/fs_test") == 0);
+    assert(getcwd(cwd, sizeof(cwd)) != NULL);
+    assert(strncmp(cwd, "/fs_test", 8) == 0);
+    assert(chdir("/fs_test/none") == -1 && errno == ENOENT);
+    puts("test_dir: OK");
+}
+
+void test_file()
+{
+    const char *content = "ArceOS fs test";
+    int fd = openat(AT_FDCWD, "file.txt", O_RDWR | O_CREAT | O_TRUNC, 0644);
+    assert(fd >= 0);
+    assert(write(fd, content, strlen(content)) == strlen(content));
+    assert(fsync(fd) == 0);
+    assert(fdatasync(fd) == 0);
+
+    struct stat st;
+    assert(ftruncate(fd, 6) == 0);
+    assert(fstat(fd, &st) == 0 && st.st_size == 6);
+    assert(ftruncate(fd, -1) == -1 && errno == EINVAL);
+    close(fd);
+
+    assert(access("file.txt", F_OK) == 0);
+    assert(access("file.txt", R_OK | W_OK) == 0);
+    assert(access("none.txt", F_OK) == -1 && errno == ENOENT);
+
+    assert(truncate("file.txt", 100) == 0);
+    assert(fstatat(AT_FDCWD, "file.txt", &st, 0) == 0 && st.st_size == 100);
+    assert(truncate("none.txt", 0) == -1 && errno == ENOENT);
+    puts("test_file: OK");
+}
+
+void test_at()
+{
+    struct stat st;
+    int fd = open("/fs_test/file.txt", O_RDONLY);
+    assert(fd >= 0);
+    assert(fsync(fd) == 0);
+    assert(fstatat(fd, "", &st, AT_EMPTY_PATH) == 0 && st.st_size == 100);
+    assert(fstatat(fd, "file.txt", &st, 0) == -1 && errno == ENOTDIR);
+    assert(faccessat(fd, "file.txt", F_OK, 0) == -1 && errno == ENOTDIR);
+    close(fd);
+
+    assert(renameat(AT_FDCWD, "file.txt", AT_FDCWD, "/fs_test/moved.txt") == 0);
+    assert(access("file.txt", F_OK) == -1 && errno == ENOENT);
+    assert(faccessat(AT_FDCWD, "moved.txt", R_OK, 0) == 0);
+
+    assert(mkdirat(AT_FDCWD, "subdir", 0755) == 0);
+    assert(unlinkat(AT_FDCWD, "subdir", 0x1234) == -1 && errno == EINVAL);
+    assert(unlinkat(AT_FDCWD, "subdir", AT_REMOVEDIR) == 0);
+    assert(unlinkat(AT_FDCWD, "moved.txt", 0) == 0);
+    puts("test_at: OK");
+}
+
+static const char *names[] = {"a.txt", "b.txt", "c.txt"};
+
+static int count_entries(DIR *dir)
+{
+    int found = 0;
+    struct dirent *de;
+    while ((de = readdir(dir)) != NULL) {
+        for (int i = 0; i < 3; i++) {
+            if (strcmp(de->d_name, names[i]) == 0) {
+                assert(de->d_type == DT_REG);
+                found++;
+            }
+        }
+    }
+    return found;
+}
+
+void test_dirent()
+{
+    char buf[16];
+    struct stat st;
+    assert(mkdir("/fs_test/dir", 0755) == 0);
+    int dfd = open("/fs_test/dir", O_RDONLY | O_DIRECTORY);
+    assert(dfd >= 0);
+    assert(fstat(dfd, &st) == 0 && S_ISDIR(st.st_mode));
+    assert(read(dfd, buf, sizeof(buf)) == -1 && errno == EISDIR);
+    assert(open("/fs_test/dir", O_RDWR) == -1 && errno == EISDIR);
+
+    // Paths are resolved relative to the directory fd.
+    for (int i = 0; i < 3; i++) {
+        int fd = openat(dfd, names[i], O_WRONLY | O_CREAT, 0644);
+        assert(fd >= 0);
+        close(fd);
+    }
+    assert(fstatat(dfd, "a.txt", &st, 0) == 0 && S_ISREG(st.st_mode));
+    assert(open("/fs_test/dir/a.txt", O_RDONLY | O_DIRECTORY) == -1 && errno == ENOTDIR);
+    assert(getdents64(dfd, buf, sizeof(buf)) == -1 && errno == EINVAL);
+
+    DIR *dir = opendir("/fs_test/dir");
+    assert(dir != NULL);
+    assert(count_entries(dir) == 3);
+    rewinddir(dir);
+    assert(count_entries(dir) == 3);
+    assert(closedir(dir) == 0);
+    assert(opendir("/fs_test/dir/a.txt") == NULL && errno == ENOTDIR);
+    assert(opendir("/fs_test/none") == NULL && errno == ENOENT);
+
+    assert(fchdir(dfd) == 0);
+    assert(access("b.txt", F_OK) == 0);
+    assert(chdir("/fs_test") == 0);
+
+    for (int i = 0; i < 3; i++) assert(unlinkat(dfd, names[i], 0) == 0);
+    close(dfd);
+    assert(rmdir("/fs_test/dir") == 0);
+    puts("test_dirent: OK");
+}
+
+static volatile int locked = 0;
+
+static int set_lock(int fd, int cmd, short type, off_t start, off_t len)
+{
+    struct flock fl = {.l_type = type, .l_whence = SEEK_SET, .l_start = start, .l_len = len};
+    return fcntl(fd, cmd, &fl);
+}
+
+static void *lock_waiter(void *arg)
+{
+    int fd = open("/fs_test/lock.txt", O_RDWR);
+    assert(fd >= 0);
+    assert(set_lock(fd, F_SETLKW, F_WRLCK, 0, 0) == 0);
+    locked = 1;
+    close(fd);
+    return NULL;
+}
+
+void test_lock()
+{
+    int fd1 = open("/fs_test/lock.txt", O_RDWR | O_CREAT, 0644);
+    int fd2 = open("/fs_test/lock.txt", O_RDWR);
+    assert(fd1 >= 0 && fd2 >= 0);
+
+    assert(flock(fd1, LOCK_SH) == 0);
+    assert(flock(fd2, LOCK_SH | LOCK_NB) == 0);
+    assert(flock(fd2, LOCK_EX | LOCK_NB) == -1 && errno == EWOULDBLOCK);
+    assert(flock(fd1, LOCK_UN) == 0);
+    assert(flock(fd2, LOCK_EX | LOCK_NB) == 0);
+    assert(flock(fd2, LOCK_UN) == 0);
+    assert(flock(fd1, 0x100) == -1 && errno == EINVAL);
+
+    struct flock fl;
+    assert(set_lock(fd1, F_SETLK, F_WRLCK, 10, 10) == 0);
+    assert(set_lock(fd2, F_SETLK, F_RDLCK, 0, 10) == 0);
+    assert(set_lock(fd2, F_SETLK, F_RDLCK, 15, 1) == -1 && errno == EAGAIN);
+    fl = (struct flock){.l_type = F_WRLCK, .l_whence = SEEK_SET, .l_start = 0, .l_len = 0};
+    assert(fcntl(fd2, F_GETLK, &fl) == 0);
+    assert(fl.l_type == F_WRLCK && fl.l_start == 10 && fl.l_len == 10);
+
+    // Unlocking the middle of a lock splits it into two.
+    assert(set_lock(fd1, F_SETLK, F_UNLCK, 12, 5) == 0);
+    assert(set_lock(fd2, F_SETLK, F_WRLCK, 12, 5) == 0);
+    assert(set_lock(fd2, F_SETLK, F_WRLCK, 11, 2) == -1 && errno == EAGAIN);
+    assert(set_lock(fd2, F_SETLK, F_UNLCK, 0, 0) == 0);
+
+    // The waiter is blocked until `fd1` is closed, which releases its locks.
+    pthread_t t;
+    assert(pthread_create(&t, NULL, lock_waiter, NULL) == 0);
+    usleep(100000);
+    assert(!locked);
+    close(fd1);
+    assert(pthread_join(t, NULL) == 0);
+    assert(locked);
+
+    fl = (struct flock){.l_type = F_WRLCK, .l_whence = SEEK_SET, .l_start = 0, .l_len = 0};
+    assert(fcntl(fd2, F_GETLK, &fl) == 0 && fl.l_type == F_UNLCK);
+    close(fd2);
+    assert(unlink("/fs_test/lock.txt") == 0);
+    puts("test_lock: OK");
+}
+
+void test_rw_at()
+{
+    char buf[16] = {0}, head[4] = {0}, tail[8] = {0};
+    int fd = open("/fs_test/rw.txt", O_RDWR | O_CREAT | O_TRUNC, 0644);
+    assert(fd >= 0);
+    assert(write(fd, "0123456789", 10) == 10);
+
+    // The file position is not changed by positional reads and writes.
+    assert(pwrite(fd, "ab", 2, 4) == 2);
+    assert(pread(fd, buf, 6, 2) == 6 && memcmp(buf, "23ab67", 6) == 0);
+    assert(lseek(fd, 0, SEEK_CUR) == 10);
+    assert(pread(fd, buf, 4, -1) == -1 && errno == EINVAL);
+
+    struct iovec iov[2] = {{head, 3}, {tail, 7}};
+    assert(preadv(fd, iov, 2, 1) == 9);
+    assert(memcmp(head, "123", 3) == 0 && memcmp(tail, "ab6789", 6) == 0);
+    iov[0] = (struct iovec){"xy", 2};
+    iov[1] = (struct iovec){"z", 1};
+    assert(pwritev(fd, iov, 2, 10) == 3);
+
+    assert(lseek(fd, 8, SEEK_SET) == 8);
+    memset(head, 0, sizeof(head));
+    memset(tail, 0, sizeof(tail));
+    iov[0] = (struct iovec){head, 3};
+    iov[1] = (struct iovec){tail, 7};
+    assert(readv(fd, iov, 2) == 5);
+    assert(memcmp(head, "89x", 3) == 0 && memcmp(tail, "yz", 2) == 0);
+    assert(readv(fd, iov, -1) == -1 && errno == EINVAL);
+    close(fd);
+
+    // Pipes have no file positions.
+    int fds[2];
+    assert(pipe(fds) == 0);
+    assert(pwrite(fds[1], "a", 1, 0) == -1 && errno == ESPIPE);
+
+    // `readv` reads once, so it does not block after the data available.
+    assert(write(fds[1], "abc", 3) == 3);
+    iov[0] = (struct iovec){head, 2};
+    iov[1] = (struct iovec){tail, 7};
+    assert(readv(fds[0], iov, 2) == 3);
+    assert(memcmp(head, "ab", 2) == 0 && tail[0] == 'c');
+    close(fds[0]);
+    close(fds[1]);
+    assert(unlink("/fs_test/rw.txt") == 0);
+    puts("test_rw_at: OK");
+}
+
+void test_remove()
+{
+    assert(unlink("/fs_test/none.txt") == -1 && errno == ENOENT);
+    assert(chdir("/") == 0);
+    assert(rmdir("/fs_test") == 0);
+    assert(access("/fs_test", F_OK) == -1 && errno == ENOENT);
+    puts("test_remove: OK");
+}
+
+int main()
+{
+    test_dir();
+    test_file();
+    test_at();
+    test_dirent();
+    test_lock();
+    test_rw_at();
+    test_remove();
+    puts("(C)File system tests run OK!");
+    return 0;
+}
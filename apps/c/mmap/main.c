@@ -0,0 +1,107 @@
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+#define PAGE_SIZE 4096
+
+void test_anonymous()
+{
+    size_t len = 4 * PAGE_SIZE;
+    char *p = mmap(NULL, len, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(p != MAP_FAILED);
+    for (size_t i = 0; i < len; i++) assert(p[i] == 0);
+    memset(p, 0x5a, len);
+
+    // Unmap the second page, and map it again at the same place.
+    assert(munmap(p + PAGE_SIZE, PAGE_SIZE) == 0);
+    char *q = mmap(p + PAGE_SIZE, PAGE_SIZE, PROT_READ | PROT_WRITE,
+                   MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED, -1, 0);
+    assert(q == p + PAGE_SIZE);
+    assert(q[0] == 0 && p[0] == 0x5a && p[2 * PAGE_SIZE] == 0x5a);
+
+    // A failed MAP_FIXED leaves the old mappings alone.
+    assert(mmap(p, (size_t)1 << 40, PROT_READ | PROT_WRITE,
+                MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED, -1, 0) == MAP_FAILED);
+    assert(errno == ENOMEM);
+    assert(p[0] == 0x5a && p[3 * PAGE_SIZE] == 0x5a);
+
+    assert(munmap(p, len) == 0);
+    puts("test_anonymous: OK");
+}
+
+void test_mprotect()
+{
+    char *p = mmap(NULL, 2 * PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(p != MAP_FAILED);
+    strcpy(p, "hello");
+    assert(mprotect(p, PAGE_SIZE, PROT_NONE) == 0);
+    assert(mprotect(p, PAGE_SIZE, PROT_READ) == 0);
+    assert(strcmp(p, "hello") == 0);
+    assert(mprotect(p + 4 * PAGE_SIZE, PAGE_SIZE, PROT_READ) == -1 && errno == ENOMEM);
+    assert(munmap(p, 2 * PAGE_SIZE) == 0);
+    puts("test_mprotect: OK");
+}
+
+void test_mremap()
+{
+    char *p = mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(p != MAP_FAILED);
+    strcpy(p, "remap");
+
+    // Block in-place expansion, so that the mapping has to move.
+    char *block = mmap(p + PAGE_SIZE, PAGE_SIZE, PROT_READ, MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED,
+                       -1, 0);
+    assert(block == p + PAGE_SIZE);
+    assert(mremap(p, PAGE_SIZE, 3 * PAGE_SIZE, 0) == MAP_FAILED && errno == ENOMEM);
+
+    char *q = mremap(p, PAGE_SIZE, 3 * PAGE_SIZE, MREMAP_MAYMOVE);
+    assert(q != MAP_FAILED && q != p);
+    assert(strcmp(q, "remap") == 0 && q[2 * PAGE_SIZE] == 0);
+    q[3 * PAGE_SIZE - 1] = 1;
+
+    assert(mremap(q, 3 * PAGE_SIZE, PAGE_SIZE, 0) == q);
+    assert(munmap(q, PAGE_SIZE) == 0);
+    assert(munmap(block, PAGE_SIZE) == 0);
+    puts("test_mremap: OK");
+}
+
+void test_file()
+{
+    const char *path = "/mmap_test.txt";
+    const char *content = "ArceOS mmap test";
+    int fd = open(path, O_RDWR | O_CREAT | O_TRUNC, 0644);
+    assert(fd >= 0);
+    for (int i = 0; i < PAGE_SIZE; i++) assert(write(fd, "x", 1) == 1);
+    assert(write(fd, content, strlen(content)) == strlen(content));
+
+    char *p = mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE, fd, PAGE_SIZE);
+    assert(p != MAP_FAILED);
+    assert(memcmp(p, content, strlen(content)) == 0 && p[strlen(content)] == 0);
+
+    // Private mappings are not written back.
+    p[0] = 'a';
+    char c;
+    assert(lseek(fd, PAGE_SIZE, SEEK_SET) == PAGE_SIZE);
+    assert(read(fd, &c, 1) == 1 && c == 'A');
+
+    assert(mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0) == MAP_FAILED);
+    assert(errno == ENODEV);
+
+    assert(munmap(p, PAGE_SIZE) == 0);
+    close(fd);
+    puts("test_file: OK");
+}
+
+int main()
+{
+    test_anonymous();
+    test_mprotect();
+    test_mremap();
+    test_file();
+    puts("(C)mmap tests run OK!");
+    return 0;
+}
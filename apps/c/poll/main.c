@@ -0,0 +1,88 @@
+#include <arpa/inet.h>
+#include <assert.h>
+#include <netinet/in.h>
+#include <poll.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <time.h>
+#include <unistd.h>
+
+void test_stdio()
+{
+    struct pollfd fds[2] = {
+        {.fd = STDOUT_FILENO, .events = POLLOUT},
+        {.fd = 100, .events = POLLIN},
+    };
+    assert(poll(fds, 2, 0) == 2);
+    assert(fds[0].revents == POLLOUT);
+    assert(fds[1].revents == POLLNVAL);
+    puts("test_stdio: OK");
+}
+
+void test_pipe()
+{
+    int p[2];
+    assert(pipe(p) == 0);
+
+    struct pollfd fds[2] = {{.fd = p[0], .events = POLLIN}, {.fd = p[1], .events = POLLOUT}};
+    assert(poll(fds, 2, 0) == 1);
+    assert(fds[0].revents == 0 && fds[1].revents == POLLOUT);
+
+    // Nothing to read, should time out.
+    struct timespec ts = {0, 100 * 1000000};
+    assert(ppoll(fds, 1, &ts, NULL) == 0);
+
+    assert(write(p[1], "x", 1) == 1);
+    assert(poll(fds, 1, -1) == 1 && fds[0].revents == POLLIN);
+
+    char c;
+    assert(read(p[0], &c, 1) == 1 && c == 'x');
+    close(p[1]);
+    assert(poll(fds, 1, -1) == 1);
+    assert(fds[0].revents == (POLLIN | POLLHUP));
+    close(p[0]);
+    puts("test_pipe: OK");
+}
+
+void test_udp()
+{
+    int fd = socket(AF_INET, SOCK_DGRAM, IPPROTO_UDP);
+    assert(fd >= 0);
+    struct sockaddr_in addr = {.sin_family = AF_INET, .sin_port = htons(5555)};
+    addr.sin_addr.s_addr = htonl(0x7f000001); // 127.0.0.1
+    assert(bind(fd, (struct sockaddr *)&addr, sizeof(addr)) == 0);
+
+    struct pollfd pfd = {.fd = fd, .events = POLLIN};
+    assert(poll(&pfd, 1, 100) == 0 && pfd.revents == 0);
+
+    assert(sendto(fd, "ping", 4, 0, (struct sockaddr *)&addr, sizeof(addr)) == 4);
+    assert(poll(&pfd, 1, 1000) == 1 && pfd.revents == POLLIN);
+
+    char buf[8] = {0};
+    assert(recv(fd, buf, sizeof(buf), 0) == 4 && strcmp(buf, "ping") == 0);
+    close(fd);
+    puts("test_udp: OK");
+}
+
+void test_tcp_unconnected()
+{
+    int fd = socket(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+    assert(fd >= 0);
+
+    // Never connected, so not hung up either.
+    struct pollfd pfd = {.fd = fd, .events = POLLIN | POLLOUT};
+    assert(poll(&pfd, 1, 0) == 0 && pfd.revents == 0);
+    close(fd);
+    puts("test_tcp_unconnected: OK");
+}
+
+int main()
+{
+    test_stdio();
+    test_pipe();
+    test_udp();
+    test_tcp_unconnected();
+    puts("(C)Poll tests run OK!");
+    return 0;
+}
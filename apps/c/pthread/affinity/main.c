@@ -0,0 +1,119 @@
+#include <assert.h>
+#include <errno.h>
+#include <pthread.h>
+#include <sched.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+#define NUM_CPUS    4
+#define NUM_THREADS 8
+#define NUM_ROUNDS  100
+
+static int count_cpus(const cpu_set_t *set)
+{
+    int n = 0;
+    for (int i = 0; i < 8 * (int)sizeof(cpu_set_t); i++)
+        if (CPU_ISSET(i, set)) n++;
+    return n;
+}
+
+void test_default()
+{
+    cpu_set_t set;
+    CPU_ZERO(&set);
+    assert(sched_getaffinity(0, sizeof(set), &set) == 0);
+    // All CPUs are allowed by default.
+    assert(count_cpus(&set) == NUM_CPUS);
+    for (int i = 0; i < NUM_CPUS; i++) assert(CPU_ISSET(i, &set));
+    puts("test_default: OK");
+}
+
+void test_errors()
+{
+    cpu_set_t set;
+    CPU_ZERO(&set);
+    assert(sched_setaffinity(0, sizeof(set), &set) == -1 && errno == EINVAL);
+    // CPUs not in the system are ignored.
+    CPU_SET(NUM_CPUS, &set);
+    assert(sched_setaffinity(0, sizeof(set), &set) == -1 && errno == EINVAL);
+    assert(sched_setaffinity(0, sizeof(set), NULL) == -1 && errno == EFAULT);
+    assert(sched_getaffinity(-1000, sizeof(set), &set) == -1 && errno == ESRCH);
+    assert(pthread_setaffinity_np(pthread_self(), sizeof(set), &set) == EINVAL);
+    puts("test_errors: OK");
+}
+
+void test_current()
+{
+    cpu_set_t set;
+    for (int cpu = 0; cpu < NUM_CPUS; cpu++) {
+        CPU_ZERO(&set);
+        CPU_SET(cpu, &set);
+        // The current thread is migrated to the CPU before it returns.
+        assert(sched_setaffinity(0, sizeof(set), &set) == 0);
+        assert(sched_getcpu() == cpu);
+        sched_yield();
+        assert(sched_getcpu() == cpu);
+        CPU_ZERO(&set);
+        assert(sched_getaffinity(0, sizeof(set), &set) == 0);
+        assert(count_cpus(&set) == 1 && CPU_ISSET(cpu, &set));
+    }
+    CPU_ZERO(&set);
+    for (int i = 0; i < NUM_CPUS; i++) CPU_SET(i, &set);
+    assert(sched_setaffinity(0, sizeof(set), &set) == 0);
+    puts("test_current: OK");
+}
+
+static pthread_barrier_t barrier;
+
+void *worker(void *arg)
+{
+    long cpu = (long)arg;
+    pthread_barrier_wait(&barrier);
+    for (int i = 0; i < NUM_ROUNDS; i++) {
+        cpu_set_t set;
+        CPU_ZERO(&set);
+        assert(pthread_getaffinity_np(pthread_self(), sizeof(set), &set) == 0);
+        assert(count_cpus(&set) == 1 && CPU_ISSET(cpu, &set));
+        // It really runs on the CPU, also after being woken up.
+        assert(sched_getcpu() == cpu);
+        if (i % 10 == 0)
+            usleep(1000);
+        else
+            sched_yield();
+    }
+    return (void *)cpu;
+}
+
+void test_threads()
+{
+    pthread_t t[NUM_THREADS];
+    assert(pthread_barrier_init(&barrier, NULL, NUM_THREADS + 1) == 0);
+    for (long i = 0; i < NUM_THREADS; i++) {
+        long cpu = i % NUM_CPUS;
+        cpu_set_t set;
+        CPU_ZERO(&set);
+        CPU_SET(cpu, &set);
+        assert(pthread_create(&t[i], NULL, worker, (void *)cpu) == 0);
+        // Move the thread while it's waiting at the barrier.
+        assert(pthread_setaffinity_np(t[i], sizeof(set), &set) == 0);
+    }
+    pthread_barrier_wait(&barrier);
+    for (long i = 0; i < NUM_THREADS; i++) {
+        void *ret;
+        assert(pthread_join(t[i], &ret) == 0);
+        assert((long)ret == i % NUM_CPUS);
+    }
+    assert(pthread_barrier_destroy(&barrier) == 0);
+    puts("test_threads: OK");
+}
+
+int main()
+{
+    test_default();
+    test_errors();
+    test_current();
+    test_threads();
+    puts("(C)Pthread affinity tests run OK!");
+    return 0;
+}
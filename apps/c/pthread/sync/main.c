@@ -0,0 +1,209 @@
+#include <assert.h>
+#include <errno.h>
+#include <pthread.h>
+#include <sched.h>
+#include <stdio.h>
+#include <time.h>
+#include <unistd.h>
+
+#define NUM_THREADS 4
+
+static pthread_mutex_t lock = PTHREAD_MUTEX_INITIALIZER;
+static pthread_cond_t cond = PTHREAD_COND_INITIALIZER;
+static pthread_rwlock_t rwlock = PTHREAD_RWLOCK_INITIALIZER;
+static pthread_barrier_t barrier;
+
+static int ready = 0;
+static int consumed = 0;
+static int shared = 0;
+
+void test_trylock()
+{
+    assert(pthread_mutex_trylock(&lock) == 0);
+    assert(pthread_mutex_trylock(&lock) == EBUSY);
+    pthread_mutex_unlock(&lock);
+    assert(pthread_mutex_trylock(&lock) == 0);
+    pthread_mutex_unlock(&lock);
+    puts("test_trylock: OK");
+}
+
+void *consumer(void *arg)
+{
+    pthread_mutex_lock(&lock);
+    while (ready == 0) pthread_cond_wait(&cond, &lock);
+    ready--;
+    consumed++;
+    pthread_mutex_unlock(&lock);
+    return NULL;
+}
+
+void test_cond()
+{
+    pthread_t t[NUM_THREADS];
+    for (int i = 0; i < NUM_THREADS; i++) pthread_create(&t[i], NULL, consumer, NULL);
+
+    // wake up one consumer
+    pthread_mutex_lock(&lock);
+    ready = 1;
+    pthread_cond_signal(&cond);
+    pthread_mutex_unlock(&lock);
+
+    // wake up the rest
+    pthread_mutex_lock(&lock);
+    ready += NUM_THREADS - 1;
+    pthread_cond_broadcast(&cond);
+    pthread_mutex_unlock(&lock);
+
+    for (int i = 0; i < NUM_THREADS; i++) pthread_join(t[i], NULL);
+    printf("test_cond: consumed = %d\n", consumed);
+}
+
+void test_cond_timedwait()
+{
+    struct timespec before, after, ts;
+    clock_gettime(CLOCK_REALTIME, &before);
+    ts = before;
+    ts.tv_nsec += 200 * 1000 * 1000;
+    if (ts.tv_nsec >= 1000000000) {
+        ts.tv_sec += 1;
+        ts.tv_nsec -= 1000000000;
+    }
+
+    pthread_mutex_lock(&lock);
+    int ret = pthread_cond_timedwait(&cond, &lock, &ts);
+    pthread_mutex_unlock(&lock);
+    clock_gettime(CLOCK_REALTIME, &after);
+
+    long elapsed_ms =
+        (after.tv_sec - before.tv_sec) * 1000 + (after.tv_nsec - before.tv_nsec) / 1000000;
+    assert(ret == ETIMEDOUT);
+    assert(elapsed_ms >= 200);
+    puts("test_cond_timedwait: ETIMEDOUT");
+}
+
+void *reader(void *arg)
+{
+    for (int i = 0; i < 100; i++) {
+        pthread_rwlock_rdlock(&rwlock);
+        int value = shared;
+        assert(value % 2 == 0);
+        pthread_rwlock_unlock(&rwlock);
+    }
+    return NULL;
+}
+
+void *writer(void *arg)
+{
+    for (int i = 0; i < 100; i++) {
+        pthread_rwlock_wrlock(&rwlock);
+        shared++;
+        for (int j = 0; j < 100; j++) getpid();
+        shared++;
+        pthread_rwlock_unlock(&rwlock);
+    }
+    return NULL;
+}
+
+void test_rwlock()
+{
+    pthread_t t[NUM_THREADS];
+    for (int i = 0; i < NUM_THREADS; i++)
+        pthread_create(&t[i], NULL, i % 2 ? reader : writer, NULL);
+    for (int i = 0; i < NUM_THREADS; i++) pthread_join(t[i], NULL);
+
+    assert(pthread_rwlock_tryrdlock(&rwlock) == 0);
+    assert(pthread_rwlock_tryrdlock(&rwlock) == 0);
+    assert(pthread_rwlock_trywrlock(&rwlock) == EBUSY);
+    pthread_rwlock_unlock(&rwlock);
+    pthread_rwlock_unlock(&rwlock);
+    assert(pthread_rwlock_trywrlock(&rwlock) == 0);
+    assert(pthread_rwlock_tryrdlock(&rwlock) == EBUSY);
+    pthread_rwlock_unlock(&rwlock);
+    printf("test_rwlock: shared = %d\n", shared);
+}
+
+static int written = 0;
+
+void *blocked_writer(void *arg)
+{
+    pthread_rwlock_wrlock(&rwlock);
+    written = 1;
+    pthread_rwlock_unlock(&rwlock);
+    return NULL;
+}
+
+void *try_reader(void *arg)
+{
+    // Readers still get in until the writer is waiting, then they are blocked
+    // even though the lock is only held for reading.
+    while (pthread_rwlock_tryrdlock(&rwlock) == 0) {
+        pthread_rwlock_unlock(&rwlock);
+        sched_yield();
+    }
+    return NULL;
+}
+
+void test_rwlock_writer_preferred()
+{
+    pthread_t t, r;
+    assert(pthread_rwlock_rdlock(&rwlock) == 0);
+    pthread_create(&t, NULL, blocked_writer, NULL);
+    pthread_create(&r, NULL, try_reader, NULL);
+    pthread_join(r, NULL);
+    assert(!written);
+
+    // The thread holding the read lock can still take it again.
+    assert(pthread_rwlock_rdlock(&rwlock) == 0);
+    assert(pthread_rwlock_tryrdlock(&rwlock) == 0);
+    pthread_rwlock_unlock(&rwlock);
+    pthread_rwlock_unlock(&rwlock);
+    assert(!written);
+    pthread_rwlock_unlock(&rwlock);
+    pthread_join(t, NULL);
+    assert(written);
+    puts("test_rwlock_writer_preferred: OK");
+}
+
+static int arrived = 0;
+
+void *barrier_func(void *arg)
+{
+    long serial = 0;
+    for (int round = 0; round < 3; round++) {
+        __atomic_add_fetch(&arrived, 1, __ATOMIC_SEQ_CST);
+        int ret = pthread_barrier_wait(&barrier);
+        assert(ret == 0 || ret == PTHREAD_BARRIER_SERIAL_THREAD);
+        // everyone has arrived in this round
+        assert(__atomic_load_n(&arrived, __ATOMIC_SEQ_CST) >= (round + 1) * NUM_THREADS);
+        if (ret == PTHREAD_BARRIER_SERIAL_THREAD) serial++;
+        pthread_barrier_wait(&barrier);
+    }
+    return (void *)serial;
+}
+
+void test_barrier()
+{
+    pthread_t t[NUM_THREADS];
+    long serial = 0;
+    pthread_barrier_init(&barrier, NULL, NUM_THREADS);
+    for (int i = 0; i < NUM_THREADS; i++) pthread_create(&t[i], NULL, barrier_func, NULL);
+    for (int i = 0; i < NUM_THREADS; i++) {
+        void *ret;
+        pthread_join(t[i], &ret);
+        serial += (long)ret;
+    }
+    pthread_barrier_destroy(&barrier);
+    printf("test_barrier: serial threads = %ld\n", serial);
+}
+
+int main()
+{
+    test_trylock();
+    test_cond();
+    test_cond_timedwait();
+    test_rwlock();
+    test_rwlock_writer_preferred();
+    test_barrier();
+    puts("(C)Pthread sync tests run OK!");
+    return 0;
+}
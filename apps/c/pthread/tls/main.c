@@ -0,0 +1,120 @@
+#include <assert.h>
+#include <errno.h>
+#include <pthread.h>
+#include <sched.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+#define NUM_THREADS 4
+#define NUM_ROUNDS  100
+
+static pthread_barrier_t barrier;
+
+void *errno_worker(void *arg)
+{
+    long id = (long)arg;
+    for (int i = 0; i < NUM_ROUNDS; i++) {
+        // Other threads run in between, but can not change our errno.
+        errno = 1000 + id;
+        sched_yield();
+        assert(errno == 1000 + id);
+        assert(close(-1) == -1 && errno == EBADF);
+    }
+    return NULL;
+}
+
+void test_errno()
+{
+    pthread_t t[NUM_THREADS];
+    errno = 0;
+    for (long i = 0; i < NUM_THREADS; i++)
+        assert(pthread_create(&t[i], NULL, errno_worker, (void *)i) == 0);
+    for (int i = 0; i < NUM_THREADS; i++) assert(pthread_join(t[i], NULL) == 0);
+    // The errno of the main thread is untouched.
+    assert(errno == 0);
+    puts("test_errno: OK");
+}
+
+void *strtok_worker(void *arg)
+{
+    long id = (long)arg;
+    char buf[64];
+    for (int i = 0; i < NUM_ROUNDS; i++) {
+        snprintf(buf, sizeof(buf), "%ld,a,,%ld;b", id, id);
+        char *tok = strtok(buf, ",;");
+        pthread_barrier_wait(&barrier);
+        long n = 0;
+        for (; tok; tok = strtok(NULL, ",;")) {
+            if (n == 0 || n == 2)
+                assert(atoi(tok) == id);
+            n++;
+            sched_yield();
+        }
+        assert(n == 4);
+    }
+    return NULL;
+}
+
+void test_strtok()
+{
+    char s[] = ";;x;;y";
+    char *p;
+    assert(strcmp(strtok_r(s, ";", &p), "x") == 0);
+    assert(strcmp(strtok_r(NULL, ";", &p), "y") == 0);
+    assert(strtok_r(NULL, ";", &p) == NULL);
+
+    pthread_t t[NUM_THREADS];
+    pthread_barrier_init(&barrier, NULL, NUM_THREADS);
+    for (long i = 0; i < NUM_THREADS; i++)
+        assert(pthread_create(&t[i], NULL, strtok_worker, (void *)i) == 0);
+    for (int i = 0; i < NUM_THREADS; i++) assert(pthread_join(t[i], NULL) == 0);
+    pthread_barrier_destroy(&barrier);
+    puts("test_strtok: OK");
+}
+
+void *strerror_worker(void *arg)
+{
+    int err = (long)arg % 2 ? ENOENT : EAGAIN;
+    const char *expect = err == ENOENT ? "No such file or directory" : "Try again";
+    for (int i = 0; i < NUM_ROUNDS; i++) {
+        char *msg = strerror(err);
+        sched_yield();
+        assert(strcmp(msg, expect) == 0);
+    }
+    return NULL;
+}
+
+void test_strerror()
+{
+    // The previous longer message must not leak into a shorter one.
+    strerror(ENOENT);
+    assert(strcmp(strerror(0), "Success") == 0);
+
+    char buf[8];
+    assert(strerror_r(ENOENT, buf, sizeof(buf)) == ERANGE && strcmp(buf, "No such") == 0);
+
+    pthread_t t[NUM_THREADS];
+    for (long i = 0; i < NUM_THREADS; i++)
+        assert(pthread_create(&t[i], NULL, strerror_worker, (void *)i) == 0);
+    for (int i = 0; i < NUM_THREADS; i++) assert(pthread_join(t[i], NULL) == 0);
+    puts("test_strerror: OK");
+}
+
+void test_rand_r()
+{
+    unsigned a = 42, b = 42;
+    for (int i = 0; i < 10; i++) assert(rand_r(&a) == rand_r(&b));
+    puts("test_rand_r: OK");
+}
+
+int main()
+{
+    test_errno();
+    test_strtok();
+    test_strerror();
+    test_rand_r();
+    puts("(C)Pthread TLS tests run OK!");
+    return 0;
+}
@@ -0,0 +1,42 @@
+#include <assert.h>
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/random.h>
+
+void test_getrandom()
+{
+    unsigned char a[64], b[64];
+    assert(getrandom(a, sizeof(a), 0) == sizeof(a));
+    assert(getrandom(b, sizeof(b), GRND_NONBLOCK | GRND_RANDOM) == sizeof(b));
+    // The chance of two equal buffers is negligible.
+    assert(memcmp(a, b, sizeof(a)) != 0);
+
+    // Large requests are filled completely.
+    static unsigned char large[65536];
+    assert(getrandom(large, sizeof(large), 0) == sizeof(large));
+    int zeros = 0;
+    for (size_t i = 0; i < sizeof(large); i++) zeros += large[i] == 0;
+    assert(zeros < 1024);
+
+    assert(getrandom(a, 0, 0) == 0);
+    assert(getrandom(a, sizeof(a), 0x80) == -1 && errno == EINVAL);
+    puts("test_getrandom: OK");
+}
+
+void test_getentropy()
+{
+    unsigned char buf[256] = {0}, zero[256] = {0};
+    assert(getentropy(buf, sizeof(buf)) == 0);
+    assert(memcmp(buf, zero, sizeof(buf)) != 0);
+    assert(getentropy(buf, sizeof(buf) + 1) == -1 && errno == EIO);
+    puts("test_getentropy: OK");
+}
+
+int main()
+{
+    test_getrandom();
+    test_getentropy();
+    puts("(C)Random tests run OK!");
+    return 0;
+}
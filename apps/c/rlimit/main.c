@@ -0,0 +1,139 @@
+#include <assert.h>
+#include <errno.h>
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/resource.h>
+#include <unistd.h>
+
+void test_nofile()
+{
+    struct rlimit orig, rl;
+    int fds[8], n = 0, fd;
+
+    assert(getrlimit(RLIMIT_NOFILE, &orig) == 0);
+    assert(orig.rlim_cur > 3 && orig.rlim_cur == orig.rlim_max);
+    assert(sysconf(_SC_OPEN_MAX) == orig.rlim_cur);
+
+    rl.rlim_cur = 8;
+    rl.rlim_max = orig.rlim_max;
+    assert(setrlimit(RLIMIT_NOFILE, &rl) == 0);
+    assert(sysconf(_SC_OPEN_MAX) == 8);
+    while ((fd = dup(STDOUT_FILENO)) >= 0) {
+        assert(fd < 8);
+        fds[n++] = fd;
+    }
+    assert(errno == EMFILE && n == 5);
+    assert(dup2(STDOUT_FILENO, 8) == -1 && errno == EBADF);
+    for (int i = 0; i < n; i++) close(fds[i]);
+
+    rl.rlim_cur = orig.rlim_max + 1;
+    rl.rlim_max = orig.rlim_max + 1;
+    assert(setrlimit(RLIMIT_NOFILE, &rl) == -1 && errno == EPERM);
+    rl.rlim_cur = 9;
+    rl.rlim_max = 8;
+    assert(setrlimit(RLIMIT_NOFILE, &rl) == -1 && errno == EINVAL);
+
+    assert(setrlimit(RLIMIT_NOFILE, &orig) == 0);
+    assert(getrlimit(RLIMIT_NOFILE, &rl) == 0 && rl.rlim_cur == orig.rlim_cur);
+    puts("test_nofile: OK");
+}
+
+static void *use_stack(void *arg)
+{
+    volatile char buf[256 * 1024];
+    memset((char *)buf, 1, sizeof(buf));
+    return (void *)(long)buf[sizeof(buf) - 1];
+}
+
+static void *nothing(void *arg)
+{
+    return arg;
+}
+
+void test_stack()
+{
+    struct rlimit orig, rl;
+    pthread_attr_t attr;
+    pthread_t t;
+    void *ret;
+
+    assert(getrlimit(RLIMIT_STACK, &orig) == 0 && orig.rlim_cur > 0);
+
+    rl.rlim_cur = 64 * 1024;
+    rl.rlim_max = orig.rlim_max;
+    assert(setrlimit(RLIMIT_STACK, &rl) == 0);
+    assert(getrlimit(RLIMIT_STACK, &rl) == 0 && rl.rlim_cur == 64 * 1024);
+    assert(pthread_create(&t, NULL, nothing, (void *)1) == 0);
+    assert(pthread_join(t, &ret) == 0 && ret == (void *)1);
+
+    // The stack size of the attributes takes precedence.
+    pthread_attr_init(&attr);
+    assert(pthread_attr_setstacksize(&attr, 1024 * 1024) == 0);
+    assert(pthread_create(&t, &attr, use_stack, NULL) == 0);
+    assert(pthread_join(t, &ret) == 0 && ret == (void *)1);
+
+    assert(setrlimit(RLIMIT_STACK, &orig) == 0);
+    puts("test_stack: OK");
+}
+
+void test_data()
+{
+    struct rlimit orig, rl;
+    void *p, *q;
+
+    assert(getrlimit(RLIMIT_AS, &orig) == 0 && orig.rlim_cur == RLIM_INFINITY);
+
+    rl.rlim_cur = 8 * 1024 * 1024;
+    rl.rlim_max = RLIM_INFINITY;
+    assert(setrlimit(RLIMIT_AS, &rl) == 0);
+    assert(malloc(16 * 1024 * 1024) == NULL && errno == ENOMEM);
+    assert((p = malloc(1024 * 1024)) != NULL);
+    memset(p, 0, 1024 * 1024);
+    assert(mmap(NULL, 16 * 1024 * 1024, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1,
+                0) == MAP_FAILED &&
+           errno == ENOMEM);
+    assert((q = mmap(NULL, 4096, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0)) !=
+           MAP_FAILED);
+    munmap(q, 4096);
+    free(p);
+
+    // Both limits apply.
+    assert(setrlimit(RLIMIT_AS, &orig) == 0);
+    assert(setrlimit(RLIMIT_DATA, &rl) == 0);
+    assert(malloc(16 * 1024 * 1024) == NULL && errno == ENOMEM);
+    assert(setrlimit(RLIMIT_DATA, &orig) == 0);
+    assert((p = malloc(16 * 1024 * 1024)) != NULL);
+    free(p);
+    puts("test_data: OK");
+}
+
+void test_rusage()
+{
+    struct rusage ru;
+    volatile unsigned long x = 0;
+
+    for (unsigned long i = 0; i < 10000000; i++) x += i;
+    assert(getrusage(RUSAGE_SELF, &ru) == 0);
+    assert(ru.ru_utime.tv_sec > 0 || ru.ru_utime.tv_usec > 0);
+    // At least the 16 MB allocated above.
+    assert(ru.ru_maxrss >= 16 * 1024);
+
+    assert(getrusage(RUSAGE_THREAD, &ru) == 0);
+    assert(ru.ru_utime.tv_sec > 0 || ru.ru_utime.tv_usec > 0);
+    assert(getrusage(RUSAGE_CHILDREN, &ru) == 0 && ru.ru_utime.tv_sec == 0 && ru.ru_maxrss == 0);
+    assert(getrusage(100, &ru) == -1 && errno == EINVAL);
+    puts("test_rusage: OK");
+}
+
+int main()
+{
+    test_nofile();
+    test_stack();
+    test_data();
+    test_rusage();
+    puts("(C)Rlimit tests run OK!");
+    return 0;
+}
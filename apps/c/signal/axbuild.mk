@@ -0,0 +1,4 @@
+# Signals are only delivered on returning from interrupts with preemption
+ifneq ($(filter sched_rr sched_cfs sched_rt,$(FEATURES)),)
+  APP_CFLAGS += -DTEST_PREEMPT
+endif
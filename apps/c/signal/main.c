@@ -0,0 +1,218 @@
+#include <assert.h>
+#include <errno.h>
+#include <poll.h>
+#include <pthread.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/time.h>
+#include <time.h>
+#include <unistd.h>
+
+static volatile int usr1_count = 0;
+static volatile int usr2_count = 0;
+static volatile int alrm_count = 0;
+static volatile pthread_t handler_thread;
+
+static void on_usr1(int sig)
+{
+    assert(sig == SIGUSR1);
+    usr1_count++;
+    handler_thread = pthread_self();
+}
+
+static void on_usr2(int sig, siginfo_t *info, void *ucontext)
+{
+    assert(sig == SIGUSR2 && info->si_signo == SIGUSR2);
+    usr2_count++;
+}
+
+static void on_alrm(int sig)
+{
+    assert(sig == SIGALRM);
+    alrm_count++;
+}
+
+void test_raise()
+{
+    assert(signal(SIGUSR1, on_usr1) == SIG_DFL);
+    assert(raise(SIGUSR1) == 0);
+    assert(usr1_count == 1);
+    assert(kill(getpid(), SIGUSR1) == 0);
+    assert(usr1_count == 2);
+    assert(signal(SIGUSR1, on_usr1) == on_usr1);
+
+    struct sigaction act = {.sa_sigaction = on_usr2, .sa_flags = SA_SIGINFO};
+    sigemptyset(&act.sa_mask);
+    assert(sigaction(SIGUSR2, &act, NULL) == 0);
+    assert(raise(SIGUSR2) == 0);
+    assert(usr2_count == 1);
+
+    assert(sigaction(SIGKILL, &act, NULL) == -1 && errno == EINVAL);
+    assert(raise(100) == -1 && errno == EINVAL);
+    puts("test_raise: OK");
+}
+
+void test_mask()
+{
+    sigset_t set, old;
+    sigemptyset(&set);
+    sigaddset(&set, SIGUSR2);
+    assert(sigprocmask(SIG_BLOCK, &set, &old) == 0);
+    assert(!sigismember(&old, SIGUSR2));
+
+    // Blocked signals stay pending until they are unblocked.
+    assert(raise(SIGUSR2) == 0);
+    assert(usr2_count == 1);
+    assert(pthread_sigmask(SIG_UNBLOCK, &set, &old) == 0);
+    assert(sigismember(&old, SIGUSR2));
+    assert(usr2_count == 2);
+
+    assert(sigprocmask(100, &set, NULL) == -1 && errno == EINVAL);
+    puts("test_mask: OK");
+}
+
+void test_ignore()
+{
+    assert(signal(SIGUSR2, SIG_IGN) == NULL); // SA_SIGINFO handler
+    assert(raise(SIGUSR2) == 0);
+    assert(usr2_count == 2);
+    assert(signal(SIGUSR2, SIG_DFL) == SIG_IGN);
+
+    // The default action of `SIGCHLD` is to ignore it.
+    assert(raise(SIGCHLD) == 0);
+
+    assert(kill(getpid(), 0) == 0);
+    assert(kill(12345, SIGUSR1) == -1 && errno == ESRCH);
+    puts("test_ignore: OK");
+}
+
+static void *sleeper(void *arg)
+{
+    struct timespec req = {10, 0}, rem;
+    assert(nanosleep(&req, &rem) == -1 && errno == EINTR);
+    assert(rem.tv_sec > 0);
+    return NULL;
+}
+
+void test_pthread_kill()
+{
+    pthread_t t;
+    int count = usr1_count;
+    assert(pthread_create(&t, NULL, sleeper, NULL) == 0);
+    usleep(100000);
+
+    // The sleeping thread is woken up, and runs the handler by itself.
+    assert(pthread_kill(t, SIGUSR1) == 0);
+    assert(pthread_join(t, NULL) == 0);
+    assert(usr1_count == count + 1);
+    assert(handler_thread == t);
+    puts("test_pthread_kill: OK");
+}
+
+static void *pauser(void *arg)
+{
+    assert(pause() == -1 && errno == EINTR);
+    return NULL;
+}
+
+void test_pause()
+{
+    pthread_t t;
+    int count = usr1_count;
+    assert(pthread_create(&t, NULL, pauser, NULL) == 0);
+    usleep(100000);
+
+    // The waiting thread is interrupted, and runs the handler on returning.
+    assert(pthread_kill(t, SIGUSR1) == 0);
+    assert(pthread_join(t, NULL) == 0);
+    assert(usr1_count == count + 1);
+    assert(handler_thread == t);
+    puts("test_pause: OK");
+}
+
+void test_ppoll()
+{
+    int p[2];
+    assert(pipe(p) == 0);
+    signal(SIGALRM, on_alrm);
+    sigset_t set, old;
+    sigemptyset(&set);
+    sigaddset(&set, SIGALRM);
+    assert(sigprocmask(SIG_BLOCK, &set, &old) == 0);
+
+    // Blocked except during the wait, which it interrupts.
+    int count = alrm_count;
+    struct pollfd pfd = {.fd = p[0], .events = POLLIN};
+    assert(alarm(1) == 0);
+    sigemptyset(&set);
+    assert(ppoll(&pfd, 1, NULL, &set) == -1 && errno == EINTR);
+    assert(alrm_count == count + 1);
+
+    assert(sigprocmask(SIG_SETMASK, &old, &set) == 0);
+    assert(sigismember(&set, SIGALRM));
+    close(p[0]);
+    close(p[1]);
+    puts("test_ppoll: OK");
+}
+
+void test_alarm()
+{
+    signal(SIGALRM, on_alrm);
+    assert(alarm(10) == 0);
+    assert(alarm(1) == 10);
+    assert(pause() == -1 && errno == EINTR);
+    assert(alrm_count == 1);
+    assert(alarm(0) == 0);
+    puts("test_alarm: OK");
+}
+
+void test_setitimer()
+{
+    struct itimerval val = {.it_interval = {0, 50000}, .it_value = {0, 50000}}, old;
+    assert(setitimer(ITIMER_REAL, &val, NULL) == 0);
+    while (alrm_count < 4)
+        pause();
+
+    assert(getitimer(ITIMER_REAL, &old) == 0);
+    assert(old.it_interval.tv_sec == 0 && old.it_interval.tv_usec == 50000);
+
+    val = (struct itimerval){0};
+    assert(setitimer(ITIMER_REAL, &val, &old) == 0);
+    assert(old.it_interval.tv_usec == 50000);
+    int count = alrm_count;
+    usleep(200000);
+    assert(alrm_count == count);
+
+    assert(setitimer(ITIMER_VIRTUAL, &val, NULL) == -1 && errno == EINVAL);
+    puts("test_setitimer: OK");
+}
+
+#ifdef TEST_PREEMPT
+void test_busy_loop()
+{
+    // Delivered on returning from the timer interrupt, without system calls
+    int count = alrm_count;
+    signal(SIGALRM, on_alrm);
+    assert(alarm(1) == 0);
+    while (alrm_count == count)
+        ;
+    puts("test_busy_loop: OK");
+}
+#endif
+
+int main()
+{
+    test_raise();
+    test_mask();
+    test_ignore();
+    test_pthread_kill();
+    test_pause();
+    test_ppoll();
+    test_alarm();
+    test_setitimer();
+#ifdef TEST_PREEMPT
+    test_busy_loop();
+#endif
+    puts("(C)Signal tests run OK!");
+    return 0;
+}
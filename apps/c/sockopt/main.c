@@ -0,0 +1,90 @@
+#include <arpa/inet.h>
+#include <assert.h>
+#include <errno.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <stdio.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+static int get_int(int fd, int level, int name)
+{
+    int val;
+    socklen_t len = sizeof(val);
+    assert(getsockopt(fd, level, name, &val, &len) == 0 && len == sizeof(val));
+    return val;
+}
+
+static void set_int(int fd, int level, int name, int val)
+{
+    assert(setsockopt(fd, level, name, &val, sizeof(val)) == 0);
+}
+
+static struct sockaddr_in loopback(int port)
+{
+    struct sockaddr_in addr = {.sin_family = AF_INET, .sin_port = htons(port)};
+    addr.sin_addr.s_addr = htonl(0x7f000001); // 127.0.0.1
+    return addr;
+}
+
+void test_keepalive()
+{
+    int fd = socket(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+    assert(fd >= 0);
+    assert(get_int(fd, SOL_SOCKET, SO_KEEPALIVE) == 0);
+
+    set_int(fd, IPPROTO_TCP, TCP_KEEPINTVL, 10);
+    set_int(fd, SOL_SOCKET, SO_KEEPALIVE, 1);
+    assert(get_int(fd, SOL_SOCKET, SO_KEEPALIVE) == 1);
+    assert(get_int(fd, IPPROTO_TCP, TCP_KEEPINTVL) == 10);
+
+    set_int(fd, SOL_SOCKET, SO_KEEPALIVE, 0);
+    assert(get_int(fd, SOL_SOCKET, SO_KEEPALIVE) == 0);
+    close(fd);
+    puts("test_keepalive: OK");
+}
+
+void test_reuseaddr()
+{
+    struct sockaddr_in addr = loopback(6000);
+    int listener = socket(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+    assert(listener >= 0);
+    assert(bind(listener, (struct sockaddr *)&addr, sizeof(addr)) == 0);
+    assert(listen(listener, 1) == 0);
+
+    // A port that is listened on can not be bound, even with SO_REUSEADDR.
+    int fd = socket(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+    set_int(fd, SOL_SOCKET, SO_REUSEADDR, 1);
+    assert(bind(fd, (struct sockaddr *)&addr, sizeof(addr)) == -1 && errno == EADDRINUSE);
+    close(fd);
+
+    int client = socket(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+    assert(connect(client, (struct sockaddr *)&addr, sizeof(addr)) == 0);
+    int conn = accept(listener, NULL, NULL);
+    assert(conn >= 0);
+    close(listener);
+
+    // The accepted connection still uses the port.
+    fd = socket(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+    assert(bind(fd, (struct sockaddr *)&addr, sizeof(addr)) == -1 && errno == EADDRINUSE);
+    close(fd);
+
+    fd = socket(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+    set_int(fd, SOL_SOCKET, SO_REUSEADDR, 1);
+    assert(get_int(fd, SOL_SOCKET, SO_REUSEADDR) == 1);
+    assert(bind(fd, (struct sockaddr *)&addr, sizeof(addr)) == 0);
+    assert(listen(fd, 1) == 0);
+
+    close(fd);
+    close(conn);
+    close(client);
+    puts("test_reuseaddr: OK");
+}
+
+int main()
+{
+    test_keepalive();
+    test_reuseaddr();
+    puts("(C)Sockopt tests run OK!");
+    return 0;
+}
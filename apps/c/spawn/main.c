@@ -0,0 +1,102 @@
+#include <assert.h>
+#include <errno.h>
+#include <spawn.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static pid_t spawn(const posix_spawn_file_actions_t *fa, char *const argv[])
+{
+    pid_t pid;
+    assert(posix_spawn(&pid, argv[0], fa, NULL, argv, NULL) == 0);
+    assert(pid > 0 && pid != getpid());
+    return pid;
+}
+
+void test_status()
+{
+    int status;
+    pid_t pid = spawn(NULL, (char *[]){"status", "42", NULL});
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 42);
+
+    pid = spawn(NULL, (char *[]){"/bin/status", "7", "exit", NULL});
+    assert(wait(&status) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 7);
+
+    pid = spawn(NULL, (char *[]){"ppid", "0", NULL});
+    char arg[16];
+    snprintf(arg, sizeof(arg), "%d", getpid());
+    pid_t pid2 = spawn(NULL, (char *[]){"ppid", arg, NULL});
+    assert(waitpid(pid2, &status, 0) == pid2 && WEXITSTATUS(status) == 0);
+    assert(waitpid(pid, &status, 0) == pid && WEXITSTATUS(status) == 1);
+
+    // All children have been waited for.
+    assert(waitpid(-1, &status, WNOHANG) == -1 && errno == ECHILD);
+    assert(waitpid(pid, &status, 0) == -1 && errno == ECHILD);
+    puts("test_status: OK");
+}
+
+void test_not_found()
+{
+    pid_t pid;
+    char *argv[] = {"none", NULL};
+    assert(posix_spawn(&pid, "none", NULL, NULL, argv, NULL) == ENOENT);
+    assert(posix_spawnp(&pid, "none", NULL, NULL, argv, NULL) == ENOENT);
+    puts("test_not_found: OK");
+}
+
+void test_pipe()
+{
+    int fds[2];
+    assert(pipe(fds) == 0);
+    posix_spawn_file_actions_t fa;
+    assert(posix_spawn_file_actions_init(&fa) == 0);
+    assert(posix_spawn_file_actions_adddup2(&fa, fds[1], STDOUT_FILENO) == 0);
+    assert(posix_spawn_file_actions_addclose(&fa, fds[0]) == 0);
+    assert(posix_spawn_file_actions_addclose(&fa, fds[1]) == 0);
+    pid_t pid = spawn(&fa, (char *[]){"echo", "hello", "world", NULL});
+    assert(posix_spawn_file_actions_destroy(&fa) == 0);
+
+    // The write end is closed in the parent, and in the child when it exits.
+    close(fds[1]);
+    char buf[32];
+    size_t len = 0;
+    ssize_t n;
+    while ((n = read(fds[0], buf + len, sizeof(buf) - 1 - len)) > 0) len += n;
+    assert(n == 0);
+    buf[len] = '\0';
+    assert(strcmp(buf, "hello world\n") == 0);
+    close(fds[0]);
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    puts("test_pipe: OK");
+}
+
+void test_bad_action()
+{
+    posix_spawn_file_actions_t fa;
+    assert(posix_spawn_file_actions_init(&fa) == 0);
+    assert(posix_spawn_file_actions_addclose(&fa, 100) == 0);
+    pid_t pid = spawn(&fa, (char *[]){"echo", "unreachable", NULL});
+    assert(posix_spawn_file_actions_destroy(&fa) == 0);
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 127);
+    puts("test_bad_action: OK");
+}
+
+int main()
+{
+    assert(getppid() == 0);
+    test_status();
+    test_not_found();
+    test_pipe();
+    test_bad_action();
+    puts("(C)Spawn tests run OK!");
+    return 0;
+}
@@ -0,0 +1,30 @@
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+// Programs run by `posix_spawn`, registered in `dl_exports.txt`.
+
+int echo_main(int argc, char **argv)
+{
+    for (int i = 1; i < argc; i++) {
+        write(STDOUT_FILENO, argv[i], strlen(argv[i]));
+        write(STDOUT_FILENO, i + 1 < argc ? " " : "\n", 1);
+    }
+    return 0;
+}
+
+int status_main(int argc, char **argv)
+{
+    int code = argc > 1 ? atoi(argv[1]) : 0;
+    if (argc > 2 && strcmp(argv[2], "exit") == 0)
+        exit(code);
+    return code;
+}
+
+// Exits with 0 if the parent PID is `argv[1]`.
+int ppid_main(int argc, char **argv)
+{
+    if (argc < 2 || getppid() != atoi(argv[1]) || getpid() == getppid())
+        return 1;
+    return 0;
+}
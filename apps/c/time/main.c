@@ -0,0 +1,125 @@
+#include <assert.h>
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+
+extern char **environ;
+
+static long long ts_nanos(const struct timespec *ts)
+{
+    return ts->tv_sec * 1000000000LL + ts->tv_nsec;
+}
+
+void test_clocks()
+{
+    struct timespec t0, t1, res;
+
+    // The wall clock comes from the RTC, so it should be after 2020-01-01.
+    assert(clock_gettime(CLOCK_REALTIME, &t0) == 0 && t0.tv_sec > 1577836800);
+    assert(time(NULL) >= t0.tv_sec);
+
+    assert(clock_gettime(CLOCK_MONOTONIC, &t0) == 0);
+    usleep(10000);
+    assert(clock_gettime(CLOCK_MONOTONIC, &t1) == 0);
+    assert(ts_nanos(&t1) - ts_nanos(&t0) >= 10000000);
+
+    assert(clock_getres(CLOCK_MONOTONIC, &res) == 0 && res.tv_sec == 0 && res.tv_nsec > 0);
+    assert(clock_gettime(100, &t0) == -1 && errno == EINVAL);
+    assert(clock_getres(100, &res) == -1 && errno == EINVAL);
+    puts("test_clocks: OK");
+}
+
+void test_settime()
+{
+    struct timespec orig, t, mono0, mono1;
+    assert(clock_gettime(CLOCK_REALTIME, &orig) == 0);
+    assert(clock_gettime(CLOCK_MONOTONIC, &mono0) == 0);
+
+    t.tv_sec = 1700000000;
+    t.tv_nsec = 0;
+    assert(clock_settime(CLOCK_REALTIME, &t) == 0);
+    assert(time(NULL) >= 1700000000 && time(NULL) < 1700000010);
+    assert(clock_settime(CLOCK_MONOTONIC, &t) == -1 && errno == EINVAL);
+
+    // Setting the wall clock does not affect the monotonic clock.
+    assert(clock_gettime(CLOCK_MONOTONIC, &mono1) == 0);
+    assert(ts_nanos(&mono1) >= ts_nanos(&mono0));
+    assert(ts_nanos(&mono1) - ts_nanos(&mono0) < 10000000000LL);
+
+    assert(clock_settime(CLOCK_REALTIME, &orig) == 0);
+    assert(time(NULL) >= orig.tv_sec);
+    puts("test_settime: OK");
+}
+
+void test_cputime()
+{
+    struct timespec p0, p1, th;
+    clock_t c0, c1;
+    volatile unsigned long x = 0;
+
+    assert(clock_gettime(CLOCK_PROCESS_CPUTIME_ID, &p0) == 0);
+    c0 = clock();
+    for (unsigned long i = 0; i < 10000000; i++) x += i;
+    assert(clock_gettime(CLOCK_PROCESS_CPUTIME_ID, &p1) == 0);
+    c1 = clock();
+    assert(ts_nanos(&p1) > ts_nanos(&p0));
+    assert(c0 >= 0 && c1 > c0);
+
+    // A thread never runs longer than the whole process.
+    assert(clock_gettime(CLOCK_THREAD_CPUTIME_ID, &th) == 0);
+    assert(ts_nanos(&th) > 0 && ts_nanos(&th) <= ts_nanos(&p1) + 1000000000LL);
+    puts("test_cputime: OK");
+}
+
+void test_timezone()
+{
+    char buf[26];
+    struct tm tm;
+    time_t t = 1700000000; // Tue Nov 14 22:13:20 UTC 2023
+    char *env[] = {"TZ=CET-1CEST,M3.5.0,M10.5.0/3", NULL};
+    char **orig_environ = environ;
+
+    // Without TZ, local time is UTC.
+    assert(localtime_r(&t, &tm) && tm.tm_hour == 22 && !tm.tm_isdst);
+    assert(!strcmp(ctime_r(&t, buf), "Tue Nov 14 22:13:20 2023\n"));
+
+    environ = env;
+    tzset();
+    assert(!strcmp(tzname[0], "CET") && !strcmp(tzname[1], "CEST"));
+    assert(timezone == -3600 && daylight);
+
+    assert(localtime_r(&t, &tm) && tm.tm_hour == 23 && !tm.tm_isdst);
+    assert(tm.__tm_gmtoff == 3600 && !strcmp(tm.__tm_zone, "CET"));
+    assert(!strcmp(ctime_r(&t, buf), "Tue Nov 14 23:13:20 2023\n"));
+    assert(mktime(&tm) == t);
+
+    t = 1690000000; // Sat Jul 22 04:26:40 UTC 2023
+    assert(localtime_r(&t, &tm) && tm.tm_hour == 6 && tm.tm_isdst);
+    assert(tm.__tm_gmtoff == 7200 && !strcmp(tm.__tm_zone, "CEST"));
+    assert(mktime(&tm) == t);
+    assert(!strcmp(asctime_r(&tm, buf), "Sat Jul 22 06:26:40 2023\n"));
+
+    // The transition into DST, at 01:00 UTC on the last Sunday of March.
+    t = 1711846800;
+    assert(localtime_r(&t, &tm) && tm.tm_hour == 3 && tm.tm_isdst);
+    t--;
+    assert(localtime_r(&t, &tm) && tm.tm_hour == 1 && tm.tm_min == 59 && !tm.tm_isdst);
+
+    environ = orig_environ;
+    tzset();
+    assert(timezone == 0 && !daylight);
+    puts("test_timezone: OK");
+}
+
+int main()
+{
+    test_clocks();
+    test_settime();
+    test_cputime();
+    test_timezone();
+    puts("(C)Time tests run OK!");
+    return 0;
+}
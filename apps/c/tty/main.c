@@ -0,0 +1,93 @@
+#include <assert.h>
+#include <errno.h>
+#include <poll.h>
+#include <stdio.h>
+#include <sys/ioctl.h>
+#include <termios.h>
+#include <unistd.h>
+
+void test_isatty()
+{
+    assert(isatty(STDIN_FILENO) && isatty(STDOUT_FILENO) && isatty(STDERR_FILENO));
+
+    int p[2];
+    assert(pipe(p) == 0);
+    assert(!isatty(p[0]) && errno == ENOTTY);
+    assert(write(p[1], "abc", 3) == 3);
+    int n;
+    assert(ioctl(p[0], FIONREAD, &n) == 0 && n == 3);
+    close(p[0]);
+    close(p[1]);
+    puts("test_isatty: OK");
+}
+
+void test_termios()
+{
+    struct termios orig, raw, t;
+    assert(tcgetattr(STDIN_FILENO, &orig) == 0);
+    assert(orig.c_lflag & ICANON && orig.c_lflag & ECHO && orig.c_lflag & ISIG);
+    assert(orig.c_iflag & ICRNL && orig.c_cc[VINTR] == 3 && orig.c_cc[VMIN] == 1);
+
+    raw = orig;
+    cfmakeraw(&raw);
+    assert(tcsetattr(STDIN_FILENO, TCSAFLUSH, &raw) == 0);
+    // The same terminal is behind all standard streams.
+    assert(tcgetattr(STDOUT_FILENO, &t) == 0);
+    assert(!(t.c_lflag & (ICANON | ECHO | ISIG)) && !(t.c_iflag & ICRNL));
+    assert(cfgetospeed(&t) == cfgetospeed(&orig));
+
+    assert(tcsetattr(STDIN_FILENO, 3, &orig) == -1 && errno == EINVAL);
+    assert(tcsetattr(STDIN_FILENO, TCSANOW, &orig) == 0);
+    assert(tcgetattr(STDIN_FILENO, &t) == 0 && t.c_lflag == orig.c_lflag);
+    assert(tcflush(STDIN_FILENO, TCIFLUSH) == 0 && tcdrain(STDOUT_FILENO) == 0);
+    puts("test_termios: OK");
+}
+
+void test_winsize()
+{
+    struct winsize orig, ws = {.ws_row = 50, .ws_col = 132};
+    assert(ioctl(STDOUT_FILENO, TIOCGWINSZ, &orig) == 0);
+    assert(orig.ws_row > 0 && orig.ws_col > 0);
+    assert(ioctl(STDOUT_FILENO, TIOCSWINSZ, &ws) == 0);
+    assert(tcgetwinsize(STDIN_FILENO, &ws) == 0 && ws.ws_row == 50 && ws.ws_col == 132);
+    assert(tcsetwinsize(STDOUT_FILENO, &orig) == 0);
+    puts("test_winsize: OK");
+}
+
+void test_nonblock()
+{
+    char c;
+    int n, on = 1, off = 0;
+    assert(tcflush(STDIN_FILENO, TCIFLUSH) == 0);
+    assert(ioctl(STDIN_FILENO, FIONREAD, &n) == 0 && n == 0);
+
+    struct pollfd fds = {.fd = STDIN_FILENO, .events = POLLIN};
+    assert(poll(&fds, 1, 0) == 0);
+
+    assert(ioctl(STDIN_FILENO, FIONBIO, &on) == 0);
+    assert(read(STDIN_FILENO, &c, 1) == -1 && errno == EAGAIN);
+    assert(ioctl(STDIN_FILENO, FIONBIO, &off) == 0);
+
+    // A raw read with `VMIN` = 0 returns immediately.
+    struct termios orig, t;
+    assert(tcgetattr(STDIN_FILENO, &orig) == 0);
+    t = orig;
+    cfmakeraw(&t);
+    t.c_cc[VMIN] = 0;
+    assert(tcsetattr(STDIN_FILENO, TCSANOW, &t) == 0);
+    assert(read(STDIN_FILENO, &c, 1) == 0);
+    assert(tcsetattr(STDIN_FILENO, TCSANOW, &orig) == 0);
+
+    assert(ioctl(STDOUT_FILENO, 0x1234, 0) == -1 && errno == ENOTTY);
+    puts("test_nonblock: OK");
+}
+
+int main()
+{
+    test_isatty();
+    test_termios();
+    test_winsize();
+    test_nonblock();
+    puts("(C)Tty tests run OK!");
+    return 0;
+}
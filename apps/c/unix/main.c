@@ -0,0 +1,202 @@
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <stddef.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/epoll.h>
+#include <sys/select.h>
+#include <sys/socket.h>
+#include <sys/uio.h>
+#include <sys/un.h>
+#include <unistd.h>
+
+static socklen_t make_addr(struct sockaddr_un *addr, const char *path, int abstract)
+{
+    memset(addr, 0, sizeof(*addr));
+    addr->sun_family = AF_UNIX;
+    size_t len = strlen(path);
+    memcpy(addr->sun_path + !!abstract, path, len);
+    return offsetof(struct sockaddr_un, sun_path) + len + !!abstract;
+}
+
+void test_stream_pair()
+{
+    int sv[2];
+    char buf[16];
+    assert(socketpair(AF_UNIX, SOCK_STREAM, 0, sv) == 0);
+    assert(write(sv[0], "hello", 5) == 5);
+    assert(write(sv[0], " world", 6) == 6);
+    assert(read(sv[1], buf, sizeof(buf)) == 11 && memcmp(buf, "hello world", 11) == 0);
+    assert(send(sv[1], "pong", 4, 0) == 4);
+    assert(recv(sv[0], buf, sizeof(buf), 0) == 4 && memcmp(buf, "pong", 4) == 0);
+
+    int type;
+    socklen_t len = sizeof(type);
+    assert(getsockopt(sv[0], SOL_SOCKET, SO_TYPE, &type, &len) == 0 && type == SOCK_STREAM);
+
+    // EOF after the peer shuts down, and `EPIPE` after it closes.
+    assert(shutdown(sv[1], SHUT_RDWR) == 0);
+    assert(read(sv[0], buf, sizeof(buf)) == 0);
+    close(sv[1]);
+    assert(write(sv[0], "x", 1) == -1 && errno == EPIPE);
+    close(sv[0]);
+    puts("test_stream_pair: OK");
+}
+
+void test_dgram_pair()
+{
+    int sv[2];
+    char buf[16];
+    assert(socketpair(AF_UNIX, SOCK_DGRAM | SOCK_NONBLOCK, 0, sv) == 0);
+    assert(recv(sv[1], buf, sizeof(buf), 0) == -1 && errno == EAGAIN);
+
+    // Message boundaries are preserved, and long messages are truncated.
+    assert(send(sv[0], "first", 5, 0) == 5);
+    assert(send(sv[0], "second message", 14, 0) == 14);
+    assert(recv(sv[1], buf, sizeof(buf), 0) == 5 && memcmp(buf, "first", 5) == 0);
+    assert(recv(sv[1], buf, 6, 0) == 6 && memcmp(buf, "second", 6) == 0);
+    assert(recv(sv[1], buf, sizeof(buf), 0) == -1 && errno == EAGAIN);
+
+    // `recvmsg` scatters a datagram, and reports its truncation.
+    char head[2], tail[4];
+    struct iovec iov[2] = {{head, 2}, {tail, 4}};
+    struct msghdr msg = {.msg_iov = iov, .msg_iovlen = 2};
+    assert(send(sv[0], "scattered", 9, 0) == 9);
+    assert(recvmsg(sv[1], &msg, 0) == 6 && (msg.msg_flags & MSG_TRUNC));
+    assert(memcmp(head, "sc", 2) == 0 && memcmp(tail, "atte", 4) == 0);
+    assert(send(sv[0], "fits", 4, 0) == 4);
+    assert(recvmsg(sv[1], &msg, 0) == 4 && msg.msg_flags == 0);
+
+    close(sv[1]);
+    assert(send(sv[0], "x", 1, 0) == -1 && errno == ECONNREFUSED);
+    close(sv[0]);
+    puts("test_dgram_pair: OK");
+}
+
+void test_stream_path()
+{
+    struct sockaddr_un addr, peer;
+    socklen_t addrlen = make_addr(&addr, "unix_test.sock", 0);
+    char buf[16];
+
+    int listener = socket(AF_UNIX, SOCK_STREAM, 0);
+    assert(listener >= 0);
+    assert(bind(listener, (struct sockaddr *)&addr, addrlen) == 0);
+    assert(access("unix_test.sock", F_OK) == 0);
+    assert(listen(listener, 8) == 0);
+
+    int other = socket(AF_UNIX, SOCK_STREAM, 0);
+    assert(bind(other, (struct sockaddr *)&addr, addrlen) == -1 && errno == EADDRINUSE);
+
+    // Connecting completes before the connection is accepted.
+    assert(connect(other, (struct sockaddr *)&addr, addrlen) == 0);
+    socklen_t len = sizeof(peer);
+    int conn = accept(listener, (struct sockaddr *)&peer, &len);
+    assert(conn >= 0 && len == offsetof(struct sockaddr_un, sun_path));
+
+    len = sizeof(peer);
+    assert(getpeername(other, (struct sockaddr *)&peer, &len) == 0);
+    assert(peer.sun_family == AF_UNIX && strcmp(peer.sun_path, "/unix_test.sock") == 0);
+    assert(write(other, "ping", 4) == 4);
+    assert(read(conn, buf, sizeof(buf)) == 4 && memcmp(buf, "ping", 4) == 0);
+    close(conn);
+    close(other);
+
+    // The file is left after the socket is closed.
+    close(listener);
+    other = socket(AF_UNIX, SOCK_STREAM, 0);
+    assert(connect(other, (struct sockaddr *)&addr, addrlen) == -1 && errno == ECONNREFUSED);
+    assert(unlink("unix_test.sock") == 0);
+    assert(connect(other, (struct sockaddr *)&addr, addrlen) == -1 && errno == ENOENT);
+    close(other);
+    puts("test_stream_path: OK");
+}
+
+void test_dgram_abstract()
+{
+    struct sockaddr_un addr, from;
+    socklen_t addrlen = make_addr(&addr, "unix_test", 1);
+    char buf[16];
+
+    int server = socket(AF_UNIX, SOCK_DGRAM, 0);
+    int client = socket(AF_UNIX, SOCK_DGRAM, 0);
+    assert(server >= 0 && client >= 0);
+    assert(bind(server, (struct sockaddr *)&addr, addrlen) == 0);
+
+    // An unnamed address binds the socket to a unique abstract name.
+    struct sockaddr_un unnamed = {.sun_family = AF_UNIX};
+    assert(bind(client, (struct sockaddr *)&unnamed, sizeof(sa_family_t)) == 0);
+
+    assert(sendto(client, "hi", 2, 0, (struct sockaddr *)&addr, addrlen) == 2);
+    socklen_t len = sizeof(from);
+    assert(recvfrom(server, buf, sizeof(buf), 0, (struct sockaddr *)&from, &len) == 2);
+    assert(memcmp(buf, "hi", 2) == 0);
+    assert(len > sizeof(sa_family_t) + 1 && from.sun_path[0] == '\0');
+
+    // Reply to the source address.
+    assert(sendto(server, "ok", 2, 0, (struct sockaddr *)&from, len) == 2);
+    assert(recv(client, buf, sizeof(buf), 0) == 2 && memcmp(buf, "ok", 2) == 0);
+
+    int stream = socket(AF_UNIX, SOCK_STREAM, 0);
+    assert(connect(stream, (struct sockaddr *)&addr, addrlen) == -1 && errno == EPROTOTYPE);
+    close(stream);
+    close(client);
+    close(server);
+    puts("test_dgram_abstract: OK");
+}
+
+void test_poll()
+{
+    struct sockaddr_un addr;
+    socklen_t addrlen = make_addr(&addr, "unix_poll", 1);
+    int listener = socket(AF_UNIX, SOCK_STREAM | SOCK_NONBLOCK, 0);
+    assert(bind(listener, (struct sockaddr *)&addr, addrlen) == 0);
+    assert(listen(listener, 8) == 0);
+    assert(accept(listener, NULL, NULL) == -1 && errno == EAGAIN);
+
+    int epfd = epoll_create(1);
+    struct epoll_event ev = {.events = EPOLLIN, .data.fd = listener};
+    assert(epoll_ctl(epfd, EPOLL_CTL_ADD, listener, &ev) == 0);
+    assert(epoll_wait(epfd, &ev, 1, 0) == 0);
+
+    int client = socket(AF_UNIX, SOCK_STREAM, 0);
+    assert(connect(client, (struct sockaddr *)&addr, addrlen) == 0);
+    assert(epoll_wait(epfd, &ev, 1, 100) == 1 && ev.data.fd == listener);
+    struct sockaddr_un peer;
+    socklen_t len = sizeof(peer);
+    int conn = accept(listener, (struct sockaddr *)&peer, &len);
+    assert(conn >= 0);
+
+    fd_set rfds;
+    FD_ZERO(&rfds);
+    FD_SET(conn, &rfds);
+    struct timeval tv = {0, 10000};
+    assert(select(conn + 1, &rfds, NULL, NULL, &tv) == 0);
+    assert(write(client, "x", 1) == 1);
+    FD_SET(conn, &rfds);
+    assert(select(conn + 1, &rfds, NULL, NULL, &tv) == 1 && FD_ISSET(conn, &rfds));
+
+    // EOF is readable too.
+    char c;
+    assert(read(conn, &c, 1) == 1 && c == 'x');
+    close(client);
+    FD_SET(conn, &rfds);
+    assert(select(conn + 1, &rfds, NULL, NULL, &tv) == 1 && read(conn, &c, 1) == 0);
+
+    close(conn);
+    close(epfd);
+    close(listener);
+    puts("test_poll: OK");
+}
+
+int main()
+{
+    test_stream_pair();
+    test_dgram_pair();
+    test_stream_path();
+    test_dgram_abstract();
+    test_poll();
+    puts("(C)Unix socket tests run OK!");
+    return 0;
+}
@@ -6,6 +6,6 @@ extern crate axstd;
 #[no_mangle]
 fn main() {
     axstd::println!("Benchmarking bandwidth...");
-    axnet::bench_transmit();
-    // axnet::bench_receive();
+    axnet::bench_transmit().expect("no NIC device");
+    // axnet::bench_receive().expect("no NIC device");
 }
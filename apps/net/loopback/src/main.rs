@@ -0,0 +1,63 @@
+#![cfg_attr(feature = "axstd", no_std)]
+#![cfg_attr(feature = "axstd", no_main)]
+
+#[macro_use]
+#[cfg(feature = "axstd")]
+extern crate axstd as std;
+
+use std::io::{self, prelude::*};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::thread;
+
+const TCP_PORT: u16 = 5555;
+const UDP_PORT: u16 = 5556;
+const MESSAGE: &[u8] = b"Hello, loopback!";
+
+fn test_tcp() -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", TCP_PORT))?;
+    let server = thread::spawn(move || -> io::Result<()> {
+        let (mut stream, addr) = listener.accept()?;
+        println!("TCP accepted: {}", addr);
+        let mut buf = [0; 64];
+        loop {
+            let n = stream.read(&mut buf)?;
+            if n == 0 {
+                return Ok(());
+            }
+            stream.write_all(&buf[..n])?;
+        }
+    });
+
+    let mut stream = TcpStream::connect(("127.0.0.1", TCP_PORT))?;
+    stream.write_all(MESSAGE)?;
+    let mut buf = [0; MESSAGE.len()];
+    stream.read_exact(&mut buf)?;
+    assert_eq!(buf, MESSAGE);
+    drop(stream);
+    server.join().unwrap()?;
+    println!("TCP echo OK");
+    Ok(())
+}
+
+fn test_udp() -> io::Result<()> {
+    let server = UdpSocket::bind(("127.0.0.1", UDP_PORT))?;
+    let client = UdpSocket::bind("127.0.0.1:0")?;
+    client.send_to(MESSAGE, ("127.0.0.1", UDP_PORT))?;
+
+    let mut buf = [0; 64];
+    let (n, addr) = server.recv_from(&mut buf)?;
+    assert_eq!(&buf[..n], MESSAGE);
+    server.send_to(&buf[..n], addr)?;
+    let (n, _) = client.recv_from(&mut buf)?;
+    assert_eq!(&buf[..n], MESSAGE);
+    println!("UDP echo OK");
+    Ok(())
+}
+
+#[cfg_attr(feature = "axstd", no_mangle)]
+fn main() {
+    println!("Hello, loopback test!");
+    test_tcp().expect("TCP loopback test failed");
+    test_udp().expect("UDP loopback test failed");
+    println!("Loopback tests run OK!");
+}
@@ -0,0 +1,95 @@
+//! Benchmarks the TCP bandwidth, with the data copied between the socket and
+//! a user buffer (`recv`/`send`) and accessed in place in the buffers of the
+//! socket (`recv_in_place`/`send_in_place`).
+//!
+//! Run it with `make A=apps/net/tcpbench LOG=info NET=y run`. Each round
+//! accepts a new connection on port 5555, forwarded from the host by QEMU.
+//! Connect to it from the host for each of the two receive rounds, for
+//! example with `iperf -c 127.0.0.1 -p 5555 -t 30`, then for each of the two
+//! transmit rounds with `nc 127.0.0.1 5555 > /dev/null`.
+//!
+//! Use `apps/net/bwbench` for the bandwidth of the NIC alone
+//! (`axnet::bench_transmit`/`axnet::bench_receive`).
+
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate axstd as std;
+
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use axnet::TcpSocket;
+use std::time::{Duration, Instant};
+
+const PORT: u16 = 5555;
+const ROUND: Duration = Duration::from_secs(10);
+const BUF_SIZE: usize = 64 * 1024;
+
+/// The work done on each received chunk, the same in both receive rounds.
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, &b| sum ^ b)
+}
+
+/// Accepts a connection, and calls `op` on it until the round ends or it
+/// returns 0. `op` returns the number of bytes received or sent, and the
+/// checksum of received data.
+fn bench<F>(listener: &TcpSocket, name: &str, mut op: F)
+where
+    F: FnMut(&TcpSocket) -> (usize, u8),
+{
+    println!("{}: waiting for a connection...", name);
+    let stream = listener.accept().expect("failed to accept");
+    let start = Instant::now();
+    let (mut bytes, mut sum) = (0, 0);
+    while start.elapsed() < ROUND {
+        let (len, s) = op(&stream);
+        if len == 0 {
+            break;
+        }
+        bytes += len;
+        sum ^= s;
+    }
+    let secs = start.elapsed().as_secs_f64();
+    println!(
+        "{}: {} bytes, {:.3} Gbits/sec (checksum {:#04x})",
+        name,
+        bytes,
+        (bytes * 8) as f64 / secs / 1e9,
+        sum
+    );
+}
+
+#[no_mangle]
+fn main() {
+    let listener = TcpSocket::new();
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), PORT);
+    listener.bind(addr).expect("failed to bind");
+    listener.listen().expect("failed to listen");
+    println!("Benchmarking TCP bandwidth on {}...", addr);
+
+    let mut buf = [0; BUF_SIZE];
+    bench(&listener, "recv", |stream| {
+        let len = stream.recv(&mut buf).unwrap_or(0);
+        (len, checksum(&buf[..len]))
+    });
+    bench(&listener, "recv_in_place", |stream| {
+        stream
+            .recv_in_place(|data| (data.len(), (data.len(), checksum(data))))
+            .unwrap_or((0, 0))
+    });
+
+    buf.fill(0xa5);
+    bench(&listener, "send", |stream| {
+        (stream.send(&buf).unwrap_or(0), 0)
+    });
+    bench(&listener, "send_in_place", |stream| {
+        let len = stream
+            .send_in_place(|space| {
+                space.fill(0xa5);
+                (space.len(), space.len())
+            })
+            .unwrap_or(0);
+        (len, 0)
+    });
+}
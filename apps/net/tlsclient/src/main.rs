@@ -0,0 +1,38 @@
+//! A TLS echo client, to be used with `scripts/net/tls-test-server.py`.
+//!
+//! The CA certificate printed by the test server should be copied to `/ca.pem`
+//! in the disk image.
+
+#![cfg_attr(feature = "axstd", no_std)]
+#![cfg_attr(feature = "axstd", no_main)]
+
+#[macro_use]
+#[cfg(feature = "axstd")]
+extern crate axstd as std;
+
+use std::io::{self, prelude::*};
+use std::net::{TcpStream, TlsConnector};
+
+const DEST: &str = "10.0.2.2:4433";
+const SERVER_NAME: &str = "localhost";
+const CA_PATH: &str = "/ca.pem";
+const MESSAGE: &[u8] = b"Hello, TLS server!";
+
+fn client() -> io::Result<()> {
+    let connector = TlsConnector::new(CA_PATH)?;
+    let stream = TcpStream::connect(DEST)?;
+    let mut stream = connector.connect(SERVER_NAME, stream)?;
+    println!("TLS handshake with {} done", DEST);
+
+    stream.write_all(MESSAGE)?;
+    let mut buf = [0; MESSAGE.len()];
+    stream.read_exact(&mut buf)?;
+    println!("echo: {}", core::str::from_utf8(&buf).unwrap());
+    stream.shutdown()
+}
+
+#[cfg_attr(feature = "axstd", no_mangle)]
+fn main() {
+    println!("Hello, TLS client!");
+    client().expect("test TLS client failed");
+}
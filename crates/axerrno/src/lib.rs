@@ -43,6 +43,9 @@ pub enum AxError {
     ConnectionReset,
     /// A non-empty directory was specified where an empty directory was expected.
     DirectoryNotEmpty,
+    /// The operation was interrupted, typically by a signal, before it could
+    /// complete.
+    Interrupted,
     /// Data not valid for the operation were encountered.
     ///
     /// Unlike [`InvalidInput`], this typically means that the operation
@@ -60,6 +63,9 @@ pub enum AxError {
     Io,
     /// The filesystem object is, unexpectedly, a directory.
     IsADirectory,
+    /// A message is too long to be sent at once, such as a datagram larger
+    /// than the send buffer.
+    MessageTooLong,
     /// Not enough space/cannot allocate memory.
     NoMemory,
     /// A filesystem object is, unexpectedly, not a directory.
@@ -195,10 +201,12 @@ impl AxError {
             ConnectionRefused => "Connection refused",
             ConnectionReset => "Connection reset",
             DirectoryNotEmpty => "Directory not empty",
+            Interrupted => "Operation interrupted",
             InvalidData => "Invalid data",
             InvalidInput => "Invalid input parameter",
             Io => "I/O error",
             IsADirectory => "Is a directory",
+            MessageTooLong => "Message too long",
             NoMemory => "Out of memory",
             NotADirectory => "Not a directory",
             NotConnected => "Not connected",
@@ -248,9 +256,11 @@ impl From<AxError> for LinuxError {
             ConnectionRefused => LinuxError::ECONNREFUSED,
             ConnectionReset => LinuxError::ECONNRESET,
             DirectoryNotEmpty => LinuxError::ENOTEMPTY,
+            Interrupted => LinuxError::EINTR,
             InvalidInput | InvalidData => LinuxError::EINVAL,
             Io => LinuxError::EIO,
             IsADirectory => LinuxError::EISDIR,
+            MessageTooLong => LinuxError::EMSGSIZE,
             NoMemory => LinuxError::ENOMEM,
             NotADirectory => LinuxError::ENOTDIR,
             NotConnected => LinuxError::ENOTCONN,
@@ -283,7 +293,7 @@ mod tests {
     #[test]
     fn test_try_from() {
         let max_code = core::mem::variant_count::<AxError>() as i32;
-        assert_eq!(max_code, 22);
+        assert_eq!(max_code, 24);
         assert_eq!(max_code, AxError::WriteZero.code());
 
         assert_eq!(AxError::AddrInUse.code(), 1);
@@ -8,6 +8,7 @@ extern crate alloc;
 
 mod dir;
 mod null;
+mod random;
 mod zero;
 
 #[cfg(test)]
@@ -15,6 +16,7 @@ mod tests;
 
 pub use self::dir::DirNode;
 pub use self::null::NullDev;
+pub use self::random::RandomDev;
 pub use self::zero::ZeroDev;
 
 use alloc::sync::Arc;
@@ -0,0 +1,45 @@
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult};
+
+/// A random device behaves like `/dev/random` and `/dev/urandom`.
+///
+/// It returns bytes from a random number generator when read, and mixes all
+/// writes into the generator.
+pub struct RandomDev {
+    fill: fn(&mut [u8]),
+    mix: fn(&[u8]),
+}
+
+impl RandomDev {
+    /// Create a new instance, which reads bytes by `fill`, and mixes written
+    /// bytes by `mix`.
+    pub const fn new(fill: fn(&mut [u8]), mix: fn(&[u8])) -> Self {
+        Self { fill, mix }
+    }
+}
+
+impl VfsNodeOps for RandomDev {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::default_file(),
+            VfsNodeType::CharDevice,
+            0,
+            0,
+        ))
+    }
+
+    fn read_at(&self, _offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        (self.fill)(buf);
+        Ok(buf.len())
+    }
+
+    fn write_at(&self, _offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        (self.mix)(buf);
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(())
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use axfs_vfs::{VfsError, VfsNodeType, VfsResult};
@@ -113,3 +114,30 @@ fn test_devfs() {
     test_devfs_ops(&devfs).unwrap();
     test_get_parent(&devfs).unwrap();
 }
+
+#[test]
+fn test_random_dev() {
+    static MIXED: AtomicUsize = AtomicUsize::new(0);
+    fn fill(buf: &mut [u8]) {
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = i as u8 ^ 0x5a;
+        }
+    }
+    fn mix(buf: &[u8]) {
+        MIXED.fetch_add(buf.len(), Ordering::SeqCst);
+    }
+
+    let devfs = DeviceFileSystem::new();
+    devfs.add("urandom", Arc::new(RandomDev::new(fill, mix)));
+
+    let node = devfs.root_dir().lookup("urandom").unwrap();
+    assert_eq!(
+        node.get_attr().unwrap().file_type(),
+        VfsNodeType::CharDevice
+    );
+    let mut buf = [0; 4];
+    assert_eq!(node.read_at(100, &mut buf).unwrap(), 4);
+    assert_eq!(buf, [0x5a, 0x5b, 0x58, 0x59]);
+    assert_eq!(node.write_at(0, &buf[..3]).unwrap(), 3);
+    assert_eq!(MIXED.load(Ordering::SeqCst), 3);
+}
@@ -32,6 +32,11 @@ impl VfsNodeOps for FileNode {
         Ok(())
     }
 
+    fn fsync(&self) -> VfsResult {
+        // The content lives in memory, there is nothing to write back.
+        Ok(())
+    }
+
     fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
         let content = self.content.read();
         let start = content.len().min(offset as usize);
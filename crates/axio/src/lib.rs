@@ -270,12 +270,36 @@ where
 }
 
 /// I/O poll results.
+///
+/// More states may be added, so it can only be created by [`PollState::new`]
+/// outside of this crate.
 #[derive(Debug, Default, Clone, Copy)]
+#[non_exhaustive]
 pub struct PollState {
     /// Object can be read now.
     pub readable: bool,
     /// Object can be writen now.
     pub writable: bool,
+    /// The peer has closed the connection, or the other end of the pipe is
+    /// closed.
+    pub hangup: bool,
+}
+
+impl PollState {
+    /// Creates a poll result with the given readiness, and no other states.
+    pub const fn new(readable: bool, writable: bool) -> Self {
+        Self {
+            readable,
+            writable,
+            hangup: false,
+        }
+    }
+
+    /// Sets whether the peer or the other end has been closed.
+    pub const fn with_hangup(mut self, hangup: bool) -> Self {
+        self.hangup = hangup;
+        self
+    }
 }
 
 /// An iterator over the lines of an instance of `BufRead`.
@@ -7,11 +7,13 @@
 //! - [`driver_block`][2]: Common traits for block storage drivers.
 //! - [`driver_display`][3]: Common traits and types for graphics display drivers.
 //! - [`driver_net`][4]: Common traits and types for network (NIC) drivers.
+//! - [`driver_rng`][5]: Common traits for random number generator drivers.
 //!
 //! [1]: https://github.com/rcore-os/arceos
 //! [2]: ../driver_block/index.html
 //! [3]: ../driver_display/index.html
 //! [4]: ../driver_net/index.html
+//! [5]: ../driver_rng/index.html
 
 #![no_std]
 #![feature(const_trait_impl)]
@@ -27,6 +29,8 @@ pub enum DeviceType {
     Net,
     /// Graphic display device (e.g., GPU)
     Display,
+    /// Hardware random number generator (e.g., virtio-rng).
+    Rng,
 }
 
 /// The error type for device operation failures.
@@ -15,6 +15,15 @@ const RX_BUFFER_SIZE: usize = 1024;
 const MEM_POOL: usize = 4096;
 const MEM_POOL_ENTRY_SIZE: usize = 2048;
 
+/// Extended Interrupt Cause Register (read to clear).
+const IXGBE_EICR: usize = 0x0800;
+/// Extended Interrupt Mask Set Register.
+const IXGBE_EIMS: usize = 0x0880;
+/// Interrupt Vector Allocation Register for RX/TX queues 0 and 1.
+const IXGBE_IVAR0: usize = 0x0900;
+/// The "allocation valid" bit in an `IVAR` entry.
+const IXGBE_IVAR_ALLOC_VAL: u32 = 0x80;
+
 /// The ixgbe NIC device driver.
 ///
 /// `QS` is the ixgbe queue size, `QN` is the ixgbe queue num.
@@ -22,6 +31,8 @@ pub struct IxgbeNic<H: IxgbeHal, const QS: usize, const QN: u16> {
     inner: IxgbeDevice<H, QS>,
     mem_pool: Arc<MemPool>,
     rx_buffer_queue: VecDeque<NetBufPtr>,
+    base: usize,
+    irq: Option<usize>,
 }
 
 unsafe impl<H: IxgbeHal, const QS: usize, const QN: u16> Sync for IxgbeNic<H, QS, QN> {}
@@ -30,7 +41,9 @@ unsafe impl<H: IxgbeHal, const QS: usize, const QN: u16> Send for IxgbeNic<H, QS
 impl<H: IxgbeHal, const QS: usize, const QN: u16> IxgbeNic<H, QS, QN> {
     /// Creates a net ixgbe NIC instance and initialize, or returns a error if
     /// any step fails.
-    pub fn init(base: usize, len: usize) -> DevResult<Self> {
+    ///
+    /// If `irq` is given, the interrupt of RX queue 0 is enabled.
+    pub fn init(base: usize, len: usize, irq: Option<usize>) -> DevResult<Self> {
         let mem_pool = MemPool::allocate::<H>(MEM_POOL, MEM_POOL_ENTRY_SIZE)
             .map_err(|_| DevError::NoMemory)?;
         let inner = IxgbeDevice::<H, QS>::init(base, len, QN, QN, &mem_pool).map_err(|err| {
@@ -39,11 +52,27 @@ impl<H: IxgbeHal, const QS: usize, const QN: u16> IxgbeNic<H, QS, QN> {
         })?;
 
         let rx_buffer_queue = VecDeque::with_capacity(RX_BUFFER_SIZE);
-        Ok(Self {
+        let nic = Self {
             inner,
             mem_pool,
             rx_buffer_queue,
-        })
+            base,
+            irq,
+        };
+        if irq.is_some() {
+            // Map RX queue 0 to interrupt cause 0 and unmask it.
+            nic.write_reg(IXGBE_IVAR0, IXGBE_IVAR_ALLOC_VAL);
+            nic.write_reg(IXGBE_EIMS, 1);
+        }
+        Ok(nic)
+    }
+
+    fn read_reg(&self, offset: usize) -> u32 {
+        unsafe { core::ptr::read_volatile((self.base + offset) as *const u32) }
+    }
+
+    fn write_reg(&self, offset: usize, value: u32) {
+        unsafe { core::ptr::write_volatile((self.base + offset) as *mut u32, value) }
     }
 }
 
@@ -136,6 +165,14 @@ impl<H: IxgbeHal, const QS: usize, const QN: u16> NetDriverOps for IxgbeNic<H, Q
         let tx_buf = IxgbeNetBuf::alloc(&self.mem_pool, size).map_err(|_| DevError::NoMemory)?;
         Ok(NetBufPtr::from(tx_buf))
     }
+
+    fn irq_num(&self) -> Option<usize> {
+        self.irq
+    }
+
+    fn ack_interrupt(&mut self) -> bool {
+        self.irq.is_some() && self.read_reg(IXGBE_EICR) != 0
+    }
 }
 
 impl From<IxgbeNetBuf> for NetBufPtr {
@@ -64,6 +64,18 @@ pub trait NetDriverOps: BaseDriverOps {
     /// Allocate a memory buffer of a specified size for network transmission,
     /// returns [`DevResult`]
     fn alloc_tx_buffer(&mut self, size: usize) -> DevResult<NetBufPtr>;
+
+    /// The IRQ number raised by the NIC on packet arrival, if it has one.
+    fn irq_num(&self) -> Option<usize> {
+        None
+    }
+
+    /// Acknowledges a pending interrupt of the NIC.
+    ///
+    /// Returns `true` if there was an interrupt to acknowledge.
+    fn ack_interrupt(&mut self) -> bool {
+        false
+    }
 }
 
 /// A raw buffer struct for network device.
@@ -0,0 +1,17 @@
+//! Common traits and types for hardware random number generator drivers.
+
+#![no_std]
+
+#[doc(no_inline)]
+pub use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
+
+/// Operations that require a hardware random number generator driver to
+/// implement.
+pub trait RngDriverOps: BaseDriverOps {
+    /// Fills `buf` with random bytes from the device.
+    ///
+    /// Returns the number of bytes filled, which may be less than the length
+    /// of `buf`, or [`DevError::Again`] if the device is not ready yet, in
+    /// which case it can be called again later.
+    fn read_random(&mut self, buf: &mut [u8]) -> DevResult<usize>;
+}
@@ -20,6 +20,8 @@ mod blk;
 mod gpu;
 #[cfg(feature = "net")]
 mod net;
+#[cfg(feature = "rng")]
+mod rng;
 
 #[cfg(feature = "block")]
 pub use self::blk::VirtIoBlkDev;
@@ -27,6 +29,8 @@ pub use self::blk::VirtIoBlkDev;
 pub use self::gpu::VirtIoGpuDev;
 #[cfg(feature = "net")]
 pub use self::net::VirtIoNetDev;
+#[cfg(feature = "rng")]
+pub use self::rng::VirtIoRngDev;
 
 pub use virtio_drivers::transport::pci::bus as pci;
 pub use virtio_drivers::transport::{mmio::MmioTransport, pci::PciTransport, Transport};
@@ -75,6 +79,7 @@ const fn as_dev_type(t: VirtIoDevType) -> Option<DeviceType> {
         Block => Some(DeviceType::Block),
         Network => Some(DeviceType::Net),
         GPU => Some(DeviceType::Display),
+        EntropySource => Some(DeviceType::Rng),
         _ => None,
     }
 }
@@ -0,0 +1,194 @@
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+use core::sync::atomic::{fence, Ordering};
+
+use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
+use driver_rng::RngDriverOps;
+use virtio_drivers::transport::{DeviceStatus, Transport};
+use virtio_drivers::{BufferDirection, Hal, PhysAddr};
+
+const PAGE_SIZE: usize = 0x1000;
+
+const VIRTIO_F_VERSION_1: u64 = 1 << 32;
+const VIRTQ_DESC_F_WRITE: u16 = 2;
+
+/// The only queue of the device, `requestq`.
+const QUEUE: u16 = 0;
+const QUEUE_SIZE: usize = 4;
+/// How many times to poll the used ring before giving up with
+/// [`DevError::Again`].
+const MAX_POLLS: usize = 1 << 20;
+
+// The DMA region contains the descriptor table and the available ring in the
+// first page, the used ring in the second page (as the legacy layout
+// requires), and the buffer in the third page.
+const DMA_PAGES: usize = 3;
+const AVAIL_OFFSET: usize = core::mem::size_of::<Descriptor>() * QUEUE_SIZE;
+const USED_OFFSET: usize = PAGE_SIZE;
+const BUF_OFFSET: usize = 2 * PAGE_SIZE;
+
+#[repr(C)]
+struct Descriptor {
+    addr: u64,
+    len: u32,
+    flags: u16,
+    next: u16,
+}
+
+/// The VirtIO entropy device driver.
+///
+/// The `virtio-drivers` crate does not support it yet, so it drives the only
+/// virtqueue itself, with one request in flight at a time.
+pub struct VirtIoRngDev<H: Hal, T: Transport> {
+    transport: T,
+    dma_paddr: PhysAddr,
+    dma_vaddr: NonNull<u8>,
+    avail_idx: u16,
+    last_used_idx: u16,
+    /// The length of the request in flight, which a previous call gave up
+    /// waiting for.
+    pending: Option<usize>,
+    _hal: PhantomData<H>,
+}
+
+unsafe impl<H: Hal, T: Transport> Send for VirtIoRngDev<H, T> {}
+unsafe impl<H: Hal, T: Transport> Sync for VirtIoRngDev<H, T> {}
+
+impl<H: Hal, T: Transport> VirtIoRngDev<H, T> {
+    /// Creates a new driver instance and initializes the device, or returns
+    /// an error if any step fails.
+    pub fn try_new(mut transport: T) -> DevResult<Self> {
+        let status = DeviceStatus::ACKNOWLEDGE | DeviceStatus::DRIVER;
+        transport.set_status(DeviceStatus::empty());
+        transport.set_status(status);
+        let features = transport.read_device_features();
+        transport.write_driver_features(features & VIRTIO_F_VERSION_1);
+        transport.set_status(status | DeviceStatus::FEATURES_OK);
+        if !transport.get_status().contains(DeviceStatus::FEATURES_OK) {
+            return Err(DevError::Unsupported);
+        }
+        transport.set_guest_page_size(PAGE_SIZE as u32);
+
+        if transport.queue_used(QUEUE) || (transport.max_queue_size() as usize) < QUEUE_SIZE {
+            return Err(DevError::BadState);
+        }
+        let (dma_paddr, dma_vaddr) = H::dma_alloc(DMA_PAGES, BufferDirection::Both);
+        if dma_paddr == 0 {
+            return Err(DevError::NoMemory);
+        }
+        unsafe { dma_vaddr.as_ptr().write_bytes(0, DMA_PAGES * PAGE_SIZE) };
+        transport.queue_set(
+            QUEUE,
+            QUEUE_SIZE as u32,
+            dma_paddr,
+            dma_paddr + AVAIL_OFFSET,
+            dma_paddr + USED_OFFSET,
+        );
+        transport.set_status(status | DeviceStatus::FEATURES_OK | DeviceStatus::DRIVER_OK);
+
+        Ok(Self {
+            transport,
+            dma_paddr,
+            dma_vaddr,
+            avail_idx: 0,
+            last_used_idx: 0,
+            pending: None,
+            _hal: PhantomData,
+        })
+    }
+
+    fn ptr<U>(&self, offset: usize) -> *mut U {
+        unsafe { self.dma_vaddr.as_ptr().add(offset).cast() }
+    }
+
+    /// Requests `len` random bytes into the buffer.
+    fn submit(&mut self, len: usize) {
+        let slot = self.avail_idx as usize % QUEUE_SIZE;
+        unsafe {
+            self.ptr::<Descriptor>(0).write_volatile(Descriptor {
+                addr: (self.dma_paddr + BUF_OFFSET) as u64,
+                len: len as u32,
+                flags: VIRTQ_DESC_F_WRITE,
+                next: 0,
+            });
+            // `ring[slot]` of the available ring, after `flags` and `idx`.
+            self.ptr::<u16>(AVAIL_OFFSET + 4 + 2 * slot)
+                .write_volatile(0);
+            fence(Ordering::SeqCst);
+            self.avail_idx = self.avail_idx.wrapping_add(1);
+            self.ptr::<u16>(AVAIL_OFFSET + 2)
+                .write_volatile(self.avail_idx);
+            fence(Ordering::SeqCst);
+        }
+        self.transport.notify(QUEUE);
+    }
+
+    /// Returns the number of bytes written by the device if the request in
+    /// flight has completed.
+    fn poll_used(&mut self) -> Option<usize> {
+        // `idx` of the used ring.
+        if unsafe { self.ptr::<u16>(USED_OFFSET + 2).read_volatile() } == self.last_used_idx {
+            return None;
+        }
+        fence(Ordering::SeqCst);
+        let slot = self.last_used_idx as usize % QUEUE_SIZE;
+        self.last_used_idx = self.last_used_idx.wrapping_add(1);
+        // `len` of `ring[slot]` of the used ring, whose elements are `(id, len)`.
+        let written = unsafe {
+            self.ptr::<u32>(USED_OFFSET + 4 + 8 * slot + 4)
+                .read_volatile()
+        };
+        Some(written as usize)
+    }
+}
+
+impl<H: Hal, T: Transport> Drop for VirtIoRngDev<H, T> {
+    fn drop(&mut self) {
+        // Reset the device before freeing the queue.
+        self.transport.set_status(DeviceStatus::empty());
+        unsafe { H::dma_dealloc(self.dma_paddr, self.dma_vaddr, DMA_PAGES) };
+    }
+}
+
+impl<H: Hal, T: Transport> const BaseDriverOps for VirtIoRngDev<H, T> {
+    fn device_name(&self) -> &str {
+        "virtio-rng"
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Rng
+    }
+}
+
+impl<H: Hal, T: Transport> RngDriverOps for VirtIoRngDev<H, T> {
+    fn read_random(&mut self, buf: &mut [u8]) -> DevResult<usize> {
+        // Wait for the request left by a previous call, if any.
+        let len = match self.pending {
+            Some(len) => len,
+            None => {
+                let len = buf.len().min(PAGE_SIZE);
+                self.submit(len);
+                len
+            }
+        };
+        self.pending = Some(len);
+
+        let mut polls = 0;
+        let written = loop {
+            if let Some(written) = self.poll_used() {
+                break written;
+            }
+            polls += 1;
+            if polls >= MAX_POLLS {
+                return Err(DevError::Again);
+            }
+            core::hint::spin_loop();
+        };
+        self.pending = None;
+        let written = written.min(len).min(buf.len());
+        unsafe {
+            core::ptr::copy_nonoverlapping(self.ptr::<u8>(BUF_OFFSET), buf.as_mut_ptr(), written)
+        };
+        Ok(written)
+    }
+}
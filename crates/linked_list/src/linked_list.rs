@@ -205,6 +205,12 @@ impl<G: AdapterWrapped> List<G> {
     pub fn cursor_front(&self) -> Cursor<'_, G> {
         self.list.cursor_front()
     }
+
+    /// Returns a cursor starting on the last (back) element of the list.
+    #[inline]
+    pub fn cursor_back(&self) -> Cursor<'_, G> {
+        self.list.cursor_back()
+    }
 }
 
 impl<G: AdapterWrapped> Default for List<G> {
@@ -10,6 +10,10 @@
 //! pointer register to obtain the corresponding per-CPU data area, and then add
 //! an offset to access the corresponding field.
 //!
+//! The per-CPU data on other CPUs can be accessed by `remote_ptr(cpu_id)`,
+//! which adds the offset to the base address of the per-CPU data area of that
+//! CPU. It is up to the caller to synchronize with the owning CPU.
+//!
 //! # Notes
 //!
 //! Since RISC-V does not provide separate thread pointer registers for user and
@@ -47,6 +51,9 @@
 
 extern crate percpu_macros;
 
+// For the generated code of `def_percpu` used in this crate.
+extern crate self as percpu;
+
 #[cfg_attr(feature = "sp-naive", path = "naive.rs")]
 mod imp;
 
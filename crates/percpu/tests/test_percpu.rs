@@ -99,4 +99,22 @@ fn test_percpu() {
         assert_eq!(s.foo, 0x2333);
         assert_eq!(s.bar, 100);
     });
+
+    unsafe {
+        assert_eq!(USIZE.remote_ptr(0), USIZE.current_ptr());
+        assert_eq!(STRUCT.remote_ref_raw(0).foo, 0x2333);
+    }
+
+    #[cfg(not(feature = "sp-naive"))]
+    unsafe {
+        assert_eq!(base + STRUCT.offset(), STRUCT.remote_ptr(0) as usize);
+        let remote_base = percpu_area_base(1);
+        assert_eq!(remote_base + USIZE.offset(), USIZE.remote_ptr(1) as usize);
+
+        *(USIZE.remote_ptr(1) as *mut usize) = 0xdead_0000;
+        assert_eq!(USIZE.read_current(), 0xffff_0000);
+        set_local_thread_pointer(1);
+        assert_eq!(USIZE.read_current(), 0xdead_0000);
+        set_local_thread_pointer(0);
+    }
 }
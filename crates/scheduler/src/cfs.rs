@@ -7,12 +7,22 @@ use crate::BaseScheduler;
 /// task for CFS
 pub struct CFSTask<T> {
     inner: T,
+    entity: CFSEntity,
+}
+
+/// The states of a task used by the CFS.
+pub(crate) struct CFSEntity {
     init_vruntime: AtomicIsize,
     delta: AtomicIsize,
     nice: AtomicIsize,
     id: AtomicIsize,
 }
 
+/// Tasks that can be scheduled by the CFS.
+pub(crate) trait AsCFSEntity {
+    fn cfs_entity(&self) -> &CFSEntity;
+}
+
 // https://elixir.bootlin.com/linux/latest/source/include/linux/sched/prio.h
 
 const NICE_RANGE_POS: usize = 19; // MAX_NICE in Linux
@@ -33,6 +43,25 @@ impl<T> CFSTask<T> {
     pub const fn new(inner: T) -> Self {
         Self {
             inner,
+            entity: CFSEntity::new(),
+        }
+    }
+
+    /// Returns a reference to the inner task struct.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> AsCFSEntity for CFSTask<T> {
+    fn cfs_entity(&self) -> &CFSEntity {
+        &self.entity
+    }
+}
+
+impl CFSEntity {
+    pub(crate) const fn new() -> Self {
+        Self {
             init_vruntime: AtomicIsize::new(0_isize),
             delta: AtomicIsize::new(0_isize),
             nice: AtomicIsize::new(0_isize),
@@ -83,11 +112,6 @@ impl<T> CFSTask<T> {
     fn task_tick(&self) {
         self.delta.fetch_add(1, Ordering::Release);
     }
-
-    /// Returns a reference to the inner task struct.
-    pub const fn inner(&self) -> &T {
-        &self.inner
-    }
 }
 
 impl<T> Deref for CFSTask<T> {
@@ -101,18 +125,14 @@ impl<T> Deref for CFSTask<T> {
 ///
 /// [1]: https://en.wikipedia.org/wiki/Completely_Fair_Scheduler
 pub struct CFScheduler<T> {
-    ready_queue: BTreeMap<(isize, isize), Arc<CFSTask<T>>>, // (vruntime, taskid)
-    min_vruntime: Option<AtomicIsize>,
-    id_pool: AtomicIsize,
+    queue: CFSQueue<CFSTask<T>>,
 }
 
 impl<T> CFScheduler<T> {
     /// Creates a new empty [`CFScheduler`].
     pub const fn new() -> Self {
         Self {
-            ready_queue: BTreeMap::new(),
-            min_vruntime: None,
-            id_pool: AtomicIsize::new(0_isize),
+            queue: CFSQueue::new(),
         }
     }
     /// get the name of scheduler
@@ -127,13 +147,61 @@ impl<T> BaseScheduler for CFScheduler<T> {
     fn init(&mut self) {}
 
     fn add_task(&mut self, task: Self::SchedItem) {
+        self.queue.add_task(task)
+    }
+
+    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
+        self.queue.remove_task(task)
+    }
+
+    fn find_task<F>(&self, pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        self.queue.find_task(pred)
+    }
+
+    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+        self.queue.pick_next_task()
+    }
+
+    fn put_prev_task(&mut self, prev: Self::SchedItem, _preempt: bool) {
+        self.queue.put_prev_task(prev)
+    }
+
+    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
+        self.queue.task_tick(current)
+    }
+
+    fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
+        self.queue.set_priority(task, prio)
+    }
+}
+
+/// The ready queue of the CFS, which can hold any tasks with a [`CFSEntity`].
+pub(crate) struct CFSQueue<E> {
+    ready_queue: BTreeMap<(isize, isize), Arc<E>>, // (vruntime, taskid)
+    min_vruntime: Option<AtomicIsize>,
+    id_pool: AtomicIsize,
+}
+
+impl<E: AsCFSEntity> CFSQueue<E> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            ready_queue: BTreeMap::new(),
+            min_vruntime: None,
+            id_pool: AtomicIsize::new(0_isize),
+        }
+    }
+
+    pub(crate) fn add_task(&mut self, task: Arc<E>) {
         if self.min_vruntime.is_none() {
             self.min_vruntime = Some(AtomicIsize::new(0_isize));
         }
         let vruntime = self.min_vruntime.as_mut().unwrap().load(Ordering::Acquire);
         let taskid = self.id_pool.fetch_add(1, Ordering::Release);
-        task.set_vruntime(vruntime);
-        task.set_id(taskid);
+        task.cfs_entity().set_vruntime(vruntime);
+        task.cfs_entity().set_id(taskid);
         self.ready_queue.insert((vruntime, taskid), task);
         if let Some(((min_vruntime, _), _)) = self.ready_queue.first_key_value() {
             self.min_vruntime = Some(AtomicIsize::new(*min_vruntime));
@@ -142,11 +210,18 @@ impl<T> BaseScheduler for CFScheduler<T> {
         }
     }
 
-    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
-        if let Some((_, tmp)) = self
+    pub(crate) fn remove_task(&mut self, task: &Arc<E>) -> Option<Arc<E>> {
+        let entity = task.cfs_entity();
+        let key = (entity.get_vruntime(), entity.get_id());
+        // A task not in the queue may have the same key as another one.
+        if !self
             .ready_queue
-            .remove_entry(&(task.clone().get_vruntime(), task.clone().get_id()))
+            .get(&key)
+            .is_some_and(|t| Arc::ptr_eq(t, task))
         {
+            return None;
+        }
+        if let Some((_, tmp)) = self.ready_queue.remove_entry(&key) {
             if let Some(((min_vruntime, _), _)) = self.ready_queue.first_key_value() {
                 self.min_vruntime = Some(AtomicIsize::new(*min_vruntime));
             } else {
@@ -158,7 +233,14 @@ impl<T> BaseScheduler for CFScheduler<T> {
         }
     }
 
-    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+    pub(crate) fn find_task<F>(&self, mut pred: F) -> Option<Arc<E>>
+    where
+        F: FnMut(&Arc<E>) -> bool,
+    {
+        self.ready_queue.values().rev().find(|t| pred(t)).cloned()
+    }
+
+    pub(crate) fn pick_next_task(&mut self) -> Option<Arc<E>> {
         if let Some((_, v)) = self.ready_queue.pop_first() {
             Some(v)
         } else {
@@ -166,22 +248,23 @@ impl<T> BaseScheduler for CFScheduler<T> {
         }
     }
 
-    fn put_prev_task(&mut self, prev: Self::SchedItem, _preempt: bool) {
+    pub(crate) fn put_prev_task(&mut self, prev: Arc<E>) {
         let taskid = self.id_pool.fetch_add(1, Ordering::Release);
-        prev.set_id(taskid);
+        prev.cfs_entity().set_id(taskid);
         self.ready_queue
-            .insert((prev.clone().get_vruntime(), taskid), prev);
+            .insert((prev.cfs_entity().get_vruntime(), taskid), prev);
     }
 
-    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
-        current.task_tick();
+    pub(crate) fn task_tick(&mut self, current: &Arc<E>) -> bool {
+        current.cfs_entity().task_tick();
         self.min_vruntime.is_none()
-            || current.get_vruntime() > self.min_vruntime.as_mut().unwrap().load(Ordering::Acquire)
+            || current.cfs_entity().get_vruntime()
+                > self.min_vruntime.as_mut().unwrap().load(Ordering::Acquire)
     }
 
-    fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
+    pub(crate) fn set_priority(&mut self, task: &Arc<E>, prio: isize) -> bool {
         if (-20..=19).contains(&prio) {
-            task.set_priority(prio);
+            task.cfs_entity().set_priority(prio);
             true
         } else {
             false
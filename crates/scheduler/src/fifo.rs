@@ -1,4 +1,5 @@
 use alloc::sync::Arc;
+use core::mem::ManuallyDrop;
 use core::ops::Deref;
 
 use linked_list::{Adapter, Links, List};
@@ -84,6 +85,23 @@ impl<T> BaseScheduler for FifoScheduler<T> {
         unsafe { self.ready_queue.remove(task) }
     }
 
+    fn find_task<F>(&self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        let mut cursor = self.ready_queue.cursor_back();
+        while let Some(task) = cursor.current() {
+            // SAFETY: the tasks in the list are `Arc`s converted into pointers,
+            // and the list keeps owning them.
+            let task = ManuallyDrop::new(unsafe { Arc::from_raw(task) });
+            if pred(&task) {
+                return Some(Arc::clone(&task));
+            }
+            cursor.move_prev();
+        }
+        None
+    }
+
     fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
         self.ready_queue.pop_front()
     }
@@ -0,0 +1,418 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use core::ops::Deref;
+use core::sync::atomic::{AtomicIsize, AtomicU64, AtomicUsize, Ordering};
+
+use crate::cfs::{AsCFSEntity, CFSEntity, CFSQueue};
+use crate::{BaseScheduler, SchedPolicy, RT_PRIO_MAX, RT_PRIO_MIN};
+
+const CLASS_NORMAL: usize = 0;
+const CLASS_FIFO: usize = 1;
+const CLASS_RR: usize = 2;
+const CLASS_DEADLINE: usize = 3;
+
+/// A task wrapper for the [`LayeredScheduler`].
+///
+/// It holds the scheduling policy of the task, and the states used by each
+/// scheduling class.
+pub struct LayeredTask<T, const RR_TIME_SLICE: usize> {
+    inner: T,
+    class: AtomicUsize,
+    // Priority of real-time tasks.
+    rt_prio: AtomicUsize,
+    // Time slice of round-robin tasks.
+    time_slice: AtomicIsize,
+    // Budget and period of deadline tasks.
+    budget: AtomicUsize,
+    period: AtomicUsize,
+    // Budget left in the current period, and the end of the current period.
+    runtime: AtomicIsize,
+    deadline: AtomicU64,
+    // Breaks the tie of deadlines in the ready queue.
+    dl_id: AtomicU64,
+    cfs: CFSEntity,
+}
+
+impl<T, const S: usize> LayeredTask<T, S> {
+    /// Creates a new [`LayeredTask`] from the inner task struct, with the
+    /// [`SchedPolicy::Normal`] policy.
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            class: AtomicUsize::new(CLASS_NORMAL),
+            rt_prio: AtomicUsize::new(0),
+            time_slice: AtomicIsize::new(S as isize),
+            budget: AtomicUsize::new(0),
+            period: AtomicUsize::new(0),
+            runtime: AtomicIsize::new(0),
+            deadline: AtomicU64::new(0),
+            dl_id: AtomicU64::new(0),
+            cfs: CFSEntity::new(),
+        }
+    }
+
+    /// Returns the scheduling policy of the task.
+    pub fn policy(&self) -> SchedPolicy {
+        let prio = self.rt_prio.load(Ordering::Acquire);
+        match self.class.load(Ordering::Acquire) {
+            CLASS_FIFO => SchedPolicy::Fifo(prio),
+            CLASS_RR => SchedPolicy::RoundRobin(prio),
+            CLASS_DEADLINE => SchedPolicy::Deadline {
+                budget: self.budget.load(Ordering::Acquire),
+                period: self.period.load(Ordering::Acquire),
+            },
+            _ => SchedPolicy::Normal,
+        }
+    }
+
+    /// Returns a reference to the inner task struct.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn class(&self) -> usize {
+        self.class.load(Ordering::Acquire)
+    }
+
+    fn rt_prio(&self) -> usize {
+        self.rt_prio.load(Ordering::Acquire)
+    }
+
+    fn deadline(&self) -> u64 {
+        self.deadline.load(Ordering::Acquire)
+    }
+
+    fn dl_key(&self) -> (u64, u64) {
+        (self.deadline(), self.dl_id.load(Ordering::Acquire))
+    }
+
+    fn reset_time_slice(&self) {
+        self.time_slice.store(S as isize, Ordering::Release);
+    }
+
+    /// Starts a new period at `now` with a full budget.
+    fn replenish(&self, now: u64) {
+        let period = self.period.load(Ordering::Acquire);
+        self.deadline.store(now + period as u64, Ordering::Release);
+        self.runtime.store(
+            self.budget.load(Ordering::Acquire) as isize,
+            Ordering::Release,
+        );
+    }
+
+    /// Starts the next period with a full budget, when the current one ends.
+    fn next_period(&self) {
+        let period = self.period.load(Ordering::Acquire);
+        self.deadline.fetch_add(period as u64, Ordering::Release);
+        self.runtime.store(
+            self.budget.load(Ordering::Acquire) as isize,
+            Ordering::Release,
+        );
+    }
+
+    /// Checks the deadline of the task when it becomes ready at `now`. If the
+    /// budget left can not be used up before the deadline without exceeding
+    /// the bandwidth, a new period is started.
+    fn update_deadline(&self, now: u64) {
+        let period = self.period.load(Ordering::Acquire) as u64;
+        let budget = self.budget.load(Ordering::Acquire) as u64;
+        let runtime = self.runtime.load(Ordering::Acquire);
+        let deadline = self.deadline();
+        // Deadlines too far away come from the clock of another CPU.
+        if runtime <= 0
+            || deadline <= now
+            || deadline > now + period
+            || runtime as u64 * period > (deadline - now) * budget
+        {
+            self.replenish(now);
+        }
+    }
+
+    fn set_policy(&self, policy: SchedPolicy) {
+        let (class, prio) = match policy {
+            SchedPolicy::Normal => (CLASS_NORMAL, 0),
+            SchedPolicy::Fifo(prio) => (CLASS_FIFO, prio),
+            SchedPolicy::RoundRobin(prio) => (CLASS_RR, prio),
+            SchedPolicy::Deadline { budget, period } => {
+                self.budget.store(budget, Ordering::Release);
+                self.period.store(period, Ordering::Release);
+                (CLASS_DEADLINE, 0)
+            }
+        };
+        // The budget is replenished when the task is put into the scheduler.
+        self.runtime.store(0, Ordering::Release);
+        self.deadline.store(0, Ordering::Release);
+        self.reset_time_slice();
+        self.rt_prio.store(prio, Ordering::Release);
+        self.class.store(class, Ordering::Release);
+    }
+}
+
+impl<T, const S: usize> Deref for LayeredTask<T, S> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T, const S: usize> AsCFSEntity for LayeredTask<T, S> {
+    fn cfs_entity(&self) -> &CFSEntity {
+        &self.cfs
+    }
+}
+
+/// A layered scheduler with multiple scheduling classes, the tasks of a
+/// higher class always run before the ones of lower classes.
+///
+/// From the highest to the lowest, the classes are:
+///
+/// - [`SchedPolicy::Deadline`]: [Earliest deadline first][1] (EDF). Each
+///   task has a budget in every period, and is throttled until the end of the
+///   period when it has used up the budget, as a [constant bandwidth
+///   server][2]. Throttled tasks still run if there is nothing else to run.
+/// - [`SchedPolicy::Fifo`] and [`SchedPolicy::RoundRobin`]: Real-time tasks
+///   with strict priorities, the higher priority runs first. Round-robin
+///   tasks with the same priority are switched every `RR_TIME_SLICE` ticks.
+/// - [`SchedPolicy::Normal`]: The same as the [`CFScheduler`].
+///
+/// Time is measured by the ticks of the scheduler, which are counted when
+/// [`task_tick`] is called.
+///
+/// [1]: https://en.wikipedia.org/wiki/Earliest_deadline_first_scheduling
+/// [2]: https://en.wikipedia.org/wiki/Constant_bandwidth_server
+/// [`CFScheduler`]: crate::CFScheduler
+/// [`task_tick`]: BaseScheduler::task_tick
+pub struct LayeredScheduler<T, const RR_TIME_SLICE: usize> {
+    dl_queue: BTreeMap<(u64, u64), Arc<LayeredTask<T, RR_TIME_SLICE>>>, // (deadline, id)
+    throttled: BTreeMap<(u64, u64), Arc<LayeredTask<T, RR_TIME_SLICE>>>, // (deadline, id)
+    rt_queues: BTreeMap<usize, VecDeque<Arc<LayeredTask<T, RR_TIME_SLICE>>>>, // priority -> tasks
+    cfs_queue: CFSQueue<LayeredTask<T, RR_TIME_SLICE>>,
+    clock: u64,
+    dl_id_pool: u64,
+}
+
+impl<T, const S: usize> LayeredScheduler<T, S> {
+    /// Creates a new empty [`LayeredScheduler`].
+    pub const fn new() -> Self {
+        Self {
+            dl_queue: BTreeMap::new(),
+            throttled: BTreeMap::new(),
+            rt_queues: BTreeMap::new(),
+            cfs_queue: CFSQueue::new(),
+            clock: 0,
+            dl_id_pool: 0,
+        }
+    }
+    /// get the name of scheduler
+    pub fn scheduler_name() -> &'static str {
+        "Layered real-time"
+    }
+
+    fn enqueue_dl(&mut self, task: Arc<LayeredTask<T, S>>) {
+        self.dl_id_pool += 1;
+        task.dl_id.store(self.dl_id_pool, Ordering::Release);
+        self.dl_queue.insert(task.dl_key(), task);
+    }
+
+    /// Puts the deadline tasks whose budget has been used up back to the
+    /// ready queue, if their next periods have started.
+    fn unthrottle(&mut self) {
+        while let Some(entry) = self.throttled.first_entry() {
+            if entry.key().0 > self.clock {
+                break;
+            }
+            let task = entry.remove();
+            task.next_period();
+            self.enqueue_dl(task);
+        }
+    }
+
+    fn enqueue_rt(&mut self, task: Arc<LayeredTask<T, S>>, front: bool) {
+        let queue = self.rt_queues.entry(task.rt_prio()).or_default();
+        if front {
+            queue.push_front(task);
+        } else {
+            queue.push_back(task);
+        }
+    }
+
+    /// Whether there are ready tasks that should preempt a real-time task
+    /// with priority `prio`.
+    fn has_higher_than_rt(&self, prio: usize) -> bool {
+        !self.dl_queue.is_empty()
+            || self
+                .rt_queues
+                .last_key_value()
+                .is_some_and(|(&p, _)| p > prio)
+    }
+}
+
+impl<T, const S: usize> BaseScheduler for LayeredScheduler<T, S> {
+    type SchedItem = Arc<LayeredTask<T, S>>;
+
+    fn init(&mut self) {}
+
+    fn add_task(&mut self, task: Self::SchedItem) {
+        match task.class() {
+            CLASS_DEADLINE => {
+                task.update_deadline(self.clock);
+                self.enqueue_dl(task);
+            }
+            CLASS_FIFO | CLASS_RR => self.enqueue_rt(task, false),
+            _ => self.cfs_queue.add_task(task),
+        }
+    }
+
+    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
+        match task.class() {
+            CLASS_DEADLINE => {
+                let key = task.dl_key();
+                for queue in [&mut self.dl_queue, &mut self.throttled] {
+                    if queue.get(&key).is_some_and(|t| Arc::ptr_eq(t, task)) {
+                        return queue.remove(&key);
+                    }
+                }
+                None
+            }
+            CLASS_FIFO | CLASS_RR => {
+                let prio = task.rt_prio();
+                let queue = self.rt_queues.get_mut(&prio)?;
+                let task = queue
+                    .iter()
+                    .position(|t| Arc::ptr_eq(t, task))
+                    .and_then(|idx| queue.remove(idx));
+                if queue.is_empty() {
+                    self.rt_queues.remove(&prio);
+                }
+                task
+            }
+            _ => self.cfs_queue.remove_task(task),
+        }
+    }
+
+    fn find_task<F>(&self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        // Lower classes run later. Throttled deadline tasks can not run
+        // anyway, so they are left alone.
+        if let Some(task) = self.cfs_queue.find_task(&mut pred) {
+            return Some(task);
+        }
+        let mut rt_tasks = self.rt_queues.values().flat_map(|q| q.iter().rev());
+        if let Some(task) = rt_tasks.find(|t| pred(t)) {
+            return Some(task.clone());
+        }
+        self.dl_queue.values().rev().find(|t| pred(t)).cloned()
+    }
+
+    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+        if let Some((_, task)) = self.dl_queue.pop_first() {
+            return Some(task);
+        }
+        if let Some(mut entry) = self.rt_queues.last_entry() {
+            let task = entry.get_mut().pop_front();
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+            return task;
+        }
+        if let Some(task) = self.cfs_queue.pick_next_task() {
+            return Some(task);
+        }
+        // Nothing else to run, start the next period of a throttled task
+        // early instead of idling.
+        let (_, task) = self.throttled.pop_first()?;
+        task.next_period();
+        Some(task)
+    }
+
+    fn put_prev_task(&mut self, prev: Self::SchedItem, preempt: bool) {
+        match prev.class() {
+            CLASS_DEADLINE => {
+                if prev.runtime.load(Ordering::Acquire) > 0 {
+                    self.enqueue_dl(prev);
+                } else if prev.deadline() <= self.clock {
+                    // The current period has ended, start a new one now.
+                    prev.replenish(self.clock);
+                    self.enqueue_dl(prev);
+                } else {
+                    // Wait for the next period with the budget used up.
+                    self.dl_id_pool += 1;
+                    prev.dl_id.store(self.dl_id_pool, Ordering::Release);
+                    self.throttled.insert(prev.dl_key(), prev);
+                }
+            }
+            CLASS_FIFO => self.enqueue_rt(prev, preempt),
+            CLASS_RR => {
+                if prev.time_slice.load(Ordering::Acquire) > 0 && preempt {
+                    self.enqueue_rt(prev, true)
+                } else {
+                    prev.reset_time_slice();
+                    self.enqueue_rt(prev, false)
+                }
+            }
+            _ => self.cfs_queue.put_prev_task(prev),
+        }
+    }
+
+    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
+        self.clock += 1;
+        self.unthrottle();
+        match current.class() {
+            CLASS_DEADLINE => {
+                let old_runtime = current.runtime.fetch_sub(1, Ordering::Release);
+                old_runtime <= 1
+                    || self
+                        .dl_queue
+                        .first_key_value()
+                        .is_some_and(|(&(deadline, _), _)| deadline < current.deadline())
+            }
+            CLASS_FIFO => self.has_higher_than_rt(current.rt_prio()),
+            CLASS_RR => {
+                let old_slice = current.time_slice.fetch_sub(1, Ordering::Release);
+                old_slice <= 1 || self.has_higher_than_rt(current.rt_prio())
+            }
+            _ => {
+                let resched = self.cfs_queue.task_tick(current);
+                resched || !self.dl_queue.is_empty() || !self.rt_queues.is_empty()
+            }
+        }
+    }
+
+    fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
+        // The nice value is kept for the normal policy in other classes.
+        self.cfs_queue.set_priority(task, prio)
+    }
+
+    fn set_policy(&mut self, task: &Self::SchedItem, policy: SchedPolicy) -> bool {
+        let valid = match policy {
+            SchedPolicy::Normal => true,
+            SchedPolicy::Fifo(prio) | SchedPolicy::RoundRobin(prio) => {
+                (RT_PRIO_MIN..=RT_PRIO_MAX).contains(&prio)
+            }
+            SchedPolicy::Deadline { budget, period } => budget > 0 && budget <= period,
+        };
+        if !valid {
+            return false;
+        }
+        // Move the task to the queue of its new class.
+        let queued = self.remove_task(task).is_some();
+        task.set_policy(policy);
+        if queued {
+            self.add_task(task.clone());
+        }
+        true
+    }
+
+    fn should_preempt(&self, current: &Self::SchedItem, woken: &Self::SchedItem) -> bool {
+        // Normal tasks share the CPU by ticks as in the CFS.
+        match (current.class(), woken.class()) {
+            (CLASS_DEADLINE, CLASS_DEADLINE) => woken.deadline() < current.deadline(),
+            (CLASS_DEADLINE, _) | (_, CLASS_NORMAL) => false,
+            (_, CLASS_DEADLINE) | (CLASS_NORMAL, _) => true,
+            _ => woken.rt_prio() > current.rt_prio(),
+        }
+    }
+}
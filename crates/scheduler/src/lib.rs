@@ -5,12 +5,16 @@
 //! - [`FifoScheduler`]: FIFO (First-In-First-Out) scheduler (cooperative).
 //! - [`RRScheduler`]: Round-robin scheduler (preemptive).
 //! - [`CFScheduler`]: Completely Fair Scheduler (preemptive).
+//! - [`LayeredScheduler`]: Earliest-deadline-first and real-time (FIFO and
+//!   round-robin) classes above a CFS class, selected per task by
+//!   [`SchedPolicy`] (preemptive).
 
 #![cfg_attr(not(test), no_std)]
 #![feature(const_mut_refs)]
 
 mod cfs;
 mod fifo;
+mod layered;
 mod round_robin;
 
 #[cfg(test)]
@@ -20,6 +24,7 @@ extern crate alloc;
 
 pub use cfs::{CFSTask, CFScheduler};
 pub use fifo::{FifoScheduler, FifoTask};
+pub use layered::{LayeredScheduler, LayeredTask};
 pub use round_robin::{RRScheduler, RRTask};
 
 /// The base scheduler trait that all schedulers should implement.
@@ -45,6 +50,13 @@ pub trait BaseScheduler {
     /// the behavior is undefined.
     fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem>;
 
+    /// Returns a task in the scheduler for which `pred` returns `true`,
+    /// without removing it, e.g. to pick a task to migrate to another CPU.
+    /// Tasks that would run last are preferred.
+    fn find_task<F>(&self, pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool;
+
     /// Picks the next task to run, it will be removed from the scheduler.
     /// Returns [`None`] if there is not runnable task.
     fn pick_next_task(&mut self) -> Option<Self::SchedItem>;
@@ -66,4 +78,52 @@ pub trait BaseScheduler {
 
     /// set priority for a task
     fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool;
+
+    /// Sets the scheduling policy of a task, which may be in the scheduler or
+    /// not (e.g. running or blocked). Returns `false` if the policy is not
+    /// supported or its parameters are invalid.
+    ///
+    /// Only [`SchedPolicy::Normal`] is supported by default.
+    fn set_policy(&mut self, _task: &Self::SchedItem, policy: SchedPolicy) -> bool {
+        policy == SchedPolicy::Normal
+    }
+
+    /// Returns whether the `woken` task, just added to the scheduler, should
+    /// preempt the `current` running task right away, instead of waiting for
+    /// [`task_tick`](Self::task_tick) to request it.
+    ///
+    /// Woken tasks never preempt by default.
+    fn should_preempt(&self, _current: &Self::SchedItem, _woken: &Self::SchedItem) -> bool {
+        false
+    }
+}
+
+/// The highest priority of real-time tasks.
+pub const RT_PRIO_MAX: usize = 99;
+
+/// The lowest priority of real-time tasks.
+pub const RT_PRIO_MIN: usize = 1;
+
+/// Scheduling policies of tasks.
+///
+/// Schedulers other than [`LayeredScheduler`] only support
+/// [`SchedPolicy::Normal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedPolicy {
+    /// Time-sharing tasks, scheduled by their nice values.
+    Normal,
+    /// Real-time tasks with the given priority, which run until they block,
+    /// yield, or are preempted by tasks with higher priorities.
+    Fifo(usize),
+    /// The same as [`SchedPolicy::Fifo`], but tasks with the same priority
+    /// share the CPU in time slices.
+    RoundRobin(usize),
+    /// Tasks that run for at most `budget` ticks in every `period` ticks, and
+    /// are scheduled by their deadlines, which are the ends of their periods.
+    Deadline {
+        /// Run time of the task in each period, in timer ticks.
+        budget: usize,
+        /// Length of each period, in timer ticks.
+        period: usize,
+    },
 }
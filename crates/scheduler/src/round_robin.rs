@@ -89,6 +89,13 @@ impl<T, const S: usize> BaseScheduler for RRScheduler<T, S> {
             .and_then(|idx| self.ready_queue.remove(idx))
     }
 
+    fn find_task<F>(&self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        self.ready_queue.iter().rev().find(|t| pred(t)).cloned()
+    }
+
     fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
         self.ready_queue.pop_front()
     }
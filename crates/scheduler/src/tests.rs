@@ -28,6 +28,25 @@ macro_rules! def_test_sched {
                 assert_eq!(n, NUM_TASKS);
             }
 
+            #[test]
+            fn test_find_task() {
+                let mut scheduler = <$scheduler>::new();
+                for i in 0..10 {
+                    scheduler.add_task(Arc::new(<$task>::new(i)));
+                }
+
+                // The task that would run last is preferred.
+                let t = scheduler.find_task(|t| *t.inner() % 4 == 0).unwrap();
+                assert_eq!(*t.inner(), 8);
+                assert!(scheduler.find_task(|t| *t.inner() >= 10).is_none());
+
+                scheduler.remove_task(&t).unwrap();
+                let order: Vec<_> = core::iter::from_fn(|| scheduler.pick_next_task())
+                    .map(|t| *t.inner())
+                    .collect();
+                assert_eq!(order, [0, 1, 2, 3, 4, 5, 6, 7, 9]);
+            }
+
             #[test]
             fn bench_yield() {
                 const NUM_TASKS: usize = 1_000_000;
@@ -82,3 +101,263 @@ macro_rules! def_test_sched {
 def_test_sched!(fifo, FifoScheduler::<usize>, FifoTask::<usize>);
 def_test_sched!(rr, RRScheduler::<usize, 5>, RRTask::<usize, 5>);
 def_test_sched!(cfs, CFScheduler::<usize>, CFSTask::<usize>);
+def_test_sched!(
+    layered,
+    LayeredScheduler::<usize, 5>,
+    LayeredTask::<usize, 5>
+);
+
+mod layered_classes {
+    use crate::*;
+    use alloc::sync::Arc;
+
+    type Task = Arc<LayeredTask<usize, 5>>;
+    type Scheduler = LayeredScheduler<usize, 5>;
+
+    fn new_tasks(scheduler: &mut Scheduler, policies: &[SchedPolicy]) -> Vec<Task> {
+        let mut tasks = Vec::new();
+        for (i, &policy) in policies.iter().enumerate() {
+            let t = Arc::new(LayeredTask::new(i));
+            assert!(scheduler.set_policy(&t, policy));
+            scheduler.add_task(t.clone());
+            tasks.push(t);
+        }
+        tasks
+    }
+
+    /// Runs the next task for one tick, returns its ID and whether it should
+    /// be rescheduled.
+    fn run_tick(scheduler: &mut Scheduler) -> usize {
+        let next = scheduler.pick_next_task().unwrap();
+        scheduler.task_tick(&next);
+        let id = *next.inner();
+        scheduler.put_prev_task(next, false);
+        id
+    }
+
+    #[test]
+    fn test_class_order() {
+        let mut scheduler = Scheduler::new();
+        new_tasks(
+            &mut scheduler,
+            &[
+                SchedPolicy::Normal,
+                SchedPolicy::RoundRobin(10),
+                SchedPolicy::Fifo(20),
+                SchedPolicy::Deadline {
+                    budget: 2,
+                    period: 10,
+                },
+                SchedPolicy::Fifo(10),
+            ],
+        );
+        let order: Vec<_> = core::iter::from_fn(|| scheduler.pick_next_task())
+            .map(|t| *t.inner())
+            .collect();
+        assert_eq!(order, [3, 2, 1, 4, 0]);
+    }
+
+    #[test]
+    fn test_fifo_and_rr() {
+        let mut scheduler = Scheduler::new();
+        new_tasks(
+            &mut scheduler,
+            &[
+                SchedPolicy::Fifo(10),
+                SchedPolicy::Fifo(10),
+                SchedPolicy::Normal,
+            ],
+        );
+        // FIFO tasks are never switched by ticks.
+        let t = scheduler.pick_next_task().unwrap();
+        assert_eq!(*t.inner(), 0);
+        for _ in 0..100 {
+            assert!(!scheduler.task_tick(&t));
+        }
+        // A preempted FIFO task stays at the head.
+        scheduler.put_prev_task(t, true);
+        assert_eq!(run_tick(&mut scheduler), 0);
+        // A yielded FIFO task goes to the tail.
+        assert_eq!(run_tick(&mut scheduler), 1);
+
+        let mut scheduler = Scheduler::new();
+        new_tasks(
+            &mut scheduler,
+            &[SchedPolicy::RoundRobin(10), SchedPolicy::RoundRobin(10)],
+        );
+        // Round-robin tasks are switched after 5 ticks.
+        for i in 0..20 {
+            let t = scheduler.pick_next_task().unwrap();
+            assert_eq!(*t.inner(), i % 2);
+            for j in 0..5 {
+                assert_eq!(scheduler.task_tick(&t), j == 4);
+            }
+            scheduler.put_prev_task(t, true);
+        }
+    }
+
+    #[test]
+    fn test_preempt_lower_class() {
+        let mut scheduler = Scheduler::new();
+        let tasks = new_tasks(&mut scheduler, &[SchedPolicy::Normal]);
+        let normal = scheduler.pick_next_task().unwrap();
+
+        let fifo = Arc::new(LayeredTask::new(1));
+        assert!(scheduler.set_policy(&fifo, SchedPolicy::Fifo(1)));
+        scheduler.add_task(fifo);
+        assert!(scheduler.task_tick(&normal));
+        scheduler.put_prev_task(normal, true);
+        let fifo = scheduler.pick_next_task().unwrap();
+        assert_eq!(*fifo.inner(), 1);
+
+        // A higher priority preempts the FIFO task.
+        assert!(!scheduler.task_tick(&fifo));
+        assert!(scheduler.set_policy(&tasks[0], SchedPolicy::Fifo(2)));
+        assert!(scheduler.task_tick(&fifo));
+        scheduler.put_prev_task(fifo, true);
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 0);
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 1);
+        assert!(scheduler.pick_next_task().is_none());
+    }
+
+    #[test]
+    fn test_wakeup_preempt() {
+        let mut scheduler = Scheduler::new();
+        let tasks = new_tasks(
+            &mut scheduler,
+            &[
+                SchedPolicy::Normal,
+                SchedPolicy::Normal,
+                SchedPolicy::Fifo(10),
+                SchedPolicy::RoundRobin(10),
+                SchedPolicy::Fifo(20),
+                SchedPolicy::Deadline {
+                    budget: 2,
+                    period: 10,
+                },
+                SchedPolicy::Deadline {
+                    budget: 2,
+                    period: 20,
+                },
+            ],
+        );
+        let preempts =
+            |curr: usize, woken: usize| scheduler.should_preempt(&tasks[curr], &tasks[woken]);
+        assert!(!preempts(0, 1));
+        assert!(preempts(0, 2) && !preempts(2, 0));
+        assert!(!preempts(2, 3) && !preempts(3, 2));
+        assert!(preempts(2, 4) && !preempts(4, 2));
+        assert!(preempts(4, 5) && !preempts(5, 4));
+        assert!(preempts(6, 5) && !preempts(5, 6));
+    }
+
+    #[test]
+    fn test_edf() {
+        let mut scheduler = Scheduler::new();
+        new_tasks(
+            &mut scheduler,
+            &[
+                SchedPolicy::Deadline {
+                    budget: 1,
+                    period: 4,
+                },
+                SchedPolicy::Deadline {
+                    budget: 2,
+                    period: 3,
+                },
+                SchedPolicy::Normal,
+            ],
+        );
+        // Earliest deadline first, and the deadline tasks get their
+        // bandwidths, the normal task runs in the rest time.
+        let order: Vec<_> = (0..4).map(|_| run_tick(&mut scheduler)).collect();
+        assert_eq!(order, [1, 1, 0, 1]);
+        let mut runs = [0; 3];
+        for _ in 0..1200 {
+            runs[run_tick(&mut scheduler)] += 1;
+        }
+        assert_eq!(runs, [300, 800, 100]);
+    }
+
+    #[test]
+    fn test_edf_idle() {
+        let mut scheduler = Scheduler::new();
+        let tasks = new_tasks(
+            &mut scheduler,
+            &[SchedPolicy::Deadline {
+                budget: 1,
+                period: 100,
+            }],
+        );
+        assert_eq!(
+            tasks[0].policy(),
+            SchedPolicy::Deadline {
+                budget: 1,
+                period: 100
+            }
+        );
+        // A throttled task runs if there is nothing else to run.
+        for _ in 0..10 {
+            assert_eq!(run_tick(&mut scheduler), 0);
+        }
+        scheduler.pick_next_task().unwrap();
+        assert!(scheduler.pick_next_task().is_none());
+    }
+
+    #[test]
+    fn test_edf_budget() {
+        let mut scheduler = Scheduler::new();
+        new_tasks(
+            &mut scheduler,
+            &[
+                SchedPolicy::Deadline {
+                    budget: 3,
+                    period: 10,
+                },
+                SchedPolicy::Normal,
+            ],
+        );
+        // The deadline task gets 3 of every 10 ticks.
+        let mut runs = [0; 2];
+        for _ in 0..1000 {
+            runs[run_tick(&mut scheduler)] += 1;
+        }
+        assert_eq!(runs, [300, 700]);
+    }
+
+    #[test]
+    fn test_set_policy() {
+        let mut scheduler = Scheduler::new();
+        let tasks = new_tasks(&mut scheduler, &[SchedPolicy::Normal, SchedPolicy::Normal]);
+        let t = &tasks[1];
+        assert!(!scheduler.set_policy(t, SchedPolicy::Fifo(0)));
+        assert!(!scheduler.set_policy(t, SchedPolicy::RoundRobin(RT_PRIO_MAX + 1)));
+        assert!(!scheduler.set_policy(
+            t,
+            SchedPolicy::Deadline {
+                budget: 0,
+                period: 10
+            }
+        ));
+        assert!(!scheduler.set_policy(
+            t,
+            SchedPolicy::Deadline {
+                budget: 11,
+                period: 10
+            }
+        ));
+        assert_eq!(t.policy(), SchedPolicy::Normal);
+
+        // The queued task is moved to its new class.
+        assert!(scheduler.set_policy(t, SchedPolicy::RoundRobin(RT_PRIO_MAX)));
+        assert_eq!(t.policy(), SchedPolicy::RoundRobin(RT_PRIO_MAX));
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 1);
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 0);
+
+        let other = FifoScheduler::new();
+        let mut other = other;
+        let t = Arc::new(FifoTask::new(0));
+        assert!(other.set_policy(&t, SchedPolicy::Normal));
+        assert!(!other.set_policy(&t, SchedPolicy::Fifo(1)));
+    }
+}
@@ -12,6 +12,7 @@ extern crate log;
 extern crate alloc;
 
 mod page;
+mod quota;
 
 use allocator::{AllocResult, BaseAllocator, BitmapPageAllocator, ByteAllocator, PageAllocator};
 use core::alloc::{GlobalAlloc, Layout};
@@ -22,6 +23,9 @@ const PAGE_SIZE: usize = 0x1000;
 const MIN_HEAP_SIZE: usize = 0x8000; // 32 K
 
 pub use page::GlobalPage;
+pub use quota::{
+    app_charge, app_peak_bytes, app_quota, app_uncharge, app_used_bytes, set_app_quota,
+};
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "slab")] {
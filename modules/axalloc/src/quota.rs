@@ -0,0 +1,56 @@
+//! Memory quota of the application.
+//!
+//! The application shares the heap with the kernel, so only the memory it
+//! asks for explicitly (e.g. by `malloc` or `mmap` of the C library) is
+//! charged, by calling [`app_charge`] before allocating and [`app_uncharge`]
+//! after deallocating.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use allocator::{AllocError, AllocResult};
+
+static QUOTA: AtomicUsize = AtomicUsize::new(usize::MAX);
+static USED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the maximum number of bytes the application can allocate.
+pub fn app_quota() -> usize {
+    QUOTA.load(Ordering::Relaxed)
+}
+
+/// Sets the maximum number of bytes the application can allocate.
+///
+/// Memory already allocated is not affected, even if it exceeds the new
+/// quota, but further allocations will fail until enough of it is freed.
+pub fn set_app_quota(bytes: usize) {
+    QUOTA.store(bytes, Ordering::Relaxed);
+}
+
+/// Returns the number of bytes currently allocated by the application.
+pub fn app_used_bytes() -> usize {
+    USED.load(Ordering::Relaxed)
+}
+
+/// Returns the maximum of [`app_used_bytes`] so far.
+pub fn app_peak_bytes() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
+
+/// Charges `size` bytes to the application.
+///
+/// Returns [`AllocError::NoMemory`] if that exceeds the quota.
+pub fn app_charge(size: usize) -> AllocResult {
+    let quota = app_quota();
+    let old = USED
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+            used.checked_add(size).filter(|&new| new <= quota)
+        })
+        .map_err(|_| AllocError::NoMemory)?;
+    PEAK.fetch_max(old + size, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Gives back `size` bytes charged by [`app_charge`].
+pub fn app_uncharge(size: usize) {
+    USED.fetch_sub(size, Ordering::Relaxed);
+}
@@ -1,6 +1,7 @@
 const NET_DEV_FEATURES: &[&str] = &["ixgbe", "virtio-net"];
 const BLOCK_DEV_FEATURES: &[&str] = &["ramdisk", "bcm2835-sdhci", "virtio-blk"];
 const DISPLAY_DEV_FEATURES: &[&str] = &["virtio-gpu"];
+const RNG_DEV_FEATURES: &[&str] = &["virtio-rng"];
 
 fn has_feature(feature: &str) -> bool {
     std::env::var(format!(
@@ -28,6 +29,7 @@ fn main() {
         ("net", NET_DEV_FEATURES),
         ("block", BLOCK_DEV_FEATURES),
         ("display", DISPLAY_DEV_FEATURES),
+        ("rng", RNG_DEV_FEATURES),
     ] {
         if !has_feature(dev_kind) {
             continue;
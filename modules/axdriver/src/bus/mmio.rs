@@ -1,13 +1,19 @@
-#[allow(unused_imports)]
-use crate::{prelude::*, AllDevices};
+#[cfg(feature = "virtio")]
+use crate::prelude::*;
+use crate::AllDevices;
 
 impl AllDevices {
     pub(crate) fn probe_bus_devices(&mut self) {
         // TODO: parse device tree
         #[cfg(feature = "virtio")]
-        for reg in axconfig::VIRTIO_MMIO_REGIONS {
+        for (i, reg) in axconfig::VIRTIO_MMIO_REGIONS.iter().enumerate() {
+            // VirtIO MMIO devices use consecutive IRQ numbers, if known.
+            let irq = match axconfig::VIRTIO_MMIO_IRQ_BASE {
+                0 => None,
+                base => Some(base + i),
+            };
             for_each_drivers!(type Driver, {
-                if let Some(dev) = Driver::probe_mmio(reg.0, reg.1) {
+                if let Some(dev) = Driver::probe_mmio(reg.0, reg.1, irq) {
                     info!(
                         "registered a new {:?} device at [PA:{:#x}, PA:{:#x}): {:?}",
                         dev.device_type(),
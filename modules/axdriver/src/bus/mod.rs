@@ -2,3 +2,6 @@
 mod mmio;
 #[cfg(bus = "pci")]
 mod pci;
+
+#[cfg(all(bus = "pci", any(feature = "virtio", net_dev = "ixgbe")))]
+pub(crate) use self::pci::pci_irq_num;
@@ -5,6 +5,45 @@ use driver_pci::{
 };
 
 const PCI_BAR_NUM: u8 = 6;
+#[cfg(any(feature = "virtio", net_dev = "ixgbe"))]
+const PCI_INTERRUPT_LINE_OFFSET: usize = 0x3c; // followed by the interrupt pin
+
+/// Returns the IRQ number of the legacy interrupt (INTx) of the PCI device,
+/// or `None` if it is not connected or cannot be routed on this platform.
+///
+/// If the platform sets `pci-intx-irq-base`, the pins are swizzled by the
+/// device number as the QEMU `virt` machines do. Devices behind PCI bridges
+/// are not supported then. Otherwise the interrupt line register is used,
+/// which is only filled by the firmware on x86.
+#[cfg(any(feature = "virtio", net_dev = "ixgbe"))]
+pub(crate) fn pci_irq_num(bdf: DeviceFunction) -> Option<usize> {
+    let offset = ((bdf.bus as usize) << 20)
+        | ((bdf.device as usize) << 15)
+        | ((bdf.function as usize) << 12)
+        | PCI_INTERRUPT_LINE_OFFSET;
+    let vaddr = phys_to_virt((axconfig::PCI_ECAM_BASE + offset).into());
+    let line = unsafe { core::ptr::read_volatile(vaddr.as_ptr()) };
+    let pin = unsafe { core::ptr::read_volatile(vaddr.as_ptr().add(1)) };
+    if pin == 0 {
+        return None;
+    }
+    if axconfig::PCI_INTX_IRQ_BASE != 0 {
+        let pin = (pin as usize - 1 + bdf.device as usize) % 4;
+        return Some(axconfig::PCI_INTX_IRQ_BASE + pin);
+    }
+    if line == 0 || line == 0xff {
+        return None;
+    }
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "x86_64")] {
+            // Legacy interrupt lines are routed to the IO APIC pins of the
+            // same number, whose vectors start from 0x20.
+            Some(0x20 + line as usize)
+        } else {
+            None
+        }
+    }
+}
 
 fn config_pci_device(
     root: &mut PciRoot,
@@ -19,7 +19,11 @@ pub trait DriverProbe {
     }
 
     #[cfg(bus = "mmio")]
-    fn probe_mmio(_mmio_base: usize, _mmio_size: usize) -> Option<AxDeviceEnum> {
+    fn probe_mmio(
+        _mmio_base: usize,
+        _mmio_size: usize,
+        _irq: Option<usize>,
+    ) -> Option<AxDeviceEnum> {
         None
     }
 
@@ -51,6 +55,12 @@ register_display_driver!(
     <virtio::VirtIoGpu as VirtIoDevMeta>::Device
 );
 
+#[cfg(rng_dev = "virtio-rng")]
+register_rng_driver!(
+    <virtio::VirtIoRng as VirtIoDevMeta>::Driver,
+    <virtio::VirtIoRng as VirtIoDevMeta>::Device
+);
+
 cfg_if::cfg_if! {
     if #[cfg(block_dev = "ramdisk")] {
         pub struct RamDiskDriver;
@@ -112,7 +122,8 @@ cfg_if::cfg_if! {
                             } => {
                                 let ixgbe_nic = IxgbeNic::<IxgbeHalImpl, QS, QN>::init(
                                     phys_to_virt((address as usize).into()).into(),
-                                    size as usize
+                                    size as usize,
+                                    crate::bus::pci_irq_num(bdf),
                                 )
                                 .expect("failed to initialize ixgbe device");
                                 return Some(AxDeviceEnum::from_net(ixgbe_nic));
@@ -69,6 +69,29 @@ cfg_if! {
     }
 }
 
+cfg_if! {
+    if #[cfg(rng_dev = "dummy")] {
+        pub struct DummyRngDev;
+        pub struct DummyRngDriver;
+        register_rng_driver!(DummyRngDriver, DummyRngDev);
+
+        impl BaseDriverOps for DummyRngDev {
+            fn device_type(&self) -> DeviceType {
+                DeviceType::Rng
+            }
+            fn device_name(&self) -> &str {
+                "dummy-rng"
+            }
+        }
+
+        impl RngDriverOps for DummyRngDev {
+            fn read_random(&mut self, _: &mut [u8]) -> DevResult<usize> {
+                Err(DevError::Unsupported)
+            }
+        }
+    }
+}
+
 cfg_if! {
     if #[cfg(display_dev = "dummy")] {
         pub struct DummyDisplayDev;
@@ -8,8 +8,9 @@
 //! driver they want.
 //!
 //! For each device category (i.e., net, block, display, etc.), an unified type
-//! is used to represent all devices in that category. Currently, there are 3
-//! categories: [`AxNetDevice`], [`AxBlockDevice`], and [`AxDisplayDevice`].
+//! is used to represent all devices in that category. Currently, there are 4
+//! categories: [`AxNetDevice`], [`AxBlockDevice`], [`AxDisplayDevice`], and
+//! [`AxRngDevice`].
 //!
 //! # Concepts
 //!
@@ -34,6 +35,7 @@
 //! | Block | `virtio-blk` | VirtIO block device |
 //! | Network | `virtio-net` | VirtIO network device |
 //! | Display | `virtio-gpu` | VirtIO graphics device |
+//! | Random number generator | `virtio-rng` | VirtIO entropy device |
 //!
 //! # Other Cargo Features
 //!
@@ -42,12 +44,13 @@
 //!    enabeld by default.
 //! - `bus-pci`: use PCI bus to probe all PCI devices.
 //! - `virtio`: use VirtIO devices. This is enabled if any of `virtio-blk`,
-//!   `virtio-net` or `virtio-gpu` is enabled.
+//!   `virtio-net`, `virtio-gpu` or `virtio-rng` is enabled.
 //! - `net`: use network devices. This is enabled if any feature of network
 //!    devices is selected. If this feature is enabled without any network device
 //!    features, a dummy struct is used for [`AxNetDevice`].
 //! - `block`: use block storage devices. Similar to the `net` feature.
 //! - `display`: use graphics display devices. Similar to the `net` feature.
+//! - `rng`: use random number generator devices. Similar to the `net` feature.
 //!
 //! [`VirtioNetDev`]: driver_virtio::VirtIoNetDev
 //! [`Box<dyn NetDriverOps>`]: driver_net::NetDriverOps
@@ -90,6 +93,8 @@ pub use self::structs::AxBlockDevice;
 pub use self::structs::AxDisplayDevice;
 #[cfg(feature = "net")]
 pub use self::structs::AxNetDevice;
+#[cfg(feature = "rng")]
+pub use self::structs::AxRngDevice;
 
 /// A structure that contains all device drivers, organized by their category.
 #[derive(Default)]
@@ -103,6 +108,9 @@ pub struct AllDevices {
     /// All graphics device drivers.
     #[cfg(feature = "display")]
     pub display: AxDeviceContainer<AxDisplayDevice>,
+    /// All random number generator device drivers.
+    #[cfg(feature = "rng")]
+    pub rng: AxDeviceContainer<AxRngDevice>,
 }
 
 impl AllDevices {
@@ -143,6 +151,8 @@ impl AllDevices {
             AxDeviceEnum::Block(dev) => self.block.push(dev),
             #[cfg(feature = "display")]
             AxDeviceEnum::Display(dev) => self.display.push(dev),
+            #[cfg(feature = "rng")]
+            AxDeviceEnum::Rng(dev) => self.rng.push(dev),
         }
     }
 }
@@ -179,6 +189,14 @@ pub fn init_drivers() -> AllDevices {
             debug!("  graphics device {}: {:?}", i, dev.device_name());
         }
     }
+    #[cfg(feature = "rng")]
+    {
+        debug!("number of random number generators: {}", all_devs.rng.len());
+        for (i, dev) in all_devs.rng.iter().enumerate() {
+            assert_eq!(dev.device_type(), DeviceType::Rng);
+            debug!("  random number generator {}: {:?}", i, dev.device_name());
+        }
+    }
 
     all_devs
 }
@@ -26,6 +26,14 @@ macro_rules! register_display_driver {
     };
 }
 
+macro_rules! register_rng_driver {
+    ($driver_type:ty, $device_type:ty) => {
+        /// The unified type of the random number generator devices.
+        #[cfg(not(feature = "dyn"))]
+        pub type AxRngDevice = $device_type;
+    };
+}
+
 macro_rules! for_each_drivers {
     (type $drv_type:ident, $code:block) => {{
         #[allow(unused_imports)]
@@ -49,6 +57,11 @@ macro_rules! for_each_drivers {
             type $drv_type = <virtio::VirtIoGpu as VirtIoDevMeta>::Driver;
             $code
         }
+        #[cfg(rng_dev = "virtio-rng")]
+        {
+            type $drv_type = <virtio::VirtIoRng as VirtIoDevMeta>::Driver;
+            $code
+        }
         #[cfg(block_dev = "ramdisk")]
         {
             type $drv_type = crate::drivers::RamDiskDriver;
@@ -12,6 +12,9 @@ pub type AxBlockDevice = Box<dyn BlockDriverOps>;
 /// The unified type of the graphics display devices.
 #[cfg(feature = "display")]
 pub type AxDisplayDevice = Box<dyn DisplayDriverOps>;
+/// The unified type of the random number generator devices.
+#[cfg(feature = "rng")]
+pub type AxRngDevice = Box<dyn RngDriverOps>;
 
 impl super::AxDeviceEnum {
     /// Constructs a network device.
@@ -31,6 +34,12 @@ impl super::AxDeviceEnum {
     pub fn from_display(dev: impl DisplayDriverOps + 'static) -> Self {
         Self::Display(Box::new(dev))
     }
+
+    /// Constructs a random number generator device.
+    #[cfg(feature = "rng")]
+    pub fn from_rng(dev: impl RngDriverOps + 'static) -> Self {
+        Self::Rng(Box::new(dev))
+    }
 }
 
 /// A structure that contains all device drivers of a certain category.
@@ -18,6 +18,9 @@ pub enum AxDeviceEnum {
     /// Graphic display device.
     #[cfg(feature = "display")]
     Display(AxDisplayDevice),
+    /// Random number generator device.
+    #[cfg(feature = "rng")]
+    Rng(AxRngDevice),
 }
 
 impl BaseDriverOps for AxDeviceEnum {
@@ -31,6 +34,8 @@ impl BaseDriverOps for AxDeviceEnum {
             Self::Block(_) => DeviceType::Block,
             #[cfg(feature = "display")]
             Self::Display(_) => DeviceType::Display,
+            #[cfg(feature = "rng")]
+            Self::Rng(_) => DeviceType::Rng,
             _ => unreachable!(),
         }
     }
@@ -45,6 +50,8 @@ impl BaseDriverOps for AxDeviceEnum {
             Self::Block(dev) => dev.device_name(),
             #[cfg(feature = "display")]
             Self::Display(dev) => dev.device_name(),
+            #[cfg(feature = "rng")]
+            Self::Rng(dev) => dev.device_name(),
             _ => unreachable!(),
         }
     }
@@ -4,6 +4,8 @@ pub use crate::drivers::AxBlockDevice;
 pub use crate::drivers::AxDisplayDevice;
 #[cfg(feature = "net")]
 pub use crate::drivers::AxNetDevice;
+#[cfg(feature = "rng")]
+pub use crate::drivers::AxRngDevice;
 
 impl super::AxDeviceEnum {
     /// Constructs a network device.
@@ -23,6 +25,12 @@ impl super::AxDeviceEnum {
     pub const fn from_display(dev: AxDisplayDevice) -> Self {
         Self::Display(dev)
     }
+
+    /// Constructs a random number generator device.
+    #[cfg(feature = "rng")]
+    pub const fn from_rng(dev: AxRngDevice) -> Self {
+        Self::Rng(dev)
+    }
 }
 
 /// A structure that contains all device drivers of a certain category.
@@ -4,7 +4,7 @@ use axerrno::{ax_err, ax_err_type, AxResult};
 use axfs_vfs::{VfsError, VfsNodeRef};
 use axio::SeekFrom;
 use capability::{Cap, WithCap};
-use core::fmt;
+use core::{fmt, ops::Range};
 
 #[cfg(feature = "myfs")]
 pub use crate::dev::Disk;
@@ -20,11 +20,14 @@ pub type FileAttr = axfs_vfs::VfsNodeAttr;
 /// Alias of [`axfs_vfs::VfsNodePerm`].
 pub type FilePerm = axfs_vfs::VfsNodePerm;
 
+pub use crate::lock::{FileLock, FileLocker, LockKind};
+
 /// An opened file object, with open permissions and a cursor.
 pub struct File {
     node: WithCap<VfsNodeRef>,
     is_append: bool,
     offset: u64,
+    locker: FileLocker,
 }
 
 /// An opened directory object, with open permissions and a cursor for
@@ -153,6 +156,7 @@ impl File {
             node.truncate(0)?;
         }
         Ok(Self {
+            locker: FileLocker::new(node.clone()),
             node: WithCap::new(node, access_cap),
             is_append: opts.append,
             offset: 0,
@@ -240,6 +244,37 @@ impl File {
     pub fn get_attr(&self) -> AxResult<FileAttr> {
         self.node.access(Cap::empty())?.get_attr()
     }
+
+    /// Places an advisory lock on the byte `range` of the file, replacing the
+    /// locks of this file object in that range.
+    ///
+    /// Locks of other file objects on the same file conflict with it if they
+    /// overlap and either is exclusive. If `wait` is true, blocks until they
+    /// are released, otherwise returns [`WouldBlock`](axerrno::AxError::WouldBlock).
+    ///
+    /// All locks are released when the file object is dropped.
+    pub fn lock(&self, kind: LockKind, range: Range<u64>, wait: bool) -> AxResult {
+        self.locker()?.lock(kind, range, wait)
+    }
+
+    /// Releases the advisory locks of this file object on the byte `range`.
+    pub fn unlock(&self, range: Range<u64>) -> AxResult {
+        self.locker()?.unlock(range);
+        Ok(())
+    }
+
+    /// Returns the first lock of other file objects that would prevent this
+    /// file object from placing the given lock, if any.
+    pub fn test_lock(&self, kind: LockKind, range: Range<u64>) -> AxResult<Option<FileLock>> {
+        Ok(self.locker()?.test_lock(kind, range))
+    }
+
+    /// Returns the owner of the advisory locks of this file object, which can
+    /// wait for locks without borrowing the file object.
+    pub fn locker(&self) -> AxResult<FileLocker> {
+        self.node.access(Cap::empty())?;
+        Ok(self.locker.clone())
+    }
 }
 
 impl Directory {
@@ -329,6 +364,23 @@ impl Directory {
         Ok(n)
     }
 
+    /// Returns the index of the next entry that
+    /// [`read_dir`](Directory::read_dir) will read.
+    pub fn tell_dir(&self) -> usize {
+        self.entry_idx
+    }
+
+    /// Moves the cursor of [`read_dir`](Directory::read_dir) to the entry at
+    /// `idx`. Seeking to 0 restarts the iteration.
+    pub fn seek_dir(&mut self, idx: usize) {
+        self.entry_idx = idx;
+    }
+
+    /// Gets the directory attributes.
+    pub fn get_attr(&self) -> AxResult<FileAttr> {
+        self.node.access(Cap::empty())?.get_attr()
+    }
+
     /// Rename a file or directory to a new name.
     /// Delete the original file if `old` already exists.
     ///
@@ -340,7 +392,9 @@ impl Directory {
 
 impl Drop for File {
     fn drop(&mut self) {
-        unsafe { self.node.access_unchecked().release().ok() };
+        let node = unsafe { self.node.access_unchecked() };
+        self.locker.unlock(0..u64::MAX);
+        node.release().ok();
     }
 }
 
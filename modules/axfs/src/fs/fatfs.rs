@@ -1,4 +1,6 @@
-use alloc::sync::Arc;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
 use core::cell::UnsafeCell;
 
 use axfs_vfs::{VfsDirEntry, VfsError, VfsNodePerm, VfsResult};
@@ -10,13 +12,27 @@ use crate::dev::Disk;
 
 const BLOCK_SIZE: usize = 512;
 
+type FatFile<'a> = File<'a, Disk, NullTimeProvider, LossyOemCpConverter>;
+type FatDir<'a> = Dir<'a, Disk, NullTimeProvider, LossyOemCpConverter>;
+
 pub struct FatFileSystem {
     inner: fatfs::FileSystem<Disk, NullTimeProvider, LossyOemCpConverter>,
     root_dir: UnsafeCell<Option<VfsNodeRef>>,
+    /// Nodes of the files in use, keyed by their paths from the root.
+    ///
+    /// FAT has no inodes, so a file looked up again gets the same node as
+    /// long as it is referenced, which then identifies the file like an inode
+    /// (e.g. for file locks).
+    files: Mutex<BTreeMap<String, Weak<FileWrapper<'static>>>>,
 }
 
-pub struct FileWrapper<'a>(Mutex<File<'a, Disk, NullTimeProvider, LossyOemCpConverter>>);
-pub struct DirWrapper<'a>(Dir<'a, Disk, NullTimeProvider, LossyOemCpConverter>);
+pub struct FileWrapper<'a>(Mutex<FatFile<'a>>);
+pub struct DirWrapper<'a> {
+    dir: FatDir<'a>,
+    /// The path from the root, empty for the root directory.
+    path: String,
+    fs: &'a FatFileSystem,
+}
 
 unsafe impl Sync for FatFileSystem {}
 unsafe impl Send for FatFileSystem {}
@@ -35,6 +51,7 @@ impl FatFileSystem {
         Self {
             inner,
             root_dir: UnsafeCell::new(None),
+            files: Mutex::new(BTreeMap::new()),
         }
     }
 
@@ -45,20 +62,63 @@ impl FatFileSystem {
         Self {
             inner,
             root_dir: UnsafeCell::new(None),
+            files: Mutex::new(BTreeMap::new()),
         }
     }
 
     pub fn init(&'static self) {
         // must be called before later operations
-        unsafe { *self.root_dir.get() = Some(Self::new_dir(self.inner.root_dir())) }
+        unsafe { *self.root_dir.get() = Some(self.new_dir(self.inner.root_dir(), String::new())) }
+    }
+
+    /// Returns the node of the file at `path`, the one in use if any, in which
+    /// case the newly opened `file` is dropped.
+    fn new_file(&'static self, file: FatFile<'static>, path: String) -> Arc<FileWrapper<'static>> {
+        let mut files = self.files.lock();
+        if let Some(node) = files.get(&path).and_then(Weak::upgrade) {
+            return node;
+        }
+        files.retain(|_, node| node.strong_count() > 0);
+        let node = Arc::new(FileWrapper(Mutex::new(file)));
+        files.insert(path, Arc::downgrade(&node));
+        node
+    }
+
+    fn new_dir(&'static self, dir: FatDir<'static>, path: String) -> Arc<DirWrapper<'static>> {
+        Arc::new(DirWrapper {
+            dir,
+            path,
+            fs: self,
+        })
     }
 
-    fn new_file(file: File<'_, Disk, NullTimeProvider, LossyOemCpConverter>) -> Arc<FileWrapper> {
-        Arc::new(FileWrapper(Mutex::new(file)))
+    /// Moves the nodes in use at `path` and under it to `new_path` after a
+    /// rename, or forgets them after a removal if `new_path` is `None`.
+    fn move_files(&self, path: &str, new_path: Option<&str>) {
+        let mut files = self.files.lock();
+        let moved: alloc::vec::Vec<String> = files
+            .keys()
+            .filter(|p| {
+                p.as_str() == path || p.strip_prefix(path).is_some_and(|r| r.starts_with('/'))
+            })
+            .cloned()
+            .collect();
+        for old in moved {
+            let node = files.remove(&old).unwrap();
+            if let Some(new_path) = new_path {
+                files.insert(alloc::format!("{}{}", new_path, &old[path.len()..]), node);
+            }
+        }
     }
+}
 
-    fn new_dir(dir: Dir<'_, Disk, NullTimeProvider, LossyOemCpConverter>) -> Arc<DirWrapper> {
-        Arc::new(DirWrapper(dir))
+impl DirWrapper<'_> {
+    /// Returns the canonical path from the root of `path` relative to this
+    /// directory.
+    fn child_path(&self, path: &str) -> String {
+        axfs_vfs::path::canonicalize(&alloc::format!("{}/{}", self.path, path))
+            .trim_start_matches('/')
+            .into()
     }
 }
 
@@ -90,6 +150,10 @@ impl VfsNodeOps for FileWrapper<'static> {
         file.seek(SeekFrom::Start(size)).map_err(as_vfs_err)?; // TODO: more efficient
         file.truncate().map_err(as_vfs_err)
     }
+
+    fn fsync(&self) -> VfsResult {
+        self.0.lock().flush().map_err(as_vfs_err)
+    }
 }
 
 impl VfsNodeOps for DirWrapper<'static> {
@@ -106,9 +170,10 @@ impl VfsNodeOps for DirWrapper<'static> {
     }
 
     fn parent(&self) -> Option<VfsNodeRef> {
-        self.0
+        let path = self.path.rsplit_once('/').map_or("", |(parent, _)| parent);
+        self.dir
             .open_dir("..")
-            .map_or(None, |dir| Some(FatFileSystem::new_dir(dir)))
+            .map_or(None, |dir| Some(self.fs.new_dir(dir, path.into())))
     }
 
     fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
@@ -122,10 +187,10 @@ impl VfsNodeOps for DirWrapper<'static> {
         }
 
         // TODO: use `fatfs::Dir::find_entry`, but it's not public.
-        if let Ok(file) = self.0.open_file(path) {
-            Ok(FatFileSystem::new_file(file))
-        } else if let Ok(dir) = self.0.open_dir(path) {
-            Ok(FatFileSystem::new_dir(dir))
+        if let Ok(file) = self.dir.open_file(path) {
+            Ok(self.fs.new_file(file, self.child_path(path)))
+        } else if let Ok(dir) = self.dir.open_dir(path) {
+            Ok(self.fs.new_dir(dir, self.child_path(path)))
         } else {
             Err(VfsError::NotFound)
         }
@@ -143,11 +208,11 @@ impl VfsNodeOps for DirWrapper<'static> {
 
         match ty {
             VfsNodeType::File => {
-                self.0.create_file(path).map_err(as_vfs_err)?;
+                self.dir.create_file(path).map_err(as_vfs_err)?;
                 Ok(())
             }
             VfsNodeType::Dir => {
-                self.0.create_dir(path).map_err(as_vfs_err)?;
+                self.dir.create_dir(path).map_err(as_vfs_err)?;
                 Ok(())
             }
             _ => Err(VfsError::Unsupported),
@@ -161,11 +226,13 @@ impl VfsNodeOps for DirWrapper<'static> {
         if let Some(rest) = path.strip_prefix("./") {
             return self.remove(rest);
         }
-        self.0.remove(path).map_err(as_vfs_err)
+        self.dir.remove(path).map_err(as_vfs_err)?;
+        self.fs.move_files(&self.child_path(path), None);
+        Ok(())
     }
 
     fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
-        let mut iter = self.0.iter().skip(start_idx);
+        let mut iter = self.dir.iter().skip(start_idx);
         for (i, out_entry) in dirents.iter_mut().enumerate() {
             let x = iter.next();
             match x {
@@ -192,9 +259,13 @@ impl VfsNodeOps for DirWrapper<'static> {
             src_path, dst_path
         );
 
-        self.0
-            .rename(src_path, &self.0, dst_path)
-            .map_err(as_vfs_err)
+        self.dir
+            .rename(src_path, &self.dir, dst_path)
+            .map_err(as_vfs_err)?;
+        let dst_path = self.child_path(dst_path);
+        self.fs
+            .move_files(&self.child_path(src_path), Some(&dst_path));
+        Ok(())
     }
 }
 
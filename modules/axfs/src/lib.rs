@@ -15,6 +15,8 @@
 //!    to create and initialize other filesystems. This feature is **disabled** by
 //!    by default, but it will override other filesystem selection features if
 //!    both are enabled.
+//! - `multitask`: Allow tasks to block until conflicting advisory file locks
+//!    are released. This feature is **disabled** by default.
 //!
 //! [FAT]: https://en.wikipedia.org/wiki/File_Allocation_Table
 //! [`MyFileSystemIf`]: fops::MyFileSystemIf
@@ -28,6 +30,7 @@ extern crate alloc;
 
 mod dev;
 mod fs;
+mod lock;
 mod mounts;
 mod root;
 
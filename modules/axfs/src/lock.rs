@@ -0,0 +1,234 @@
+//! Advisory file locks, used by both `flock` and `fcntl` byte-range locks.
+//!
+//! Locks are owned by opened [`File`](crate::fops::File) objects, and are
+//! released when the owner is dropped.
+//!
+//! Files are identified by their nodes, which file systems keep the same for
+//! a file as long as it is opened. So a file renamed while locked is still the
+//! same file for locks.
+
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::ops::Range;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use axerrno::{ax_err, AxResult};
+use axfs_vfs::VfsNodeRef;
+use axsync::spin::SpinNoIrq;
+
+/// The kind of an advisory file lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockKind {
+    /// A shared (read) lock, can be held by multiple owners.
+    Shared,
+    /// An exclusive (write) lock, can be held by only one owner.
+    Exclusive,
+}
+
+/// An advisory lock held on a byte range of a file.
+#[derive(Debug, Clone)]
+pub struct FileLock {
+    /// The kind of the lock.
+    pub kind: LockKind,
+    /// The locked bytes. An end of `u64::MAX` means the lock extends to the
+    /// end of the file, however large it grows.
+    pub range: Range<u64>,
+    owner: usize,
+}
+
+impl FileLock {
+    fn overlaps(&self, range: &Range<u64>) -> bool {
+        self.range.start < range.end && range.start < self.range.end
+    }
+
+    fn conflicts(&self, owner: usize, kind: LockKind, range: &Range<u64>) -> bool {
+        self.owner != owner
+            && self.overlaps(range)
+            && (self.kind == LockKind::Exclusive || kind == LockKind::Exclusive)
+    }
+}
+
+/// The locks on a file, and the tasks waiting for them.
+struct NodeLocks {
+    locks: Vec<FileLock>,
+    /// Tasks waiting for conflicting locks to be released, each holds a
+    /// reference while waiting.
+    #[cfg(feature = "multitask")]
+    queue: Arc<axtask::WaitQueue>,
+}
+
+impl NodeLocks {
+    fn new() -> Self {
+        Self {
+            locks: Vec::new(),
+            #[cfg(feature = "multitask")]
+            queue: Arc::new(axtask::WaitQueue::new()),
+        }
+    }
+
+    /// Whether nobody holds or waits for locks on the file.
+    fn is_unused(&self) -> bool {
+        #[cfg(feature = "multitask")]
+        if Arc::strong_count(&self.queue) > 1 {
+            return false;
+        }
+        self.locks.is_empty()
+    }
+}
+
+/// Locks of all files, keyed by the addresses of their nodes.
+static LOCKS: SpinNoIrq<BTreeMap<usize, NodeLocks>> = SpinNoIrq::new(BTreeMap::new());
+static NEXT_OWNER: AtomicUsize = AtomicUsize::new(1);
+
+/// Removes the parts of the locks of `owner` that fall in `range`.
+fn remove_range(locks: &mut Vec<FileLock>, owner: usize, range: &Range<u64>) {
+    let mut remained = Vec::new();
+    locks.retain(|l| {
+        if l.owner != owner || !l.overlaps(range) {
+            return true;
+        }
+        if l.range.start < range.start {
+            remained.push(FileLock {
+                range: l.range.start..range.start,
+                ..l.clone()
+            });
+        }
+        if range.end < l.range.end {
+            remained.push(FileLock {
+                range: range.end..l.range.end,
+                ..l.clone()
+            });
+        }
+        false
+    });
+    locks.append(&mut remained);
+}
+
+fn try_lock(node: usize, owner: usize, kind: LockKind, range: &Range<u64>) -> bool {
+    let mut table = LOCKS.lock();
+    let locks = &mut table.entry(node).or_insert_with(NodeLocks::new).locks;
+    if locks.iter().any(|l| l.conflicts(owner, kind, range)) {
+        return false;
+    }
+    remove_range(locks, owner, range);
+    locks.push(FileLock {
+        kind,
+        range: range.clone(),
+        owner,
+    });
+    true
+}
+
+/// Removes the entry of the file if it is no longer used.
+#[cfg(feature = "multitask")]
+fn remove_if_unused(node: usize) {
+    let mut table = LOCKS.lock();
+    if table.get(&node).is_some_and(|n| n.is_unused()) {
+        table.remove(&node);
+    }
+}
+
+/// The owner of the advisory locks that an opened file places, obtained by
+/// [`File::locker`](crate::fops::File::locker).
+///
+/// It places locks without borrowing the file object, so that other
+/// operations on the file are not blocked while it waits for a lock.
+#[derive(Clone)]
+pub struct FileLocker {
+    /// Keeps the address of the node, which identifies the file, valid.
+    node: VfsNodeRef,
+    owner: usize,
+}
+
+impl FileLocker {
+    /// Creates a new owner of locks on the file of `node`.
+    pub(crate) fn new(node: VfsNodeRef) -> Self {
+        Self {
+            node,
+            owner: NEXT_OWNER.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    fn node_id(&self) -> usize {
+        Arc::as_ptr(&self.node) as *const () as usize
+    }
+
+    /// Places a lock on `range` of the file, replacing the locks of the same
+    /// owner in that range.
+    ///
+    /// If it conflicts with locks of other owners, blocks until they are
+    /// released when `wait` is true, or returns
+    /// [`WouldBlock`](axerrno::AxError::WouldBlock) otherwise. Without the
+    /// `multitask` feature, nobody can release them, so it never blocks. With
+    /// the `signal` feature, the wait is interrupted by signals, which returns
+    /// [`Interrupted`](axerrno::AxError::Interrupted).
+    pub fn lock(&self, kind: LockKind, range: Range<u64>, wait: bool) -> AxResult {
+        let node = self.node_id();
+        if try_lock(node, self.owner, kind, &range) {
+            return Ok(());
+        }
+        #[cfg(feature = "multitask")]
+        if wait {
+            let queue = LOCKS
+                .lock()
+                .entry(node)
+                .or_insert_with(NodeLocks::new)
+                .queue
+                .clone();
+            let locked = || try_lock(node, self.owner, kind, &range);
+            #[cfg(feature = "signal")]
+            let res = queue
+                .wait_until_interruptible(locked)
+                .map_err(|_| axerrno::AxError::Interrupted);
+            #[cfg(not(feature = "signal"))]
+            let res = {
+                queue.wait_until(locked);
+                Ok(())
+            };
+            drop(queue);
+            remove_if_unused(node);
+            return res;
+        }
+        #[cfg(not(feature = "multitask"))]
+        let _ = wait;
+        ax_err!(WouldBlock)
+    }
+
+    /// Releases the locks of this owner on `range` of the file.
+    pub fn unlock(&self, range: Range<u64>) {
+        let node = self.node_id();
+        let mut table = LOCKS.lock();
+        if let Some(entry) = table.get_mut(&node) {
+            remove_range(&mut entry.locks, self.owner, &range);
+            let unused = entry.is_unused();
+            #[cfg(feature = "multitask")]
+            let queue = entry.queue.clone();
+            if unused {
+                table.remove(&node);
+            }
+            drop(table);
+            #[cfg(feature = "multitask")]
+            queue.notify_all(true);
+        }
+    }
+
+    /// Returns the first lock of other owners on the file that conflicts with
+    /// the given one.
+    pub fn test_lock(&self, kind: LockKind, range: Range<u64>) -> Option<FileLock> {
+        LOCKS
+            .lock()
+            .get(&self.node_id())?
+            .locks
+            .iter()
+            .find(|l| l.conflicts(self.owner, kind, &range))
+            .cloned()
+    }
+}
+
+impl core::fmt::Debug for FileLocker {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FileLocker")
+            .field("node", &self.node_id())
+            .field("owner", &self.owner)
+            .finish()
+    }
+}
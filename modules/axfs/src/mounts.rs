@@ -8,10 +8,15 @@ pub(crate) fn devfs() -> Arc<fs::devfs::DeviceFileSystem> {
     let null = fs::devfs::NullDev;
     let zero = fs::devfs::ZeroDev;
     let bar = fs::devfs::ZeroDev;
+    let random = fs::devfs::RandomDev::new(axrand::fill_bytes, axrand::add_entropy);
+    let urandom = fs::devfs::RandomDev::new(axrand::fill_bytes, axrand::add_entropy);
     let devfs = fs::devfs::DeviceFileSystem::new();
     let foo_dir = devfs.mkdir("foo");
     devfs.add("null", Arc::new(null));
     devfs.add("zero", Arc::new(zero));
+    // diff: `/dev/random` never blocks, the same as `/dev/urandom`.
+    devfs.add("random", Arc::new(random));
+    devfs.add("urandom", Arc::new(urandom));
     foo_dir.add("bar", Arc::new(bar));
     Arc::new(devfs)
 }
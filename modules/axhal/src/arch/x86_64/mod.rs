@@ -108,3 +108,32 @@ pub fn read_thread_pointer() -> usize {
 pub unsafe fn write_thread_pointer(fs_base: usize) {
     unsafe { msr::wrmsr(msr::IA32_FS_BASE, fs_base as u64) }
 }
+
+/// Reads a random number from the hardware random number generator of the
+/// CPU, i.e. `rdseed`, or `rdrand` if the former is not supported.
+///
+/// Returns [`None`] if neither is supported, or the generator keeps failing.
+pub fn hw_random() -> Option<u64> {
+    let cpuid = raw_cpuid::CpuId::new();
+    let has_rdseed = cpuid
+        .get_extended_feature_info()
+        .is_some_and(|f| f.has_rdseed());
+    let has_rdrand = cpuid.get_feature_info().is_some_and(|f| f.has_rdrand());
+    // Both may fail transiently if the entropy is exhausted, retry a few times.
+    for _ in 0..16 {
+        let val: u64;
+        let ok: u8;
+        if has_rdseed {
+            unsafe { asm!("rdseed {}", "setc {}", out(reg) val, out(reg_byte) ok) };
+        } else if has_rdrand {
+            unsafe { asm!("rdrand {}", "setc {}", out(reg) val, out(reg_byte) ok) };
+        } else {
+            return None;
+        }
+        if ok != 0 {
+            return Some(val);
+        }
+        core::hint::spin_loop();
+    }
+    None
+}
@@ -10,6 +10,10 @@ pub const MAX_IRQ_COUNT: usize = 1024;
 /// The timer IRQ number.
 pub const TIMER_IRQ_NUM: usize = translate_irq(14, InterruptType::PPI).unwrap();
 
+/// The IPI (inter-processor interrupt) IRQ number.
+#[cfg(feature = "smp")]
+pub const IPI_IRQ_NUM: usize = translate_irq(1, InterruptType::SGI).unwrap();
+
 /// The UART IRQ number.
 pub const UART_IRQ_NUM: usize = translate_irq(axconfig::UART_IRQ, InterruptType::SPI).unwrap();
 
@@ -28,6 +32,12 @@ pub fn set_enable(irq_num: usize, enabled: bool) {
     GICD.lock().set_enable(irq_num as _, enabled);
 }
 
+/// Sends an IPI to the given CPU, which raises [`IPI_IRQ_NUM`] on it.
+#[cfg(feature = "smp")]
+pub fn send_ipi(cpu_id: usize) {
+    GICD.lock().send_sgi(cpu_id, IPI_IRQ_NUM);
+}
+
 /// Registers an IRQ handler for the given IRQ.
 ///
 /// It also enables the IRQ if the registration succeeds. It returns `false` if
@@ -9,3 +9,6 @@ pub mod gic;
 
 #[cfg(not(platform_family = "aarch64-bsta1000b"))]
 pub mod pl011;
+
+#[cfg(platform_family = "aarch64-qemu-virt")]
+pub mod pl031;
@@ -0,0 +1,19 @@
+//! PL031 Real Time Clock.
+
+use memory_addr::PhysAddr;
+
+use crate::mem::phys_to_virt;
+
+const RTC_BASE: PhysAddr = PhysAddr::from(axconfig::RTC_PADDR);
+
+/// Data register, the current time in seconds since the epoch.
+const RTCDR: usize = 0x00;
+
+/// Reads the RTC and sets the wall clock time.
+pub fn init_early() {
+    let secs = unsafe {
+        let base = phys_to_virt(RTC_BASE).as_usize();
+        core::ptr::read_volatile((base + RTCDR) as *const u32)
+    };
+    crate::time::set_wall_time(crate::time::TimeValue::from_secs(secs as u64));
+}
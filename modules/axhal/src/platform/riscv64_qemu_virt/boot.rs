@@ -8,9 +8,22 @@ static mut BOOT_STACK: [u8; TASK_STACK_SIZE] = [0; TASK_STACK_SIZE];
 #[link_section = ".data.boot_page_table"]
 static mut BOOT_PT_SV39: [u64; 512] = [0; 512];
 
+/// Second level table of the MMIO regions in the low 1 GiB.
+#[link_section = ".data.boot_page_table"]
+static mut BOOT_PT_SV39_MMIO: [u64; 512] = [0; 512];
+
 unsafe fn init_boot_page_table() {
     // 0x8000_0000..0xc000_0000, VRWX_GAD, 1G block
     BOOT_PT_SV39[2] = (0x80000 << 10) | 0xef;
+    // 0xffff_ffc0_0000_0000..0xffff_ffc0_4000_0000, V, next level table
+    // Only the RTC and PLIC are mapped, which are accessed before the kernel
+    // page table is set up or without the `paging` feature.
+    BOOT_PT_SV39[0x100] = (BOOT_PT_SV39_MMIO.as_ptr() as u64 >> 12 << 10) | 0x01;
+    // 0xffff_ffc0_0000_0000..0xffff_ffc0_0020_0000 (RTC), VRW_GAD, 2M block
+    BOOT_PT_SV39_MMIO[0] = 0xe7;
+    // 0xffff_ffc0_0c00_0000..0xffff_ffc0_0c40_0000 (PLIC), VRW_GAD, 2M blocks
+    BOOT_PT_SV39_MMIO[0x60] = (0x0c000 << 10) | 0xe7;
+    BOOT_PT_SV39_MMIO[0x61] = (0x0c200 << 10) | 0xe7;
     // 0xffff_ffc0_8000_0000..0xffff_ffc0_c000_0000, VRWX_GAD, 1G block
     BOOT_PT_SV39[0x102] = (0x80000 << 10) | 0xef;
 }
@@ -1,14 +1,13 @@
-//! TODO: PLIC
-
 use crate::irq::IrqHandler;
+use crate::mem::phys_to_virt;
 use lazy_init::LazyInit;
-use riscv::register::sie;
+use memory_addr::PhysAddr;
+use riscv::register::{sie, sip};
 
 /// `Interrupt` bit in `scause`
 pub(super) const INTC_IRQ_BASE: usize = 1 << (usize::BITS - 1);
 
 /// Supervisor software interrupt in `scause`
-#[allow(unused)]
 pub(super) const S_SOFT: usize = INTC_IRQ_BASE + 1;
 
 /// Supervisor timer interrupt in `scause`
@@ -19,33 +18,111 @@ pub(super) const S_EXT: usize = INTC_IRQ_BASE + 9;
 
 static TIMER_HANDLER: LazyInit<IrqHandler> = LazyInit::new();
 
-/// The maximum number of IRQs.
+static IPI_HANDLER: LazyInit<IrqHandler> = LazyInit::new();
+
+/// The maximum number of IRQs, which is the number of interrupt sources of the
+/// PLIC.
+///
+/// Only PLIC sources are kept in the IRQ handler table, so their numbers never
+/// collide with the local interrupts of `scause`, which have their own
+/// handlers.
 pub const MAX_IRQ_COUNT: usize = 1024;
 
 /// The timer IRQ number (supervisor timer interrupt in `scause`).
 pub const TIMER_IRQ_NUM: usize = S_TIMER;
 
+/// The IPI (inter-processor interrupt) IRQ number (supervisor software
+/// interrupt in `scause`).
+#[cfg(feature = "smp")]
+pub const IPI_IRQ_NUM: usize = S_SOFT;
+
+const PLIC_BASE: PhysAddr = PhysAddr::from(axconfig::PLIC_PADDR);
+
+/// Minimal driver of the platform-level interrupt controller (PLIC), only the
+/// supervisor-mode context of each hart is used.
+mod plic {
+    use core::ptr::{read_volatile, write_volatile};
+
+    const PRIORITY_BASE: usize = 0;
+    const ENABLE_BASE: usize = 0x2000;
+    const ENABLE_STRIDE: usize = 0x80;
+    const CONTEXT_BASE: usize = 0x20_0000;
+    const CONTEXT_STRIDE: usize = 0x1000;
+    const THRESHOLD_OFFSET: usize = 0;
+    const CLAIM_OFFSET: usize = 4;
+
+    fn reg(offset: usize) -> *mut u32 {
+        (super::phys_to_virt(super::PLIC_BASE).as_usize() + offset) as *mut u32
+    }
+
+    /// The S-mode context of the current hart.
+    fn context() -> usize {
+        crate::cpu::this_cpu_id() * 2 + 1
+    }
+
+    fn context_reg(offset: usize) -> *mut u32 {
+        reg(CONTEXT_BASE + context() * CONTEXT_STRIDE + offset)
+    }
+
+    pub fn set_enable(irq: usize, enabled: bool) {
+        let enable_reg = reg(ENABLE_BASE + context() * ENABLE_STRIDE + (irq / 32) * 4);
+        unsafe {
+            if enabled {
+                write_volatile(reg(PRIORITY_BASE + irq * 4), 1);
+                write_volatile(enable_reg, read_volatile(enable_reg) | 1 << (irq % 32));
+            } else {
+                write_volatile(enable_reg, read_volatile(enable_reg) & !(1 << (irq % 32)));
+            }
+        }
+    }
+
+    pub fn set_threshold(threshold: u32) {
+        unsafe { write_volatile(context_reg(THRESHOLD_OFFSET), threshold) }
+    }
+
+    pub fn claim() -> usize {
+        unsafe { read_volatile(context_reg(CLAIM_OFFSET)) as usize }
+    }
+
+    pub fn complete(irq: usize) {
+        unsafe { write_volatile(context_reg(CLAIM_OFFSET), irq as u32) }
+    }
+}
+
 macro_rules! with_cause {
-    ($cause: expr, @TIMER => $timer_op: expr, @EXT => $ext_op: expr $(,)?) => {
+    (
+        $cause: expr,
+        @TIMER => $timer_op: expr,
+        @SOFT => $soft_op: expr,
+        @EXT => $ext_op: expr,
+        @PLIC => $plic_op: expr $(,)?
+    ) => {
         match $cause {
             S_TIMER => $timer_op,
+            S_SOFT => $soft_op,
             S_EXT => $ext_op,
+            irq if irq & INTC_IRQ_BASE == 0 => $plic_op,
             _ => panic!("invalid trap cause: {:#x}", $cause),
         }
     };
 }
 
 /// Enables or disables the given IRQ.
-pub fn set_enable(scause: usize, _enabled: bool) {
-    if scause == S_EXT {
-        // TODO: set enable in PLIC
+///
+/// IRQ numbers without the `Interrupt` bit of `scause` are external interrupt
+/// sources of the PLIC.
+pub fn set_enable(scause: usize, enabled: bool) {
+    if scause & INTC_IRQ_BASE == 0 {
+        plic::set_enable(scause, enabled);
     }
 }
 
 /// Registers an IRQ handler for the given IRQ.
 ///
 /// It also enables the IRQ if the registration succeeds. It returns `false` if
-/// the registration failed.
+/// the registration failed. External interrupts are registered by the numbers
+/// of their PLIC sources, registering the supervisor external interrupt itself
+/// fails.
 pub fn register_handler(scause: usize, handler: IrqHandler) -> bool {
     with_cause!(
         scause,
@@ -55,7 +132,14 @@ pub fn register_handler(scause: usize, handler: IrqHandler) -> bool {
         } else {
             false
         },
-        @EXT => crate::irq::register_handler_common(scause & !INTC_IRQ_BASE, handler),
+        @SOFT => if !IPI_HANDLER.is_init() {
+            IPI_HANDLER.init_by(handler);
+            true
+        } else {
+            false
+        },
+        @EXT => false,
+        @PLIC => crate::irq::register_handler_common(scause, handler),
     )
 }
 
@@ -71,10 +155,30 @@ pub fn dispatch_irq(scause: usize) {
             trace!("IRQ: timer");
             TIMER_HANDLER();
         },
-        @EXT => crate::irq::dispatch_irq_common(0), // TODO: get IRQ number from PLIC
+        @SOFT => {
+            trace!("IRQ: IPI");
+            unsafe { sip::clear_ssoft() };
+            if IPI_HANDLER.is_init() {
+                IPI_HANDLER();
+            }
+        },
+        @EXT => {
+            let irq = plic::claim();
+            if irq != 0 {
+                crate::irq::dispatch_irq_common(irq);
+                plic::complete(irq);
+            }
+        },
+        @PLIC => unreachable!(),
     );
 }
 
+/// Sends an IPI to the given hart, which raises [`IPI_IRQ_NUM`] on it.
+#[cfg(feature = "smp")]
+pub fn send_ipi(hartid: usize) {
+    sbi_rt::send_ipi(sbi_rt::HartMask::from_mask_base(1, hartid));
+}
+
 pub(super) fn init_percpu() {
     // enable soft interrupts, timer interrupts, and external interrupts
     unsafe {
@@ -82,4 +186,6 @@ pub(super) fn init_percpu() {
         sie::set_stimer();
         sie::set_sext();
     }
+    // accept external interrupts of any priority
+    plic::set_threshold(0);
 }
@@ -1,5 +1,8 @@
+use memory_addr::PhysAddr;
 use riscv::register::time;
 
+use crate::mem::phys_to_virt;
+
 const NANOS_PER_TICK: u64 = crate::time::NANOS_PER_SEC / axconfig::TIMER_FREQUENCY as u64;
 
 /// Returns the current clock time in hardware ticks.
@@ -28,6 +31,24 @@ pub fn set_oneshot_timer(deadline_ns: u64) {
     sbi_rt::set_timer(nanos_to_ticks(deadline_ns));
 }
 
+/// Base address of the goldfish RTC.
+const RTC_BASE: PhysAddr = PhysAddr::from(axconfig::RTC_PADDR);
+/// The low 32 bits of the time in nanoseconds since the epoch, reading it
+/// latches the high 32 bits.
+const RTC_TIME_LOW: usize = 0x00;
+const RTC_TIME_HIGH: usize = 0x04;
+
+/// Reads the RTC and sets the wall clock time.
+pub(super) fn init_early() {
+    let nanos = unsafe {
+        let base = phys_to_virt(RTC_BASE).as_usize();
+        let low = core::ptr::read_volatile((base + RTC_TIME_LOW) as *const u32);
+        let high = core::ptr::read_volatile((base + RTC_TIME_HIGH) as *const u32);
+        (high as u64) << 32 | low as u64
+    };
+    crate::time::set_wall_time(crate::time::TimeValue::from_nanos(nanos));
+}
+
 pub(super) fn init_percpu() {
     #[cfg(feature = "irq")]
     sbi_rt::set_timer(0);
@@ -14,6 +14,8 @@ pub(super) mod vectors {
     pub const APIC_TIMER_VECTOR: u8 = 0xf0;
     pub const APIC_SPURIOUS_VECTOR: u8 = 0xf1;
     pub const APIC_ERROR_VECTOR: u8 = 0xf2;
+    pub const APIC_IPI_VECTOR: u8 = 0xf3;
+    pub const IO_APIC_VECTOR_BASE: u8 = 0x20;
 }
 
 /// The maximum number of IRQs.
@@ -22,6 +24,10 @@ pub const MAX_IRQ_COUNT: usize = 256;
 /// The timer IRQ number.
 pub const TIMER_IRQ_NUM: usize = APIC_TIMER_VECTOR as usize;
 
+/// The IPI (inter-processor interrupt) IRQ number.
+#[cfg(feature = "smp")]
+pub const IPI_IRQ_NUM: usize = APIC_IPI_VECTOR as usize;
+
 const IO_APIC_BASE: PhysAddr = PhysAddr::from(0xFEC0_0000);
 
 static mut LOCAL_APIC: Option<LocalApic> = None;
@@ -32,12 +38,13 @@ static IO_APIC: LazyInit<SpinNoIrq<IoApic>> = LazyInit::new();
 #[cfg(feature = "irq")]
 pub fn set_enable(vector: usize, enabled: bool) {
     // should not affect LAPIC interrupts
-    if vector < APIC_TIMER_VECTOR as _ {
+    if (IO_APIC_VECTOR_BASE as usize..APIC_TIMER_VECTOR as usize).contains(&vector) {
+        let pin = (vector - IO_APIC_VECTOR_BASE as usize) as u8;
         unsafe {
             if enabled {
-                IO_APIC.lock().enable_irq(vector as u8);
+                IO_APIC.lock().enable_irq(pin);
             } else {
-                IO_APIC.lock().disable_irq(vector as u8);
+                IO_APIC.lock().disable_irq(pin);
             }
         }
     }
@@ -63,6 +70,12 @@ pub fn dispatch_irq(vector: usize) {
     unsafe { local_apic().end_of_interrupt() };
 }
 
+/// Sends an IPI to the given CPU, which raises [`IPI_IRQ_NUM`] on it.
+#[cfg(all(feature = "irq", feature = "smp"))]
+pub fn send_ipi(cpu_id: usize) {
+    unsafe { local_apic().send_ipi(APIC_IPI_VECTOR, raw_apic_id(cpu_id as u8)) };
+}
+
 pub(super) fn local_apic<'a>() -> &'a mut LocalApic {
     // It's safe as LAPIC is per-cpu.
     unsafe { LOCAL_APIC.as_mut().unwrap() }
@@ -114,7 +127,9 @@ pub(super) fn init_primary() {
     }
 
     info!("Initialize IO APIC...");
-    let io_apic = unsafe { IoApic::new(phys_to_virt(IO_APIC_BASE).as_usize() as u64) };
+    let mut io_apic = unsafe { IoApic::new(phys_to_virt(IO_APIC_BASE).as_usize() as u64) };
+    // map IO APIC pin `n` to vector `IO_APIC_VECTOR_BASE + n`, all masked
+    unsafe { io_apic.init(IO_APIC_VECTOR_BASE) };
     IO_APIC.init_by(SpinNoIrq::new(io_apic));
 }
 
@@ -0,0 +1,105 @@
+//! CMOS Real Time Clock.
+
+use x86_64::instructions::port::{Port, PortWriteOnly};
+
+const CMOS_ADDR_PORT: u16 = 0x70;
+const CMOS_DATA_PORT: u16 = 0x71;
+
+const REG_SECONDS: u8 = 0x00;
+const REG_MINUTES: u8 = 0x02;
+const REG_HOURS: u8 = 0x04;
+const REG_DAY: u8 = 0x07;
+const REG_MONTH: u8 = 0x08;
+const REG_YEAR: u8 = 0x09;
+const REG_CENTURY: u8 = 0x32;
+const REG_STATUS_A: u8 = 0x0a;
+const REG_STATUS_B: u8 = 0x0b;
+
+/// Status A: an update of the time registers is in progress.
+const STATUS_A_UPDATING: u8 = 0x80;
+/// Status B: the hours are in the 24-hour format.
+const STATUS_B_24_HOUR: u8 = 0x02;
+/// Status B: the values are binary instead of BCD.
+const STATUS_B_BINARY: u8 = 0x04;
+/// The PM bit of the hours in the 12-hour format.
+const HOUR_PM: u8 = 0x80;
+
+fn read_reg(reg: u8) -> u8 {
+    unsafe {
+        PortWriteOnly::new(CMOS_ADDR_PORT).write(reg);
+        Port::new(CMOS_DATA_PORT).read()
+    }
+}
+
+fn read_time_regs() -> [u8; 7] {
+    while read_reg(REG_STATUS_A) & STATUS_A_UPDATING != 0 {
+        core::hint::spin_loop();
+    }
+    [
+        REG_SECONDS,
+        REG_MINUTES,
+        REG_HOURS,
+        REG_DAY,
+        REG_MONTH,
+        REG_YEAR,
+        REG_CENTURY,
+    ]
+    .map(read_reg)
+}
+
+/// Returns the number of days since 1970-01-01 of the given date.
+const fn days_since_epoch(year: u64, month: u64, day: u64) -> u64 {
+    // Count years from March, so that the leap day is at the end of a year.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Reads the RTC, returns the time in seconds since the epoch.
+pub fn read_secs() -> u64 {
+    // Read until two consecutive results agree, in case the registers were
+    // updated in the middle.
+    let mut regs = read_time_regs();
+    loop {
+        let again = read_time_regs();
+        if again == regs {
+            break;
+        }
+        regs = again;
+    }
+
+    let status_b = read_reg(REG_STATUS_B);
+    let decode = |v: u8| -> u64 {
+        if status_b & STATUS_B_BINARY != 0 {
+            v as u64
+        } else {
+            (v & 0x0f) as u64 + (v >> 4) as u64 * 10
+        }
+    };
+    let [sec, min, hour, day, month, year, century] = regs;
+    let mut hour_24 = decode(hour & !HOUR_PM);
+    if status_b & STATUS_B_24_HOUR == 0 {
+        hour_24 %= 12;
+        if hour & HOUR_PM != 0 {
+            hour_24 += 12;
+        }
+    }
+    // Assume the 21st century if the century register is invalid.
+    let century = match decode(century) {
+        c @ 19..=99 => c,
+        _ => 20,
+    };
+    let year = century * 100 + decode(year);
+
+    days_since_epoch(year, decode(month), decode(day)) * 86400
+        + hour_24 * 3600
+        + decode(min) * 60
+        + decode(sec)
+}
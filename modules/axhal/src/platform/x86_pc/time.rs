@@ -54,6 +54,7 @@ pub(super) fn init_early() {
     }
 
     unsafe { INIT_TICK = core::arch::x86_64::_rdtsc() };
+    crate::time::set_wall_time(crate::time::TimeValue::from_secs(super::rtc::read_secs()));
 }
 
 pub(super) fn init_primary() {
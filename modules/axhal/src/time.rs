@@ -1,5 +1,7 @@
 //! Time-related operations.
 
+use core::sync::atomic::{AtomicU64, Ordering};
+
 pub use core::time::Duration;
 
 /// A measurement of the system clock.
@@ -35,6 +37,37 @@ pub fn current_time() -> TimeValue {
     TimeValue::from_nanos(current_time_nanos())
 }
 
+/// The wall clock time when the system booted, in nanoseconds since the Unix
+/// epoch. It is read from the RTC during platform initialization if there is
+/// one, or starts at the epoch.
+static EPOCH_OFFSET_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the offset between the wall clock time and the monotonic clock
+/// time, in nanoseconds.
+pub fn epochoffset_nanos() -> u64 {
+    EPOCH_OFFSET_NANOS.load(Ordering::Relaxed)
+}
+
+/// Returns the current wall clock time in nanoseconds since the Unix epoch.
+pub fn wall_time_nanos() -> u64 {
+    current_time_nanos() + epochoffset_nanos()
+}
+
+/// Returns the current wall clock time since the Unix epoch in [`TimeValue`].
+pub fn wall_time() -> TimeValue {
+    TimeValue::from_nanos(wall_time_nanos())
+}
+
+/// Sets the current wall clock time, which does not affect the monotonic
+/// clock.
+///
+/// The wall clock can not be set earlier than the monotonic clock, i.e. the
+/// time since boot.
+pub fn set_wall_time(time: TimeValue) {
+    let offset = (time.as_nanos() as u64).saturating_sub(current_time_nanos());
+    EPOCH_OFFSET_NANOS.store(offset, Ordering::Relaxed);
+}
+
 /// Busy waiting for the given duration.
 pub fn busy_wait(dur: Duration) {
     busy_wait_until(current_time() + dur);
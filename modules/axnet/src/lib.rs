@@ -8,12 +8,17 @@
 //!
 //! - [`TcpSocket`]: A TCP socket that provides POSIX-like APIs.
 //! - [`UdpSocket`]: A UDP socket that provides POSIX-like APIs.
+//! - [`UdpRecvBuf`]: A UDP datagram received without copying.
 //! - [`dns_query`]: Function for DNS query.
 //!
 //! # Cargo Features
 //!
 //! - `smoltcp`: Use [smoltcp] as the underlying network stack. This is enabled
 //!   by default.
+//! - `irq` and `multitask`: When both are enabled, the network stack is polled
+//!   on NIC interrupts by a background task, and blocked sockets sleep on wait
+//!   queues instead of busy polling. Others can wait for the state changes of
+//!   sockets with [`set_event_hook`].
 //!
 //! [smoltcp]: https://github.com/smoltcp-rs/smoltcp
 
@@ -33,17 +38,25 @@ cfg_if::cfg_if! {
 }
 
 pub use self::net_impl::TcpSocket;
-pub use self::net_impl::UdpSocket;
 pub use self::net_impl::{bench_receive, bench_transmit};
 pub use self::net_impl::{dns_query, poll_interfaces};
+pub use self::net_impl::{UdpRecvBuf, UdpSocket};
+
+#[cfg(all(feature = "irq", feature = "multitask"))]
+pub use self::net_impl::{is_irq_driven, set_event_hook};
 
 use axdriver::{prelude::*, AxDeviceContainer};
 
 /// Initializes the network subsystem by NIC devices.
+///
+/// The loopback interface is always available, even if there is no NIC.
 pub fn init_network(mut net_devs: AxDeviceContainer<AxNetDevice>) {
     info!("Initialize network subsystem...");
 
-    let dev = net_devs.take_one().expect("No NIC device found!");
-    info!("  use NIC 0: {:?}", dev.device_name());
+    let dev = net_devs.take_one();
+    match &dev {
+        Some(dev) => info!("  use NIC 0: {:?}", dev.device_name()),
+        None => warn!("  No NIC device found, only loopback is available"),
+    }
     net_impl::init(dev);
 }
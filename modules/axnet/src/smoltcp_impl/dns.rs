@@ -2,12 +2,11 @@ use alloc::vec::Vec;
 use axerrno::{ax_err_type, AxError, AxResult};
 use core::net::IpAddr;
 
-use smoltcp::iface::SocketHandle;
 use smoltcp::socket::dns::{self, GetQueryResultError, StartQueryError};
 use smoltcp::wire::DnsQueryType;
 
 use super::addr::into_core_ipaddr;
-use super::{SocketSetWrapper, ETH0, SOCKET_SET};
+use super::{IfaceId, SocketHandle, SocketSetWrapper, ETH0, SOCKET_SET};
 
 /// A DNS socket.
 struct DnsSocket {
@@ -19,7 +18,7 @@ impl DnsSocket {
     /// Creates a new DNS socket.
     pub fn new() -> Self {
         let socket = SocketSetWrapper::new_dns_socket();
-        let handle = Some(SOCKET_SET.add(socket));
+        let handle = Some(SOCKET_SET.add(IfaceId::Eth0, socket));
         Self { handle }
     }
 
@@ -35,7 +34,10 @@ impl DnsSocket {
     pub fn query(&self, name: &str, query_type: DnsQueryType) -> AxResult<Vec<IpAddr>> {
         // let local_addr = self.local_addr.unwrap_or_else(f);
         let handle = self.handle.ok_or_else(|| ax_err_type!(InvalidInput))?;
-        let iface = &ETH0.iface;
+        let iface = &ETH0
+            .try_get()
+            .ok_or_else(|| ax_err_type!(ConnectionRefused, "no NIC device for DNS query"))?
+            .iface;
         let query_handle = SOCKET_SET
             .with_socket_mut::<dns::Socket, _, _>(handle, |socket| {
                 socket.start_query(iface.lock().context(), name, query_type)
@@ -3,24 +3,38 @@ use core::ops::{Deref, DerefMut};
 
 use axerrno::{ax_err, AxError, AxResult};
 use axsync::Mutex;
-use smoltcp::iface::{SocketHandle, SocketSet};
+use smoltcp::iface::SocketSet;
 use smoltcp::socket::tcp::{self, State};
 use smoltcp::wire::{IpAddress, IpEndpoint, IpListenEndpoint};
 
-use super::{SocketSetWrapper, LISTEN_QUEUE_SIZE, SOCKET_SET};
+use super::waiter::SocketWaiter;
+use super::{IfaceId, SocketHandle, SocketSetWrapper, LISTEN_QUEUE_SIZE, SOCKET_SET};
 
 const PORT_NUM: usize = 65536;
 
 struct ListenTableEntry {
     listen_endpoint: IpListenEndpoint,
     syn_queue: VecDeque<SocketHandle>,
+    rx_buf_len: usize,
+    tx_buf_len: usize,
+    /// The waiter of the listening socket, woken up when connections in the
+    /// SYN queue change state.
+    waiter: SocketWaiter,
 }
 
 impl ListenTableEntry {
-    pub fn new(listen_endpoint: IpListenEndpoint) -> Self {
+    pub fn new(
+        listen_endpoint: IpListenEndpoint,
+        rx_buf_len: usize,
+        tx_buf_len: usize,
+        waiter: SocketWaiter,
+    ) -> Self {
         Self {
             listen_endpoint,
             syn_queue: VecDeque::with_capacity(LISTEN_QUEUE_SIZE),
+            rx_buf_len,
+            tx_buf_len,
+            waiter,
         }
     }
 
@@ -61,12 +75,37 @@ impl ListenTable {
         self.tcp[port as usize].lock().is_none()
     }
 
-    pub fn listen(&self, listen_endpoint: IpListenEndpoint) -> AxResult {
+    /// Returns whether a socket can be bound to the given port.
+    ///
+    /// The port must not be listened on. Unless `reuse_addr` (`SO_REUSEADDR`)
+    /// is set, it must not be used by any connection either, e.g. one accepted
+    /// by a listener that is closed since.
+    pub fn can_bind(&self, port: u16, reuse_addr: bool) -> bool {
+        // Do not hold the entry while locking the socket sets, as incoming
+        // packets are handled in the reverse order.
+        self.can_listen(port) && (reuse_addr || !SOCKET_SET.tcp_port_in_use(port))
+    }
+
+    /// Starts listening on the given endpoint. Sockets created for incoming
+    /// connections use the given buffer sizes, and wake up `waiter` until they
+    /// are accepted.
+    pub fn listen(
+        &self,
+        listen_endpoint: IpListenEndpoint,
+        rx_buf_len: usize,
+        tx_buf_len: usize,
+        waiter: SocketWaiter,
+    ) -> AxResult {
         let port = listen_endpoint.port;
         assert_ne!(port, 0);
         let mut entry = self.tcp[port as usize].lock();
         if entry.is_none() {
-            *entry = Some(Box::new(ListenTableEntry::new(listen_endpoint)));
+            *entry = Some(Box::new(ListenTableEntry::new(
+                listen_endpoint,
+                rx_buf_len,
+                tx_buf_len,
+                waiter,
+            )));
             Ok(())
         } else {
             ax_err!(AddrInUse, "socket listen() failed")
@@ -114,6 +153,7 @@ impl ListenTable {
         &self,
         src: IpEndpoint,
         dst: IpEndpoint,
+        iface: IfaceId,
         sockets: &mut SocketSet<'_>,
     ) {
         if let Some(entry) = self.tcp[dst.port as usize].lock().deref_mut() {
@@ -126,13 +166,17 @@ impl ListenTable {
                 warn!("SYN queue overflow!");
                 return;
             }
-            let mut socket = SocketSetWrapper::new_tcp_socket();
+            let mut socket = SocketSetWrapper::new_tcp_socket(entry.rx_buf_len, entry.tx_buf_len);
             if socket.listen(entry.listen_endpoint).is_ok() {
-                let handle = sockets.add(socket);
+                let handle = SocketHandle {
+                    iface,
+                    inner: sockets.add(socket),
+                };
                 debug!(
                     "TCP socket {}: prepare for connection {} -> {}",
                     handle, src, entry.listen_endpoint
                 );
+                entry.waiter.register(handle);
                 entry.syn_queue.push_back(handle);
             }
         }
@@ -0,0 +1,77 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use smoltcp::iface::SocketSet;
+use smoltcp::phy::{Device, DeviceCapabilities, Medium, RxToken, TxToken};
+use smoltcp::time::Instant;
+
+use super::{snoop_tcp_packet, IfaceId};
+
+/// MTU of the loopback device, same as Linux.
+const LOOPBACK_MTU: usize = 65536;
+
+/// An in-memory device that receives every IP packet it transmits.
+pub(crate) struct LoopbackDev {
+    queue: VecDeque<Vec<u8>>,
+}
+
+impl LoopbackDev {
+    pub const fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+impl Device for LoopbackDev {
+    type RxToken<'a> = LoopbackRxToken where Self: 'a;
+    type TxToken<'a> = LoopbackTxToken<'a> where Self: 'a;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let buf = self.queue.pop_front()?;
+        Some((LoopbackRxToken(buf), LoopbackTxToken(&mut self.queue)))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        Some(LoopbackTxToken(&mut self.queue))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.max_transmission_unit = LOOPBACK_MTU;
+        caps.max_burst_size = None;
+        caps.medium = Medium::Ip;
+        caps
+    }
+}
+
+pub(crate) struct LoopbackRxToken(Vec<u8>);
+pub(crate) struct LoopbackTxToken<'a>(&'a mut VecDeque<Vec<u8>>);
+
+impl RxToken for LoopbackRxToken {
+    fn preprocess(&self, sockets: &mut SocketSet<'_>) {
+        snoop_tcp_packet(&self.0, IfaceId::Lo, sockets).ok();
+    }
+
+    fn consume<R, F>(mut self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        trace!("LO RECV {} bytes: {:02X?}", self.0.len(), self.0);
+        f(&mut self.0)
+    }
+}
+
+impl<'a> TxToken for LoopbackTxToken<'a> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mut buf = vec![0; len];
+        let ret = f(&mut buf);
+        trace!("LO SEND {} bytes: {:02X?}", len, buf);
+        self.0.push_back(buf);
+        ret
+    }
+}
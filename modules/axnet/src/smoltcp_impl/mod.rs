@@ -2,29 +2,41 @@ mod addr;
 mod bench;
 mod dns;
 mod listen_table;
+mod loopback;
+mod options;
 mod tcp;
 mod udp;
+mod waiter;
+mod zero_copy;
 
 use alloc::vec;
 use core::cell::RefCell;
+use core::fmt;
 use core::ops::DerefMut;
 
 use axdriver::prelude::*;
+use axerrno::{ax_err, ax_err_type, AxResult};
 use axhal::time::{current_time_nanos, NANOS_PER_MICROS};
 use axsync::Mutex;
 use driver_net::{DevError, NetBufPtr};
 use lazy_init::LazyInit;
-use smoltcp::iface::{Config, Interface, SocketHandle, SocketSet};
+use smoltcp::iface::{Config, Interface, Route, SocketSet};
 use smoltcp::phy::{Device, DeviceCapabilities, Medium, RxToken, TxToken};
 use smoltcp::socket::{self, AnySocket};
 use smoltcp::time::Instant;
-use smoltcp::wire::{EthernetAddress, HardwareAddress, IpAddress, IpCidr};
+use smoltcp::wire::{EthernetAddress, HardwareAddress, IpAddress, IpCidr, Ipv4Address};
 
 use self::listen_table::ListenTable;
+use self::loopback::LoopbackDev;
+use self::waiter::SocketWaiter;
 
 pub use self::dns::dns_query;
 pub use self::tcp::TcpSocket;
 pub use self::udp::UdpSocket;
+pub use self::zero_copy::UdpRecvBuf;
+
+#[cfg(all(feature = "irq", feature = "multitask"))]
+pub use self::waiter::{is_irq_driven, set_event_hook};
 
 macro_rules! env_or_default {
     ($key:literal) => {
@@ -40,9 +52,10 @@ const GATEWAY: &str = env_or_default!("AX_GW");
 const DNS_SEVER: &str = "8.8.8.8";
 const IP_PREFIX: u8 = 24;
 
-const STANDARD_MTU: usize = 1500;
+const LOOPBACK_IP: IpAddress = IpAddress::v4(127, 0, 0, 1);
+const LOOPBACK_PREFIX: u8 = 8;
 
-const RANDOM_SEED: u64 = 0xA2CE_05A2_CE05_A2CE;
+const STANDARD_MTU: usize = 1500;
 
 const TCP_RX_BUF_LEN: usize = 64 * 1024;
 const TCP_TX_BUF_LEN: usize = 64 * 1024;
@@ -52,40 +65,73 @@ const LISTEN_QUEUE_SIZE: usize = 512;
 
 static LISTEN_TABLE: LazyInit<ListenTable> = LazyInit::new();
 static SOCKET_SET: LazyInit<SocketSetWrapper> = LazyInit::new();
-static ETH0: LazyInit<InterfaceWrapper> = LazyInit::new();
+static LO: LazyInit<InterfaceWrapper<LoopbackDev>> = LazyInit::new();
+/// The NIC interface, not initialized if there is no NIC.
+static ETH0: LazyInit<InterfaceWrapper<DeviceWrapper>> = LazyInit::new();
+
+/// Identifies a network interface, and the socket set polled by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum IfaceId {
+    Lo,
+    Eth0,
+}
 
-struct SocketSetWrapper<'a>(Mutex<SocketSet<'a>>);
+/// A handle of a socket in the socket set of the interface `iface`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SocketHandle {
+    iface: IfaceId,
+    inner: smoltcp::iface::SocketHandle,
+}
+
+/// Sockets of each interface, kept in separate sets since smoltcp does not
+/// check routes when sockets on `Medium::Ip` interfaces transmit.
+struct SocketSetWrapper<'a> {
+    lo: Mutex<SocketSet<'a>>,
+    eth0: Mutex<SocketSet<'a>>,
+}
 
 struct DeviceWrapper {
     inner: RefCell<AxNetDevice>, // use `RefCell` is enough since it's wrapped in `Mutex` in `InterfaceWrapper`.
+    /// The IP address of the interface, to find UDP datagrams to it.
+    ip: Ipv4Address,
 }
 
-struct InterfaceWrapper {
+struct InterfaceWrapper<D> {
     name: &'static str,
-    ether_addr: EthernetAddress,
-    dev: Mutex<DeviceWrapper>,
+    hardware_addr: HardwareAddress,
+    dev: Mutex<D>,
     iface: Mutex<Interface>,
 }
 
 impl<'a> SocketSetWrapper<'a> {
     fn new() -> Self {
-        Self(Mutex::new(SocketSet::new(vec![])))
+        Self {
+            lo: Mutex::new(SocketSet::new(vec![])),
+            eth0: Mutex::new(SocketSet::new(vec![])),
+        }
     }
 
-    pub fn new_tcp_socket() -> socket::tcp::Socket<'a> {
-        let tcp_rx_buffer = socket::tcp::SocketBuffer::new(vec![0; TCP_RX_BUF_LEN]);
-        let tcp_tx_buffer = socket::tcp::SocketBuffer::new(vec![0; TCP_TX_BUF_LEN]);
+    fn set(&self, iface: IfaceId) -> &Mutex<SocketSet<'a>> {
+        match iface {
+            IfaceId::Lo => &self.lo,
+            IfaceId::Eth0 => &self.eth0,
+        }
+    }
+
+    pub fn new_tcp_socket(rx_buf_len: usize, tx_buf_len: usize) -> socket::tcp::Socket<'a> {
+        let tcp_rx_buffer = socket::tcp::SocketBuffer::new(vec![0; rx_buf_len]);
+        let tcp_tx_buffer = socket::tcp::SocketBuffer::new(vec![0; tx_buf_len]);
         socket::tcp::Socket::new(tcp_rx_buffer, tcp_tx_buffer)
     }
 
-    pub fn new_udp_socket() -> socket::udp::Socket<'a> {
+    pub fn new_udp_socket(rx_buf_len: usize, tx_buf_len: usize) -> socket::udp::Socket<'a> {
         let udp_rx_buffer = socket::udp::PacketBuffer::new(
             vec![socket::udp::PacketMetadata::EMPTY; 8],
-            vec![0; UDP_RX_BUF_LEN],
+            vec![0; rx_buf_len],
         );
         let udp_tx_buffer = socket::udp::PacketBuffer::new(
             vec![socket::udp::PacketMetadata::EMPTY; 8],
-            vec![0; UDP_TX_BUF_LEN],
+            vec![0; tx_buf_len],
         );
         socket::udp::Socket::new(udp_rx_buffer, udp_tx_buffer)
     }
@@ -95,8 +141,9 @@ impl<'a> SocketSetWrapper<'a> {
         socket::dns::Socket::new(&[server_addr], vec![])
     }
 
-    pub fn add<T: AnySocket<'a>>(&self, socket: T) -> SocketHandle {
-        let handle = self.0.lock().add(socket);
+    pub fn add<T: AnySocket<'a>>(&self, iface: IfaceId, socket: T) -> SocketHandle {
+        let inner = self.set(iface).lock().add(socket);
+        let handle = SocketHandle { iface, inner };
         debug!("socket {}: created", handle);
         handle
     }
@@ -105,8 +152,8 @@ impl<'a> SocketSetWrapper<'a> {
     where
         F: FnOnce(&T) -> R,
     {
-        let set = self.0.lock();
-        let socket = set.get(handle);
+        let set = self.set(handle.iface).lock();
+        let socket = set.get(handle.inner);
         f(socket)
     }
 
@@ -114,46 +161,120 @@ impl<'a> SocketSetWrapper<'a> {
     where
         F: FnOnce(&mut T) -> R,
     {
-        let mut set = self.0.lock();
-        let socket = set.get_mut(handle);
+        let mut set = self.set(handle.iface).lock();
+        let socket = set.get_mut(handle.inner);
         f(socket)
     }
 
     pub fn poll_interfaces(&self) {
-        ETH0.poll(&self.0);
+        let mut changed = LO.poll(&self.lo);
+        let mut delivered = false;
+        if let Some(eth0) = ETH0.try_get() {
+            changed |= eth0.poll(&self.eth0);
+            delivered = zero_copy::take_delivered();
+        }
+        if changed || delivered {
+            #[cfg(all(feature = "irq", feature = "multitask"))]
+            waiter::notify_sockets(self, delivered);
+        }
+    }
+
+    /// Returns how long to wait before the next poll of all interfaces, or
+    /// `None` if there are no pending timers.
+    #[cfg(all(feature = "irq", feature = "multitask"))]
+    pub fn poll_delay(&self) -> Option<core::time::Duration> {
+        let lo_delay = LO.poll_delay(&self.lo);
+        let eth0_delay = ETH0.try_get().and_then(|eth0| eth0.poll_delay(&self.eth0));
+        match (lo_delay, eth0_delay) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns whether any open TCP connection on any interface uses the given
+    /// local port.
+    pub fn tcp_port_in_use(&self, port: u16) -> bool {
+        IfaceId::all().iter().any(|&iface| {
+            self.set(iface).lock().iter().any(|(_, socket)| {
+                socket::tcp::Socket::downcast(socket).is_some_and(|socket| {
+                    socket.state() != socket::tcp::State::Closed
+                        && socket.local_endpoint().is_some_and(|ep| ep.port == port)
+                })
+            })
+        })
     }
 
     pub fn remove(&self, handle: SocketHandle) {
-        self.0.lock().remove(handle);
+        let mut set = self.set(handle.iface).lock();
+        // Unregistered with the set locked, before the handle can be reused.
+        SocketWaiter::unregister(handle);
+        set.remove(handle.inner);
+        drop(set);
         debug!("socket {}: destroyed", handle);
     }
 }
 
-impl InterfaceWrapper {
-    fn new(name: &'static str, dev: AxNetDevice, ether_addr: EthernetAddress) -> Self {
-        let mut config = Config::new(HardwareAddress::Ethernet(ether_addr));
-        config.random_seed = RANDOM_SEED;
+fn current_time() -> Instant {
+    Instant::from_micros_const((current_time_nanos() / NANOS_PER_MICROS) as i64)
+}
+
+impl fmt::Display for SocketHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.iface {
+            IfaceId::Lo => write!(f, "lo/{}", self.inner),
+            IfaceId::Eth0 => write!(f, "eth0/{}", self.inner),
+        }
+    }
+}
+
+impl IfaceId {
+    /// Returns the interfaces that exist, the loopback one always comes first.
+    fn all() -> &'static [IfaceId] {
+        if ETH0.try_get().is_some() {
+            &[IfaceId::Lo, IfaceId::Eth0]
+        } else {
+            &[IfaceId::Lo]
+        }
+    }
+
+    /// Returns the smoltcp interface, which must exist.
+    fn iface(self) -> &'static Mutex<Interface> {
+        match self {
+            IfaceId::Lo => &LO.iface,
+            IfaceId::Eth0 => &ETH0.iface,
+        }
+    }
+}
 
-        let mut dev = DeviceWrapper::new(dev);
-        let iface = Mutex::new(Interface::new(config, &mut dev, Self::current_time()));
+/// Returns the interface that packets to `addr` go through.
+fn route_iface(addr: IpAddress) -> AxResult<IfaceId> {
+    match addr {
+        IpAddress::Ipv4(v4) if v4.is_loopback() => Ok(IfaceId::Lo),
+        _ if ETH0.try_get().is_some() => Ok(IfaceId::Eth0),
+        _ => ax_err!(ConnectionRefused, "no route to host: no NIC device"),
+    }
+}
+
+impl<D: Device> InterfaceWrapper<D> {
+    fn new(name: &'static str, mut dev: D, hardware_addr: HardwareAddress) -> Self {
+        let mut config = Config::new(hardware_addr);
+        config.random_seed = axrand::random_u64();
+
+        let iface = Mutex::new(Interface::new(config, &mut dev, current_time()));
         Self {
             name,
-            ether_addr,
+            hardware_addr,
             dev: Mutex::new(dev),
             iface,
         }
     }
 
-    fn current_time() -> Instant {
-        Instant::from_micros_const((current_time_nanos() / NANOS_PER_MICROS) as i64)
-    }
-
     pub fn name(&self) -> &str {
         self.name
     }
 
-    pub fn ethernet_address(&self) -> EthernetAddress {
-        self.ether_addr
+    pub fn hardware_address(&self) -> HardwareAddress {
+        self.hardware_addr
     }
 
     pub fn setup_ip_addr(&self, ip: IpAddress, prefix_len: u8) {
@@ -170,19 +291,72 @@ impl InterfaceWrapper {
         };
     }
 
-    pub fn poll(&self, sockets: &Mutex<SocketSet>) {
+    /// Polls the interface, returns whether the state of any socket may have
+    /// changed.
+    pub fn poll(&self, sockets: &Mutex<SocketSet>) -> bool {
         let mut dev = self.dev.lock();
         let mut iface = self.iface.lock();
         let mut sockets = sockets.lock();
-        let timestamp = Self::current_time();
-        iface.poll(timestamp, dev.deref_mut(), &mut sockets);
+        let timestamp = current_time();
+        iface.poll(timestamp, dev.deref_mut(), &mut sockets)
+    }
+
+    /// Returns how long to wait before the next [`poll`](Self::poll), or
+    /// `None` if there are no pending timers.
+    #[cfg(all(feature = "irq", feature = "multitask"))]
+    pub fn poll_delay(&self, sockets: &Mutex<SocketSet>) -> Option<core::time::Duration> {
+        let iface = self.iface.lock();
+        let sockets = sockets.lock();
+        iface
+            .poll_delay(current_time(), &sockets)
+            .map(|d| core::time::Duration::from_micros(d.total_micros()))
+    }
+}
+
+impl InterfaceWrapper<LoopbackDev> {
+    /// Accepts packets to the whole 127.0.0.0/8, not only `LOOPBACK_IP`.
+    pub fn setup_loopback_route(&self) {
+        let mut iface = self.iface.lock();
+        iface.set_any_ip(true);
+        iface.routes_mut().update(|routes| {
+            let cidr = IpCidr::new(IpAddress::v4(127, 0, 0, 0), LOOPBACK_PREFIX);
+            routes
+                .push(Route {
+                    cidr,
+                    via_router: LOOPBACK_IP,
+                    preferred_until: None,
+                    expires_at: None,
+                })
+                .unwrap();
+        });
+    }
+}
+
+impl InterfaceWrapper<DeviceWrapper> {
+    /// Gives a receive buffer lent by [`UdpRecvBuf`] back to the NIC.
+    fn recycle_rx_buffer(&self, rx_buf: NetBufPtr) {
+        if let Err(e) = self.dev.lock().inner.borrow_mut().recycle_rx_buffer(rx_buf) {
+            warn!("recycle_rx_buffer failed: {:?}", e);
+        }
+    }
+}
+
+#[cfg(all(feature = "irq", feature = "multitask"))]
+impl InterfaceWrapper<DeviceWrapper> {
+    pub fn irq_num(&self) -> Option<usize> {
+        self.dev.lock().inner.borrow().irq_num()
+    }
+
+    pub fn ack_interrupt(&self) -> bool {
+        self.dev.lock().inner.borrow_mut().ack_interrupt()
     }
 }
 
 impl DeviceWrapper {
-    fn new(inner: AxNetDevice) -> Self {
+    fn new(inner: AxNetDevice, ip: Ipv4Address) -> Self {
         Self {
             inner: RefCell::new(inner),
+            ip,
         }
     }
 }
@@ -201,13 +375,20 @@ impl Device for DeviceWrapper {
         if !dev.can_transmit() {
             return None;
         }
-        let rx_buf = match dev.receive() {
-            Ok(buf) => buf,
-            Err(err) => {
-                if !matches!(err, DevError::Again) {
-                    warn!("receive failed: {:?}", err);
+        let rx_buf = loop {
+            let buf = match dev.receive() {
+                Ok(buf) => buf,
+                Err(err) => {
+                    if !matches!(err, DevError::Again) {
+                        warn!("receive failed: {:?}", err);
+                    }
+                    return None;
                 }
-                return None;
+            };
+            // UDP datagrams to bound sockets bypass smoltcp.
+            let (mac, rx_queue_size) = (EthernetAddress(dev.mac_address().0), dev.rx_queue_size());
+            if let Some(buf) = zero_copy::deliver(buf, mac, self.ip, rx_queue_size) {
+                break buf;
             }
         };
         Some((AxNetRxToken(&self.inner, rx_buf), AxNetTxToken(&self.inner)))
@@ -240,7 +421,7 @@ struct AxNetTxToken<'a>(&'a RefCell<AxNetDevice>);
 
 impl<'a> RxToken for AxNetRxToken<'a> {
     fn preprocess(&self, sockets: &mut SocketSet<'_>) {
-        snoop_tcp_packet(self.1.packet(), sockets).ok();
+        snoop_tcp_frame(self.1.packet(), IfaceId::Eth0, sockets).ok();
     }
 
     fn consume<R, F>(self, f: F) -> R
@@ -273,11 +454,23 @@ impl<'a> TxToken for AxNetTxToken<'a> {
     }
 }
 
-fn snoop_tcp_packet(buf: &[u8], sockets: &mut SocketSet<'_>) -> Result<(), smoltcp::wire::Error> {
-    use smoltcp::wire::{EthernetFrame, IpProtocol, Ipv4Packet, TcpPacket};
+fn snoop_tcp_frame(
+    buf: &[u8],
+    iface: IfaceId,
+    sockets: &mut SocketSet<'_>,
+) -> Result<(), smoltcp::wire::Error> {
+    let ether_frame = smoltcp::wire::EthernetFrame::new_checked(buf)?;
+    snoop_tcp_packet(ether_frame.payload(), iface, sockets)
+}
+
+fn snoop_tcp_packet(
+    buf: &[u8],
+    iface: IfaceId,
+    sockets: &mut SocketSet<'_>,
+) -> Result<(), smoltcp::wire::Error> {
+    use smoltcp::wire::{IpProtocol, Ipv4Packet, TcpPacket};
 
-    let ether_frame = EthernetFrame::new_checked(buf)?;
-    let ipv4_packet = Ipv4Packet::new_checked(ether_frame.payload())?;
+    let ipv4_packet = Ipv4Packet::new_checked(buf)?;
 
     if ipv4_packet.next_header() == IpProtocol::Tcp {
         let tcp_packet = TcpPacket::new_checked(ipv4_packet.payload())?;
@@ -286,7 +479,7 @@ fn snoop_tcp_packet(buf: &[u8], sockets: &mut SocketSet<'_>) -> Result<(), smolt
         let is_first = tcp_packet.syn() && !tcp_packet.ack();
         if is_first {
             // create a socket for the first incoming TCP packet, as the later accept() returns.
-            LISTEN_TABLE.incoming_tcp_packet(src_addr, dst_addr, sockets);
+            LISTEN_TABLE.incoming_tcp_packet(src_addr, dst_addr, iface, sockets);
         }
     }
     Ok(())
@@ -301,30 +494,60 @@ pub fn poll_interfaces() {
 }
 
 /// Benchmark raw socket transmit bandwidth.
-pub fn bench_transmit() {
-    ETH0.dev.lock().bench_transmit_bandwidth();
+///
+/// Returns [`Err(Unsupported)`](axerrno::AxError::Unsupported) if there is no
+/// NIC device.
+pub fn bench_transmit() -> AxResult {
+    bench_nic()?.dev.lock().bench_transmit_bandwidth();
+    Ok(())
 }
 
 /// Benchmark raw socket receive bandwidth.
-pub fn bench_receive() {
-    ETH0.dev.lock().bench_receive_bandwidth();
+///
+/// Returns [`Err(Unsupported)`](axerrno::AxError::Unsupported) if there is no
+/// NIC device.
+pub fn bench_receive() -> AxResult {
+    bench_nic()?.dev.lock().bench_receive_bandwidth();
+    Ok(())
+}
+
+fn bench_nic() -> AxResult<&'static InterfaceWrapper<DeviceWrapper>> {
+    ETH0.try_get()
+        .ok_or_else(|| ax_err_type!(Unsupported, "no NIC device to benchmark"))
 }
 
-pub(crate) fn init(net_dev: AxNetDevice) {
-    let ether_addr = EthernetAddress(net_dev.mac_address().0);
-    let eth0 = InterfaceWrapper::new("eth0", net_dev, ether_addr);
+pub(crate) fn init(net_dev: Option<AxNetDevice>) {
+    let lo = InterfaceWrapper::new("lo", LoopbackDev::new(), HardwareAddress::Ip);
+    lo.setup_ip_addr(LOOPBACK_IP, LOOPBACK_PREFIX);
+    lo.setup_loopback_route();
+    LO.init_by(lo);
+    info!("created net interface {:?}:", LO.name());
+    info!("  ip:       {}/{}", LOOPBACK_IP, LOOPBACK_PREFIX);
+
+    if let Some(net_dev) = net_dev {
+        let ip = IP.parse().expect("invalid IP address");
+        let IpAddress::Ipv4(ipv4) = ip;
+        let ether_addr = EthernetAddress(net_dev.mac_address().0);
+        let eth0 = InterfaceWrapper::new(
+            "eth0",
+            DeviceWrapper::new(net_dev, ipv4),
+            HardwareAddress::Ethernet(ether_addr),
+        );
+
+        let gateway = GATEWAY.parse().expect("invalid gateway IP address");
+        eth0.setup_ip_addr(ip, IP_PREFIX);
+        eth0.setup_gateway(gateway);
+        ETH0.init_by(eth0);
 
-    let ip = IP.parse().expect("invalid IP address");
-    let gateway = GATEWAY.parse().expect("invalid gateway IP address");
-    eth0.setup_ip_addr(ip, IP_PREFIX);
-    eth0.setup_gateway(gateway);
+        info!("created net interface {:?}:", ETH0.name());
+        info!("  ether:    {}", ETH0.hardware_address());
+        info!("  ip:       {}/{}", ip, IP_PREFIX);
+        info!("  gateway:  {}", gateway);
+    }
 
-    ETH0.init_by(eth0);
     SOCKET_SET.init_by(SocketSetWrapper::new());
     LISTEN_TABLE.init_by(ListenTable::new());
 
-    info!("created net interface {:?}:", ETH0.name());
-    info!("  ether:    {}", ETH0.ethernet_address());
-    info!("  ip:       {}/{}", ip, IP_PREFIX);
-    info!("  gateway:  {}", gateway);
+    #[cfg(all(feature = "irq", feature = "multitask"))]
+    waiter::init();
 }
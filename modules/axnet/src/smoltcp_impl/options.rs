@@ -0,0 +1,125 @@
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use axerrno::{ax_err, AxResult};
+
+/// Minimum size of socket receive/transmit buffers.
+const MIN_BUF_LEN: usize = 1024;
+/// Maximum size of socket receive/transmit buffers.
+const MAX_BUF_LEN: usize = 4 * 1024 * 1024;
+
+/// An optional [`Duration`] that can be loaded and stored atomically.
+///
+/// `u64::MAX` nanoseconds is used to represent `None`, so zero durations are
+/// still distinguishable (e.g. `SO_LINGER` with a zero timeout).
+pub(crate) struct AtomicDuration(AtomicU64);
+
+impl AtomicDuration {
+    const NONE: u64 = u64::MAX;
+
+    pub const fn none() -> Self {
+        Self(AtomicU64::new(Self::NONE))
+    }
+
+    pub fn load(&self) -> Option<Duration> {
+        match self.0.load(Ordering::Acquire) {
+            Self::NONE => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    pub fn store(&self, dur: Option<Duration>) {
+        let nanos = match dur {
+            Some(dur) => (dur.as_nanos() as u64).min(Self::NONE - 1),
+            None => Self::NONE,
+        };
+        self.0.store(nanos, Ordering::Release);
+    }
+}
+
+/// Options shared by TCP and UDP sockets.
+pub(crate) struct SocketOptions {
+    reuse_addr: AtomicBool,
+    recv_buf_len: AtomicUsize,
+    send_buf_len: AtomicUsize,
+    recv_timeout: AtomicDuration,
+    send_timeout: AtomicDuration,
+}
+
+impl SocketOptions {
+    pub const fn new(recv_buf_len: usize, send_buf_len: usize) -> Self {
+        Self {
+            reuse_addr: AtomicBool::new(false),
+            recv_buf_len: AtomicUsize::new(recv_buf_len),
+            send_buf_len: AtomicUsize::new(send_buf_len),
+            recv_timeout: AtomicDuration::none(),
+            send_timeout: AtomicDuration::none(),
+        }
+    }
+
+    pub fn reuse_addr(&self) -> bool {
+        self.reuse_addr.load(Ordering::Acquire)
+    }
+
+    pub fn set_reuse_addr(&self, reuse: bool) {
+        self.reuse_addr.store(reuse, Ordering::Release);
+    }
+
+    pub fn recv_buf_len(&self) -> usize {
+        self.recv_buf_len.load(Ordering::Acquire)
+    }
+
+    pub fn send_buf_len(&self) -> usize {
+        self.send_buf_len.load(Ordering::Acquire)
+    }
+
+    /// Sets the receive buffer size, clamped to `MIN_BUF_LEN..=MAX_BUF_LEN`.
+    pub fn set_recv_buf_len(&self, len: usize) -> AxResult {
+        self.recv_buf_len
+            .store(clamp_buf_len(len)?, Ordering::Release);
+        Ok(())
+    }
+
+    /// Sets the transmit buffer size, clamped to `MIN_BUF_LEN..=MAX_BUF_LEN`.
+    pub fn set_send_buf_len(&self, len: usize) -> AxResult {
+        self.send_buf_len
+            .store(clamp_buf_len(len)?, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn recv_timeout(&self) -> Option<Duration> {
+        self.recv_timeout.load()
+    }
+
+    pub fn send_timeout(&self) -> Option<Duration> {
+        self.send_timeout.load()
+    }
+
+    /// Sets the receive timeout. A zero duration is treated as `None`.
+    pub fn set_recv_timeout(&self, timeout: Option<Duration>) {
+        self.recv_timeout.store(timeout.filter(|t| !t.is_zero()));
+    }
+
+    /// Sets the transmit timeout. A zero duration is treated as `None`.
+    pub fn set_send_timeout(&self, timeout: Option<Duration>) {
+        self.send_timeout.store(timeout.filter(|t| !t.is_zero()));
+    }
+
+    /// Copies all options from `other`.
+    pub fn inherit(&self, other: &Self) {
+        self.set_reuse_addr(other.reuse_addr());
+        self.recv_buf_len
+            .store(other.recv_buf_len(), Ordering::Release);
+        self.send_buf_len
+            .store(other.send_buf_len(), Ordering::Release);
+        self.recv_timeout.store(other.recv_timeout());
+        self.send_timeout.store(other.send_timeout());
+    }
+}
+
+fn clamp_buf_len(len: usize) -> AxResult<usize> {
+    if len == 0 {
+        return ax_err!(InvalidInput, "socket buffer size must be non-zero");
+    }
+    Ok(len.clamp(MIN_BUF_LEN, MAX_BUF_LEN))
+}
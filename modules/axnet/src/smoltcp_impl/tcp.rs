@@ -2,17 +2,22 @@ use core::cell::UnsafeCell;
 use core::fmt::Debug;
 use core::net::SocketAddr;
 use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use core::time::Duration;
 
 use axerrno::{ax_err, ax_err_type, AxError, AxResult};
 use axio::PollState;
 use axsync::Mutex;
 
-use smoltcp::iface::SocketHandle;
 use smoltcp::socket::tcp::{self, ConnectError, State};
 use smoltcp::wire::{IpEndpoint, IpListenEndpoint};
 
 use super::addr::{from_core_sockaddr, into_core_sockaddr, is_unspecified, UNSPECIFIED_ENDPOINT};
-use super::{SocketSetWrapper, ETH0, LISTEN_TABLE, SOCKET_SET};
+use super::options::{AtomicDuration, SocketOptions};
+use super::waiter::{self, SocketWaiter};
+use super::{
+    route_iface, SocketHandle, SocketSetWrapper, LISTEN_TABLE, SOCKET_SET, TCP_RX_BUF_LEN,
+    TCP_TX_BUF_LEN,
+};
 
 // State transitions:
 // CLOSED -(connect)-> BUSY -> CONNECTING -> CONNECTED -(shutdown)-> BUSY -> CLOSED
@@ -42,34 +47,48 @@ pub struct TcpSocket {
     local_addr: UnsafeCell<IpEndpoint>,
     peer_addr: UnsafeCell<IpEndpoint>,
     nonblock: AtomicBool,
+    opts: SocketOptions,
+    nodelay: AtomicBool,
+    keep_alive: AtomicDuration,
+    linger: AtomicDuration,
+    waiter: SocketWaiter,
 }
 
 unsafe impl Sync for TcpSocket {}
 
 impl TcpSocket {
     /// Creates a new TCP socket.
-    pub const fn new() -> Self {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
         Self {
             state: AtomicU8::new(STATE_CLOSED),
             handle: UnsafeCell::new(None),
             local_addr: UnsafeCell::new(UNSPECIFIED_ENDPOINT),
             peer_addr: UnsafeCell::new(UNSPECIFIED_ENDPOINT),
             nonblock: AtomicBool::new(false),
+            opts: SocketOptions::new(TCP_RX_BUF_LEN, TCP_TX_BUF_LEN),
+            nodelay: AtomicBool::new(false),
+            keep_alive: AtomicDuration::none(),
+            linger: AtomicDuration::none(),
+            waiter: SocketWaiter::new(),
         }
     }
 
     /// Creates a new TCP socket that is already connected.
-    const fn new_connected(
-        handle: SocketHandle,
-        local_addr: IpEndpoint,
-        peer_addr: IpEndpoint,
-    ) -> Self {
+    fn new_connected(handle: SocketHandle, local_addr: IpEndpoint, peer_addr: IpEndpoint) -> Self {
+        let waiter = SocketWaiter::new();
+        waiter.register(handle);
         Self {
             state: AtomicU8::new(STATE_CONNECTED),
             handle: UnsafeCell::new(Some(handle)),
             local_addr: UnsafeCell::new(local_addr),
             peer_addr: UnsafeCell::new(peer_addr),
             nonblock: AtomicBool::new(false),
+            opts: SocketOptions::new(TCP_RX_BUF_LEN, TCP_TX_BUF_LEN),
+            nodelay: AtomicBool::new(false),
+            keep_alive: AtomicDuration::none(),
+            linger: AtomicDuration::none(),
+            waiter,
         }
     }
 
@@ -116,23 +135,166 @@ impl TcpSocket {
         self.nonblock.store(nonblocking, Ordering::Release);
     }
 
+    /// Returns whether the `SO_REUSEADDR` option is set.
+    #[inline]
+    pub fn reuse_address(&self) -> bool {
+        self.opts.reuse_addr()
+    }
+
+    /// Sets the `SO_REUSEADDR` option.
+    ///
+    /// With this option, [`bind`](Self::bind) succeeds even if connections on
+    /// the port are still open, e.g. ones accepted by a closed listener. A port
+    /// that is listened on can never be bound again.
+    #[inline]
+    pub fn set_reuse_address(&self, reuse: bool) {
+        self.opts.set_reuse_addr(reuse);
+    }
+
+    /// Returns the size of the receive buffer in bytes.
+    #[inline]
+    pub fn recv_buffer_size(&self) -> usize {
+        self.opts.recv_buf_len()
+    }
+
+    /// Sets the size of the receive buffer in bytes (`SO_RCVBUF`).
+    ///
+    /// Buffers are allocated when the socket connects or starts listening, so
+    /// it returns [`Err(InvalidInput)`](AxError::InvalidInput) afterwards.
+    pub fn set_recv_buffer_size(&self, size: usize) -> AxResult {
+        self.check_buffer_resizable()?;
+        self.opts.set_recv_buf_len(size)
+    }
+
+    /// Returns the size of the transmit buffer in bytes.
+    #[inline]
+    pub fn send_buffer_size(&self) -> usize {
+        self.opts.send_buf_len()
+    }
+
+    /// Sets the size of the transmit buffer in bytes (`SO_SNDBUF`).
+    ///
+    /// Buffers are allocated when the socket connects or starts listening, so
+    /// it returns [`Err(InvalidInput)`](AxError::InvalidInput) afterwards.
+    pub fn set_send_buffer_size(&self, size: usize) -> AxResult {
+        self.check_buffer_resizable()?;
+        self.opts.set_send_buf_len(size)
+    }
+
+    /// Returns the timeout of [`recv`](Self::recv) and [`accept`](Self::accept).
+    #[inline]
+    pub fn recv_timeout(&self) -> Option<Duration> {
+        self.opts.recv_timeout()
+    }
+
+    /// Sets the timeout of [`recv`](Self::recv) and [`accept`](Self::accept)
+    /// (`SO_RCVTIMEO`).
+    ///
+    /// When the timeout expires, [`Err(WouldBlock)`](AxError::WouldBlock) is
+    /// returned. `None` or a zero duration means blocking indefinitely.
+    #[inline]
+    pub fn set_recv_timeout(&self, timeout: Option<Duration>) {
+        self.opts.set_recv_timeout(timeout);
+    }
+
+    /// Returns the timeout of [`send`](Self::send) and [`connect`](Self::connect).
+    #[inline]
+    pub fn send_timeout(&self) -> Option<Duration> {
+        self.opts.send_timeout()
+    }
+
+    /// Sets the timeout of [`send`](Self::send) and [`connect`](Self::connect)
+    /// (`SO_SNDTIMEO`).
+    ///
+    /// When the timeout expires, [`Err(WouldBlock)`](AxError::WouldBlock) is
+    /// returned. `None` or a zero duration means blocking indefinitely.
+    #[inline]
+    pub fn set_send_timeout(&self, timeout: Option<Duration>) {
+        self.opts.set_send_timeout(timeout);
+    }
+
+    /// Returns whether Nagle's algorithm is disabled (`TCP_NODELAY`).
+    #[inline]
+    pub fn nodelay(&self) -> bool {
+        self.nodelay.load(Ordering::Acquire)
+    }
+
+    /// Disables or enables Nagle's algorithm (`TCP_NODELAY`).
+    pub fn set_nodelay(&self, nodelay: bool) {
+        self.nodelay.store(nodelay, Ordering::Release);
+        self.with_handle(|socket| socket.set_nagle_enabled(!nodelay));
+    }
+
+    /// Returns the keep-alive interval, or `None` if keep-alive is disabled.
+    #[inline]
+    pub fn keep_alive(&self) -> Option<Duration> {
+        self.keep_alive.load()
+    }
+
+    /// Sets the keep-alive interval (`SO_KEEPALIVE`, `TCP_KEEPINTVL`).
+    ///
+    /// `None` disables keep-alive packets.
+    pub fn set_keep_alive(&self, interval: Option<Duration>) {
+        self.keep_alive.store(interval);
+        self.with_handle(|socket| socket.set_keep_alive(interval.map(Into::into)));
+    }
+
+    /// Returns the linger timeout of [`shutdown`](Self::shutdown), or `None`
+    /// if `SO_LINGER` is disabled.
+    #[inline]
+    pub fn linger(&self) -> Option<Duration> {
+        self.linger.load()
+    }
+
+    /// Sets the linger timeout of [`shutdown`](Self::shutdown) (`SO_LINGER`).
+    ///
+    /// With `Some(timeout)`, a blocking [`shutdown`](Self::shutdown) waits
+    /// until all pending data is sent or the timeout expires. A zero timeout
+    /// resets the connection immediately.
+    #[inline]
+    pub fn set_linger(&self, timeout: Option<Duration>) {
+        self.linger.store(timeout);
+    }
+
     /// Connects to the given address and port.
     ///
     /// The local port is generated automatically.
     pub fn connect(&self, remote_addr: SocketAddr) -> AxResult {
         self.update_state(STATE_CLOSED, STATE_CONNECTING, || {
-            // SAFETY: no other threads can read or write these fields.
-            let handle = unsafe { self.handle.get().read() }
-                .unwrap_or_else(|| SOCKET_SET.add(SocketSetWrapper::new_tcp_socket()));
-
             // TODO: check remote addr unreachable
             let remote_endpoint = from_core_sockaddr(remote_addr);
             let bound_endpoint = self.bound_endpoint()?;
-            let iface = &ETH0.iface;
+            let iface = route_iface(remote_endpoint.addr)?;
+
+            // SAFETY: no other threads can read or write these fields.
+            let handle = match unsafe { self.handle.get().read() } {
+                Some(handle) if handle.iface == iface => handle,
+                old => {
+                    // The socket must be in the set of the interface it goes
+                    // through, recreate it if the last connect() went through
+                    // another interface.
+                    if let Some(old) = old {
+                        SOCKET_SET.remove(old);
+                        unsafe { self.handle.get().write(None) };
+                    }
+                    let mut socket = SocketSetWrapper::new_tcp_socket(
+                        self.opts.recv_buf_len(),
+                        self.opts.send_buf_len(),
+                    );
+                    self.apply_options(&mut socket);
+                    let handle = SOCKET_SET.add(iface, socket);
+                    self.waiter.register(handle);
+                    handle
+                }
+            };
             let (local_endpoint, remote_endpoint) = SOCKET_SET
                 .with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
                     socket
-                        .connect(iface.lock().context(), remote_endpoint, bound_endpoint)
+                        .connect(
+                            iface.iface().lock().context(),
+                            remote_endpoint,
+                            bound_endpoint,
+                        )
                         .or_else(|e| match e {
                             ConnectError::InvalidState => {
                                 ax_err!(BadState, "socket connect() failed")
@@ -161,7 +323,7 @@ impl TcpSocket {
         if self.is_nonblocking() {
             Err(AxError::WouldBlock)
         } else {
-            self.block_on(|| {
+            self.block_on(self.send_timeout(), || {
                 let PollState { writable, .. } = self.poll_connect()?;
                 if !writable {
                     Err(AxError::WouldBlock)
@@ -182,9 +344,10 @@ impl TcpSocket {
     /// [`accept`](Self::accept).
     pub fn bind(&self, mut local_addr: SocketAddr) -> AxResult {
         self.update_state(STATE_CLOSED, STATE_CLOSED, || {
-            // TODO: check addr is available
             if local_addr.port() == 0 {
                 local_addr.set_port(get_ephemeral_port()?);
+            } else if !LISTEN_TABLE.can_bind(local_addr.port(), self.reuse_address()) {
+                return ax_err!(AddrInUse, "socket bind() failed");
             }
             // SAFETY: no other threads can read or write `self.local_addr` as we
             // have changed the state to `BUSY`.
@@ -210,7 +373,12 @@ impl TcpSocket {
             unsafe {
                 (*self.local_addr.get()).port = bound_endpoint.port;
             }
-            LISTEN_TABLE.listen(bound_endpoint)?;
+            LISTEN_TABLE.listen(
+                bound_endpoint,
+                self.opts.recv_buf_len(),
+                self.opts.send_buf_len(),
+                self.waiter.clone(),
+            )?;
             debug!("TCP socket listening on {}", bound_endpoint);
             Ok(())
         })
@@ -230,10 +398,12 @@ impl TcpSocket {
 
         // SAFETY: `self.local_addr` should be initialized after `bind()`.
         let local_port = unsafe { self.local_addr.get().read().port };
-        self.block_on(|| {
+        self.block_on(self.recv_timeout(), || {
             let (handle, (local_addr, peer_addr)) = LISTEN_TABLE.accept(local_port)?;
             debug!("TCP socket accepted a new connection {}", peer_addr);
-            Ok(TcpSocket::new_connected(handle, local_addr, peer_addr))
+            let socket = TcpSocket::new_connected(handle, local_addr, peer_addr);
+            socket.inherit_options(self);
+            Ok(socket)
         })
     }
 
@@ -244,12 +414,20 @@ impl TcpSocket {
             // SAFETY: `self.handle` should be initialized in a connected socket, and
             // no other threads can read or write it.
             let handle = unsafe { self.handle.get().read().unwrap() };
+            let linger = self.linger();
             SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
                 debug!("TCP socket {}: shutting down", handle);
-                socket.close();
+                if linger == Some(Duration::ZERO) {
+                    socket.abort();
+                } else {
+                    socket.close();
+                }
             });
             unsafe { self.local_addr.get().write(UNSPECIFIED_ENDPOINT) }; // clear bound address
             SOCKET_SET.poll_interfaces();
+            if let Some(timeout) = linger.filter(|t| !t.is_zero() && !self.is_nonblocking()) {
+                self.wait_for_send_queue(handle, timeout);
+            }
             Ok(())
         })
         .unwrap_or(Ok(()))?;
@@ -280,7 +458,7 @@ impl TcpSocket {
 
         // SAFETY: `self.handle` should be initialized in a connected socket.
         let handle = unsafe { self.handle.get().read().unwrap() };
-        self.block_on(|| {
+        self.block_on(self.recv_timeout(), || {
             SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
                 if !socket.is_active() {
                     // not open
@@ -303,6 +481,102 @@ impl TcpSocket {
         })
     }
 
+    /// Receives data in place in the receive buffer of the socket, saving the
+    /// copy into a user buffer.
+    ///
+    /// `f` is lent the received bytes in the receive buffer, and returns how
+    /// many of them it consumed along with a value passed back to the caller.
+    /// It may be lent fewer bytes than are queued, as the buffer is a ring. An
+    /// empty slice is lent if the connection has been closed.
+    ///
+    /// This is not zero-copy: unlike
+    /// [`UdpSocket::recv_buf_from`](super::UdpSocket::recv_buf_from), the data
+    /// has been copied once from the NIC, since smoltcp reassembles the
+    /// segments in its receive buffer.
+    pub fn recv_in_place<F, R>(&self, f: F) -> AxResult<R>
+    where
+        F: FnOnce(&[u8]) -> (usize, R),
+    {
+        if self.is_connecting() {
+            return Err(AxError::WouldBlock);
+        } else if !self.is_connected() {
+            return ax_err!(NotConnected, "socket recv_in_place() failed");
+        }
+
+        // SAFETY: `self.handle` should be initialized in a connected socket.
+        let handle = unsafe { self.handle.get().read().unwrap() };
+        let mut f = Some(f);
+        self.block_on(self.recv_timeout(), || {
+            SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                if !socket.is_active() {
+                    // not open
+                    ax_err!(ConnectionRefused, "socket recv_in_place() failed")
+                } else if !socket.may_recv() {
+                    // connection closed
+                    Ok(f.take().unwrap()(&[]).1)
+                } else if socket.recv_queue() > 0 {
+                    // data available
+                    let f = f.take().unwrap();
+                    socket
+                        .recv(|buf| {
+                            let (len, ret) = f(buf);
+                            (len.min(buf.len()), ret)
+                        })
+                        .map_err(|_| ax_err_type!(BadState, "socket recv_in_place() failed"))
+                } else {
+                    // no more data
+                    Err(AxError::WouldBlock)
+                }
+            })
+        })
+    }
+
+    /// Transmits data filled in place in the transmit buffer of the socket,
+    /// saving the copy from a user buffer.
+    ///
+    /// `f` is lent the free space of the transmit buffer to fill, and returns
+    /// how many bytes it wrote along with a value passed back to the caller.
+    /// It may be lent less space than is free, as the buffer is a ring.
+    ///
+    /// This is not zero-copy: smoltcp still copies the data into the NIC's
+    /// buffers when the segments are transmitted.
+    pub fn send_in_place<F, R>(&self, f: F) -> AxResult<R>
+    where
+        F: FnOnce(&mut [u8]) -> (usize, R),
+    {
+        if self.is_connecting() {
+            return Err(AxError::WouldBlock);
+        } else if !self.is_connected() {
+            return ax_err!(NotConnected, "socket send_in_place() failed");
+        }
+
+        // SAFETY: `self.handle` should be initialized in a connected socket.
+        let handle = unsafe { self.handle.get().read().unwrap() };
+        let mut f = Some(f);
+        self.block_on(self.send_timeout(), || {
+            SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                if !socket.is_active() || !socket.may_send() {
+                    // closed by remote
+                    ax_err!(ConnectionReset, "socket send_in_place() failed")
+                } else if socket.can_send() {
+                    // connected, and the tx buffer is not full
+                    let f = f.take().unwrap();
+                    let ret = socket
+                        .send(|buf| {
+                            let (len, ret) = f(buf);
+                            (len.min(buf.len()), ret)
+                        })
+                        .map_err(|_| ax_err_type!(BadState, "socket send_in_place() failed"))?;
+                    waiter::notify_poller();
+                    Ok(ret)
+                } else {
+                    // tx buffer is full
+                    Err(AxError::WouldBlock)
+                }
+            })
+        })
+    }
+
     /// Transmits data in the given buffer.
     pub fn send(&self, buf: &[u8]) -> AxResult<usize> {
         if self.is_connecting() {
@@ -313,7 +587,7 @@ impl TcpSocket {
 
         // SAFETY: `self.handle` should be initialized in a connected socket.
         let handle = unsafe { self.handle.get().read().unwrap() };
-        self.block_on(|| {
+        self.block_on(self.send_timeout(), || {
             SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
                 if !socket.is_active() || !socket.may_send() {
                     // closed by remote
@@ -324,6 +598,7 @@ impl TcpSocket {
                     let len = socket
                         .send_slice(buf)
                         .map_err(|_| ax_err_type!(BadState, "socket send() failed"))?;
+                    waiter::notify_poller();
                     Ok(len)
                 } else {
                     // tx buffer is full
@@ -339,10 +614,7 @@ impl TcpSocket {
             STATE_CONNECTING => self.poll_connect(),
             STATE_CONNECTED => self.poll_stream(),
             STATE_LISTENING => self.poll_listener(),
-            _ => Ok(PollState {
-                readable: false,
-                writable: false,
-            }),
+            _ => Ok(PollState::new(false, false).with_hangup(self.is_hung_up())),
         }
     }
 }
@@ -403,6 +675,64 @@ impl TcpSocket {
         self.get_state() == STATE_LISTENING
     }
 
+    /// Whether the connection has been closed, or has failed to be
+    /// established. A socket that never connected is not hung up.
+    fn is_hung_up(&self) -> bool {
+        // SAFETY: only checks whether the handle has been created.
+        let has_handle = unsafe { self.handle.get().read().is_some() };
+        has_handle && self.get_state() == STATE_CLOSED
+    }
+
+    fn check_buffer_resizable(&self) -> AxResult {
+        // SAFETY: only checks whether the handle has been created.
+        let has_handle = unsafe { self.handle.get().read().is_some() };
+        if has_handle || self.get_state() != STATE_CLOSED {
+            return ax_err!(
+                InvalidInput,
+                "socket buffer size can only be set before connect() or listen()"
+            );
+        }
+        Ok(())
+    }
+
+    /// Calls `f` on the underlying smoltcp socket if it has been created.
+    fn with_handle<F: FnOnce(&mut tcp::Socket)>(&self, f: F) {
+        // SAFETY: the handle is never changed once created.
+        if let Some(handle) = unsafe { self.handle.get().read() } {
+            SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, f);
+        }
+    }
+
+    /// Applies TCP options to a newly created smoltcp socket.
+    fn apply_options(&self, socket: &mut tcp::Socket) {
+        socket.set_nagle_enabled(!self.nodelay());
+        socket.set_keep_alive(self.keep_alive().map(Into::into));
+    }
+
+    /// Copies all options from the listening socket to an accepted socket.
+    fn inherit_options(&self, listener: &TcpSocket) {
+        self.opts.inherit(&listener.opts);
+        self.nodelay.store(listener.nodelay(), Ordering::Release);
+        self.keep_alive.store(listener.keep_alive());
+        self.linger.store(listener.linger());
+        self.with_handle(|socket| self.apply_options(socket));
+    }
+
+    /// Waits until all pending data is sent or the timeout expires.
+    fn wait_for_send_queue(&self, handle: SocketHandle, timeout: Duration) {
+        self.waiter
+            .block_on(Some(timeout), || {
+                let pending = SOCKET_SET
+                    .with_socket::<tcp::Socket, _, _>(handle, |socket| socket.send_queue() > 0);
+                if pending {
+                    Err(AxError::WouldBlock)
+                } else {
+                    Ok(())
+                }
+            })
+            .ok();
+    }
+
     fn bound_endpoint(&self) -> AxResult<IpListenEndpoint> {
         // SAFETY: no other threads can read or write `self.local_addr`.
         let local_addr = unsafe { self.local_addr.get().read() };
@@ -444,52 +774,41 @@ impl TcpSocket {
                     true
                 }
             });
-        Ok(PollState {
-            readable: false,
-            writable,
-        })
+        Ok(PollState::new(false, writable).with_hangup(self.is_hung_up()))
     }
 
     fn poll_stream(&self) -> AxResult<PollState> {
         // SAFETY: `self.handle` should be initialized in a connected socket.
         let handle = unsafe { self.handle.get().read().unwrap() };
         SOCKET_SET.with_socket::<tcp::Socket, _, _>(handle, |socket| {
-            Ok(PollState {
-                readable: !socket.may_recv() || socket.can_recv(),
-                writable: !socket.may_send() || socket.can_send(),
-            })
+            let readable = !socket.may_recv() || socket.can_recv();
+            let writable = !socket.may_send() || socket.can_send();
+            let hangup = !socket.may_recv() && !socket.may_send();
+            Ok(PollState::new(readable, writable).with_hangup(hangup))
         })
     }
 
     fn poll_listener(&self) -> AxResult<PollState> {
         // SAFETY: `self.local_addr` should be initialized in a listening socket.
         let local_addr = unsafe { self.local_addr.get().read() };
-        Ok(PollState {
-            readable: LISTEN_TABLE.can_accept(local_addr.port)?,
-            writable: false,
-        })
+        let readable = LISTEN_TABLE.can_accept(local_addr.port)?;
+        Ok(PollState::new(readable, false))
     }
 
     /// Block the current thread until the given function completes or fails.
     ///
     /// If the socket is non-blocking, it calls the function once and returns
     /// immediately. Otherwise, it may call the function multiple times if it
-    /// returns [`Err(WouldBlock)`](AxError::WouldBlock).
-    fn block_on<F, T>(&self, mut f: F) -> AxResult<T>
+    /// returns [`Err(WouldBlock)`](AxError::WouldBlock), until the optional
+    /// `timeout` expires.
+    fn block_on<F, T>(&self, timeout: Option<Duration>, mut f: F) -> AxResult<T>
     where
         F: FnMut() -> AxResult<T>,
     {
         if self.is_nonblocking() {
             f()
         } else {
-            loop {
-                SOCKET_SET.poll_interfaces();
-                match f() {
-                    Ok(t) => return Ok(t),
-                    Err(AxError::WouldBlock) => axtask::yield_now(),
-                    Err(e) => return Err(e),
-                }
-            }
+            self.waiter.block_on(timeout, f)
         }
     }
 }
@@ -532,22 +851,15 @@ impl Debug for TcpSocket {
 fn get_ephemeral_port() -> AxResult<u16> {
     const PORT_START: u16 = 0xc000;
     const PORT_END: u16 = 0xffff;
-    static CURR: Mutex<u16> = Mutex::new(PORT_START);
-
-    let mut curr = CURR.lock();
-    let mut tries = 0;
-    // TODO: more robust
-    while tries <= PORT_END - PORT_START {
-        let port = *curr;
-        if *curr == PORT_END {
-            *curr = PORT_START;
-        } else {
-            *curr += 1;
-        }
+    const NUM_PORTS: u32 = (PORT_END - PORT_START) as u32 + 1;
+
+    // Probe from a random port, so that the ports are hard to guess.
+    let offset = (axrand::random_u64() % NUM_PORTS as u64) as u32;
+    for i in 0..NUM_PORTS {
+        let port = PORT_START + ((offset + i) % NUM_PORTS) as u16;
         if LISTEN_TABLE.can_listen(port) {
             return Ok(port);
         }
-        tries += 1;
     }
     ax_err!(AddrInUse, "no avaliable ports!")
 }
@@ -1,37 +1,67 @@
+use alloc::{sync::Arc, vec::Vec};
 use core::net::SocketAddr;
 use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
 
 use axerrno::{ax_err, ax_err_type, AxError, AxResult};
 use axio::PollState;
 use axsync::Mutex;
 use spin::RwLock;
 
-use smoltcp::iface::SocketHandle;
 use smoltcp::socket::udp::{self, BindError, SendError};
 use smoltcp::wire::{IpEndpoint, IpListenEndpoint};
 
 use super::addr::{from_core_sockaddr, into_core_sockaddr, is_unspecified, UNSPECIFIED_ENDPOINT};
-use super::{SocketSetWrapper, SOCKET_SET};
+use super::options::SocketOptions;
+use super::waiter::{self, SocketWaiter};
+use super::zero_copy::{RecvQueue, UdpRecvBuf};
+use super::{route_iface, IfaceId, SocketHandle, SocketSetWrapper, SOCKET_SET};
+use super::{UDP_RX_BUF_LEN, UDP_TX_BUF_LEN};
+
+/// Where [`UdpSocket::recv_impl`] receives a datagram from.
+enum RecvSource<'a, 'b> {
+    /// The datagrams queued from the NIC, which may be empty.
+    Nic(&'a RecvQueue),
+    /// A smoltcp socket that has datagrams.
+    Smoltcp(&'a mut udp::Socket<'b>),
+}
 
 /// A UDP socket that provides POSIX-like APIs.
 pub struct UdpSocket {
-    handle: SocketHandle,
+    /// One smoltcp socket for each interface, since a socket bound to the
+    /// unspecified address receives from all of them.
+    handles: Vec<SocketHandle>,
+    /// Datagrams received from the NIC without going through smoltcp.
+    nic_queue: Arc<RecvQueue>,
     local_addr: RwLock<Option<IpEndpoint>>,
     peer_addr: RwLock<Option<IpEndpoint>>,
     nonblock: AtomicBool,
+    opts: SocketOptions,
+    waiter: SocketWaiter,
 }
 
 impl UdpSocket {
     /// Creates a new UDP socket.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        let socket = SocketSetWrapper::new_udp_socket();
-        let handle = SOCKET_SET.add(socket);
+        let waiter = SocketWaiter::new();
+        let handles = IfaceId::all()
+            .iter()
+            .map(|&iface| {
+                let socket = SocketSetWrapper::new_udp_socket(UDP_RX_BUF_LEN, UDP_TX_BUF_LEN);
+                let handle = SOCKET_SET.add(iface, socket);
+                waiter.register(handle);
+                handle
+            })
+            .collect();
         Self {
-            handle,
+            handles,
+            nic_queue: Arc::new(RecvQueue::new(waiter.clone())),
             local_addr: RwLock::new(None),
             peer_addr: RwLock::new(None),
             nonblock: AtomicBool::new(false),
+            opts: SocketOptions::new(UDP_RX_BUF_LEN, UDP_TX_BUF_LEN),
+            waiter,
         }
     }
 
@@ -69,6 +99,79 @@ impl UdpSocket {
         self.nonblock.store(nonblocking, Ordering::Release);
     }
 
+    /// Returns whether the `SO_REUSEADDR` option is set.
+    #[inline]
+    pub fn reuse_address(&self) -> bool {
+        self.opts.reuse_addr()
+    }
+
+    /// Sets the `SO_REUSEADDR` option.
+    ///
+    /// Bound ports are not tracked by the stack, so this option is only
+    /// recorded for [`reuse_address`](Self::reuse_address).
+    #[inline]
+    pub fn set_reuse_address(&self, reuse: bool) {
+        self.opts.set_reuse_addr(reuse);
+    }
+
+    /// Returns the size of the receive buffer in bytes.
+    #[inline]
+    pub fn recv_buffer_size(&self) -> usize {
+        self.opts.recv_buf_len()
+    }
+
+    /// Sets the size of the receive buffer in bytes (`SO_RCVBUF`).
+    ///
+    /// It must be called before [`bind`](Self::bind), otherwise
+    /// [`Err(InvalidInput)`](AxError::InvalidInput) is returned.
+    pub fn set_recv_buffer_size(&self, size: usize) -> AxResult {
+        self.resize_buffers(|| self.opts.set_recv_buf_len(size))
+    }
+
+    /// Returns the size of the transmit buffer in bytes.
+    #[inline]
+    pub fn send_buffer_size(&self) -> usize {
+        self.opts.send_buf_len()
+    }
+
+    /// Sets the size of the transmit buffer in bytes (`SO_SNDBUF`).
+    ///
+    /// It must be called before [`bind`](Self::bind), otherwise
+    /// [`Err(InvalidInput)`](AxError::InvalidInput) is returned.
+    pub fn set_send_buffer_size(&self, size: usize) -> AxResult {
+        self.resize_buffers(|| self.opts.set_send_buf_len(size))
+    }
+
+    /// Returns the timeout of receive operations.
+    #[inline]
+    pub fn recv_timeout(&self) -> Option<Duration> {
+        self.opts.recv_timeout()
+    }
+
+    /// Sets the timeout of receive operations (`SO_RCVTIMEO`).
+    ///
+    /// When the timeout expires, [`Err(WouldBlock)`](AxError::WouldBlock) is
+    /// returned. `None` or a zero duration means blocking indefinitely.
+    #[inline]
+    pub fn set_recv_timeout(&self, timeout: Option<Duration>) {
+        self.opts.set_recv_timeout(timeout);
+    }
+
+    /// Returns the timeout of send operations.
+    #[inline]
+    pub fn send_timeout(&self) -> Option<Duration> {
+        self.opts.send_timeout()
+    }
+
+    /// Sets the timeout of send operations (`SO_SNDTIMEO`).
+    ///
+    /// When the timeout expires, [`Err(WouldBlock)`](AxError::WouldBlock) is
+    /// returned. `None` or a zero duration means blocking indefinitely.
+    #[inline]
+    pub fn set_send_timeout(&self, timeout: Option<Duration>) {
+        self.opts.set_send_timeout(timeout);
+    }
+
     /// Binds an unbound socket to the given address and port.
     ///
     /// It's must be called before [`send_to`](Self::send_to) and
@@ -88,15 +191,18 @@ impl UdpSocket {
             addr: (!is_unspecified(local_endpoint.addr)).then_some(local_endpoint.addr),
             port: local_endpoint.port,
         };
-        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-            socket.bind(endpoint).or_else(|e| match e {
-                BindError::InvalidState => ax_err!(AlreadyExists, "socket bind() failed"),
-                BindError::Unaddressable => ax_err!(InvalidInput, "socket bind() failed"),
-            })
-        })?;
+        for &handle in &self.handles {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                socket.bind(endpoint).or_else(|e| match e {
+                    BindError::InvalidState => ax_err!(AlreadyExists, "socket bind() failed"),
+                    BindError::Unaddressable => ax_err!(InvalidInput, "socket bind() failed"),
+                })
+            })?;
+        }
 
+        self.nic_queue.register(endpoint.addr, endpoint.port);
         *self_local_addr = Some(local_endpoint);
-        debug!("UDP socket {}: bound on {}", self.handle, endpoint);
+        debug!("UDP socket {}: bound on {}", self.handles[0], endpoint);
         Ok(())
     }
 
@@ -112,18 +218,64 @@ impl UdpSocket {
     /// Receives a single datagram message on the socket. On success, returns
     /// the number of bytes read and the origin.
     pub fn recv_from(&self, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)> {
-        self.recv_impl(|socket| match socket.recv_slice(buf) {
-            Ok((len, meta)) => Ok((len, into_core_sockaddr(meta.endpoint))),
-            Err(_) => ax_err!(BadState, "socket recv_from() failed"),
+        self.recv_impl(|source| match source {
+            RecvSource::Nic(queue) => {
+                let (data, src) = queue.pop()?;
+                Some(Ok((copy_payload(buf, &data), into_core_sockaddr(src))))
+            }
+            RecvSource::Smoltcp(socket) => Some(match socket.recv_slice(buf) {
+                Ok((len, meta)) => Ok((len, into_core_sockaddr(meta.endpoint))),
+                Err(_) => ax_err!(BadState, "socket recv_from() failed"),
+            }),
         })
     }
 
+    /// Receives a single datagram message on the socket without copying it.
+    /// On success, returns the datagram and the origin.
+    ///
+    /// Datagrams from the NIC are lent in place in its receive buffer, which
+    /// is given back when the returned [`UdpRecvBuf`] is dropped. Others are
+    /// copied, see [`UdpRecvBuf::is_zero_copy`].
+    pub fn recv_buf_from(&self) -> AxResult<(UdpRecvBuf, SocketAddr)> {
+        self.recv_impl(|source| match source {
+            RecvSource::Nic(queue) => {
+                let (data, src) = queue.pop()?;
+                Some(Ok((data, into_core_sockaddr(src))))
+            }
+            RecvSource::Smoltcp(socket) => Some(match socket.recv() {
+                Ok((buf, meta)) => Ok((UdpRecvBuf::copied(buf), into_core_sockaddr(meta.endpoint))),
+                Err(_) => ax_err!(BadState, "socket recv_buf_from() failed"),
+            }),
+        })
+    }
+
+    /// Sends a datagram message of `size` bytes to the given address, whose
+    /// payload is filled by `f` in place in the transmit buffer of the socket.
+    /// On success, returns the number of bytes written.
+    ///
+    /// This saves the copy from a user buffer, but is not zero-copy: smoltcp
+    /// still copies the datagram into the NIC's buffer when it is transmitted.
+    pub fn send_in_place_to<F>(&self, size: usize, remote_addr: SocketAddr, f: F) -> AxResult<usize>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        if remote_addr.port() == 0 || remote_addr.ip().is_unspecified() {
+            return ax_err!(InvalidInput, "socket send_in_place_to() failed: invalid address");
+        }
+        self.send_in_place_impl(size, from_core_sockaddr(remote_addr), f)
+    }
+
     /// Receives a single datagram message on the socket, without removing it from
     /// the queue. On success, returns the number of bytes read and the origin.
     pub fn peek_from(&self, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)> {
-        self.recv_impl(|socket| match socket.peek_slice(buf) {
-            Ok((len, meta)) => Ok((len, into_core_sockaddr(meta.endpoint))),
-            Err(_) => ax_err!(BadState, "socket recv_from() failed"),
+        self.recv_impl(|source| match source {
+            RecvSource::Nic(queue) => {
+                queue.peek(|data, src| Ok((copy_payload(buf, data), into_core_sockaddr(src))))
+            }
+            RecvSource::Smoltcp(socket) => Some(match socket.peek_slice(buf) {
+                Ok((len, meta)) => Ok((len, into_core_sockaddr(meta.endpoint))),
+                Err(_) => ax_err!(BadState, "socket recv_from() failed"),
+            }),
         })
     }
 
@@ -142,7 +294,7 @@ impl UdpSocket {
         }
 
         *self_peer_addr = Some(from_core_sockaddr(addr));
-        debug!("UDP socket {}: connected to {}", self.handle, addr);
+        debug!("UDP socket {}: connected to {}", self.handles[0], addr);
         Ok(())
     }
 
@@ -152,30 +304,71 @@ impl UdpSocket {
         self.send_impl(buf, remote_endpoint)
     }
 
+    /// Sends a datagram message of `size` bytes to the remote address to which
+    /// it is connected, whose payload is filled by `f` in place in the transmit
+    /// buffer of the socket.
+    ///
+    /// See [`send_in_place_to`](Self::send_in_place_to) for what is copied.
+    pub fn send_in_place<F>(&self, size: usize, f: F) -> AxResult<usize>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let remote_endpoint = self.remote_endpoint()?;
+        self.send_in_place_impl(size, remote_endpoint, f)
+    }
+
     /// Receives a single datagram message on the socket from the remote address
     /// to which it is connected. On success, returns the number of bytes read.
     pub fn recv(&self, buf: &mut [u8]) -> AxResult<usize> {
         let remote_endpoint = self.remote_endpoint()?;
-        self.recv_impl(|socket| {
-            let (len, meta) = socket
-                .recv_slice(buf)
-                .map_err(|_| ax_err_type!(BadState, "socket recv() failed"))?;
-            if !is_unspecified(remote_endpoint.addr) && remote_endpoint.addr != meta.endpoint.addr {
-                return Err(AxError::WouldBlock);
-            }
-            if remote_endpoint.port != 0 && remote_endpoint.port != meta.endpoint.port {
-                return Err(AxError::WouldBlock);
-            }
-            Ok(len)
+        self.recv_impl(|source| match source {
+            RecvSource::Nic(queue) => loop {
+                // Datagrams from others are dropped.
+                let (data, src) = queue.pop()?;
+                if is_from(remote_endpoint, src) {
+                    return Some(Ok(copy_payload(buf, &data)));
+                }
+            },
+            RecvSource::Smoltcp(socket) => Some(match socket.recv_slice(buf) {
+                Ok((len, meta)) if is_from(remote_endpoint, meta.endpoint) => Ok(len),
+                Ok(_) => Err(AxError::WouldBlock),
+                Err(_) => ax_err!(BadState, "socket recv() failed"),
+            }),
+        })
+    }
+
+    /// Receives a single datagram message on the socket from the remote address
+    /// to which it is connected, without copying it.
+    ///
+    /// See [`recv_buf_from`](Self::recv_buf_from) for how the datagram is
+    /// lent.
+    pub fn recv_buf(&self) -> AxResult<UdpRecvBuf> {
+        let remote_endpoint = self.remote_endpoint()?;
+        self.recv_impl(|source| match source {
+            RecvSource::Nic(queue) => loop {
+                let (data, src) = queue.pop()?;
+                if is_from(remote_endpoint, src) {
+                    return Some(Ok(data));
+                }
+            },
+            RecvSource::Smoltcp(socket) => Some(match socket.recv() {
+                Ok((buf, meta)) if is_from(remote_endpoint, meta.endpoint) => {
+                    Ok(UdpRecvBuf::copied(buf))
+                }
+                Ok(_) => Err(AxError::WouldBlock),
+                Err(_) => ax_err!(BadState, "socket recv_buf() failed"),
+            }),
         })
     }
 
     /// Close the socket.
     pub fn shutdown(&self) -> AxResult {
-        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-            debug!("UDP socket {}: shutting down", self.handle);
-            socket.close();
-        });
+        for &handle in &self.handles {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                debug!("UDP socket {}: shutting down", handle);
+                socket.close();
+            });
+        }
         SOCKET_SET.poll_interfaces();
         Ok(())
     }
@@ -183,17 +376,16 @@ impl UdpSocket {
     /// Whether the socket is readable or writable.
     pub fn poll(&self) -> AxResult<PollState> {
         if self.local_addr.read().is_none() {
-            return Ok(PollState {
-                readable: false,
-                writable: false,
+            return Ok(PollState::new(false, false));
+        }
+        let (mut readable, mut writable) = (!self.nic_queue.is_empty(), true);
+        for &handle in &self.handles {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                readable |= socket.can_recv();
+                writable &= socket.can_send();
             });
         }
-        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-            Ok(PollState {
-                readable: socket.can_recv(),
-                writable: socket.can_send(),
-            })
-        })
+        Ok(PollState::new(readable, writable))
     }
 }
 
@@ -207,22 +399,36 @@ impl UdpSocket {
     }
 
     fn send_impl(&self, buf: &[u8], remote_endpoint: IpEndpoint) -> AxResult<usize> {
+        self.send_in_place_impl(buf.len(), remote_endpoint, |dst| dst.copy_from_slice(buf))
+    }
+
+    fn send_in_place_impl<F>(&self, size: usize, remote_endpoint: IpEndpoint, f: F) -> AxResult<usize>
+    where
+        F: FnOnce(&mut [u8]),
+    {
         if self.local_addr.read().is_none() {
             return ax_err!(NotConnected, "socket send() failed");
         }
+        // It would never fit in the transmit buffer.
+        if size > self.opts.send_buf_len() {
+            return ax_err!(MessageTooLong, "socket send() failed");
+        }
 
-        self.block_on(|| {
-            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
+        let iface = route_iface(remote_endpoint.addr)?;
+        let handle = *self.handles.iter().find(|h| h.iface == iface).unwrap();
+        let mut f = Some(f);
+        self.block_on(self.send_timeout(), || {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
                 if socket.can_send() {
-                    socket
-                        .send_slice(buf, remote_endpoint)
-                        .map_err(|e| match e {
-                            SendError::BufferFull => AxError::WouldBlock,
-                            SendError::Unaddressable => {
-                                ax_err_type!(ConnectionRefused, "socket send() failed")
-                            }
-                        })?;
-                    Ok(buf.len())
+                    let buf = socket.send(size, remote_endpoint).map_err(|e| match e {
+                        SendError::BufferFull => AxError::WouldBlock,
+                        SendError::Unaddressable => {
+                            ax_err_type!(ConnectionRefused, "socket send() failed")
+                        }
+                    })?;
+                    f.take().unwrap()(buf);
+                    waiter::notify_poller();
+                    Ok(size)
                 } else {
                     // tx buffer is full
                     Err(AxError::WouldBlock)
@@ -231,64 +437,108 @@ impl UdpSocket {
         })
     }
 
+    /// Receives with `op` from the datagrams queued from the NIC, and then
+    /// from the smoltcp sockets that have datagrams.
     fn recv_impl<F, T>(&self, mut op: F) -> AxResult<T>
     where
-        F: FnMut(&mut udp::Socket) -> AxResult<T>,
+        F: FnMut(RecvSource) -> Option<AxResult<T>>,
     {
         if self.local_addr.read().is_none() {
             return ax_err!(NotConnected, "socket send() failed");
         }
 
-        self.block_on(|| {
-            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-                if socket.can_recv() {
-                    // data available
-                    op(socket)
-                } else {
-                    // no more data
-                    Err(AxError::WouldBlock)
+        self.block_on(self.recv_timeout(), || {
+            // The queued datagrams are older than those in smoltcp, see
+            // `zero_copy`.
+            if let Some(res) = op(RecvSource::Nic(&self.nic_queue)) {
+                return res;
+            }
+            for &handle in &self.handles {
+                let res = SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                    if socket.can_recv() {
+                        // data available
+                        op(RecvSource::Smoltcp(socket))
+                    } else {
+                        if handle.iface == IfaceId::Eth0 {
+                            self.nic_queue.set_drained();
+                        }
+                        None
+                    }
+                });
+                if let Some(res) = res {
+                    return res;
                 }
-            })
+            }
+            // no more data
+            Err(AxError::WouldBlock)
         })
     }
 
-    fn block_on<F, T>(&self, mut f: F) -> AxResult<T>
+    /// Updates the buffer sizes with `f`, then replaces the unbound smoltcp
+    /// socket with one using the new sizes.
+    fn resize_buffers<F>(&self, f: F) -> AxResult
+    where
+        F: FnOnce() -> AxResult,
+    {
+        let local_addr = self.local_addr.write();
+        if local_addr.is_some() {
+            return ax_err!(
+                InvalidInput,
+                "socket buffer size can only be set before bind()"
+            );
+        }
+        f()?;
+        let (rx_len, tx_len) = (self.opts.recv_buf_len(), self.opts.send_buf_len());
+        for &handle in &self.handles {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                *socket = SocketSetWrapper::new_udp_socket(rx_len, tx_len);
+            });
+        }
+        Ok(())
+    }
+
+    fn block_on<F, T>(&self, timeout: Option<Duration>, mut f: F) -> AxResult<T>
     where
         F: FnMut() -> AxResult<T>,
     {
         if self.is_nonblocking() {
             f()
         } else {
-            loop {
-                SOCKET_SET.poll_interfaces();
-                match f() {
-                    Ok(t) => return Ok(t),
-                    Err(AxError::WouldBlock) => axtask::yield_now(),
-                    Err(e) => return Err(e),
-                }
-            }
+            self.waiter.block_on(timeout, f)
         }
     }
 }
 
 impl Drop for UdpSocket {
     fn drop(&mut self) {
+        if let Some(local_addr) = *self.local_addr.read() {
+            self.nic_queue.unregister(local_addr.port);
+        }
         self.shutdown().ok();
-        SOCKET_SET.remove(self.handle);
+        for &handle in &self.handles {
+            SOCKET_SET.remove(handle);
+        }
     }
 }
 
+/// Returns whether a datagram from `src` passes the filter of a socket
+/// connected to `peer`.
+fn is_from(peer: IpEndpoint, src: IpEndpoint) -> bool {
+    (is_unspecified(peer.addr) || peer.addr == src.addr)
+        && (peer.port == 0 || peer.port == src.port)
+}
+
+/// Copies as much of the payload as fits into `buf`, the rest is discarded.
+fn copy_payload(buf: &mut [u8], payload: &[u8]) -> usize {
+    let len = buf.len().min(payload.len());
+    buf[..len].copy_from_slice(&payload[..len]);
+    len
+}
+
 fn get_ephemeral_port() -> AxResult<u16> {
     const PORT_START: u16 = 0xc000;
     const PORT_END: u16 = 0xffff;
-    static CURR: Mutex<u16> = Mutex::new(PORT_START);
-    let mut curr = CURR.lock();
-
-    let port = *curr;
-    if *curr == PORT_END {
-        *curr = PORT_START;
-    } else {
-        *curr += 1;
-    }
-    Ok(port)
+    const NUM_PORTS: u64 = (PORT_END - PORT_START) as u64 + 1;
+    // Random, so that the ports are hard to guess.
+    Ok(PORT_START + (axrand::random_u64() % NUM_PORTS) as u16)
 }
@@ -0,0 +1,346 @@
+//! Blocking support of sockets.
+//!
+//! When both `irq` and `multitask` features are enabled and the NIC raises
+//! interrupts, each socket has its own wait queue, registered for the handles
+//! of its smoltcp sockets. A background task polls the interface whenever the
+//! NIC interrupt fires or the next timer of smoltcp (see
+//! [`Interface::poll_delay`]) expires. After each poll, only the sockets whose
+//! readiness has changed are woken up.
+//!
+//! Otherwise, a blocked socket just yields the CPU and polls the interface
+//! again.
+//!
+//! [`Interface::poll_delay`]: smoltcp::iface::Interface::poll_delay
+
+use core::time::Duration;
+
+use axerrno::{AxError, AxResult};
+use axhal::time::{current_time, TimeValue};
+
+use super::{SocketHandle, SOCKET_SET};
+
+#[cfg(all(feature = "irq", feature = "multitask"))]
+pub(crate) use self::irq_driven::{init, notify_poller, notify_sockets};
+#[cfg(all(feature = "irq", feature = "multitask"))]
+pub use self::irq_driven::{is_irq_driven, set_event_hook};
+
+/// Blocks the operations of a socket until they are ready.
+///
+/// Clones share the same wait queue, e.g. a listening socket and the sockets
+/// of the connections it has not accepted yet.
+#[derive(Clone)]
+pub(crate) struct SocketWaiter {
+    #[cfg(all(feature = "irq", feature = "multitask"))]
+    inner: alloc::sync::Arc<irq_driven::Waiter>,
+}
+
+impl SocketWaiter {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(all(feature = "irq", feature = "multitask"))]
+            inner: alloc::sync::Arc::new(irq_driven::Waiter::new()),
+        }
+    }
+
+    /// Wakes up this socket when the readiness of the smoltcp socket `handle`
+    /// changes. A handle is registered for one socket at a time.
+    pub fn register(&self, handle: SocketHandle) {
+        #[cfg(all(feature = "irq", feature = "multitask"))]
+        irq_driven::register(handle, &self.inner);
+        #[cfg(not(all(feature = "irq", feature = "multitask")))]
+        let _ = handle;
+    }
+
+    /// Stops watching the smoltcp socket `handle`, which must be done before
+    /// it is removed from the socket set.
+    pub fn unregister(handle: SocketHandle) {
+        #[cfg(all(feature = "irq", feature = "multitask"))]
+        irq_driven::unregister(handle);
+        #[cfg(not(all(feature = "irq", feature = "multitask")))]
+        let _ = handle;
+    }
+
+    /// Wakes up the tasks blocked on this socket.
+    pub fn notify(&self) {
+        #[cfg(all(feature = "irq", feature = "multitask"))]
+        self.inner.notify();
+    }
+
+    /// Repeatedly calls `f` until it returns something other than
+    /// [`AxError::WouldBlock`], or the `timeout` expires, in which case
+    /// `WouldBlock` is returned.
+    pub fn block_on<F, T>(&self, timeout: Option<Duration>, mut f: F) -> AxResult<T>
+    where
+        F: FnMut() -> AxResult<T>,
+    {
+        let deadline = timeout.map(|t| current_time() + t);
+        loop {
+            SOCKET_SET.poll_interfaces();
+            let seq = self.prepare_wait();
+            match f() {
+                Ok(t) => return Ok(t),
+                Err(AxError::WouldBlock) => {
+                    if deadline.is_some_and(|ddl| current_time() >= ddl) {
+                        return Err(AxError::WouldBlock);
+                    }
+                    self.wait(seq, deadline);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Records the readiness of the sockets before `f` checks them in
+    /// [`block_on`](Self::block_on), so that any later change wakes this socket
+    /// up. Returns the sequence number to wait on.
+    #[cfg(all(feature = "irq", feature = "multitask"))]
+    fn prepare_wait(&self) -> u64 {
+        let seq = self.inner.seq();
+        if irq_driven::is_enabled() {
+            irq_driven::refresh(&self.inner);
+        }
+        seq
+    }
+
+    #[cfg(not(all(feature = "irq", feature = "multitask")))]
+    fn prepare_wait(&self) -> u64 {
+        0
+    }
+
+    /// Sleeps until this socket is woken up after `seq`, or `deadline` is
+    /// reached.
+    #[cfg(all(feature = "irq", feature = "multitask"))]
+    fn wait(&self, seq: u64, deadline: Option<TimeValue>) {
+        if !irq_driven::is_enabled() {
+            axtask::yield_now();
+            return;
+        }
+        self.inner.wait(seq, deadline);
+    }
+
+    #[cfg(not(all(feature = "irq", feature = "multitask")))]
+    fn wait(&self, _seq: u64, _deadline: Option<TimeValue>) {
+        axtask::yield_now();
+    }
+}
+
+/// Notifies the poll task that there are new packets to transmit.
+#[cfg(not(all(feature = "irq", feature = "multitask")))]
+pub(crate) fn notify_poller() {}
+
+#[cfg(all(feature = "irq", feature = "multitask"))]
+mod irq_driven {
+    use alloc::collections::BTreeMap;
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+    use core::time::Duration;
+
+    use axhal::time::{current_time, TimeValue};
+    use axtask::WaitQueue;
+    use lazy_init::LazyInit;
+    use smoltcp::iface::SocketSet;
+    use smoltcp::socket::{tcp, udp, AnySocket, Socket};
+    use spinlock::SpinNoIrq;
+
+    use super::super::{IfaceId, SocketHandle, SocketSetWrapper, ETH0, SOCKET_SET};
+
+    /// Upper bound of the sleep time of the poll task, in case interrupts are
+    /// lost.
+    const MAX_POLL_DELAY: Duration = Duration::from_millis(100);
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+    static NIC_IRQ: AtomicUsize = AtomicUsize::new(0);
+    static POLL_PENDING: AtomicBool = AtomicBool::new(false);
+    static POLL_QUEUE: WaitQueue = WaitQueue::new();
+
+    /// The waiters of the registered smoltcp sockets, and the readiness of
+    /// each socket when last checked.
+    ///
+    /// It is locked after the socket sets.
+    static WAITERS: SpinNoIrq<BTreeMap<SocketHandle, (Arc<Waiter>, u64)>> =
+        SpinNoIrq::new(BTreeMap::new());
+
+    static EVENT_HOOK: LazyInit<fn()> = LazyInit::new();
+
+    /// The wait queue of a socket.
+    pub(crate) struct Waiter {
+        queue: WaitQueue,
+        /// Increased every time the socket is woken up.
+        seq: AtomicU64,
+    }
+
+    impl Waiter {
+        pub const fn new() -> Self {
+            Self {
+                queue: WaitQueue::new(),
+                seq: AtomicU64::new(0),
+            }
+        }
+
+        pub fn seq(&self) -> u64 {
+            self.seq.load(Ordering::Acquire)
+        }
+
+        pub fn notify(&self) {
+            self.seq.fetch_add(1, Ordering::AcqRel);
+            self.queue.notify_all(false);
+        }
+
+        pub fn wait(&self, seq: u64, deadline: Option<TimeValue>) {
+            let changed = || self.seq() != seq;
+            match deadline {
+                Some(ddl) => {
+                    let dur = ddl.saturating_sub(current_time());
+                    self.queue.wait_timeout_until(dur, changed);
+                }
+                None => self.queue.wait_until(changed),
+            }
+        }
+    }
+
+    /// Returns a value that changes whenever the socket may become readable
+    /// or writable.
+    fn readiness(socket: &Socket) -> u64 {
+        if let Some(socket) = tcp::Socket::downcast(socket) {
+            socket.state() as u64
+                | ((socket.recv_queue() as u64) << 4)
+                | ((socket.send_queue() as u64) << 34)
+        } else if let Some(socket) = udp::Socket::downcast(socket) {
+            socket.can_recv() as u64
+                | ((socket.can_send() as u64) << 1)
+                | ((socket.is_open() as u64) << 2)
+        } else {
+            0
+        }
+    }
+
+    /// Updates the readiness of the registered sockets in `sockets`, and wakes
+    /// up those that have changed. Only the sockets of `waiter` are updated
+    /// without waking them up if it is given.
+    fn update(iface: IfaceId, sockets: &SocketSet, waiter: Option<&Arc<Waiter>>) -> bool {
+        let mut waiters = WAITERS.lock();
+        let mut woken = false;
+        for (inner, socket) in sockets.iter() {
+            let handle = SocketHandle { iface, inner };
+            let Some((owner, last)) = waiters.get_mut(&handle) else {
+                continue;
+            };
+            if waiter.is_some_and(|w| !Arc::ptr_eq(w, owner)) {
+                continue;
+            }
+            let now = readiness(socket);
+            if now != *last {
+                *last = now;
+                if waiter.is_none() {
+                    owner.notify();
+                    woken = true;
+                }
+            }
+        }
+        woken
+    }
+
+    pub fn register(handle: SocketHandle, waiter: &Arc<Waiter>) {
+        WAITERS.lock().insert(handle, (waiter.clone(), 0));
+    }
+
+    pub fn unregister(handle: SocketHandle) {
+        WAITERS.lock().remove(&handle);
+    }
+
+    /// Records the current readiness of the sockets of `waiter`.
+    pub fn refresh(waiter: &Arc<Waiter>) {
+        for &iface in IfaceId::all() {
+            update(iface, &SOCKET_SET.set(iface).lock(), Some(waiter));
+        }
+    }
+
+    pub(super) fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Acquire)
+    }
+
+    /// Returns whether the network stack is polled on NIC interrupts.
+    ///
+    /// Otherwise, the state of sockets only changes when they are polled,
+    /// so callers waiting for it have to poll by themselves.
+    pub fn is_irq_driven() -> bool {
+        is_enabled()
+    }
+
+    /// Sets a function called every time the state of sockets may have
+    /// changed, after blocked sockets are woken up. It can only be set once.
+    ///
+    /// It is called in the poll task or in the blocked tasks, and must not
+    /// block.
+    pub fn set_event_hook(hook: fn()) {
+        EVENT_HOOK.init_by(hook);
+    }
+
+    /// Wakes up the sockets whose readiness has changed after the interfaces
+    /// are polled.
+    pub fn notify_sockets(sets: &SocketSetWrapper, delivered: bool) {
+        let mut woken = delivered;
+        for &iface in IfaceId::all() {
+            woken |= update(iface, &sets.set(iface).lock(), None);
+        }
+        if woken {
+            if let Some(hook) = EVENT_HOOK.try_get() {
+                hook();
+            }
+        }
+    }
+
+    /// Notifies the poll task that there are new packets to transmit.
+    pub fn notify_poller() {
+        if is_enabled() {
+            POLL_PENDING.store(true, Ordering::Release);
+            POLL_QUEUE.notify_one(false);
+        }
+    }
+
+    fn nic_irq_handler() {
+        // The interrupt is acknowledged to the NIC later in the poll task,
+        // mask it to avoid an interrupt storm until then.
+        axhal::irq::set_enable(NIC_IRQ.load(Ordering::Acquire), false);
+        POLL_PENDING.store(true, Ordering::Release);
+        POLL_QUEUE.notify_one(true);
+    }
+
+    fn poll_task() {
+        let irq = NIC_IRQ.load(Ordering::Acquire);
+        loop {
+            // Acknowledge before polling, so that packets arriving during the
+            // poll raise a new interrupt.
+            ETH0.ack_interrupt();
+            SOCKET_SET.poll_interfaces();
+            axhal::irq::set_enable(irq, true);
+
+            let delay = SOCKET_SET
+                .poll_delay()
+                .map_or(MAX_POLL_DELAY, |d| d.min(MAX_POLL_DELAY));
+            if delay.is_zero() {
+                axtask::yield_now();
+            } else {
+                POLL_QUEUE.wait_timeout_until(delay, || POLL_PENDING.swap(false, Ordering::AcqRel));
+            }
+        }
+    }
+
+    /// Registers the interrupt handler of the NIC and spawns the poll task.
+    ///
+    /// Sockets fall back to busy polling if the NIC has no interrupt.
+    pub fn init() {
+        let Some(irq) = ETH0.try_get().and_then(|eth0| eth0.irq_num()) else {
+            warn!("NIC has no IRQ, fall back to polling");
+            return;
+        };
+        NIC_IRQ.store(irq, Ordering::Release);
+        if !axhal::irq::register_handler(irq, nic_irq_handler) {
+            warn!("failed to register NIC IRQ {}, fall back to polling", irq);
+            return;
+        }
+        axtask::spawn(poll_task);
+        ENABLED.store(true, Ordering::Release);
+        info!("  irq:      {}", irq);
+    }
+}
@@ -0,0 +1,236 @@
+//! Zero-copy receive of UDP datagrams from the NIC.
+//!
+//! Datagrams to the port of a bound UDP socket are taken out of the NIC's
+//! receive queue before smoltcp sees them, and queued on the socket as
+//! [`UdpRecvBuf`]s, which lend the payload in place in the NIC's buffer. The
+//! buffer is given back to the NIC when the [`UdpRecvBuf`] is dropped.
+//!
+//! The NIC can not receive into lent buffers, so at most half of them are
+//! lent at a time, and at most [`MAX_QUEUED`] per socket. Beyond that,
+//! datagrams go through smoltcp and are copied as usual. The socket is then
+//! marked as spilled, and gets no more datagrams from the NIC until it has
+//! received all the copied ones, so that the order is kept.
+//!
+//! Datagrams are queued in the receive path of the NIC with its device
+//! locked, so the queues are guarded by spin locks that do not sleep, and are
+//! locked in the order: device, `QUEUES`, [`RecvQueue`]. Dropping a lent
+//! buffer locks the device, so it must be done with no queue locked.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::{sync::Arc, vec::Vec};
+use core::ops::{Deref, Range};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use driver_net::NetBufPtr;
+use smoltcp::wire::{EthernetAddress, EthernetFrame, EthernetProtocol, IpAddress, IpEndpoint};
+use smoltcp::wire::{IpProtocol, Ipv4Address, Ipv4Packet, UdpPacket};
+use spinlock::SpinNoIrq;
+
+use super::waiter::SocketWaiter;
+use super::ETH0;
+
+/// Maximum number of datagrams queued on a socket.
+const MAX_QUEUED: usize = 16;
+
+/// Number of the NIC's receive buffers lent out.
+static LENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Set when a datagram is queued, until the next poll notices it.
+static DELIVERED: AtomicBool = AtomicBool::new(false);
+
+/// Queues of the bound UDP sockets and the addresses they are bound to
+/// (`None` for any address), keyed by port.
+static QUEUES: SpinNoIrq<BTreeMap<u16, (Option<IpAddress>, Arc<RecvQueue>)>> =
+    SpinNoIrq::new(BTreeMap::new());
+
+/// A UDP datagram, whose payload is lent in place in the NIC's receive
+/// buffer.
+///
+/// Datagrams that went through smoltcp, such as those on the loopback
+/// interface, are copied into an owned buffer instead.
+pub struct UdpRecvBuf {
+    /// The NIC's buffer, `None` if the payload is copied.
+    nic_buf: Option<NetBufPtr>,
+    copied: Vec<u8>,
+    /// Range of the payload in `nic_buf`.
+    payload: Range<usize>,
+}
+
+// SAFETY: the NIC's buffer is owned by `UdpRecvBuf` until it is recycled, and
+// can be recycled from any thread.
+unsafe impl Send for UdpRecvBuf {}
+
+impl UdpRecvBuf {
+    pub(crate) fn copied(payload: &[u8]) -> Self {
+        Self {
+            nic_buf: None,
+            copied: payload.to_vec(),
+            payload: 0..0,
+        }
+    }
+
+    /// Returns whether the payload is lent from the NIC without copying.
+    pub fn is_zero_copy(&self) -> bool {
+        self.nic_buf.is_some()
+    }
+}
+
+impl Deref for UdpRecvBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.nic_buf {
+            Some(buf) => &buf.packet()[self.payload.clone()],
+            None => &self.copied,
+        }
+    }
+}
+
+impl Drop for UdpRecvBuf {
+    fn drop(&mut self) {
+        if let Some(buf) = self.nic_buf.take() {
+            LENT.fetch_sub(1, Ordering::Relaxed);
+            ETH0.recycle_rx_buffer(buf);
+        }
+    }
+}
+
+/// The datagrams received from the NIC by a UDP socket.
+///
+/// [`UdpRecvBuf`]s must not be dropped with the queue locked, since the NIC
+/// queues datagrams with its device locked.
+pub(crate) struct RecvQueue {
+    bufs: SpinNoIrq<VecDeque<(UdpRecvBuf, IpEndpoint)>>,
+    spilled: AtomicBool,
+    /// The waiter of the socket, woken up when a datagram is queued.
+    waiter: SocketWaiter,
+}
+
+impl RecvQueue {
+    pub fn new(waiter: SocketWaiter) -> Self {
+        Self {
+            bufs: SpinNoIrq::new(VecDeque::new()),
+            spilled: AtomicBool::new(false),
+            waiter,
+        }
+    }
+
+    /// Starts queuing datagrams to `port` from the NIC. Nothing happens if
+    /// another socket has done so, which then receives them through smoltcp.
+    pub fn register(self: &Arc<Self>, addr: Option<IpAddress>, port: u16) {
+        QUEUES
+            .lock()
+            .entry(port)
+            .or_insert_with(|| (addr, self.clone()));
+    }
+
+    /// Stops queuing datagrams to `port`, and drops those queued.
+    pub fn unregister(self: &Arc<Self>, port: u16) {
+        let mut queues = QUEUES.lock();
+        if queues.get(&port).is_some_and(|(_, q)| Arc::ptr_eq(q, self)) {
+            queues.remove(&port);
+        }
+        drop(queues);
+        // Unlocked before the buffers are dropped.
+        let bufs = core::mem::take(&mut *self.bufs.lock());
+        drop(bufs);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bufs.lock().is_empty()
+    }
+
+    pub fn pop(&self) -> Option<(UdpRecvBuf, IpEndpoint)> {
+        self.bufs.lock().pop_front()
+    }
+
+    pub fn peek<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&[u8], IpEndpoint) -> R,
+    {
+        self.bufs.lock().front().map(|(buf, src)| f(buf, *src))
+    }
+
+    /// Called when the smoltcp socket on the NIC has no datagrams left, so
+    /// that datagrams are queued from the NIC again.
+    pub fn set_drained(&self) {
+        self.spilled.store(false, Ordering::Release);
+    }
+}
+
+/// Queues `buf` on a UDP socket if it is a datagram to one. Otherwise, `buf`
+/// is returned to be passed to smoltcp.
+pub(crate) fn deliver(
+    buf: NetBufPtr,
+    mac: EthernetAddress,
+    ip: Ipv4Address,
+    rx_queue_size: usize,
+) -> Option<NetBufPtr> {
+    let Some((port, src, payload)) = parse_udp(buf.packet(), mac, ip) else {
+        return Some(buf);
+    };
+    let queues = QUEUES.lock();
+    let Some((addr, queue)) = queues.get(&port) else {
+        return Some(buf);
+    };
+    if addr.is_some_and(|addr| addr != IpAddress::Ipv4(ip)) {
+        return Some(buf);
+    }
+    let mut bufs = queue.bufs.lock();
+    if queue.spilled.load(Ordering::Acquire)
+        || bufs.len() >= MAX_QUEUED
+        || LENT.load(Ordering::Relaxed) >= rx_queue_size / 2
+    {
+        queue.spilled.store(true, Ordering::Release);
+        return Some(buf);
+    }
+    LENT.fetch_add(1, Ordering::Relaxed);
+    let recv_buf = UdpRecvBuf {
+        nic_buf: Some(buf),
+        copied: Vec::new(),
+        payload,
+    };
+    bufs.push_back((recv_buf, src));
+    queue.waiter.notify();
+    DELIVERED.store(true, Ordering::Release);
+    None
+}
+
+/// Returns whether datagrams have been queued since the last call.
+pub(crate) fn take_delivered() -> bool {
+    DELIVERED.swap(false, Ordering::AcqRel)
+}
+
+/// Parses a UDP datagram to `ip` that smoltcp would accept, and returns its
+/// destination port, origin and range of the payload in `frame`.
+fn parse_udp(
+    frame: &[u8],
+    mac: EthernetAddress,
+    ip: Ipv4Address,
+) -> Option<(u16, IpEndpoint, Range<usize>)> {
+    let ether_frame = EthernetFrame::new_checked(frame).ok()?;
+    if ether_frame.dst_addr() != mac || ether_frame.ethertype() != EthernetProtocol::Ipv4 {
+        return None;
+    }
+    let ipv4_packet = Ipv4Packet::new_checked(ether_frame.payload()).ok()?;
+    if ipv4_packet.dst_addr() != ip
+        || ipv4_packet.next_header() != IpProtocol::Udp
+        || ipv4_packet.more_frags()
+        || ipv4_packet.frag_offset() != 0
+        || !ipv4_packet.verify_checksum()
+    {
+        return None;
+    }
+    let src_addr = IpAddress::Ipv4(ipv4_packet.src_addr());
+    let udp_packet = UdpPacket::new_checked(ipv4_packet.payload()).ok()?;
+    if !udp_packet.verify_checksum(&src_addr, &IpAddress::Ipv4(ip)) {
+        return None;
+    }
+    let payload = udp_packet.payload();
+    let start = payload.as_ptr() as usize - frame.as_ptr() as usize;
+    Some((
+        udp_packet.dst_port(),
+        IpEndpoint::new(src_addr, udp_packet.src_port()),
+        start..start + payload.len(),
+    ))
+}
@@ -0,0 +1,87 @@
+//! Entropy sources for seeding the generator.
+
+/// Number of bytes of a seed.
+const SEED_LEN: usize = 32;
+
+#[cfg(feature = "rng")]
+static DEVICE: spinlock::SpinNoIrq<Option<axdriver::AxRngDevice>> = spinlock::SpinNoIrq::new(None);
+
+#[cfg(feature = "rng")]
+pub(crate) fn set_device(dev: axdriver::AxRngDevice) {
+    *DEVICE.lock() = Some(dev);
+}
+
+/// Gathers the jitter of the timer: the number of ticks to run a loop whose
+/// length depends on the previous measurement varies with caches, interrupts
+/// and frequency scaling. It is whitened by the ChaCha20 generator afterwards.
+fn jitter(seed: &mut [u8; SEED_LEN]) {
+    let mut state = [0u64; 4];
+    for i in 0..256 {
+        let start = axhal::time::current_ticks();
+        let mut x = start;
+        for _ in 0..(start & 0x3f) + 16 {
+            x = x.rotate_left(5) ^ axhal::time::current_ticks();
+            core::hint::spin_loop();
+        }
+        let delta = axhal::time::current_ticks().wrapping_sub(start);
+        state[i % 4] = state[i % 4].rotate_left(7) ^ delta ^ x;
+    }
+    for (chunk, word) in seed.chunks_exact_mut(8).zip(state) {
+        xor(chunk, &word.to_le_bytes());
+    }
+}
+
+/// Gathers the random number generator of the CPU, if any.
+fn cpu(seed: &mut [u8; SEED_LEN]) {
+    #[cfg(target_arch = "x86_64")]
+    for chunk in seed.chunks_exact_mut(8) {
+        match axhal::arch::hw_random() {
+            Some(val) => xor(chunk, &val.to_le_bytes()),
+            None => break,
+        }
+    }
+    let _ = seed;
+}
+
+/// Gathers the random number generator device, if any.
+#[cfg(feature = "rng")]
+fn device(seed: &mut [u8; SEED_LEN]) {
+    use axdriver::prelude::{DevError, RngDriverOps};
+
+    let mut dev = DEVICE.lock();
+    let Some(dev) = dev.as_mut() else {
+        return;
+    };
+    let mut buf = [0; SEED_LEN];
+    let mut filled = 0;
+    while filled < SEED_LEN {
+        match dev.read_random(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            // Too slow to wait for with the generator locked, the other
+            // sources are enough for now.
+            Err(DevError::Again) => break,
+            Err(e) => {
+                warn!("failed to read the entropy device: {:?}", e);
+                break;
+            }
+        }
+    }
+    xor(seed, &buf[..filled]);
+}
+
+fn xor(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= s;
+    }
+}
+
+/// Gathers a seed from all available entropy sources.
+pub(crate) fn gather() -> [u8; SEED_LEN] {
+    let mut seed = [0; SEED_LEN];
+    jitter(&mut seed);
+    cpu(&mut seed);
+    #[cfg(feature = "rng")]
+    device(&mut seed);
+    seed
+}
@@ -0,0 +1,107 @@
+//! [ArceOS](https://github.com/rcore-os/arceos) kernel random number generator.
+//!
+//! It is a ChaCha20 CSPRNG, which is seeded on first use from the jitter of
+//! the timer, and `rdseed`/`rdrand` on x86. It is reseeded from the same
+//! sources, plus the hardware random number generator passed to
+//! [`init_rng`], after every [`RESEED_INTERVAL`] bytes of output.
+//!
+//! # Cargo Features
+//!
+//! - `rng`: Use a random number generator device (e.g., virtio-rng) of
+//!   [`axdriver`] as an entropy source.
+
+#![no_std]
+
+#[macro_use]
+extern crate log;
+
+mod entropy;
+
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use spinlock::SpinNoIrq;
+
+/// The number of output bytes after which the generator is reseeded.
+pub const RESEED_INTERVAL: usize = 1 << 20;
+
+/// The number of bytes [`fill_bytes`] generates each time it locks the
+/// generator.
+const CHUNK_SIZE: usize = 256;
+
+struct Generator {
+    rng: ChaCha20Rng,
+    output: usize,
+}
+
+impl Generator {
+    fn new() -> Self {
+        Self {
+            rng: ChaCha20Rng::from_seed(entropy::gather()),
+            output: 0,
+        }
+    }
+
+    /// Mixes `data` into the state: the new seed is the next output of the
+    /// current generator XORed with `data`, so it is never weaker than before.
+    fn reseed(&mut self, data: &[u8]) {
+        let mut seed = [0; 32];
+        self.rng.fill_bytes(&mut seed);
+        for (i, b) in data.iter().enumerate() {
+            seed[i % 32] ^= b.rotate_left((i / 32) as u32);
+        }
+        self.rng = ChaCha20Rng::from_seed(seed);
+        self.output = 0;
+    }
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        if self.output >= RESEED_INTERVAL {
+            self.reseed(&entropy::gather());
+        }
+        self.rng.fill_bytes(buf);
+        self.output = self.output.saturating_add(buf.len());
+    }
+}
+
+static RNG: SpinNoIrq<Option<Generator>> = SpinNoIrq::new(None);
+
+fn with_generator<T>(f: impl FnOnce(&mut Generator) -> T) -> T {
+    f(RNG.lock().get_or_insert_with(Generator::new))
+}
+
+/// Fills `buf` with cryptographically secure random bytes.
+///
+/// The generator is locked with IRQs disabled, so a large `buf` is filled in
+/// chunks of [`CHUNK_SIZE`] bytes, each under the lock once.
+pub fn fill_bytes(buf: &mut [u8]) {
+    for chunk in buf.chunks_mut(CHUNK_SIZE) {
+        with_generator(|g| g.fill_bytes(chunk));
+    }
+}
+
+/// Returns a cryptographically secure random [`u64`].
+pub fn random_u64() -> u64 {
+    let mut buf = [0; 8];
+    fill_bytes(&mut buf);
+    u64::from_le_bytes(buf)
+}
+
+/// Mixes `data` into the generator, e.g. bytes written to `/dev/urandom`.
+///
+/// It never makes the output more predictable, even if `data` is known.
+pub fn add_entropy(data: &[u8]) {
+    with_generator(|g| g.reseed(data))
+}
+
+/// Uses the first random number generator device as an entropy source, and
+/// reseeds the generator from it immediately.
+#[cfg(feature = "rng")]
+pub fn init_rng(mut rng_devs: axdriver::AxDeviceContainer<axdriver::AxRngDevice>) {
+    use axdriver::prelude::BaseDriverOps;
+
+    info!("Initialize random number generator...");
+    if let Some(dev) = rng_devs.take_one() {
+        info!("  use entropy device: {:?}", dev.device_name());
+        entropy::set_device(dev);
+        with_generator(|g| g.reseed(&entropy::gather()));
+    }
+}
@@ -13,6 +13,7 @@
 //! - `fs`: Enable filesystem support.
 //! - `net`: Enable networking support.
 //! - `display`: Enable graphics support.
+//! - `rng`: Use a hardware random number generator as an entropy source.
 //!
 //! All the features are optional and disabled by default.
 
@@ -28,9 +29,13 @@ mod trap;
 
 #[cfg(feature = "smp")]
 mod mp;
+#[cfg(feature = "paging")]
+mod tlb;
 
 #[cfg(feature = "smp")]
 pub use self::mp::rust_main_secondary;
+#[cfg(feature = "paging")]
+pub use self::tlb::{flush_tlb_range, TLB_SHOOTDOWN_SUPPORTED};
 
 const LOGO: &str = r#"
        d8888                            .d88888b.   .d8888b.
@@ -152,11 +157,16 @@ pub extern "C" fn rust_main(cpu_id: usize, dtb: usize) -> ! {
     #[cfg(feature = "multitask")]
     axtask::init_scheduler();
 
-    #[cfg(any(feature = "fs", feature = "net", feature = "display"))]
+    #[cfg(any(feature = "fs", feature = "net", feature = "display", feature = "rng"))]
     {
         #[allow(unused_variables)]
         let all_devices = axdriver::init_drivers();
 
+        // Before the network, whose initial sequence numbers and ports are
+        // random.
+        #[cfg(feature = "rng")]
+        axrand::init_rng(all_devices.rng);
+
         #[cfg(feature = "fs")]
         axfs::init_filesystems(all_devices.block);
 
@@ -229,13 +239,24 @@ fn init_allocator() {
     }
 }
 
+#[cfg(feature = "paging")]
+static KERNEL_PAGE_TABLE: lazy_init::LazyInit<spinlock::SpinNoIrq<axhal::paging::PageTable>> =
+    lazy_init::LazyInit::new();
+
+/// Returns the kernel page table, which is shared by all CPUs.
+///
+/// Callers that modify or remove existing mappings are responsible for
+/// flushing the TLBs of all CPUs with [`flush_tlb_range`], after releasing
+/// the lock.
+#[cfg(feature = "paging")]
+pub fn kernel_page_table() -> &'static spinlock::SpinNoIrq<axhal::paging::PageTable> {
+    &KERNEL_PAGE_TABLE
+}
+
 #[cfg(feature = "paging")]
 fn remap_kernel_memory() -> Result<(), axhal::paging::PagingError> {
     use axhal::mem::{memory_regions, phys_to_virt};
     use axhal::paging::PageTable;
-    use lazy_init::LazyInit;
-
-    static KERNEL_PAGE_TABLE: LazyInit<PageTable> = LazyInit::new();
 
     if axhal::cpu::this_cpu_is_bsp() {
         let mut kernel_page_table = PageTable::try_new()?;
@@ -248,10 +269,10 @@ fn remap_kernel_memory() -> Result<(), axhal::paging::PagingError> {
                 true,
             )?;
         }
-        KERNEL_PAGE_TABLE.init_by(kernel_page_table);
+        KERNEL_PAGE_TABLE.init_by(spinlock::SpinNoIrq::new(kernel_page_table));
     }
 
-    unsafe { axhal::arch::write_page_table_root(KERNEL_PAGE_TABLE.root_paddr()) };
+    unsafe { axhal::arch::write_page_table_root(KERNEL_PAGE_TABLE.lock().root_paddr()) };
     Ok(())
 }
 
@@ -283,7 +304,17 @@ fn init_interrupt() {
         axtask::on_timer_tick();
     });
 
+    // The IPI wakes up idle CPUs, who will pick up new tasks in their run
+    // queues after it returns, and shoots down TLBs.
+    #[cfg(feature = "smp")]
+    axhal::irq::register_handler(axhal::irq::IPI_IRQ_NUM, || {
+        #[cfg(feature = "paging")]
+        tlb::shootdown::handle_ipi();
+    });
+
     // Enable IRQs before starting app
+    #[cfg(all(feature = "smp", feature = "paging"))]
+    tlb::shootdown::cpu_online();
     axhal::arch::enable_irqs();
 }
 
@@ -49,6 +49,8 @@ pub extern "C" fn rust_main_secondary(cpu_id: usize) -> ! {
         core::hint::spin_loop();
     }
 
+    #[cfg(all(feature = "irq", feature = "paging"))]
+    super::tlb::shootdown::cpu_online();
     #[cfg(feature = "irq")]
     axhal::arch::enable_irqs();
 
@@ -0,0 +1,91 @@
+//! TLB shootdown for the shared kernel page table.
+
+use axhal::mem::{VirtAddr, PAGE_SIZE_4K};
+
+/// Above this number of pages, the local CPU flushes its whole TLB instead.
+const MAX_FLUSH_PAGES: usize = 32;
+
+/// Whether [`flush_tlb_range`] can reach every CPU that may run tasks.
+///
+/// Other CPUs are flushed by IPIs, so with both `smp` and `multitask`, the
+/// `irq` feature is required. Callers that change existing mappings should
+/// refuse to do so when this is `false`.
+pub const TLB_SHOOTDOWN_SUPPORTED: bool = cfg!(any(
+    not(all(feature = "smp", feature = "multitask")),
+    feature = "irq"
+));
+
+/// Flushes the TLB entries of `[vaddr, vaddr + size)` on all CPUs.
+///
+/// Other CPUs flush their whole TLBs. It returns only after all of them have
+/// done so, so the frames that used to be mapped there can be freed then.
+///
+/// It must be called with IRQs enabled and without holding the
+/// [kernel page table](crate::kernel_page_table) lock, otherwise two CPUs
+/// shooting down each other would deadlock.
+pub fn flush_tlb_range(vaddr: VirtAddr, size: usize) {
+    let pages = size.div_ceil(PAGE_SIZE_4K);
+    if pages > MAX_FLUSH_PAGES {
+        axhal::arch::flush_tlb(None);
+    } else {
+        for i in 0..pages {
+            axhal::arch::flush_tlb(Some(vaddr + i * PAGE_SIZE_4K));
+        }
+    }
+    #[cfg(all(feature = "smp", feature = "irq"))]
+    shootdown::flush_others();
+}
+
+#[cfg(all(feature = "smp", feature = "irq"))]
+pub(crate) mod shootdown {
+    use axconfig::SMP;
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const OFFLINE: AtomicBool = AtomicBool::new(false);
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicUsize = AtomicUsize::new(0);
+
+    /// CPUs that have IRQs enabled and will answer shootdown IPIs.
+    static ONLINE: [AtomicBool; SMP] = [OFFLINE; SMP];
+    /// The latest shootdown request.
+    static REQUESTED: AtomicUsize = AtomicUsize::new(0);
+    /// The latest request each CPU has finished flushing for.
+    static FLUSHED: [AtomicUsize; SMP] = [ZERO; SMP];
+
+    /// Marks the current CPU as online, it must enable IRQs right after.
+    ///
+    /// Requests made before this are not waited for, so the whole TLB is
+    /// flushed here to catch up.
+    pub fn cpu_online() {
+        ONLINE[axhal::cpu::this_cpu_id()].store(true, Ordering::SeqCst);
+        axhal::arch::flush_tlb(None);
+    }
+
+    /// The [`IPI_IRQ_NUM`](axhal::irq::IPI_IRQ_NUM) handler.
+    pub fn handle_ipi() {
+        let seq = REQUESTED.load(Ordering::SeqCst);
+        axhal::arch::flush_tlb(None);
+        FLUSHED[axhal::cpu::this_cpu_id()].fetch_max(seq, Ordering::Release);
+    }
+
+    pub fn flush_others() {
+        // Stay on this CPU so that it skips the right one.
+        let _guard = kernel_guard::NoPreempt::new();
+        let this_cpu = axhal::cpu::this_cpu_id();
+        let seq = REQUESTED.fetch_add(1, Ordering::SeqCst) + 1;
+        // CPUs coming online later flush by themselves in `cpu_online`.
+        let mut targets = [false; SMP];
+        for (cpu, target) in targets.iter_mut().enumerate() {
+            if cpu != this_cpu && ONLINE[cpu].load(Ordering::SeqCst) {
+                *target = true;
+                axhal::irq::send_ipi(cpu);
+            }
+        }
+        for (cpu, _) in targets.iter().enumerate().filter(|(_, &t)| t) {
+            while FLUSHED[cpu].load(Ordering::Acquire) < seq {
+                core::hint::spin_loop();
+            }
+        }
+    }
+}
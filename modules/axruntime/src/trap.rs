@@ -8,6 +8,8 @@ impl axhal::trap::TrapHandler for TrapHandlerImpl {
             let guard = kernel_guard::NoPreempt::new();
             axhal::irq::dispatch_irq(_irq_num);
             drop(guard); // rescheduling may occur when preemption is re-enabled.
+            #[cfg(feature = "signal")]
+            axtask::handle_pending_signals_on_irq_return();
         }
     }
 }
@@ -2,12 +2,14 @@
 
 use alloc::{string::String, sync::Arc};
 
-pub(crate) use crate::run_queue::{AxRunQueue, RUN_QUEUE};
+pub(crate) use crate::run_queue::{current_run_queue, AxRunQueue};
 
 #[doc(cfg(feature = "multitask"))]
 pub use crate::task::{CurrentTask, TaskId, TaskInner};
 #[doc(cfg(feature = "multitask"))]
 pub use crate::wait_queue::WaitQueue;
+#[doc(cfg(feature = "multitask"))]
+pub use scheduler::{SchedPolicy, RT_PRIO_MAX, RT_PRIO_MIN};
 
 /// The reference type of a task.
 pub type AxTaskRef = Arc<AxTask>;
@@ -20,6 +22,10 @@ cfg_if::cfg_if! {
     } else if #[cfg(feature = "sched_cfs")] {
         pub(crate) type AxTask = scheduler::CFSTask<TaskInner>;
         pub(crate) type Scheduler = scheduler::CFScheduler<TaskInner>;
+    } else if #[cfg(feature = "sched_rt")] {
+        const RR_TIME_SLICE: usize = 5;
+        pub(crate) type AxTask = scheduler::LayeredTask<TaskInner, RR_TIME_SLICE>;
+        pub(crate) type Scheduler = scheduler::LayeredScheduler<TaskInner, RR_TIME_SLICE>;
     } else {
         // If no scheduler features are set, use FIFO as the default.
         pub(crate) type AxTask = scheduler::FifoTask<TaskInner>;
@@ -61,6 +67,15 @@ pub fn current() -> CurrentTask {
     CurrentTask::get()
 }
 
+/// Gets the task that runs the application's `main` function.
+///
+/// # Panics
+///
+/// Panics if the task scheduler is not initialized.
+pub fn main_task() -> AxTaskRef {
+    crate::run_queue::main_task().clone()
+}
+
 /// Initializes the task scheduler (for the primary CPU).
 pub fn init_scheduler() {
     info!("Initialize scheduling...");
@@ -84,19 +99,143 @@ pub fn init_scheduler_secondary() {
 #[doc(cfg(feature = "irq"))]
 pub fn on_timer_tick() {
     crate::timers::check_events();
-    RUN_QUEUE.lock().scheduler_timer_tick();
+    current_run_queue().scheduler_timer_tick();
+}
+
+/// Sets a timer that calls `callback` with the current time at `deadline`.
+///
+/// The callback runs in the timer interrupt handler, so it must not block.
+/// The `key` identifies the timer for [`cancel_timer`], timers with the same
+/// key can coexist.
+#[cfg(feature = "irq")]
+#[doc(cfg(feature = "irq"))]
+pub fn set_timer<F>(deadline: axhal::time::TimeValue, key: usize, callback: F)
+where
+    F: FnOnce(axhal::time::TimeValue) + Send + 'static,
+{
+    crate::timers::set_callback(deadline, key, alloc::boxed::Box::new(callback));
+}
+
+/// Cancels all timers set by [`set_timer`] with the given `key`.
+#[cfg(feature = "irq")]
+#[doc(cfg(feature = "irq"))]
+pub fn cancel_timer(key: usize) {
+    crate::timers::cancel_callback(key);
+}
+
+/// Returns the CPU time consumed by all non-idle tasks.
+///
+/// Tasks running on other CPUs are accounted when they are switched out.
+pub fn total_cpu_time() -> axhal::time::TimeValue {
+    let mut nanos = crate::run_queue::BUSY_TIME.load(core::sync::atomic::Ordering::Relaxed);
+    let curr = current();
+    if !curr.is_idle() {
+        nanos += curr.running_nanos();
+    }
+    axhal::time::TimeValue::from_nanos(nanos)
+}
+
+/// Task factory, which can be used in order to configure the properties of a
+/// new task.
+///
+/// Methods can be chained on it in order to configure it.
+pub struct TaskBuilder {
+    name: String,
+    stack_size: usize,
+    cpumask: usize,
+}
+
+impl TaskBuilder {
+    /// Creates a task builder with the default parameters.
+    ///
+    /// The default task name is an empty string. The default task stack size
+    /// is [`axconfig::TASK_STACK_SIZE`]. The task can run on all CPUs by
+    /// default.
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            stack_size: axconfig::TASK_STACK_SIZE,
+            cpumask: crate::task::ALL_CPUS,
+        }
+    }
+
+    /// Sets the name of the task.
+    pub fn name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Sets the size of the stack of the task, in bytes.
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = stack_size;
+        self
+    }
+
+    /// Sets the CPUs that the task can run on, bit `i` of `cpumask` stands
+    /// for CPU `i`.
+    pub fn affinity(mut self, cpumask: usize) -> Self {
+        self.cpumask = cpumask;
+        self
+    }
+
+    /// Spawns a new task with the configured parameters.
+    ///
+    /// The task is put into the run queue of the least loaded CPU it can run
+    /// on, and may be migrated to other allowed CPUs later for load balancing.
+    ///
+    /// Returns the task reference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the affinity mask contains no CPU less than [`axconfig::SMP`].
+    pub fn spawn<F>(self, f: F) -> AxTaskRef
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.spawn_prepared(f, |_| {})
+    }
+
+    /// Spawns a new task like [`spawn`](Self::spawn), but calls `prepare`
+    /// with the task before it is put into a run queue, so that state that
+    /// refers to the task is set up before it can run.
+    pub fn spawn_prepared<F, P>(self, f: F, prepare: P) -> AxTaskRef
+    where
+        F: FnOnce() + Send + 'static,
+        P: FnOnce(&AxTaskRef),
+    {
+        assert!(
+            self.cpumask & crate::task::ALL_CPUS != 0,
+            "no CPU in the affinity mask: {:#x}",
+            self.cpumask
+        );
+        let task = TaskInner::new(f, self.name, self.stack_size);
+        task.set_affinity(self.cpumask);
+        prepare(&task);
+        crate::run_queue::spawn_task(task.clone());
+        task
+    }
+}
+
+impl Default for TaskBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Spawns a new task with the given parameters.
 ///
+/// The task is put into the run queue of the least loaded CPU, and may be
+/// migrated to other CPUs later for load balancing.
+///
 /// Returns the task reference.
 pub fn spawn_raw<F>(f: F, name: String, stack_size: usize) -> AxTaskRef
 where
     F: FnOnce() + Send + 'static,
 {
-    let task = TaskInner::new(f, name, stack_size);
-    RUN_QUEUE.lock().add_task(task.clone());
-    task
+    TaskBuilder::new()
+        .name(name)
+        .stack_size(stack_size)
+        .spawn(f)
 }
 
 /// Spawns a new task with the default parameters.
@@ -122,13 +261,57 @@ where
 ///
 /// [CFS]: https://en.wikipedia.org/wiki/Completely_Fair_Scheduler
 pub fn set_priority(prio: isize) -> bool {
-    RUN_QUEUE.lock().set_current_priority(prio)
+    current_run_queue().set_current_priority(prio)
+}
+
+/// Sets the scheduling policy of the given task.
+///
+/// Policies other than [`SchedPolicy::Normal`] are only supported by the
+/// layered scheduler (the `sched_rt` feature).
+///
+/// The strict priority of real-time tasks only holds within the run queue of
+/// each CPU. Tasks are balanced between CPUs regardless of their classes, so a
+/// real-time task may wait on one CPU while a normal task runs on another.
+///
+/// Returns `false` if the policy is not supported or its parameters are
+/// invalid.
+pub fn set_sched_policy(task: &AxTaskRef, policy: SchedPolicy) -> bool {
+    crate::run_queue::with_task_run_queue(task, |rq| rq.set_task_policy(task, policy))
+}
+
+/// Gets the scheduling policy of the given task.
+pub fn sched_policy(task: &AxTaskRef) -> SchedPolicy {
+    #[cfg(feature = "sched_rt")]
+    {
+        task.policy()
+    }
+    #[cfg(not(feature = "sched_rt"))]
+    {
+        let _ = task;
+        SchedPolicy::Normal
+    }
+}
+
+/// Sets the CPUs that the given task can run on, bit `i` of `cpumask` stands
+/// for CPU `i`.
+///
+/// If the task is on a CPU that is no longer allowed, it's migrated to an
+/// allowed one. The current task is migrated before this function returns,
+/// other running tasks are migrated at their next reschedule.
+///
+/// Returns `false` if `cpumask` contains no CPU less than [`axconfig::SMP`].
+pub fn set_affinity(task: &AxTaskRef, cpumask: usize) -> bool {
+    if cpumask & crate::task::ALL_CPUS == 0 {
+        return false;
+    }
+    crate::run_queue::set_affinity(task, cpumask);
+    true
 }
 
 /// Current task gives up the CPU time voluntarily, and switches to another
 /// ready task.
 pub fn yield_now() {
-    RUN_QUEUE.lock().yield_current();
+    current_run_queue().yield_current();
 }
 
 /// Current task is going to sleep for the given duration.
@@ -143,14 +326,15 @@ pub fn sleep(dur: core::time::Duration) {
 /// If the feature `irq` is not enabled, it uses busy-wait instead.
 pub fn sleep_until(deadline: axhal::time::TimeValue) {
     #[cfg(feature = "irq")]
-    RUN_QUEUE.lock().sleep_until(deadline);
+    current_run_queue().sleep_until(deadline);
     #[cfg(not(feature = "irq"))]
     axhal::time::busy_wait_until(deadline);
 }
 
 /// Exits the current task.
 pub fn exit(exit_code: i32) -> ! {
-    RUN_QUEUE.lock().exit_current(exit_code)
+    current().notify_exit(exit_code);
+    current_run_queue().exit_current(exit_code)
 }
 
 /// The idle task routine.
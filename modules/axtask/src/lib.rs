@@ -12,7 +12,15 @@
 //! - `irq`: Interrupts are enabled. If this feature is enabled, timer-based
 //!    APIs can be used, such as [`sleep`], [`sleep_until`], and
 //!    [`WaitQueue::wait_timeout`].
+//! - `smp`: Multi-core support. Each CPU has its own run queue, idle CPUs
+//!   steal tasks from busy ones, and the run queues are balanced periodically.
+//!   If `irq` is also enabled, idle CPUs are woken up by IPIs when new tasks
+//!   are put into their run queues.
 //! - `preempt`: Enable preemptive scheduling.
+//! - `signal`: Enable signal emulation. Signals sent to a task are delivered
+//!   when the upper layer asks for it, and end its sleeps and interruptible
+//!   waits. If `irq` is also enabled, it provides real-time interval timers
+//!   that send `SIGALRM`.
 //! - `sched_fifo`: Use the [FIFO cooperative scheduler][1]. It also enables the
 //!   `multitask` feature if it is enabled. This feature is enabled by default,
 //!   and it can be overriden by other scheduler features.
@@ -20,10 +28,14 @@
 //!   the `multitask` and `preempt` features if it is enabled.
 //! - `sched_cfs`: Use the [Completely Fair Scheduler][3]. It also enables the
 //!   the `multitask` and `preempt` features if it is enabled.
+//! - `sched_rt`: Use the [layered scheduler][4], with deadline and real-time
+//!   policies above the CFS, which are set by [`set_sched_policy`]. It also
+//!   enables the `multitask` and `preempt` features if it is enabled.
 //!
 //! [1]: scheduler::FifoScheduler
 //! [2]: scheduler::RRScheduler
 //! [3]: scheduler::CFScheduler
+//! [4]: scheduler::LayeredScheduler
 
 #![cfg_attr(not(test), no_std)]
 #![feature(doc_cfg)]
@@ -42,10 +54,14 @@ cfg_if::cfg_if! {
 
         #[cfg(feature = "irq")]
         mod timers;
+        #[cfg(feature = "signal")]
+        mod signal;
 
         #[doc(cfg(feature = "multitask"))]
         pub use self::api::*;
         pub use self::api::{sleep, sleep_until, yield_now};
+        #[cfg(feature = "signal")]
+        pub use self::signal::*;
     } else {
         mod api_s;
         pub use self::api_s::{sleep, sleep_until, yield_now};
@@ -1,39 +1,98 @@
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use kernel_guard::{BaseGuard, NoPreemptIrqSave};
 use lazy_init::LazyInit;
-use scheduler::BaseScheduler;
-use spinlock::SpinNoIrq;
+use scheduler::{BaseScheduler, SchedPolicy};
+use spinlock::{SpinNoIrq, SpinRaw, SpinRawGuard};
 
 use crate::task::{CurrentTask, TaskState};
 use crate::{AxTaskRef, Scheduler, TaskInner, WaitQueue};
 
-// TODO: per-CPU
-pub(crate) static RUN_QUEUE: LazyInit<SpinNoIrq<AxRunQueue>> = LazyInit::new();
+/// The run queue of each CPU.
+///
+/// IRQs and preemption must be disabled when holding the lock. The lock of
+/// the current CPU is held across context switches, and is released by the
+/// next task (see [`CurrentRunQueueRef`]).
+#[percpu::def_percpu]
+static RUN_QUEUE: LazyInit<SpinRaw<AxRunQueue>> = LazyInit::new();
+
+/// The number of ready tasks in the run queue of each CPU. It can be read by
+/// other CPUs without locking the run queue.
+#[percpu::def_percpu]
+static NR_READY: AtomicUsize = AtomicUsize::new(0);
 
-// TODO: per-CPU
+/// Whether each CPU is running its idle task.
+#[percpu::def_percpu]
+static CPU_IDLE: AtomicBool = AtomicBool::new(false);
+
+/// Tasks exited on each CPU, which are dropped by the gc task of that CPU.
+#[percpu::def_percpu]
 static EXITED_TASKS: SpinNoIrq<VecDeque<AxTaskRef>> = SpinNoIrq::new(VecDeque::new());
 
+/// Tasks that are not allowed to run on each CPU any more, which are moved to
+/// other CPUs by the gc task of that CPU after they are switched out.
+#[percpu::def_percpu]
+static MIGRATING_TASKS: SpinNoIrq<VecDeque<AxTaskRef>> = SpinNoIrq::new(VecDeque::new());
+
+/// The wait queue of the gc task of each CPU.
+#[percpu::def_percpu]
 static WAIT_FOR_EXIT: WaitQueue = WaitQueue::new();
 
+/// CPU time consumed by all non-idle tasks when they were switched out, in
+/// nanoseconds.
+pub(crate) static BUSY_TIME: AtomicU64 = AtomicU64::new(0);
+
 #[percpu::def_percpu]
 static IDLE_TASK: LazyInit<AxTaskRef> = LazyInit::new();
 
+/// The task that runs the application's `main`, on the primary CPU.
+static MAIN_TASK: LazyInit<AxTaskRef> = LazyInit::new();
+
+/// Interval of the periodic load balancing, in timer ticks.
+#[cfg(feature = "irq")]
+const BALANCE_INTERVAL_TICKS: usize = 4;
+
 pub(crate) struct AxRunQueue {
+    cpu_id: usize,
     scheduler: Scheduler,
+    #[cfg(feature = "irq")]
+    ticks: usize,
+}
+
+/// A reference to the run queue of the current CPU, with its lock held and
+/// IRQs and preemption disabled.
+///
+/// As the current task may be migrated to another CPU during a reschedule,
+/// it releases the lock of the CPU where it is dropped, and must not be used
+/// after a reschedule.
+pub(crate) struct CurrentRunQueueRef {
+    rq: ManuallyDrop<SpinRawGuard<'static, AxRunQueue>>,
+    state: <NoPreemptIrqSave as BaseGuard>::State,
 }
 
 impl AxRunQueue {
-    pub fn new() -> SpinNoIrq<Self> {
+    pub fn new(cpu_id: usize) -> SpinRaw<Self> {
         let gc_task = TaskInner::new(gc_entry, "gc".into(), axconfig::TASK_STACK_SIZE);
-        let mut scheduler = Scheduler::new();
-        scheduler.add_task(gc_task);
-        SpinNoIrq::new(Self { scheduler })
+        // The gc task only handles tasks exited or migrating on its own CPU.
+        gc_task.set_affinity(1 << cpu_id);
+        let mut rq = Self {
+            cpu_id,
+            scheduler: Scheduler::new(),
+            #[cfg(feature = "irq")]
+            ticks: 0,
+        };
+        rq.enqueue(gc_task);
+        SpinRaw::new(rq)
     }
 
     pub fn add_task(&mut self, task: AxTaskRef) {
-        debug!("task spawn: {}", task.id_name());
+        debug!("task spawn: {} on CPU {}", task.id_name(), self.cpu_id);
         assert!(task.is_ready());
-        self.scheduler.add_task(task);
+        self.enqueue(task);
+        self.kick();
     }
 
     #[cfg(feature = "irq")]
@@ -43,6 +102,11 @@ impl AxRunQueue {
             #[cfg(feature = "preempt")]
             curr.set_preempt_pending(true);
         }
+
+        self.ticks = (self.ticks + 1) % BALANCE_INTERVAL_TICKS;
+        if self.ticks == 0 {
+            self.load_balance();
+        }
     }
 
     pub fn yield_current(&mut self) {
@@ -57,16 +121,31 @@ impl AxRunQueue {
             .set_priority(crate::current().as_task_ref(), prio)
     }
 
+    /// Sets the scheduling policy of `task`, which must belong to this run
+    /// queue.
+    pub fn set_task_policy(&mut self, task: &AxTaskRef, policy: SchedPolicy) -> bool {
+        debug!("task set policy: {}, {:?}", task.id_name(), policy);
+        if !self.scheduler.set_policy(task, policy) {
+            return false;
+        }
+        // The current task may be preempted by a task of a higher class.
+        #[cfg(feature = "preempt")]
+        if self.cpu_id == axhal::cpu::this_cpu_id() {
+            crate::current().set_preempt_pending(true);
+        }
+        true
+    }
+
     #[cfg(feature = "preempt")]
     pub fn preempt_resched(&mut self) {
         let curr = crate::current();
         assert!(curr.is_running());
 
         // When we get the mutable reference of the run queue, we must
-        // have held the `SpinNoIrq` lock with both IRQs and preemption
-        // disabled. So we need to set `current_disable_count` to 1 in
-        // `can_preempt()` to obtain the preemption permission before
-        //  locking the run queue.
+        // have held the lock with both IRQs and preemption disabled. So
+        // we need to set `current_disable_count` to 1 in `can_preempt()`
+        // to obtain the preemption permission before locking the run
+        // queue.
         let can_preempt = curr.can_preempt(1);
 
         debug!(
@@ -86,14 +165,15 @@ impl AxRunQueue {
         debug!("task exit: {}, exit_code={}", curr.id_name(), exit_code);
         assert!(curr.is_running());
         assert!(!curr.is_idle());
+        let exited_tasks = unsafe { EXITED_TASKS.current_ref_raw() };
         if curr.is_init() {
-            EXITED_TASKS.lock().clear();
+            exited_tasks.lock().clear();
             axhal::misc::terminate();
         } else {
             curr.set_state(TaskState::Exited);
-            curr.notify_exit(exit_code, self);
-            EXITED_TASKS.lock().push_back(curr.clone());
-            WAIT_FOR_EXIT.notify_one_locked(false, self);
+            exited_tasks.lock().push_back(curr.clone());
+            // The gc task is pinned on this CPU, so it belongs to this run queue.
+            unsafe { WAIT_FOR_EXIT.current_ref_raw() }.notify_one_locked(false, self);
             self.resched(false);
         }
         unreachable!("task exited!");
@@ -117,16 +197,50 @@ impl AxRunQueue {
         self.resched(false);
     }
 
+    /// Wakes up the blocked `task`, which must belong to this run queue.
+    ///
+    /// If the task is not allowed to run on this CPU, it is moved to another
+    /// CPU by the gc task instead.
+    ///
+    /// The current task is preempted if `resched` is true, or the scheduler
+    /// decides that the woken task should run first (e.g. it has a higher
+    /// priority). On other CPUs, that is left to their next timer ticks.
     pub fn unblock_task(&mut self, task: AxTaskRef, resched: bool) {
         debug!("task unblock: {}", task.id_name());
         if task.is_blocked() {
+            if !task.can_run_on(self.cpu_id) {
+                self.migrate_later(task);
+                return;
+            }
             task.set_state(TaskState::Ready);
-            self.scheduler.add_task(task); // TODO: priority
-            if resched {
-                #[cfg(feature = "preempt")]
-                crate::current().set_preempt_pending(true);
+            // The scheduler updates the states of the task (e.g. its deadline)
+            // when it is added, so check for preemption after that.
+            self.enqueue(task.clone());
+            #[cfg(feature = "preempt")]
+            if self.cpu_id == axhal::cpu::this_cpu_id() {
+                let curr = crate::current();
+                if resched
+                    || (!curr.is_idle() && self.scheduler.should_preempt(curr.as_task_ref(), &task))
+                {
+                    curr.set_preempt_pending(true);
+                }
             }
+            #[cfg(not(feature = "preempt"))]
+            let _ = resched;
+            self.kick();
+        }
+    }
+
+    /// Wakes up the migrating `task` moved to this run queue by the gc task.
+    fn wake_migrated(&mut self, task: AxTaskRef) {
+        if !task.can_run_on(self.cpu_id) {
+            // The affinity has been changed again during the migration.
+            self.migrate_later(task);
+            return;
         }
+        task.set_state(TaskState::Ready);
+        self.enqueue(task);
+        self.kick();
     }
 
     #[cfg(feature = "irq")]
@@ -146,20 +260,123 @@ impl AxRunQueue {
 }
 
 impl AxRunQueue {
+    fn nr_ready(&self) -> &'static AtomicUsize {
+        unsafe { NR_READY.remote_ref_raw(self.cpu_id) }
+    }
+
+    fn enqueue(&mut self, task: AxTaskRef) {
+        task.set_cpu(self.cpu_id);
+        self.scheduler.add_task(task);
+        self.nr_ready().fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn dequeue(&mut self) -> Option<AxTaskRef> {
+        let task = self.scheduler.pick_next_task()?;
+        self.nr_ready().fetch_sub(1, Ordering::Relaxed);
+        Some(task)
+    }
+
+    /// Sends an IPI to wake up the CPU of this run queue, if it is another
+    /// CPU and is idle.
+    fn kick(&self) {
+        #[cfg(all(feature = "smp", feature = "irq"))]
+        if self.cpu_id != axhal::cpu::this_cpu_id() && cpu_is_idle(self.cpu_id) {
+            axhal::irq::send_ipi(self.cpu_id);
+        }
+    }
+
+    /// Hands `task`, which is not in the scheduler, to the gc task of this
+    /// CPU, which will wake it up on a CPU it can run on.
+    ///
+    /// The task can not be put into another run queue directly, as it may not
+    /// have been switched out yet, and we can't lock two run queues at once.
+    /// It's marked as [`TaskState::Migrating`] until then, so that other
+    /// wakeups don't put it into a run queue again.
+    fn migrate_later(&mut self, task: AxTaskRef) {
+        debug!(
+            "task migrate later: {} on CPU {}",
+            task.id_name(),
+            self.cpu_id
+        );
+        task.set_state(TaskState::Migrating);
+        unsafe { MIGRATING_TASKS.remote_ref_raw(self.cpu_id) }
+            .lock()
+            .push_back(task);
+        // The gc task is pinned on this CPU, so it belongs to this run queue.
+        unsafe { WAIT_FOR_EXIT.remote_ref_raw(self.cpu_id) }.notify_one_locked(false, self);
+    }
+
+    /// Removes a ready task which can be migrated to CPU `dst_cpu` from this
+    /// run queue.
+    fn take_migratable(&mut self, dst_cpu: usize) -> Option<AxTaskRef> {
+        let task = self.scheduler.find_task(|t| t.can_run_on(dst_cpu))?;
+        let task = self.scheduler.remove_task(&task)?;
+        self.nr_ready().fetch_sub(1, Ordering::Relaxed);
+        Some(task)
+    }
+
+    /// Pulls a ready task from the busiest run queue of other CPUs, if it
+    /// has at least `min_ready` ready tasks.
+    ///
+    /// The lock of the other run queue is only tried once, so we won't
+    /// deadlock with the CPU who is pulling tasks from us.
+    fn pull_task(&mut self, min_ready: usize) -> Option<AxTaskRef> {
+        let (busiest, nr_ready) = (0..axconfig::SMP)
+            .filter(|&cpu_id| cpu_id != self.cpu_id)
+            .map(|cpu_id| (cpu_id, nr_ready(cpu_id)))
+            .max_by_key(|&(_, nr_ready)| nr_ready)?;
+        if nr_ready < min_ready {
+            return None;
+        }
+        let mut rq = run_queue(busiest)?.try_lock()?;
+        let task = rq.take_migratable(self.cpu_id)?;
+        debug!(
+            "task migrate: {}, CPU {} -> {}",
+            task.id_name(),
+            busiest,
+            self.cpu_id
+        );
+        task.set_cpu(self.cpu_id);
+        Some(task)
+    }
+
+    /// Moves a task from the busiest CPU to this CPU if they are imbalanced.
+    #[cfg(feature = "irq")]
+    fn load_balance(&mut self) {
+        let min_ready = self.nr_ready().load(Ordering::Relaxed) + 2;
+        if let Some(task) = self.pull_task(min_ready) {
+            self.enqueue(task);
+            #[cfg(feature = "preempt")]
+            if crate::current().is_idle() {
+                crate::current().set_preempt_pending(true);
+            }
+        }
+    }
+
     /// Common reschedule subroutine. If `preempt`, keep current task's time
     /// slice, otherwise reset it.
     fn resched(&mut self, preempt: bool) {
         let prev = crate::current();
         if prev.is_running() {
-            prev.set_state(TaskState::Ready);
-            if !prev.is_idle() {
+            if prev.is_idle() || prev.can_run_on(self.cpu_id) {
+                prev.set_state(TaskState::Ready);
+            } else {
+                // The affinity of the task has been changed.
+                self.migrate_later(prev.clone());
+            }
+            if prev.is_ready() && !prev.is_idle() {
                 self.scheduler.put_prev_task(prev.clone(), preempt);
+                self.nr_ready().fetch_add(1, Ordering::Relaxed);
             }
         }
-        let next = self.scheduler.pick_next_task().unwrap_or_else(|| unsafe {
-            // Safety: IRQs must be disabled at this time.
-            IDLE_TASK.current_ref_raw().get_unchecked().clone()
-        });
+        // Steal a task from other CPUs before going idle.
+        let next = self
+            .dequeue()
+            .or_else(|| self.pull_task(1))
+            .unwrap_or_else(|| unsafe {
+                // Safety: IRQs must be disabled at this time.
+                IDLE_TASK.current_ref_raw().get_unchecked().clone()
+            });
         self.switch_to(prev, next);
     }
 
@@ -176,6 +393,14 @@ impl AxRunQueue {
             return;
         }
 
+        let now = axhal::time::current_time_nanos();
+        let ran = prev_task.account_out(now);
+        if !prev_task.is_idle() {
+            BUSY_TIME.fetch_add(ran, Ordering::Relaxed);
+        }
+        next_task.account_in(now);
+        unsafe { CPU_IDLE.current_ref_raw() }.store(next_task.is_idle(), Ordering::Relaxed);
+
         unsafe {
             let prev_ctx_ptr = prev_task.ctx_mut_ptr();
             let next_ctx_ptr = next_task.ctx_mut_ptr();
@@ -191,43 +416,231 @@ impl AxRunQueue {
     }
 }
 
+impl Deref for CurrentRunQueueRef {
+    type Target = AxRunQueue;
+    fn deref(&self) -> &Self::Target {
+        &self.rq
+    }
+}
+
+impl DerefMut for CurrentRunQueueRef {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rq
+    }
+}
+
+impl Drop for CurrentRunQueueRef {
+    fn drop(&mut self) {
+        unsafe { force_unlock_current() };
+        NoPreemptIrqSave::release(self.state);
+    }
+}
+
+/// Returns the run queue of the given CPU, or [`None`] if the CPU has not
+/// initialized its scheduler.
+fn run_queue(cpu_id: usize) -> Option<&'static SpinRaw<AxRunQueue>> {
+    unsafe { RUN_QUEUE.remote_ref_raw(cpu_id) }.try_get()
+}
+
+fn nr_ready(cpu_id: usize) -> usize {
+    unsafe { NR_READY.remote_ref_raw(cpu_id) }.load(Ordering::Relaxed)
+}
+
+fn cpu_is_idle(cpu_id: usize) -> bool {
+    unsafe { CPU_IDLE.remote_ref_raw(cpu_id) }.load(Ordering::Relaxed)
+}
+
+/// Locks the run queue of the current CPU.
+pub(crate) fn current_run_queue() -> CurrentRunQueueRef {
+    let state = NoPreemptIrqSave::acquire();
+    let rq = unsafe { RUN_QUEUE.current_ref_raw() }.lock();
+    CurrentRunQueueRef {
+        rq: ManuallyDrop::new(rq),
+        state,
+    }
+}
+
+/// Releases the lock of the run queue of the current CPU, which was held
+/// across the context switch.
+///
+/// # Safety
+///
+/// The lock must be held by a [`CurrentRunQueueRef`] that is forgotten, or
+/// by the previous task before the context switch.
+pub(crate) unsafe fn force_unlock_current() {
+    RUN_QUEUE.current_ref_raw().force_unlock();
+}
+
+/// Locks the run queue that the given task belongs to, and calls `f` with it.
+///
+/// The CPU of a task is only changed with the run queue of its old CPU
+/// locked, so the task stays in the run queue until `f` returns.
+pub(crate) fn with_task_run_queue<F, T>(task: &AxTaskRef, f: F) -> T
+where
+    F: FnOnce(&mut AxRunQueue) -> T,
+{
+    let _guard = NoPreemptIrqSave::new();
+    loop {
+        let cpu_id = task.cpu();
+        let mut rq = run_queue(cpu_id).unwrap().lock();
+        // The task may be migrated before we get the lock.
+        if task.cpu() == cpu_id {
+            return f(&mut rq);
+        }
+    }
+}
+
+/// Wakes up the blocked `task` on the CPU it belongs to.
+pub(crate) fn unblock_task(task: AxTaskRef, resched: bool) {
+    with_task_run_queue(&task.clone(), |rq| rq.unblock_task(task, resched));
+}
+
+/// Selects the least loaded CPU that the task can run on, the current CPU is
+/// preferred if there is a tie.
+fn select_cpu(task: &AxTaskRef) -> usize {
+    let this_cpu = axhal::cpu::this_cpu_id();
+    let load = |cpu_id| nr_ready(cpu_id) + !cpu_is_idle(cpu_id) as usize;
+    (0..axconfig::SMP)
+        .filter(|&cpu_id| task.can_run_on(cpu_id) && run_queue(cpu_id).is_some())
+        .min_by_key(|&cpu_id| (load(cpu_id), cpu_id != this_cpu))
+        .unwrap_or(this_cpu)
+}
+
+/// Puts a new task into the run queue of the least loaded CPU it can run on.
+pub(crate) fn spawn_task(task: AxTaskRef) {
+    let _guard = NoPreemptIrqSave::new();
+    let cpu_id = select_cpu(&task);
+    run_queue(cpu_id).unwrap().lock().add_task(task);
+}
+
+/// Returns the task that runs the application's `main`.
+pub(crate) fn main_task() -> &'static AxTaskRef {
+    &MAIN_TASK
+}
+
+/// Moves the `task` handed to the gc task to a CPU it can run on, and wakes
+/// it up there.
+fn migrate_task(task: AxTaskRef) {
+    let cpu_id = select_cpu(&task);
+    debug!(
+        "task migrate: {}, CPU {} -> {}",
+        task.id_name(),
+        task.cpu(),
+        cpu_id
+    );
+    let moved = with_task_run_queue(&task, |rq| {
+        // Only a migrating task is not owned by the run queue of its CPU.
+        if task.state() != TaskState::Migrating {
+            return false;
+        }
+        if task.can_run_on(cpu_id) {
+            task.set_cpu(cpu_id);
+            return true;
+        }
+        // None of its CPUs is online yet, keep it on this CPU for now.
+        task.set_state(TaskState::Ready);
+        rq.enqueue(task.clone());
+        false
+    });
+    if moved {
+        with_task_run_queue(&task.clone(), |rq| rq.wake_migrated(task));
+    }
+}
+
+/// Sets the CPUs that `task` can run on.
+///
+/// If the task is ready on a CPU that is no longer allowed, it's moved to
+/// another CPU. If it's running, it's moved at the next reschedule, and a
+/// blocked task is moved when it's woken up.
+pub(crate) fn set_affinity(task: &AxTaskRef, cpumask: usize) {
+    task.set_affinity(cpumask);
+    let is_current = with_task_run_queue(task, |rq| {
+        if task.can_run_on(rq.cpu_id) {
+            return false;
+        }
+        if task.is_ready() {
+            // A ready task which is not running must be in the scheduler.
+            rq.scheduler.remove_task(task);
+            rq.nr_ready().fetch_sub(1, Ordering::Relaxed);
+            rq.migrate_later(task.clone());
+        } else if task.is_running() && rq.cpu_id != axhal::cpu::this_cpu_id() {
+            #[cfg(feature = "preempt")]
+            task.set_preempt_pending(true);
+            #[cfg(all(feature = "smp", feature = "irq"))]
+            axhal::irq::send_ipi(rq.cpu_id);
+        }
+        task.is_running() && rq.cpu_id == axhal::cpu::this_cpu_id()
+    });
+    if is_current {
+        crate::yield_now();
+    }
+}
+
 fn gc_entry() {
+    // The gc task is pinned, these are always the ones of its own CPU.
+    let exited_tasks = unsafe { EXITED_TASKS.current_ref_raw() };
+    let migrating_tasks = unsafe { MIGRATING_TASKS.current_ref_raw() };
+    let wait_for_exit = unsafe { WAIT_FOR_EXIT.current_ref_raw() };
     loop {
+        // Tasks here have been switched out, as we are on the same CPU.
+        loop {
+            let task = migrating_tasks.lock().pop_front();
+            match task {
+                Some(task) => migrate_task(task),
+                None => break,
+            }
+        }
+
         // Drop all exited tasks and recycle resources.
-        let n = EXITED_TASKS.lock().len();
+        let n = exited_tasks.lock().len();
         for _ in 0..n {
             // Do not do the slow drops in the critical section.
-            let task = EXITED_TASKS.lock().pop_front();
+            let task = exited_tasks.lock().pop_front();
             if let Some(task) = task {
                 if Arc::strong_count(&task) == 1 {
                     // If I'm the last holder of the task, drop it immediately.
                     drop(task);
                 } else {
-                    // Otherwise (e.g, `switch_to` is not compeleted, held by the
-                    // joiner, etc), push it back and wait for them to drop first.
-                    EXITED_TASKS.lock().push_back(task);
+                    // Otherwise (e.g, held by the joiner), push it back and
+                    // wait for them to drop first.
+                    exited_tasks.lock().push_back(task);
                 }
             }
         }
-        WAIT_FOR_EXIT.wait();
+        // Tasks are put into the lists with the run queue locked, so we won't
+        // miss the migrating ones.
+        let n = exited_tasks.lock().len();
+        wait_for_exit
+            .wait_until(|| exited_tasks.lock().len() > n || !migrating_tasks.lock().is_empty());
     }
 }
 
 pub(crate) fn init() {
+    let cpu_id = axhal::cpu::this_cpu_id();
+
     const IDLE_TASK_STACK_SIZE: usize = 4096;
     let idle_task = TaskInner::new(|| crate::run_idle(), "idle".into(), IDLE_TASK_STACK_SIZE);
+    idle_task.set_cpu(cpu_id);
     IDLE_TASK.with_current(|i| i.init_by(idle_task.clone()));
 
     let main_task = TaskInner::new_init("main".into());
+    main_task.set_cpu(cpu_id);
     main_task.set_state(TaskState::Running);
+    MAIN_TASK.init_by(main_task.clone());
 
-    RUN_QUEUE.init_by(AxRunQueue::new());
+    RUN_QUEUE.with_current(|rq| rq.init_by(AxRunQueue::new(cpu_id)));
     unsafe { CurrentTask::init_current(main_task) }
 }
 
 pub(crate) fn init_secondary() {
+    let cpu_id = axhal::cpu::this_cpu_id();
+
     let idle_task = TaskInner::new_init("idle".into());
+    idle_task.set_cpu(cpu_id);
     idle_task.set_state(TaskState::Running);
     IDLE_TASK.with_current(|i| i.init_by(idle_task.clone()));
+
+    CPU_IDLE.with_current(|idle| idle.store(true, Ordering::Relaxed));
+    RUN_QUEUE.with_current(|rq| rq.init_by(AxRunQueue::new(cpu_id)));
     unsafe { CurrentTask::init_current(idle_task) }
 }
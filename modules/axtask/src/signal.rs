@@ -0,0 +1,243 @@
+//! Signal emulation for tasks.
+//!
+//! Each task has a set of pending signals and a set of blocked signals. A
+//! signal sent to a task is recorded as pending, and is delivered by calling
+//! [`SignalIf::handle_signal`] when the task calls [`handle_pending_signals`],
+//! which the upper layer does on returning from system calls. With the
+//! `preempt` feature, signals are also delivered on returning from interrupts
+//! (see [`handle_pending_signals_on_irq_return`]), so that a task that makes
+//! no system calls still gets them.
+//!
+//! An unblocked signal ends the sleep of the task in [`sleep_until`], or its
+//! wait in [`WaitQueue::wait_until_interruptible`], so that it can return
+//! early to get the signal delivered.
+//!
+//! [`sleep_until`]: crate::sleep_until
+//! [`WaitQueue::wait_until_interruptible`]: crate::WaitQueue::wait_until_interruptible
+
+use core::sync::atomic::Ordering;
+
+#[cfg(feature = "irq")]
+use alloc::sync::Arc;
+
+#[cfg(feature = "irq")]
+use axhal::time::TimeValue;
+#[cfg(feature = "irq")]
+use spinlock::SpinNoIrq;
+
+use crate::{AxTaskRef, TaskInner};
+
+/// Signal numbers range from 1 to `NSIG - 1`.
+pub const NSIG: usize = 65;
+
+#[cfg(feature = "irq")]
+const SIGALRM: usize = 14;
+
+/// The interface to handle signals, it must be implemented by the upper
+/// layer (e.g. the POSIX API).
+#[crate_interface::def_interface]
+pub trait SignalIf {
+    /// Handles the signal `sig` in the context of the current task.
+    fn handle_signal(sig: usize);
+}
+
+/// The error of an interruptible wait that is ended by a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interrupted;
+
+const fn sig_bit(sig: usize) -> u64 {
+    1 << (sig - 1)
+}
+
+impl TaskInner {
+    /// Returns the set of pending signals, bit `n - 1` stands for signal `n`.
+    pub fn pending_signals(&self) -> u64 {
+        self.sig_pending.load(Ordering::Acquire)
+    }
+
+    /// Returns the set of blocked signals, bit `n - 1` stands for signal `n`.
+    pub fn blocked_signals(&self) -> u64 {
+        self.sig_blocked.load(Ordering::Acquire)
+    }
+
+    /// Replaces the set of blocked signals with `mask`, and returns the old one.
+    ///
+    /// Pending signals which become unblocked are not delivered until
+    /// [`handle_pending_signals`] is called.
+    pub fn set_blocked_signals(&self, mask: u64) -> u64 {
+        self.sig_blocked.swap(mask, Ordering::AcqRel)
+    }
+
+    /// Returns whether there are pending signals that are not blocked.
+    pub fn has_unblocked_signals(&self) -> bool {
+        self.pending_signals() & !self.blocked_signals() != 0
+    }
+}
+
+/// Sends the signal `sig` to the given task.
+///
+/// If the signal is not blocked and the task is sleeping or in an
+/// interruptible wait, it is woken up early.
+///
+/// # Panics
+///
+/// Panics if `sig` is not in `1..NSIG`.
+pub fn send_signal(task: &AxTaskRef, sig: usize) {
+    assert!((1..NSIG).contains(&sig), "invalid signal number: {}", sig);
+    debug!("send signal {} to {}", sig, task.id_name());
+    let bit = sig_bit(sig);
+    task.sig_pending.fetch_or(bit, Ordering::AcqRel);
+
+    if task.blocked_signals() & bit == 0 {
+        crate::run_queue::with_task_run_queue(task, |rq| {
+            if !task.is_blocked() {
+                return;
+            }
+            // The waiter leaves the wait queue by itself.
+            if task.sig_interruptible.load(Ordering::Acquire) {
+                rq.unblock_task(task.clone(), true);
+                return;
+            }
+            #[cfg(feature = "irq")]
+            if task.in_timer_list() && !task.in_wait_queue() {
+                crate::timers::cancel_alarm(task);
+                rq.unblock_task(task.clone(), true);
+            }
+        });
+    }
+}
+
+/// Delivers all pending and unblocked signals of the current task, in
+/// ascending order of signal numbers.
+///
+/// It must be called without holding any lock, since the signal handlers may
+/// do anything.
+pub fn handle_pending_signals() {
+    let curr = match crate::current_may_uninit() {
+        Some(curr) => curr,
+        None => return,
+    };
+    loop {
+        let pending = curr.pending_signals() & !curr.blocked_signals();
+        if pending == 0 {
+            break;
+        }
+        let sig = pending.trailing_zeros() as usize + 1;
+        let bit = sig_bit(sig);
+        if curr.sig_pending.fetch_and(!bit, Ordering::AcqRel) & bit != 0 {
+            crate_interface::call_interface!(SignalIf::handle_signal(sig));
+        }
+    }
+}
+
+/// Delivers the pending and unblocked signals of the current task on
+/// returning from an interrupt handler, including when the task is switched
+/// back in after being preempted there.
+///
+/// Signals are only delivered if the interrupted code can be preempted, i.e.
+/// it holds no spin locks, and IRQs are enabled while the signal handlers run.
+/// As with POSIX, the handlers must not take the locks that the interrupted
+/// code may hold (e.g. by calling functions that are not async-signal-safe).
+/// Without the `preempt` feature, it is unknown whether the interrupted code
+/// holds spin locks, so nothing is delivered.
+#[cfg(feature = "irq")]
+pub fn handle_pending_signals_on_irq_return() {
+    #[cfg(feature = "preempt")]
+    if crate::current_may_uninit()
+        .is_some_and(|curr| curr.can_preempt(0) && curr.has_unblocked_signals())
+    {
+        axhal::arch::enable_irqs();
+        handle_pending_signals();
+        axhal::arch::disable_irqs();
+    }
+}
+
+/// A real-time interval timer, like `ITIMER_REAL` of a process.
+///
+/// When it expires, `SIGALRM` is sent to the task that armed it.
+#[cfg(feature = "irq")]
+pub struct RealTimer {
+    inner: SpinNoIrq<RealTimerInner>,
+}
+
+#[cfg(feature = "irq")]
+struct RealTimerInner {
+    task: Option<AxTaskRef>,
+    deadline: Option<TimeValue>,
+    interval: TimeValue,
+    generation: u64,
+}
+
+#[cfg(feature = "irq")]
+impl RealTimer {
+    /// Creates a disarmed timer.
+    pub const fn new() -> Self {
+        Self {
+            inner: SpinNoIrq::new(RealTimerInner {
+                task: None,
+                deadline: None,
+                interval: TimeValue::ZERO,
+                generation: 0,
+            }),
+        }
+    }
+
+    /// Arms the timer to send `SIGALRM` to the current task at `deadline`,
+    /// and then every `interval` if it is not zero. The timer is disarmed if
+    /// `deadline` is [`None`].
+    ///
+    /// Returns the previous deadline and interval.
+    pub fn set(
+        self: &Arc<Self>,
+        deadline: Option<TimeValue>,
+        interval: TimeValue,
+    ) -> (Option<TimeValue>, TimeValue) {
+        let mut inner = self.inner.lock();
+        let old = (inner.deadline, inner.interval);
+        // Expired events which are not handled yet are ignored by the generation.
+        inner.generation += 1;
+        crate::timers::cancel_real_timer_event(self);
+        if let Some(deadline) = deadline {
+            crate::timers::set_real_timer_event(self.clone(), deadline, inner.generation);
+            inner.task = Some(crate::current().clone());
+        } else {
+            inner.task = None;
+        }
+        inner.deadline = deadline;
+        inner.interval = interval;
+        old
+    }
+
+    /// Returns the deadline and interval of the timer.
+    pub fn get(&self) -> (Option<TimeValue>, TimeValue) {
+        let inner = self.inner.lock();
+        (inner.deadline, inner.interval)
+    }
+
+    pub(crate) fn expired(self: &Arc<Self>, generation: u64) {
+        let mut inner = self.inner.lock();
+        if inner.generation != generation {
+            return;
+        }
+        let task = if inner.interval.is_zero() {
+            inner.deadline = None;
+            inner.task.take()
+        } else {
+            let deadline = inner.deadline.unwrap() + inner.interval;
+            crate::timers::set_real_timer_event(self.clone(), deadline, generation);
+            inner.deadline = Some(deadline);
+            inner.task.clone()
+        };
+        drop(inner);
+        if let Some(task) = task {
+            send_signal(&task, SIGALRM);
+        }
+    }
+}
+
+#[cfg(feature = "irq")]
+impl Default for RealTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
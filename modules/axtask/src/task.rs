@@ -1,18 +1,18 @@
 use alloc::{boxed::Box, string::String, sync::Arc};
 use core::ops::Deref;
-use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use core::{alloc::Layout, cell::UnsafeCell, fmt, ptr::NonNull};
 
-#[cfg(feature = "preempt")]
-use core::sync::atomic::AtomicUsize;
-
 #[cfg(feature = "tls")]
 use axhal::tls::TlsArea;
 
 use axhal::arch::TaskContext;
 use memory_addr::{align_up_4k, VirtAddr};
 
-use crate::{AxRunQueue, AxTask, AxTaskRef, WaitQueue};
+use crate::{AxTask, AxTaskRef, WaitQueue};
+
+/// The mask of all CPUs, bit `i` stands for CPU `i`.
+pub(crate) const ALL_CPUS: usize = usize::MAX >> (usize::BITS as usize - axconfig::SMP);
 
 /// A unique identifier for a thread.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -26,6 +26,8 @@ pub(crate) enum TaskState {
     Ready = 2,
     Blocked = 3,
     Exited = 4,
+    /// Being moved to another CPU by the gc task, wakeups are ignored.
+    Migrating = 5,
 }
 
 /// The inner task structure.
@@ -38,6 +40,11 @@ pub struct TaskInner {
     entry: Option<*mut dyn FnOnce()>,
     state: AtomicU8,
 
+    /// The CPU whose run queue the task belongs to.
+    cpu: AtomicUsize,
+    /// The CPUs that the task can run on, bit `i` stands for CPU `i`.
+    cpumask: AtomicUsize,
+
     in_wait_queue: AtomicBool,
     #[cfg(feature = "irq")]
     in_timer_list: AtomicBool,
@@ -47,6 +54,20 @@ pub struct TaskInner {
     #[cfg(feature = "preempt")]
     preempt_disable_count: AtomicUsize,
 
+    #[cfg(feature = "signal")]
+    pub(crate) sig_pending: AtomicU64,
+    #[cfg(feature = "signal")]
+    pub(crate) sig_blocked: AtomicU64,
+    /// Whether the task is in an interruptible wait, which signals end.
+    #[cfg(feature = "signal")]
+    pub(crate) sig_interruptible: AtomicBool,
+
+    /// CPU time consumed before the task was last switched in, in nanoseconds.
+    cpu_time: AtomicU64,
+    /// The time when the task was last switched in, in nanoseconds.
+    switched_in_at: AtomicU64,
+
+    exited: AtomicBool,
     exit_code: AtomicI32,
     wait_for_exit: WaitQueue,
 
@@ -77,6 +98,7 @@ impl From<u8> for TaskState {
             2 => Self::Ready,
             3 => Self::Blocked,
             4 => Self::Exited,
+            5 => Self::Migrating,
             _ => unreachable!(),
         }
     }
@@ -101,12 +123,24 @@ impl TaskInner {
         alloc::format!("Task({}, {:?})", self.id.as_u64(), self.name)
     }
 
+    /// Gets the CPUs that the task can run on, bit `i` stands for CPU `i`.
+    pub fn affinity(&self) -> usize {
+        self.cpumask.load(Ordering::Acquire)
+    }
+
+    /// Gets the CPU time consumed by the task, including the current running
+    /// period if it is running.
+    pub fn cpu_time(&self) -> axhal::time::TimeValue {
+        let nanos = self.cpu_time.load(Ordering::Acquire) + self.running_nanos();
+        axhal::time::TimeValue::from_nanos(nanos)
+    }
+
     /// Wait for the task to exit, and return the exit code.
     ///
     /// It will return immediately if the task has already exited (but not dropped).
     pub fn join(&self) -> Option<i32> {
         self.wait_for_exit
-            .wait_until(|| self.state() == TaskState::Exited);
+            .wait_until(|| self.exited.load(Ordering::Acquire));
         Some(self.exit_code.load(Ordering::Acquire))
     }
 }
@@ -121,6 +155,8 @@ impl TaskInner {
             is_init: false,
             entry: None,
             state: AtomicU8::new(TaskState::Ready as u8),
+            cpu: AtomicUsize::new(0),
+            cpumask: AtomicUsize::new(ALL_CPUS),
             in_wait_queue: AtomicBool::new(false),
             #[cfg(feature = "irq")]
             in_timer_list: AtomicBool::new(false),
@@ -128,6 +164,15 @@ impl TaskInner {
             need_resched: AtomicBool::new(false),
             #[cfg(feature = "preempt")]
             preempt_disable_count: AtomicUsize::new(0),
+            #[cfg(feature = "signal")]
+            sig_pending: AtomicU64::new(0),
+            #[cfg(feature = "signal")]
+            sig_blocked: AtomicU64::new(0),
+            #[cfg(feature = "signal")]
+            sig_interruptible: AtomicBool::new(false),
+            cpu_time: AtomicU64::new(0),
+            switched_in_at: AtomicU64::new(axhal::time::current_time_nanos()),
+            exited: AtomicBool::new(false),
             exit_code: AtomicI32::new(0),
             wait_for_exit: WaitQueue::new(),
             kstack: None,
@@ -212,6 +257,26 @@ impl TaskInner {
         self.is_idle
     }
 
+    #[inline]
+    pub(crate) fn cpu(&self) -> usize {
+        self.cpu.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_cpu(&self, cpu_id: usize) {
+        self.cpu.store(cpu_id, Ordering::Release)
+    }
+
+    #[inline]
+    pub(crate) fn set_affinity(&self, cpumask: usize) {
+        self.cpumask.store(cpumask & ALL_CPUS, Ordering::Release)
+    }
+
+    #[inline]
+    pub(crate) fn can_run_on(&self, cpu_id: usize) -> bool {
+        self.affinity() & (1 << cpu_id) != 0
+    }
+
     #[inline]
     pub(crate) fn in_wait_queue(&self) -> bool {
         self.in_wait_queue.load(Ordering::Acquire)
@@ -265,16 +330,45 @@ impl TaskInner {
     fn current_check_preempt_pending() {
         let curr = crate::current();
         if curr.need_resched.load(Ordering::Acquire) && curr.can_preempt(0) {
-            let mut rq = crate::RUN_QUEUE.lock();
+            let mut rq = crate::current_run_queue();
             if curr.need_resched.load(Ordering::Acquire) {
                 rq.preempt_resched();
             }
         }
     }
 
-    pub(crate) fn notify_exit(&self, exit_code: i32, rq: &mut AxRunQueue) {
+    /// Returns the time since the task was switched in (in nanoseconds), or
+    /// zero if it is not running.
+    pub(crate) fn running_nanos(&self) -> u64 {
+        if self.is_running() {
+            let now = axhal::time::current_time_nanos();
+            now.saturating_sub(self.switched_in_at.load(Ordering::Acquire))
+        } else {
+            0
+        }
+    }
+
+    /// Records that the task is switched in at `now` (in nanoseconds).
+    #[inline]
+    pub(crate) fn account_in(&self, now: u64) {
+        self.switched_in_at.store(now, Ordering::Release);
+    }
+
+    /// Records that the task is switched out at `now` (in nanoseconds), and
+    /// returns the time it has run since it was switched in.
+    #[inline]
+    pub(crate) fn account_out(&self, now: u64) -> u64 {
+        let ran = now.saturating_sub(self.switched_in_at.load(Ordering::Acquire));
+        self.cpu_time.fetch_add(ran, Ordering::AcqRel);
+        ran
+    }
+
+    /// Wakes up the joiners. It must be called without holding any run
+    /// queue, as the joiners may be on other CPUs.
+    pub(crate) fn notify_exit(&self, exit_code: i32) {
         self.exit_code.store(exit_code, Ordering::Release);
-        self.wait_for_exit.notify_all_locked(false, rq);
+        self.exited.store(true, Ordering::Release);
+        self.wait_for_exit.notify_all(false);
     }
 
     #[inline]
@@ -380,7 +474,7 @@ impl Deref for CurrentTask {
 
 extern "C" fn task_entry() -> ! {
     // release the lock that was implicitly held across the reschedule
-    unsafe { crate::RUN_QUEUE.force_unlock() };
+    unsafe { crate::run_queue::force_unlock_current() };
     #[cfg(feature = "irq")]
     axhal::arch::enable_irqs();
     let task = crate::current();
@@ -128,3 +128,53 @@ fn test_task_join() {
         assert_eq!(tasks[i].join(), Some(i as _));
     }
 }
+
+#[test]
+fn test_affinity() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let cpu_id = axhal::cpu::this_cpu_id();
+    let curr = current().as_task_ref().clone();
+    assert!(!axtask::set_affinity(&curr, 0));
+    if axconfig::SMP < usize::BITS as usize {
+        assert!(!axtask::set_affinity(&curr, 1 << axconfig::SMP));
+    }
+    assert!(axtask::set_affinity(&curr, 1 << cpu_id));
+    assert_eq!(curr.affinity(), 1 << cpu_id);
+    assert_eq!(axhal::cpu::this_cpu_id(), cpu_id);
+
+    let task = axtask::TaskBuilder::new()
+        .name("affinity".into())
+        .affinity(1 << cpu_id)
+        .spawn(move || {
+            for _ in 0..10 {
+                assert_eq!(axhal::cpu::this_cpu_id(), cpu_id);
+                axtask::sleep(core::time::Duration::from_millis(1));
+                assert_eq!(axhal::cpu::this_cpu_id(), cpu_id);
+                axtask::yield_now();
+            }
+            axtask::exit(0);
+        });
+    assert_eq!(task.affinity(), 1 << cpu_id);
+    assert_eq!(task.join(), Some(0));
+
+    assert!(axtask::set_affinity(&curr, usize::MAX));
+}
+
+#[test]
+fn test_sched_policy() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let curr = current().as_task_ref().clone();
+    assert_eq!(axtask::sched_policy(&curr), axtask::SchedPolicy::Normal);
+    assert!(axtask::set_sched_policy(&curr, axtask::SchedPolicy::Normal));
+    // Real-time policies are only supported by the layered scheduler.
+    let rt = axtask::SchedPolicy::Fifo(axtask::RT_PRIO_MAX);
+    assert_eq!(
+        axtask::set_sched_policy(&curr, rt),
+        cfg!(feature = "sched_rt")
+    );
+    assert!(axtask::set_sched_policy(&curr, axtask::SchedPolicy::Normal));
+}
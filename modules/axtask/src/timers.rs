@@ -1,34 +1,76 @@
-use alloc::sync::Arc;
+use alloc::{boxed::Box, sync::Arc};
 use axhal::time::current_time;
 use lazy_init::LazyInit;
 use spinlock::SpinNoIrq;
 use timer_list::{TimeValue, TimerEvent, TimerList};
 
-use crate::{AxTaskRef, RUN_QUEUE};
+use crate::AxTaskRef;
+#[cfg(feature = "signal")]
+use crate::RealTimer;
 
 // TODO: per-CPU
-static TIMER_LIST: LazyInit<SpinNoIrq<TimerList<TaskWakeupEvent>>> = LazyInit::new();
+static TIMER_LIST: LazyInit<SpinNoIrq<TimerList<TaskTimerEvent>>> = LazyInit::new();
 
-struct TaskWakeupEvent(AxTaskRef);
+enum TaskTimerEvent {
+    /// Wakes up the sleeping task.
+    Wakeup(AxTaskRef),
+    /// The real-time interval timer expires, with its generation.
+    #[cfg(feature = "signal")]
+    RealTimer(Arc<RealTimer>, u64),
+    /// Calls the callback, the key is used to cancel it.
+    Callback(usize, Box<dyn FnOnce(TimeValue) + Send>),
+}
 
-impl TimerEvent for TaskWakeupEvent {
-    fn callback(self, _now: TimeValue) {
-        let mut rq = RUN_QUEUE.lock();
-        self.0.set_in_timer_list(false);
-        rq.unblock_task(self.0, true);
+impl TimerEvent for TaskTimerEvent {
+    fn callback(self, now: TimeValue) {
+        match self {
+            Self::Wakeup(task) => {
+                task.set_in_timer_list(false);
+                crate::run_queue::unblock_task(task, true);
+            }
+            #[cfg(feature = "signal")]
+            Self::RealTimer(timer, generation) => timer.expired(generation),
+            Self::Callback(_, callback) => callback(now),
+        }
     }
 }
 
 pub fn set_alarm_wakeup(deadline: TimeValue, task: AxTaskRef) {
     let mut timers = TIMER_LIST.lock();
     task.set_in_timer_list(true);
-    timers.set(deadline, TaskWakeupEvent(task));
+    timers.set(deadline, TaskTimerEvent::Wakeup(task));
 }
 
 pub fn cancel_alarm(task: &AxTaskRef) {
     let mut timers = TIMER_LIST.lock();
     task.set_in_timer_list(false);
-    timers.cancel(|t| Arc::ptr_eq(&t.0, task));
+    timers.cancel(|e| matches!(e, TaskTimerEvent::Wakeup(t) if Arc::ptr_eq(t, task)));
+}
+
+#[cfg(feature = "signal")]
+pub fn set_real_timer_event(timer: Arc<RealTimer>, deadline: TimeValue, generation: u64) {
+    TIMER_LIST
+        .lock()
+        .set(deadline, TaskTimerEvent::RealTimer(timer, generation));
+}
+
+#[cfg(feature = "signal")]
+pub fn cancel_real_timer_event(timer: &Arc<RealTimer>) {
+    TIMER_LIST
+        .lock()
+        .cancel(|e| matches!(e, TaskTimerEvent::RealTimer(t, _) if Arc::ptr_eq(t, timer)));
+}
+
+pub fn set_callback(deadline: TimeValue, key: usize, callback: Box<dyn FnOnce(TimeValue) + Send>) {
+    TIMER_LIST
+        .lock()
+        .set(deadline, TaskTimerEvent::Callback(key, callback));
+}
+
+pub fn cancel_callback(key: usize) {
+    TIMER_LIST
+        .lock()
+        .cancel(|e| matches!(e, TaskTimerEvent::Callback(k, _) if *k == key));
 }
 
 pub fn check_events() {
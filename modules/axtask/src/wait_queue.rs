@@ -1,8 +1,13 @@
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
+use kernel_guard::NoPreemptIrqSave;
 use spinlock::SpinRaw;
 
-use crate::{AxRunQueue, AxTaskRef, CurrentTask, RUN_QUEUE};
+use crate::{current_run_queue, AxRunQueue, AxTaskRef, CurrentTask};
+#[cfg(feature = "signal")]
+use crate::Interrupted;
+#[cfg(feature = "signal")]
+use core::sync::atomic::Ordering;
 
 /// A queue to store sleeping tasks.
 ///
@@ -27,7 +32,7 @@ use crate::{AxRunQueue, AxTaskRef, CurrentTask, RUN_QUEUE};
 /// assert_eq!(VALUE.load(Ordering::Relaxed), 1);
 /// ```
 pub struct WaitQueue {
-    queue: SpinRaw<VecDeque<AxTaskRef>>, // we already disabled IRQs when lock the `RUN_QUEUE`
+    queue: SpinRaw<VecDeque<AxTaskRef>>, // IRQs and preemption must be disabled when locking it
 }
 
 impl WaitQueue {
@@ -45,27 +50,42 @@ impl WaitQueue {
         }
     }
 
-    fn cancel_events(&self, curr: CurrentTask) {
+    /// Returns whether the current task was still in the wait queue, i.e.,
+    /// it was woken up by the timer.
+    fn cancel_events(&self, curr: CurrentTask) -> bool {
         // A task can be wake up only one events (timer or `notify()`), remove
         // the event from another queue.
-        if curr.in_wait_queue() {
+        let guard = NoPreemptIrqSave::new();
+        let mut wq = self.queue.lock();
+        let timeout = curr.in_wait_queue();
+        if timeout {
             // wake up by timer (timeout).
-            // `RUN_QUEUE` is not locked here, so disable IRQs.
-            let _guard = kernel_guard::IrqSave::new();
-            self.queue.lock().retain(|t| !curr.ptr_eq(t));
+            wq.retain(|t| !curr.ptr_eq(t));
             curr.set_in_wait_queue(false);
         }
+        drop(wq);
+        drop(guard);
         #[cfg(feature = "irq")]
         if curr.in_timer_list() {
             // timeout was set but not triggered (wake up by `WaitQueue::notify()`)
             crate::timers::cancel_alarm(curr.as_task_ref());
         }
+        timeout
+    }
+
+    /// Removes the first task from the wait queue.
+    fn pop_front(&self) -> Option<AxTaskRef> {
+        let _guard = NoPreemptIrqSave::new();
+        let mut wq = self.queue.lock();
+        let task = wq.pop_front()?;
+        task.set_in_wait_queue(false);
+        Some(task)
     }
 
     /// Blocks the current task and put it into the wait queue, until other task
     /// notifies it.
     pub fn wait(&self) {
-        RUN_QUEUE.lock().block_current(|task| {
+        current_run_queue().block_current(|task| {
             task.set_in_wait_queue(true);
             self.queue.lock().push_back(task)
         });
@@ -82,18 +102,62 @@ impl WaitQueue {
         F: Fn() -> bool,
     {
         loop {
-            let mut rq = RUN_QUEUE.lock();
+            let mut rq = current_run_queue();
+            // Check the condition with the wait queue locked, so we won't
+            // miss the notification after it becomes true.
+            let mut wq = self.queue.lock();
             if condition() {
                 break;
             }
-            rq.block_current(|task| {
+            rq.block_current(move |task| {
                 task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
+                wq.push_back(task);
             });
         }
         self.cancel_events(crate::current());
     }
 
+    /// Blocks the current task and put it into the wait queue, until the given
+    /// `condition` becomes true, or an unblocked signal is pending for it.
+    ///
+    /// Returns [`Interrupted`] in the latter case. The signal is not delivered
+    /// here, but when [`handle_pending_signals`] is called later.
+    ///
+    /// [`handle_pending_signals`]: crate::handle_pending_signals
+    #[cfg(feature = "signal")]
+    pub fn wait_until_interruptible<F>(&self, condition: F) -> Result<(), Interrupted>
+    where
+        F: Fn() -> bool,
+    {
+        let curr = crate::current();
+        let mut blocked = false;
+        let res = loop {
+            let mut rq = current_run_queue();
+            let mut wq = self.queue.lock();
+            if condition() {
+                break Ok(());
+            }
+            // Checked with the run queue locked, so a signal sent before the
+            // task blocks is not missed.
+            if curr.has_unblocked_signals() {
+                break Err(Interrupted);
+            }
+            curr.sig_interruptible.store(true, Ordering::Release);
+            rq.block_current(move |task| {
+                task.set_in_wait_queue(true);
+                wq.push_back(task);
+            });
+            curr.sig_interruptible.store(false, Ordering::Release);
+            blocked = true;
+        };
+        let notified = blocked && !self.cancel_events(curr);
+        if res.is_err() && notified {
+            // Pass the notification on, as if it came after the signal.
+            self.notify_one(true);
+        }
+        res
+    }
+
     /// Blocks the current task and put it into the wait queue, until other tasks
     /// notify it, or the given duration has elapsed.
     #[cfg(feature = "irq")]
@@ -107,12 +171,12 @@ impl WaitQueue {
         );
         crate::timers::set_alarm_wakeup(deadline, curr.clone());
 
-        RUN_QUEUE.lock().block_current(|task| {
+        current_run_queue().block_current(|task| {
             task.set_in_wait_queue(true);
             self.queue.lock().push_back(task)
         });
-        let timeout = curr.in_wait_queue(); // still in the wait queue, must have timed out
-        self.cancel_events(curr);
+        // still in the wait queue, must have timed out
+        let timeout = self.cancel_events(curr);
         timeout
     }
 
@@ -137,28 +201,82 @@ impl WaitQueue {
 
         let mut timeout = true;
         while axhal::time::current_time() < deadline {
-            let mut rq = RUN_QUEUE.lock();
+            let mut rq = current_run_queue();
+            let mut wq = self.queue.lock();
             if condition() {
                 timeout = false;
                 break;
             }
-            rq.block_current(|task| {
+            rq.block_current(move |task| {
                 task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
+                wq.push_back(task);
             });
         }
         self.cancel_events(curr);
         timeout
     }
 
+    /// Like [`wait_timeout_until`](Self::wait_timeout_until), but also wakes
+    /// up when an unblocked signal is pending for the current task, in which
+    /// case [`Interrupted`] is returned.
+    ///
+    /// Otherwise, returns whether the given duration has elapsed.
+    #[cfg(all(feature = "irq", feature = "signal"))]
+    pub fn wait_timeout_until_interruptible<F>(
+        &self,
+        dur: core::time::Duration,
+        condition: F,
+    ) -> Result<bool, Interrupted>
+    where
+        F: Fn() -> bool,
+    {
+        let curr = crate::current();
+        let deadline = axhal::time::current_time() + dur;
+        debug!(
+            "task wait_timeout: {}, deadline={:?}",
+            curr.id_name(),
+            deadline
+        );
+        crate::timers::set_alarm_wakeup(deadline, curr.clone());
+
+        let mut res = Ok(true);
+        let mut blocked = false;
+        while axhal::time::current_time() < deadline {
+            let mut rq = current_run_queue();
+            let mut wq = self.queue.lock();
+            if condition() {
+                res = Ok(false);
+                break;
+            }
+            // Checked with the run queue locked, see `wait_until_interruptible`.
+            if curr.has_unblocked_signals() {
+                res = Err(Interrupted);
+                break;
+            }
+            curr.sig_interruptible.store(true, Ordering::Release);
+            rq.block_current(move |task| {
+                task.set_in_wait_queue(true);
+                wq.push_back(task);
+            });
+            curr.sig_interruptible.store(false, Ordering::Release);
+            blocked = true;
+        }
+        let notified = blocked && !self.cancel_events(curr);
+        if res.is_err() && notified {
+            // Pass the notification on, as if it came after the signal.
+            self.notify_one(true);
+        }
+        res
+    }
+
     /// Wakes up one task in the wait queue, usually the first one.
     ///
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
     pub fn notify_one(&self, resched: bool) -> bool {
-        let mut rq = RUN_QUEUE.lock();
-        if !self.queue.lock().is_empty() {
-            self.notify_one_locked(resched, &mut rq)
+        if let Some(task) = self.pop_front() {
+            crate::run_queue::unblock_task(task, resched);
+            true
         } else {
             false
         }
@@ -169,15 +287,8 @@ impl WaitQueue {
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
     pub fn notify_all(&self, resched: bool) {
-        loop {
-            let mut rq = RUN_QUEUE.lock();
-            if let Some(task) = self.queue.lock().pop_front() {
-                task.set_in_wait_queue(false);
-                rq.unblock_task(task, resched);
-            } else {
-                break;
-            }
-            drop(rq); // we must unlock `RUN_QUEUE` after unlocking `self.queue`.
+        while let Some(task) = self.pop_front() {
+            crate::run_queue::unblock_task(task, resched);
         }
     }
 
@@ -186,31 +297,31 @@ impl WaitQueue {
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
     pub fn notify_task(&mut self, resched: bool, task: &AxTaskRef) -> bool {
-        let mut rq = RUN_QUEUE.lock();
-        let mut wq = self.queue.lock();
-        if let Some(index) = wq.iter().position(|t| Arc::ptr_eq(t, task)) {
-            task.set_in_wait_queue(false);
-            rq.unblock_task(wq.remove(index).unwrap(), resched);
+        let task = {
+            let _guard = NoPreemptIrqSave::new();
+            let mut wq = self.queue.lock();
+            let index = wq.iter().position(|t| Arc::ptr_eq(t, task));
+            index.map(|index| {
+                task.set_in_wait_queue(false);
+                wq.remove(index).unwrap()
+            })
+        };
+        if let Some(task) = task {
+            crate::run_queue::unblock_task(task, resched);
             true
         } else {
             false
         }
     }
 
+    /// Wakes up one task in the wait queue with the run queue locked, the
+    /// task must belong to `rq`.
     pub(crate) fn notify_one_locked(&self, resched: bool, rq: &mut AxRunQueue) -> bool {
-        if let Some(task) = self.queue.lock().pop_front() {
-            task.set_in_wait_queue(false);
+        if let Some(task) = self.pop_front() {
             rq.unblock_task(task, resched);
             true
         } else {
             false
         }
     }
-
-    pub(crate) fn notify_all_locked(&self, resched: bool, rq: &mut AxRunQueue) {
-        while let Some(task) = self.queue.lock().pop_front() {
-            task.set_in_wait_queue(false);
-            rq.unblock_task(task, resched);
-        }
-    }
 }
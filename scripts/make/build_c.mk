@@ -68,16 +68,37 @@ app-objs := main.o
 
 -include $(APP)/axbuild.mk  # override `app-objs`
 
+# Symbols that can be found by `dlopen` and `dlsym`
+dl_exports := $(wildcard $(APP)/dl_exports.txt)
+ifneq ($(dl_exports),)
+  app-objs += dl_table.o
+endif
+
 app-objs := $(addprefix $(APP)/,$(app-objs))
 
+# Symbol sizes are read from the other objects of the app
+$(APP)/dl_table.c: $(dl_exports) $(filter-out $(APP)/dl_table.o,$(app-objs))
+	$(call run_cmd,NM=$(NM) sh,scripts/make/gen_dl_table.sh $^ > $@ || (rm -f $@; false))
+
 $(APP)/%.o: $(APP)/%.c $(ulib_hdr)
 	$(call run_cmd,$(CC),$(CFLAGS) $(APP_CFLAGS) -c -o $@ $<)
 
 $(rust_lib): _cargo_build
 
+# The table of functions for `dladdr` is generated from a first link of the
+# image, and must be the same when generated again from the final one.
+ksyms_elf := $(basename $(OUT_ELF))_ksyms.elf
+ksyms_src := $(APP)/ksyms.c
+ksyms_obj := $(APP)/ksyms.o
+gen_ksyms = NM=$(NM) sh scripts/make/gen_kallsyms.sh $(1) $(notdir $(OUT_ELF))
+
 $(OUT_ELF): $(c_lib) $(rust_lib) $(libgcc) $(app-objs)
 	@printf "    $(CYAN_C)Linking$(END_C) $(OUT_ELF)\n"
-	$(call run_cmd,$(LD),$(LDFLAGS) $^ -o $@)
+	$(call run_cmd,$(LD),$(LDFLAGS) $^ -o $(ksyms_elf))
+	$(call run_cmd,$(call gen_ksyms,$(ksyms_elf)),> $(ksyms_src) || (rm -f $(ksyms_src); false))
+	$(call run_cmd,$(CC),$(CFLAGS) -c -o $(ksyms_obj) $(ksyms_src))
+	$(call run_cmd,$(LD),$(LDFLAGS) $^ $(ksyms_obj) -o $@)
+	$(call run_cmd,$(call gen_ksyms,$@),| cmp -s - $(ksyms_src) || (echo "functions moved after adding $(ksyms_src)"; rm -f $@; false))
 
 $(APP)/axbuild.mk: ;
 
@@ -0,0 +1,47 @@
+#!/bin/sh
+# Generates the symbol registry used by `dlopen` and `dlsym` in axlibc.
+#
+# Usage: gen_dl_table.sh <dl_exports.txt> [<object>...]
+#
+# Each line of the input is "<library> <symbol> [<name>]", blank lines and
+# lines starting with '#' are ignored. The symbol is looked up by `<name>`,
+# which defaults to the symbol itself, so that e.g. the `main` function of a
+# program can be linked in as "echo_main" and exported as "main". The sizes
+# of the symbols, used by `dladdr`, are read from the given objects with
+# `$NM` (default `nm`). The C source is written to stdout.
+
+exports=$1
+shift
+
+{ [ $# -eq 0 ] || ${NM:-nm} -S --defined-only "$@"; } | awk '
+BEGIN { n = 0 }
+# "<address> <size> <type> <name>" lines of `nm -S`
+FILENAME == "-" { if (NF == 4) size[$4] = "0x" $2; next }
+/^[ \t]*(#|$)/ { next }
+NF != 2 && NF != 3 {
+    printf "%s:%d: expected \"<library> <symbol> [<name>]\"\n", FILENAME, FNR > "/dev/stderr"
+    err = 1
+    exit 1
+}
+{ lib[n] = $1; sym[n] = $2; name[n] = NF == 3 ? $3 : $2; n++ }
+END {
+    if (err)
+        exit 1
+    print "/* Generated from " FILENAME ", do not edit. */"
+    print ""
+    print "#include <dlfcn.h>"
+    print "#include <stddef.h>"
+    print ""
+    for (i = 0; i < n; i++)
+        if (!(sym[i] in declared)) {
+            print "extern char " sym[i] "[];"
+            declared[sym[i]] = 1
+        }
+    print ""
+    print "const struct __dl_symbol __ax_dl_symbols[] = {"
+    for (i = 0; i < n; i++)
+        print "    {\"" lib[i] "\", \"" name[i] "\", " sym[i] ", " (sym[i] in size ? size[sym[i]] : "0") "},"
+    print "    {NULL, NULL, NULL, 0},"
+    print "};"
+}
+' - "$exports"
@@ -0,0 +1,35 @@
+#!/bin/sh
+# Generates the table of the functions in a linked kernel image, used by
+# `dladdr` in axlibc to resolve addresses that are not in the symbol registry
+# (see `gen_dl_table.sh`), like `kallsyms` of Linux.
+#
+# Usage: gen_kallsyms.sh <elf> <name>
+#
+# The image is linked once without the table to get the addresses, then again
+# with it. The table is not code, so it is placed after the functions and does
+# not move them, which is checked by generating it again from the final image.
+# `<name>` is the file name reported by `dladdr`. Symbols are read with `$NM`
+# (default `nm`), and the C source is written to stdout.
+
+${NM:-nm} -n -S --defined-only "$1" | awk -v image="$2" '
+BEGIN { n = 0 }
+# "<address> [<size>] <type> <name>" lines of `nm -n -S`, sorted by address.
+# Skip local labels and the mapping symbols of ARM. Symbols without a size
+# extend to the next one.
+NF == 3 { $4 = $3; $3 = $2; $2 = "0" }
+$3 ~ /^[tT]$/ && $4 !~ /^(\.L|\$)/ { addr[n] = $1; size[n] = $2; name[n] = $4; n++ }
+END {
+    print "/* Generated from " image " by gen_kallsyms.sh, do not edit. */"
+    print ""
+    print "#include <dlfcn.h>"
+    print "#include <stddef.h>"
+    print ""
+    print "const char __ax_ksyms_image[] = \"" image "\";"
+    print ""
+    print "const struct __ksym __ax_ksyms[] = {"
+    for (i = 0; i < n; i++)
+        print "    {0x" addr[i] "UL, 0x" size[i] "UL, \"" name[i] "\"},"
+    print "    {0, 0, NULL},"
+    print "};"
+}
+'
@@ -54,6 +54,9 @@ ifeq ($(NET_DUMP), y)
   qemu_args-$(NET) += -object filter-dump,id=dump0,netdev=net0,file=netdump.pcap
 endif
 
+qemu_args-$(RNG) += \
+  -device virtio-rng-$(vdev-suffix)
+
 qemu_args-$(GRAPHIC) += \
   -device virtio-gpu-$(vdev-suffix) -vga none \
   -serial mon:stdio
@@ -0,0 +1,108 @@
+#!/usr/bin/env python3
+#
+# A TLS 1.3 echo server for testing `axstd::net::TlsStream` on the host.
+#
+# It generates a CA and a server certificate for `localhost` in the output
+# directory (default: `./tls-test`), then echoes every connection on port 4433.
+# Copy `ca.pem` to `/ca.pem` in the disk image before running the client.
+#
+# ArceOS has no wall clock yet and checks certificates against the time since
+# boot, so the certificates are valid from the Unix epoch.
+#
+# Requires the `cryptography` Python package.
+#
+# Usage: tls-test-server.py [OUT_DIR] [PORT]
+
+import datetime
+import ipaddress
+import os
+import socket
+import ssl
+import sys
+import threading
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+from cryptography.x509.oid import NameOID
+
+out_dir = sys.argv[1] if len(sys.argv) > 1 else "tls-test"
+port = int(sys.argv[2]) if len(sys.argv) > 2 else 4433
+
+
+def write_pem(name, data):
+    with open(os.path.join(out_dir, name), "wb") as f:
+        f.write(data)
+
+
+def gen_certs():
+    os.makedirs(out_dir, exist_ok=True)
+    if os.path.exists(os.path.join(out_dir, "server.pem")):
+        return
+
+    def name(cn):
+        return x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, cn)])
+
+    def builder(subject, issuer, key):
+        return (x509.CertificateBuilder()
+                .subject_name(name(subject))
+                .issuer_name(name(issuer))
+                .public_key(key.public_key())
+                .serial_number(x509.random_serial_number())
+                .not_valid_before(datetime.datetime(1970, 1, 1, tzinfo=datetime.timezone.utc))
+                .not_valid_after(datetime.datetime(2099, 12, 31, tzinfo=datetime.timezone.utc)))
+
+    ca_key = ec.generate_private_key(ec.SECP256R1())
+    ca_cert = (builder("ArceOS Test CA", "ArceOS Test CA", ca_key)
+               .add_extension(x509.BasicConstraints(ca=True, path_length=None), critical=True)
+               .sign(ca_key, hashes.SHA256()))
+    key = ec.generate_private_key(ec.SECP256R1())
+    san = x509.SubjectAlternativeName([
+        x509.DNSName("localhost"),
+        x509.IPAddress(ipaddress.ip_address("127.0.0.1")),
+    ])
+    cert = (builder("localhost", "ArceOS Test CA", key)
+            .add_extension(san, critical=False)
+            .sign(ca_key, hashes.SHA256()))
+
+    write_pem("ca.pem", ca_cert.public_bytes(serialization.Encoding.PEM))
+    write_pem("server.pem", cert.public_bytes(serialization.Encoding.PEM))
+    write_pem("server.key", key.private_bytes(
+        serialization.Encoding.PEM,
+        serialization.PrivateFormat.PKCS8,
+        serialization.NoEncryption(),
+    ))
+
+
+def echo(conn, addr):
+    with conn:
+        while True:
+            data = conn.recv(4096)
+            if not data:
+                break
+            conn.sendall(data)
+    print(f"{addr} closed")
+
+
+def main():
+    gen_certs()
+    ctx = ssl.SSLContext(ssl.PROTOCOL_TLS_SERVER)
+    ctx.minimum_version = ssl.TLSVersion.TLSv1_3
+    ctx.load_cert_chain(os.path.join(out_dir, "server.pem"), os.path.join(out_dir, "server.key"))
+
+    with socket.create_server(("0.0.0.0", port)) as sock:
+        print(f"listening on port {port}, CA certificate: {os.path.join(out_dir, 'ca.pem')}")
+        while True:
+            conn, addr = sock.accept()
+            try:
+                conn = ctx.wrap_socket(conn, server_side=True)
+            except (ssl.SSLError, OSError) as e:
+                print(f"{addr} handshake failed: {e}")
+                conn.close()
+                continue
+            print(f"{addr} connected")
+            threading.Thread(target=echo, args=(conn, addr), daemon=True).start()
+
+
+if __name__ == "__main__":
+    main()
@@ -115,14 +115,31 @@ if [ -z "$1" ]; then
         "apps/task/priority"
         "apps/task/tls"
         "apps/net/httpclient"
+        "apps/net/loopback"
         "apps/c/helloworld"
         "apps/c/memtest"
+        "apps/c/dlfcn"
+        "apps/c/mmap"
+        "apps/c/fs"
         "apps/c/sqlite3"
         "apps/c/httpclient"
+        "apps/c/poll"
+        "apps/c/unix"
+        "apps/c/sockopt"
+        "apps/c/tty"
+        "apps/c/time"
+        "apps/c/rlimit"
+        "apps/c/signal"
+        "apps/c/eventfd"
+        "apps/c/spawn"
+        "apps/c/random"
         "apps/c/pthread/basic"
         "apps/c/pthread/sleep"
         "apps/c/pthread/pipe"
         "apps/c/pthread/parallel"
+        "apps/c/pthread/sync"
+        "apps/c/pthread/tls"
+        "apps/c/pthread/affinity"
     )
 else
     test_list="$@"
@@ -1,39 +1,162 @@
 #include <dlfcn.h>
-#include <pthread.h>
+#include <stddef.h>
 #include <stdio.h>
+#include <string.h>
 
-// TODO
-int dladdr(const void *__address, Dl_info *__info)
+#include "thread_local.h"
+
+// Generated by `scripts/make/gen_dl_table.sh`, NULL if the app has no
+// `dl_exports.txt`.
+extern const struct __dl_symbol __ax_dl_symbols[] __attribute__((weak));
+
+// Generated by `scripts/make/gen_kallsyms.sh`, NULL in the first link of the
+// kernel image.
+extern const struct __ksym __ax_ksyms[] __attribute__((weak));
+extern const char __ax_ksyms_image[] __attribute__((weak));
+
+// Start and end of the kernel image, defined in the linker script.
+extern char _skernel[], _ekernel[];
+
+// The handle returned by `dlopen(NULL, ...)`.
+static char main_handle;
+
+static __ax_thread_local const char *dl_error;
+static __ax_thread_local char dl_error_buf[128];
+
+static void set_error(const char *fmt, const char *s)
 {
-    unimplemented();
-    return 0;
+    snprintf(dl_error_buf, sizeof(dl_error_buf), fmt, s);
+    dl_error = dl_error_buf;
+}
+
+static const char *basename_of(const char *path)
+{
+    const char *p = strrchr(path, '/');
+    return p ? p + 1 : path;
+}
+
+static int is_global_handle(void *handle)
+{
+    return handle == RTLD_DEFAULT || handle == RTLD_NEXT || handle == &main_handle;
 }
 
-// TODO
-void *dlopen(const char *__file, int __mode)
+// A library handle points to its first entry in the registry.
+static const struct __dl_symbol *find_lib(void *handle)
 {
-    unimplemented();
+    for (const struct __dl_symbol *s = __ax_dl_symbols; s && s->lib; s++) {
+        if (s == handle)
+            return s;
+    }
     return NULL;
 }
 
-// TODO
-char *dlerror()
+void *dlopen(const char *file, int mode)
 {
-    unimplemented();
+    if (!file)
+        return &main_handle;
+
+    // All libraries are "installed" in every directory.
+    const char *name = basename_of(file);
+    for (const struct __dl_symbol *s = __ax_dl_symbols; s && s->lib; s++) {
+        if (strcmp(s->lib, name) == 0)
+            return (void *)s;
+    }
+    set_error("%s: cannot open shared object file: No such file or directory", file);
     return NULL;
 }
 
-// TODO
-void *dlsym(void *__restrict__ __handle, const char *__restrict__ __name)
+void *dlsym(void *__restrict__ handle, const char *__restrict__ name)
 {
+    const struct __dl_symbol *lib = NULL;
+    if (!is_global_handle(handle) && !(lib = find_lib(handle))) {
+        set_error("%s", "Invalid handle");
+        return NULL;
+    }
 
-    unimplemented();
+    for (const struct __dl_symbol *s = __ax_dl_symbols; s && s->lib; s++) {
+        if ((!lib || strcmp(s->lib, lib->lib) == 0) && strcmp(s->name, name) == 0)
+            return s->addr;
+    }
+    set_error("undefined symbol: %s", name);
     return NULL;
 }
 
-// TODO
-int dlclose(void *p)
+static int contains(const struct __dl_symbol *s, const char *addr)
+{
+    const char *start = s->addr;
+    return s->size ? addr >= start && addr - start < s->size : addr == start;
+}
+
+// Finds the function of the kernel image that `addr` lies in.
+static const struct __ksym *find_ksym(const void *addr)
+{
+    if (!__ax_ksyms || !__ax_ksyms[0].name)
+        return NULL;
+
+    // The last function that starts at or before `addr`.
+    unsigned long a = (unsigned long)addr;
+    size_t lo = 0, hi = 0;
+    while (__ax_ksyms[hi].name)
+        hi++;
+    while (hi - lo > 1) {
+        size_t mid = lo + (hi - lo) / 2;
+        if (__ax_ksyms[mid].addr <= a)
+            lo = mid;
+        else
+            hi = mid;
+    }
+
+    const struct __ksym *k = &__ax_ksyms[lo];
+    if (a < k->addr)
+        return NULL;
+    if (k->size)
+        return a - k->addr < k->size ? k : NULL;
+    return !k[1].name || a < k[1].addr ? k : NULL;
+}
+
+int dladdr(const void *addr, Dl_info *info)
+{
+    if ((char *)addr < _skernel || (char *)addr >= _ekernel)
+        return 0;
+
+    // Everything is linked into the kernel image, find the registered symbol
+    // that `addr` lies in.
+    const struct __dl_symbol *found = NULL;
+    for (const struct __dl_symbol *s = __ax_dl_symbols; s && s->lib; s++) {
+        if (contains(s, addr) && (!found || s->addr > found->addr))
+            found = s;
+    }
+    if (found) {
+        info->dli_fname = found->lib;
+        info->dli_fbase = _skernel;
+        info->dli_sname = found->name;
+        info->dli_saddr = found->addr;
+        return 1;
+    }
+
+    // Otherwise, fall back to the functions of the image.
+    const struct __ksym *k = find_ksym(addr);
+    if (!k)
+        return 0;
+    info->dli_fname = __ax_ksyms_image;
+    info->dli_fbase = _skernel;
+    info->dli_sname = k->name;
+    info->dli_saddr = (void *)k->addr;
+    return 1;
+}
+
+char *dlerror()
+{
+    const char *err = dl_error;
+    dl_error = NULL;
+    return (char *)err;
+}
+
+int dlclose(void *handle)
 {
-    unimplemented();
+    if (!is_global_handle(handle) && !find_lib(handle)) {
+        set_error("%s", "Invalid handle");
+        return 1;
+    }
     return 0;
 }
@@ -0,0 +1,16 @@
+#ifdef AX_CONFIG_EVENTFD
+
+#include <sys/eventfd.h>
+#include <unistd.h>
+
+int eventfd_read(int fd, eventfd_t *value)
+{
+    return (sizeof(*value) == read(fd, value, sizeof(*value))) ? 0 : -1;
+}
+
+int eventfd_write(int fd, eventfd_t value)
+{
+    return (sizeof(value) == write(fd, &value, sizeof(value))) ? 0 : -1;
+}
+
+#endif // AX_CONFIG_EVENTFD
@@ -1,3 +1,5 @@
+#ifndef AX_CONFIG_FS
+
 #include <stdio.h>
 #include <sys/file.h>
 
@@ -7,3 +9,5 @@ int flock(int __fd, int __operation)
     unimplemented();
     return 0;
 }
+
+#endif // AX_CONFIG_FS
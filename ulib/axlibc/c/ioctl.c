@@ -1,9 +1,20 @@
-#include <stdio.h>
+#ifdef AX_CONFIG_FD
+
+#include <stdarg.h>
 #include <sys/ioctl.h>
 
-// TODO
-int ioctl(int __fd, int __request, ...)
+// TODO: remove this function in future work
+int ax_ioctl(int fd, int request, unsigned long arg);
+
+int ioctl(int fd, int request, ... /* arg */)
 {
-    unimplemented();
-    return 0;
+    unsigned long arg;
+    va_list ap;
+    va_start(ap, request);
+    arg = va_arg(ap, unsigned long);
+    va_end(ap);
+
+    return ax_ioctl(fd, request, arg);
 }
+
+#endif // AX_CONFIG_FD
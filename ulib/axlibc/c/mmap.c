@@ -1,39 +1,60 @@
+#include <errno.h>
+#include <stdarg.h>
 #include <stddef.h>
 #include <stdio.h>
 #include <sys/mman.h>
 
-// TODO:
+#ifdef AX_CONFIG_MMAP
+
+// TODO: remove this function in future work
+void *ax_mremap(void *old_address, size_t old_size, size_t new_size, int flags, void *new_address);
+
+void *mremap(void *old_address, size_t old_size, size_t new_size, int flags,
+             ... /* void *new_address */)
+{
+    void *new_address = NULL;
+
+    if (flags & MREMAP_FIXED) {
+        va_list ap;
+        va_start(ap, flags);
+        new_address = va_arg(ap, void *);
+        va_end(ap);
+    }
+
+    return ax_mremap(old_address, old_size, new_size, flags, new_address);
+}
+
+#else
+
 void *mmap(void *addr, size_t len, int prot, int flags, int fildes, off_t off)
 {
-    unimplemented();
+    errno = ENOSYS;
     return MAP_FAILED;
 }
 
-// TODO:
 int munmap(void *addr, size_t length)
 {
-    unimplemented();
-    return 0;
+    errno = ENOSYS;
+    return -1;
 }
 
-// TODO:
 void *mremap(void *old_address, size_t old_size, size_t new_size, int flags,
              ... /* void *new_address */)
 {
-    unimplemented();
-    return NULL;
+    errno = ENOSYS;
+    return MAP_FAILED;
 }
 
-// TODO
 int mprotect(void *addr, size_t len, int prot)
 {
-    unimplemented();
-    return 0;
+    errno = ENOSYS;
+    return -1;
 }
 
+#endif // AX_CONFIG_MMAP
+
 // TODO
 int madvise(void *addr, size_t len, int advice)
 {
-    unimplemented();
     return 0;
 }
@@ -1,3 +1,5 @@
+#ifndef AX_CONFIG_POLL
+
 #include <poll.h>
 #include <stdio.h>
 
@@ -7,3 +9,5 @@ int poll(struct pollfd *__fds, nfds_t __nfds, int __timeout)
     unimplemented();
     return 0;
 }
+
+#endif // AX_CONFIG_POLL
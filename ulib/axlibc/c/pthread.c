@@ -4,6 +4,7 @@
 #include <limits.h>
 #include <pthread.h>
 #include <stdio.h>
+#include <sys/resource.h>
 #include <unistd.h>
 
 int pthread_setcancelstate(int new, int *old)
@@ -32,13 +33,6 @@ int pthread_cancel(pthread_t t)
     return 0;
 }
 
-// TODO
-int pthread_mutex_trylock(pthread_mutex_t *m)
-{
-    unimplemented();
-    return 0;
-}
-
 // TODO
 int pthread_setname_np(pthread_t thread, const char *name)
 {
@@ -46,38 +40,6 @@ int pthread_setname_np(pthread_t thread, const char *name)
     return 0;
 }
 
-int pthread_cond_init(pthread_cond_t *restrict c, const pthread_condattr_t *restrict a)
-{
-    *c = (pthread_cond_t){0};
-    if (a) {
-        c->_c_clock = a->__attr & 0x7fffffff;
-        if (a->__attr >> 31)
-            c->_c_shared = (void *)-1;
-    }
-    return 0;
-}
-
-// TODO
-int pthread_cond_signal(pthread_cond_t *__cond)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int pthread_cond_wait(pthread_cond_t *__restrict__ __cond, pthread_mutex_t *__restrict__ __mutex)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int pthread_cond_broadcast(pthread_cond_t *c)
-{
-    unimplemented();
-    return 0;
-}
-
 #define DEFAULT_STACK_SIZE 131072
 #define DEFAULT_GUARD_SIZE 8192
 
@@ -86,7 +48,8 @@ int pthread_attr_init(pthread_attr_t *a)
 {
     *a = (pthread_attr_t){0};
     // __acquire_ptc();
-    a->_a_stacksize = DEFAULT_STACK_SIZE;
+    // A zero stack size is unset, new threads get the `RLIMIT_STACK` limit.
+    a->_a_stacksize = 0;
     a->_a_guardsize = DEFAULT_GUARD_SIZE;
     // __release_ptc();
     return 0;
@@ -94,7 +57,13 @@ int pthread_attr_init(pthread_attr_t *a)
 
 int pthread_attr_getstacksize(const pthread_attr_t *restrict a, size_t *restrict size)
 {
-    *size = a->_a_stacksize;
+    struct rlimit rl;
+    if (a->_a_stacksize)
+        *size = a->_a_stacksize;
+    else if (getrlimit(RLIMIT_STACK, &rl) == 0 && rl.rlim_cur != RLIM_INFINITY)
+        *size = rl.rlim_cur;
+    else
+        *size = DEFAULT_STACK_SIZE;
     return 0;
 }
 
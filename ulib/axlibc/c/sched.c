@@ -1,9 +1,13 @@
 #include <sched.h>
 #include <stdio.h>
 
+#ifndef AX_CONFIG_MULTITASK
+
 // TODO
 int sched_setaffinity(pid_t __pid, size_t __cpusetsize, const cpu_set_t *__cpuset)
 {
     unimplemented();
     return 0;
 }
+
+#endif // AX_CONFIG_MULTITASK
@@ -0,0 +1,345 @@
+#ifdef AX_CONFIG_PROCESS
+
+#include <dlfcn.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <signal.h>
+#include <spawn.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+extern char **environ;
+
+int ax_spawn(int (*entry)(void *), void *arg);
+
+enum { FDOP_CLOSE = 1, FDOP_DUP2, FDOP_OPEN, FDOP_CHDIR, FDOP_FCHDIR };
+
+struct fdop {
+    struct fdop *next;
+    int cmd, fd, srcfd, oflag;
+    mode_t mode;
+    char path[];
+};
+
+// Everything the child needs, it is owned by the child after spawning.
+struct spawn_args {
+    int (*main)(int, char **, char **);
+    char **argv;
+    char **envp;
+    struct fdop *ops;
+    int flags;
+    sigset_t mask;
+};
+
+static void free_strings(char **strs)
+{
+    if (!strs)
+        return;
+    for (char **s = strs; *s; s++) free(*s);
+    free(strs);
+}
+
+static char **copy_strings(char *const *strs)
+{
+    size_t n = 0;
+    while (strs && strs[n]) n++;
+    char **copy = calloc(n + 1, sizeof(char *));
+    if (!copy)
+        return NULL;
+    for (size_t i = 0; i < n; i++) {
+        if (!(copy[i] = strdup(strs[i]))) {
+            free_strings(copy);
+            return NULL;
+        }
+    }
+    return copy;
+}
+
+static void free_ops(struct fdop *op)
+{
+    while (op) {
+        struct fdop *next = op->next;
+        free(op);
+        op = next;
+    }
+}
+
+static struct fdop *copy_ops(const struct fdop *op)
+{
+    struct fdop *head = NULL, **tail = &head;
+    for (; op; op = op->next) {
+        size_t size = sizeof(*op) + strlen(op->path) + 1;
+        struct fdop *copy = malloc(size);
+        if (!copy) {
+            free_ops(head);
+            return NULL;
+        }
+        memcpy(copy, op, size);
+        copy->next = NULL;
+        *tail = copy;
+        tail = &copy->next;
+    }
+    return head;
+}
+
+static void free_args(struct spawn_args *args)
+{
+    free_strings(args->argv);
+    free_strings(args->envp);
+    free_ops(args->ops);
+    free(args);
+}
+
+static int apply_op(const struct fdop *op)
+{
+    switch (op->cmd) {
+    case FDOP_CLOSE:
+        return close(op->fd);
+    case FDOP_DUP2:
+        return dup2(op->srcfd, op->fd) < 0 ? -1 : 0;
+#ifdef AX_CONFIG_FS
+    case FDOP_OPEN: {
+        int fd = open(op->path, op->oflag, op->mode);
+        if (fd < 0 || fd == op->fd)
+            return fd < 0 ? -1 : 0;
+        if (dup2(fd, op->fd) < 0)
+            return -1;
+        return close(fd);
+    }
+    case FDOP_CHDIR:
+        return chdir(op->path);
+    case FDOP_FCHDIR:
+        return fchdir(op->fd);
+#endif
+    default:
+        errno = ENOSYS;
+        return -1;
+    }
+}
+
+// Runs in the main thread of the new process.
+static int spawn_entry(void *arg)
+{
+    struct spawn_args *args = arg;
+#ifdef AX_CONFIG_SIGNAL
+    if (args->flags & POSIX_SPAWN_SETSIGMASK)
+        sigprocmask(SIG_SETMASK, &args->mask, NULL);
+#endif
+    for (const struct fdop *op = args->ops; op; op = op->next) {
+        // Like the shell, a child that fails to start exits with 127.
+        if (apply_op(op) < 0) {
+            free_args(args);
+            return 127;
+        }
+    }
+
+    int argc = 0;
+    while (args->argv[argc]) argc++;
+    int ret = args->main(argc, args->argv, args->envp);
+    free_args(args);
+    return ret;
+}
+
+int posix_spawn(pid_t *restrict res, const char *restrict path,
+                const posix_spawn_file_actions_t *fa, const posix_spawnattr_t *restrict attr,
+                char *const argv[restrict], char *const envp[restrict])
+{
+    void *prog = dlopen(path, RTLD_NOW);
+    if (!prog) {
+        dlerror();
+        return ENOENT;
+    }
+    void *entry = dlsym(prog, "main");
+    dlerror();
+    dlclose(prog);
+    if (!entry)
+        return ENOEXEC;
+
+    struct spawn_args *args = calloc(1, sizeof(*args));
+    if (!args)
+        return ENOMEM;
+    args->main = (int (*)(int, char **, char **))entry;
+    args->argv = copy_strings(argv);
+    args->envp = copy_strings(envp ? envp : environ);
+    args->ops = fa ? copy_ops(fa->__actions) : NULL;
+    if (!args->argv || !args->envp || (fa && fa->__actions && !args->ops)) {
+        free_args(args);
+        return ENOMEM;
+    }
+    if (attr) {
+        args->flags = attr->__flags;
+        args->mask = attr->__mask;
+    }
+
+    pid_t pid = ax_spawn(spawn_entry, args);
+    if (pid < 0) {
+        int err = errno;
+        free_args(args);
+        return err;
+    }
+    if (res)
+        *res = pid;
+    return 0;
+}
+
+// All programs are found by their names, there is no `PATH` to search.
+int posix_spawnp(pid_t *restrict res, const char *restrict file,
+                 const posix_spawn_file_actions_t *fa, const posix_spawnattr_t *restrict attr,
+                 char *const argv[restrict], char *const envp[restrict])
+{
+    return posix_spawn(res, file, fa, attr, argv, envp);
+}
+
+int posix_spawnattr_init(posix_spawnattr_t *attr)
+{
+    memset(attr, 0, sizeof(*attr));
+    return 0;
+}
+
+int posix_spawnattr_destroy(posix_spawnattr_t *attr)
+{
+    return 0;
+}
+
+// Only `POSIX_SPAWN_SETSIGMASK` takes effect, signal actions are shared by all
+// processes, and there are no process groups.
+int posix_spawnattr_setflags(posix_spawnattr_t *attr, short flags)
+{
+    const unsigned all_flags = POSIX_SPAWN_RESETIDS | POSIX_SPAWN_SETPGROUP |
+                               POSIX_SPAWN_SETSIGDEF | POSIX_SPAWN_SETSIGMASK |
+                               POSIX_SPAWN_SETSCHEDPARAM | POSIX_SPAWN_SETSCHEDULER |
+                               POSIX_SPAWN_USEVFORK | POSIX_SPAWN_SETSID;
+    if (flags & ~all_flags)
+        return EINVAL;
+    attr->__flags = flags;
+    return 0;
+}
+
+int posix_spawnattr_getflags(const posix_spawnattr_t *restrict attr, short *restrict flags)
+{
+    *flags = attr->__flags;
+    return 0;
+}
+
+int posix_spawnattr_setpgroup(posix_spawnattr_t *attr, pid_t pgrp)
+{
+    attr->__pgrp = pgrp;
+    return 0;
+}
+
+int posix_spawnattr_getpgroup(const posix_spawnattr_t *restrict attr, pid_t *restrict pgrp)
+{
+    *pgrp = attr->__pgrp;
+    return 0;
+}
+
+int posix_spawnattr_setsigmask(posix_spawnattr_t *restrict attr, const sigset_t *restrict mask)
+{
+    attr->__mask = *mask;
+    return 0;
+}
+
+int posix_spawnattr_getsigmask(const posix_spawnattr_t *restrict attr, sigset_t *restrict mask)
+{
+    *mask = attr->__mask;
+    return 0;
+}
+
+int posix_spawnattr_setsigdefault(posix_spawnattr_t *restrict attr, const sigset_t *restrict def)
+{
+    attr->__def = *def;
+    return 0;
+}
+
+int posix_spawnattr_getsigdefault(const posix_spawnattr_t *restrict attr, sigset_t *restrict def)
+{
+    *def = attr->__def;
+    return 0;
+}
+
+int posix_spawn_file_actions_init(posix_spawn_file_actions_t *fa)
+{
+    fa->__actions = NULL;
+    return 0;
+}
+
+int posix_spawn_file_actions_destroy(posix_spawn_file_actions_t *fa)
+{
+    free_ops(fa->__actions);
+    fa->__actions = NULL;
+    return 0;
+}
+
+// Appends an action, they are applied in the order they are added.
+static struct fdop *add_op(posix_spawn_file_actions_t *fa, int cmd, const char *path)
+{
+    size_t len = path ? strlen(path) : 0;
+    struct fdop *op = calloc(1, sizeof(*op) + len + 1);
+    if (!op)
+        return NULL;
+    op->cmd = cmd;
+    if (path)
+        memcpy(op->path, path, len + 1);
+
+    struct fdop **tail = (struct fdop **)&fa->__actions;
+    while (*tail) tail = &(*tail)->next;
+    *tail = op;
+    return op;
+}
+
+int posix_spawn_file_actions_addopen(posix_spawn_file_actions_t *restrict fa, int fd,
+                                     const char *restrict path, int flags, mode_t mode)
+{
+    if (fd < 0)
+        return EBADF;
+    struct fdop *op = add_op(fa, FDOP_OPEN, path);
+    if (!op)
+        return ENOMEM;
+    op->fd = fd;
+    op->oflag = flags;
+    op->mode = mode;
+    return 0;
+}
+
+int posix_spawn_file_actions_addclose(posix_spawn_file_actions_t *fa, int fd)
+{
+    if (fd < 0)
+        return EBADF;
+    struct fdop *op = add_op(fa, FDOP_CLOSE, NULL);
+    if (!op)
+        return ENOMEM;
+    op->fd = fd;
+    return 0;
+}
+
+int posix_spawn_file_actions_adddup2(posix_spawn_file_actions_t *fa, int srcfd, int fd)
+{
+    if (srcfd < 0 || fd < 0)
+        return EBADF;
+    struct fdop *op = add_op(fa, FDOP_DUP2, NULL);
+    if (!op)
+        return ENOMEM;
+    op->srcfd = srcfd;
+    op->fd = fd;
+    return 0;
+}
+
+int posix_spawn_file_actions_addchdir_np(posix_spawn_file_actions_t *restrict fa,
+                                         const char *restrict path)
+{
+    return add_op(fa, FDOP_CHDIR, path) ? 0 : ENOMEM;
+}
+
+int posix_spawn_file_actions_addfchdir_np(posix_spawn_file_actions_t *fa, int fd)
+{
+    if (fd < 0)
+        return EBADF;
+    struct fdop *op = add_op(fa, FDOP_FCHDIR, NULL);
+    if (!op)
+        return ENOMEM;
+    op->fd = fd;
+    return 0;
+}
+
+#endif // AX_CONFIG_PROCESS
@@ -5,6 +5,8 @@
 #include <stdio.h>
 #include <string.h>
 
+#include "thread_local.h"
+
 size_t strlen(const char *s)
 {
     const char *a = s;
@@ -146,9 +148,8 @@ size_t strcspn(const char *s1, const char *s2)
     const char *a = s1;
     size_t byteset[32 / sizeof(size_t)];
 
-    if (!s2[0] || !s2[1]) {
-        for (; *s1 != *s2; s1++) return s1 - a;
-    }
+    if (!s2[0] || !s2[1])
+        return strchrnul(s1, *s2) - a;
     memset(byteset, 0, sizeof byteset);
 
     for (; *s2 != '\0'; s2++) BITOP(byteset, *(unsigned char *)s2, |=);
@@ -184,6 +185,27 @@ char *strpbrk(const char *s, const char *b)
     return *s ? (char *)s : 0;
 }
 
+char *strtok_r(char *restrict s, const char *restrict sep, char **restrict p)
+{
+    if (!s && !(s = *p))
+        return NULL;
+    s += strspn(s, sep);
+    if (!*s)
+        return *p = 0;
+    *p = s + strcspn(s, sep);
+    if (**p)
+        *(*p)++ = 0;
+    else
+        *p = 0;
+    return s;
+}
+
+char *strtok(char *restrict s, const char *restrict sep)
+{
+    static __ax_thread_local char *p;
+    return strtok_r(s, sep, &p);
+}
+
 char *strchrnul(const char *s, int c)
 {
     c = (unsigned char)c;
@@ -219,21 +241,6 @@ char *strrchr(const char *s, int c)
     return isCharFind;
 }
 
-int strerror_r(int err, char *buf, size_t buflen)
-{
-    char *msg = strerror(err);
-    size_t l = strlen(msg);
-    if (l >= buflen) {
-        if (buflen) {
-            memcpy(buf, msg, buflen - 1);
-            buf[buflen - 1] = 0;
-        }
-        return ERANGE;
-    }
-    memcpy(buf, msg, l + 1);
-    return 0;
-}
-
 void *memcpy(void *restrict dest, const void *restrict src, size_t n)
 {
     unsigned char *d = dest;
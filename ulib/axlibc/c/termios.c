@@ -0,0 +1,83 @@
+#include <errno.h>
+#include <sys/ioctl.h>
+#include <termios.h>
+
+speed_t cfgetospeed(const struct termios *tio)
+{
+    return tio->c_cflag & CBAUD;
+}
+
+speed_t cfgetispeed(const struct termios *tio)
+{
+    return cfgetospeed(tio);
+}
+
+int cfsetospeed(struct termios *tio, speed_t speed)
+{
+    if (speed & ~CBAUD) {
+        errno = EINVAL;
+        return -1;
+    }
+    tio->c_cflag &= ~CBAUD;
+    tio->c_cflag |= speed;
+    return 0;
+}
+
+int cfsetispeed(struct termios *tio, speed_t speed)
+{
+    return speed ? cfsetospeed(tio, speed) : 0;
+}
+
+int cfsetspeed(struct termios *tio, speed_t speed)
+{
+    return cfsetospeed(tio, speed);
+}
+
+void cfmakeraw(struct termios *t)
+{
+    t->c_iflag &= ~(IGNBRK | BRKINT | PARMRK | ISTRIP | INLCR | IGNCR | ICRNL | IXON);
+    t->c_oflag &= ~OPOST;
+    t->c_lflag &= ~(ECHO | ECHONL | ICANON | ISIG | IEXTEN);
+    t->c_cflag &= ~(CSIZE | PARENB);
+    t->c_cflag |= CS8;
+    t->c_cc[VMIN] = 1;
+    t->c_cc[VTIME] = 0;
+}
+
+#ifdef AX_CONFIG_FD
+
+int tcgetattr(int fd, struct termios *tio)
+{
+    return ioctl(fd, TCGETS, tio);
+}
+
+int tcsetattr(int fd, int act, const struct termios *tio)
+{
+    if (act < 0 || act > 2) {
+        errno = EINVAL;
+        return -1;
+    }
+    return ioctl(fd, TCSETS + act, tio);
+}
+
+int tcgetwinsize(int fd, struct winsize *wsz)
+{
+    return ioctl(fd, TIOCGWINSZ, wsz);
+}
+
+int tcsetwinsize(int fd, const struct winsize *wsz)
+{
+    return ioctl(fd, TIOCSWINSZ, wsz);
+}
+
+int tcdrain(int fd)
+{
+    return ioctl(fd, TCSBRK, 1);
+}
+
+int tcflush(int fd, int queue)
+{
+    return ioctl(fd, TCFLSH, queue);
+}
+
+#endif // AX_CONFIG_FD
@@ -0,0 +1,13 @@
+#ifndef _AX_THREAD_LOCAL_H
+#define _AX_THREAD_LOCAL_H
+
+// Storage class of the hidden state of non-reentrant functions, such as the
+// buffers of `strtok` and `localtime`. It is shared by all threads without
+// the `tls` feature, which the `multitask` feature implies.
+#ifdef AX_CONFIG_TLS
+#define __ax_thread_local __thread __attribute__((tls_model("local-exec")))
+#else
+#define __ax_thread_local
+#endif
+
+#endif // _AX_THREAD_LOCAL_H
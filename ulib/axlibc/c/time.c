@@ -2,12 +2,22 @@
 #include <limits.h>
 #include <stddef.h>
 #include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
 #include <sys/time.h>
 #include <time.h>
 
-long timezone = 0;
+#include "thread_local.h"
+
 const char __utc[] = "UTC";
 
+static char std_name[TZNAME_MAX + 1] = "UTC";
+static char dst_name[TZNAME_MAX + 1] = "UTC";
+
+char *tzname[2] = {std_name, dst_name};
+int daylight = 0;
+long timezone = 0;
+
 const int SEC_PER_MIN = 60;
 const int SEC_PER_HOUR = 3600;
 const int MIN_PER_HOUR = 60;
@@ -110,32 +120,219 @@ struct tm *gmtime_r(const time_t *restrict t, struct tm *restrict tm)
 
 struct tm *gmtime(const time_t *timer)
 {
-    static struct tm tm;
+    static __ax_thread_local struct tm tm;
     return gmtime_r(timer, &tm);
 }
 
+/* A DST transition rule: `Jn`, `n` or `Mm.w.d`, followed by the local time */
+struct tz_rule {
+    char kind;
+    int m, w, d;
+    long time;
+};
+
+/* Offsets are seconds west of UTC, as in `timezone` */
+static long dst_off;
+static struct tz_rule dst_start, dst_end;
+
+static const char *parse_name(const char *s, char *name)
+{
+    const char *p = s;
+    size_t len;
+    if (*p == '<') {
+        s = ++p;
+        while (*p && *p != '>') p++;
+        len = p - s;
+        if (*p)
+            p++;
+    } else {
+        while (*p && ((*p >= 'a' && *p <= 'z') || (*p >= 'A' && *p <= 'Z'))) p++;
+        len = p - s;
+    }
+    if (len < 3 || len > TZNAME_MAX)
+        return NULL;
+    memcpy(name, s, len);
+    name[len] = 0;
+    return p;
+}
+
+static const char *parse_num(const char *s, int *n)
+{
+    if (*s < '0' || *s > '9')
+        return NULL;
+    for (*n = 0; *s >= '0' && *s <= '9'; s++) *n = *n * 10 + (*s - '0');
+    return s;
+}
+
+/* `[+-]hh[:mm[:ss]]`, returns the seconds in `*off` */
+static const char *parse_offset(const char *s, long *off)
+{
+    int sign = 1, hh, mm = 0, ss = 0;
+    if (*s == '+' || *s == '-')
+        sign = *s++ == '-' ? -1 : 1;
+    if (!(s = parse_num(s, &hh)))
+        return NULL;
+    if (*s == ':' && !(s = parse_num(s + 1, &mm)))
+        return NULL;
+    if (*s == ':' && !(s = parse_num(s + 1, &ss)))
+        return NULL;
+    *off = sign * (hh * 3600L + mm * 60L + ss);
+    return s;
+}
+
+static const char *parse_rule(const char *s, struct tz_rule *r)
+{
+    r->kind = *s;
+    if (*s == 'M') {
+        if (!(s = parse_num(s + 1, &r->m)) || *s != '.' || !(s = parse_num(s + 1, &r->w)) ||
+            *s != '.' || !(s = parse_num(s + 1, &r->d)))
+            return NULL;
+        if (r->m < 1 || r->m > 12 || r->w < 1 || r->w > 5 || r->d > 6)
+            return NULL;
+    } else {
+        if (*s == 'J')
+            s++;
+        if (!(s = parse_num(s, &r->d)) || r->d > 365)
+            return NULL;
+    }
+    r->time = 7200;
+    if (*s == '/' && !(s = parse_offset(s + 1, &r->time)))
+        return NULL;
+    return s;
+}
+
+static int parse_tz(const char *s)
+{
+    long std, dst;
+    if (!(s = parse_name(s, std_name)) || !(s = parse_offset(s, &std)))
+        return -1;
+    timezone = std;
+    daylight = 0;
+    if (!*s) {
+        strcpy(dst_name, std_name);
+        dst_off = std;
+        return 0;
+    }
+
+    if (!(s = parse_name(s, dst_name)))
+        return -1;
+    dst = std - 3600;
+    if (*s && *s != ',' && !(s = parse_offset(s, &dst)))
+        return -1;
+    dst_off = dst;
+    daylight = 1;
+
+    /* Default to the US rules */
+    dst_start = (struct tz_rule){'M', 3, 2, 0, 7200};
+    dst_end = (struct tz_rule){'M', 11, 1, 0, 7200};
+    if (*s == ',') {
+        if (!(s = parse_rule(s + 1, &dst_start)) || *s != ',' ||
+            !(s = parse_rule(s + 1, &dst_end)))
+            return -1;
+    }
+    return *s ? -1 : 0;
+}
+
+void tzset(void)
+{
+    const char *tz = getenv("TZ");
+    if (tz && *tz == ':')
+        tz++;
+    if (!tz || !*tz || parse_tz(tz) < 0) {
+        strcpy(std_name, __utc);
+        strcpy(dst_name, __utc);
+        timezone = 0;
+        daylight = 0;
+    }
+}
+
+static int is_leap(long long year)
+{
+    return year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+}
+
+/* Seconds from the start of the year (in local time) of a transition */
+static long rule_to_secs(int leap, int jan1_wday, const struct tz_rule *r)
+{
+    static const int mdays[] = {31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31};
+    int yday = 0;
+    if (r->kind == 'J') {
+        /* 1-based, February 29th is never counted */
+        yday = r->d - 1 + (leap && r->d > 59);
+    } else if (r->kind == 'M') {
+        int days = mdays[r->m - 1] + (r->m == 2 && leap);
+        int first_wday, mday;
+        for (int i = 0; i < r->m - 1; i++) yday += mdays[i] + (i == 1 && leap);
+        first_wday = (jan1_wday + yday) % 7;
+        /* The d'th day of the w'th week, week 5 meaning the last one */
+        mday = 1 + (r->d - first_wday + 7) % 7 + (r->w - 1) * 7;
+        while (mday > days) mday -= 7;
+        yday += mday - 1;
+    } else {
+        yday = r->d;
+    }
+    return yday * 86400L + r->time;
+}
+
+/* Returns whether DST is in effect at the UTC time `t` */
+static int in_dst(long long t)
+{
+    struct tm tm;
+    long local, start, end;
+    int leap, jan1_wday;
+    if (!daylight || __secs_to_tm(t - timezone, &tm) < 0)
+        return 0;
+
+    /* Seconds since the start of the year, in local standard time */
+    local = tm.tm_yday * 86400L + tm.tm_hour * 3600 + tm.tm_min * 60 + tm.tm_sec;
+    leap = is_leap(tm.tm_year + 1900LL);
+    jan1_wday = ((tm.tm_wday - tm.tm_yday) % 7 + 7) % 7;
+    start = rule_to_secs(leap, jan1_wday, &dst_start);
+    /* The end rule is given in local daylight time */
+    end = rule_to_secs(leap, jan1_wday, &dst_end) - (timezone - dst_off);
+    if (start < end)
+        return local >= start && local < end;
+    /* Southern hemisphere */
+    return local >= start || local < end;
+}
+
+/* The offset west of UTC of the local time `local`, used by `mktime` */
+long __tz_offset(long long local, int isdst)
+{
+    tzset();
+    if (isdst < 0)
+        isdst = in_dst(local + timezone);
+    return isdst && daylight ? dst_off : timezone;
+}
+
 struct tm *localtime_r(const time_t *restrict t, struct tm *restrict tm)
 {
+    int isdst;
+    long off;
+
     if (*t < INT_MIN * 31622400LL || *t > INT_MAX * 31622400LL) {
         errno = EOVERFLOW;
         return 0;
     }
 
-    if (__secs_to_tm(*t, tm) < 0) {
+    tzset();
+    isdst = in_dst(*t);
+    off = isdst ? dst_off : timezone;
+    if (__secs_to_tm(*t - off, tm) < 0) {
         errno = EOVERFLOW;
         return 0;
     }
 
-    tm->tm_isdst = 0;
-    tm->__tm_gmtoff = 0;
-    tm->__tm_zone = __utc;
+    tm->tm_isdst = isdst;
+    tm->__tm_gmtoff = -off;
+    tm->__tm_zone = tzname[isdst];
 
     return tm;
 }
 
 struct tm *localtime(const time_t *timep)
 {
-    static struct tm tm;
+    static __ax_thread_local struct tm tm;
     return localtime_r(timep, &tm);
 }
 
@@ -167,32 +364,57 @@ int utimes(const char *filename, const struct timeval times[2])
     return 0;
 }
 
+#ifndef AX_CONFIG_SIGNAL
 // TODO
-void tzset()
+int setitimer(int _which, const struct itimerval *restrict _new, struct itimerval *restrict _old)
 {
     unimplemented();
-    return;
+    return 0;
 }
+#endif
 
-// TODO
-int setitimer(int _which, const struct itimerval *restrict _new, struct itimerval *restrict _old)
+char *asctime_r(const struct tm *restrict tm, char *restrict buf)
 {
-    unimplemented();
-    return 0;
+    static const char wday_name[7][3] = {"Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"};
+    static const char mon_name[12][3] = {"Jan", "Feb", "Mar", "Apr", "May", "Jun",
+                                         "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"};
+    if (snprintf(buf, 26, "%.3s %.3s%3d %.2d:%.2d:%.2d %d\n",
+                 wday_name[(unsigned)tm->tm_wday % 7], mon_name[(unsigned)tm->tm_mon % 12],
+                 tm->tm_mday, tm->tm_hour, tm->tm_min, tm->tm_sec, 1900 + tm->tm_year) >= 26) {
+        errno = EOVERFLOW;
+        return NULL;
+    }
+    return buf;
+}
+
+char *asctime(const struct tm *tm)
+{
+    static __ax_thread_local char buf[26];
+    return asctime_r(tm, buf);
 }
 
-// TODO
 char *ctime_r(const time_t *t, char *buf)
 {
-    unimplemented();
-    return NULL;
+    struct tm tm;
+    if (!localtime_r(t, &tm))
+        return NULL;
+    return asctime_r(&tm, buf);
+}
+
+char *ctime(const time_t *t)
+{
+    static __ax_thread_local char buf[26];
+    return ctime_r(t, buf);
 }
 
-// TODO
 clock_t clock(void)
 {
-    unimplemented();
-    return 0;
+    struct timespec ts;
+    if (clock_gettime(CLOCK_PROCESS_CPUTIME_ID, &ts))
+        return -1;
+    if (ts.tv_sec > LONG_MAX / CLOCKS_PER_SEC - 1)
+        return -1;
+    return ts.tv_sec * CLOCKS_PER_SEC + ts.tv_nsec / (1000000000 / CLOCKS_PER_SEC);
 }
 
 #ifdef AX_CONFIG_FP_SIMD
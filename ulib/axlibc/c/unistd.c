@@ -2,7 +2,9 @@
 #include <fcntl.h>
 #include <stdio.h>
 #include <stdlib.h>
+#include <sys/ioctl.h>
 #include <sys/types.h>
+#include <termios.h>
 #include <time.h>
 #include <unistd.h>
 
@@ -27,11 +29,18 @@ pid_t setsid(void)
     return 0;
 }
 
-// TODO
 int isatty(int fd)
 {
-    unimplemented();
+#ifdef AX_CONFIG_FD
+    struct winsize wsz;
+    return ioctl(fd, TIOCGWINSZ, &wsz) == 0;
+#else
+    // Only the standard streams exist, which are all the console.
+    if (fd >= 0 && fd <= 2)
+        return 1;
+    errno = EBADF;
     return 0;
+#endif
 }
 
 unsigned int sleep(unsigned int seconds)
@@ -54,13 +63,6 @@ int usleep(unsigned useconds)
 
 #ifdef AX_CONFIG_FS
 
-// TODO:
-int access(const char *pathname, int mode)
-{
-    unimplemented();
-    return 0;
-}
-
 // TODO:
 ssize_t readlink(const char *path, char *buf, size_t bufsiz)
 {
@@ -68,34 +70,6 @@ ssize_t readlink(const char *path, char *buf, size_t bufsiz)
     return 0;
 }
 
-// TODO:
-int unlink(const char *pathname)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO:
-int rmdir(const char *pathname)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO:
-int fsync(int fd)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int fdatasync(int __fildes)
-{
-    unimplemented();
-    return 0;
-}
-
 // TODO:
 int fchown(int fd, uid_t owner, gid_t group)
 {
@@ -103,27 +77,6 @@ int fchown(int fd, uid_t owner, gid_t group)
     return 0;
 }
 
-// TODO:
-int ftruncate(int fd, off_t length)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int chdir(const char *__path)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int truncate(const char *path, off_t length)
-{
-    unimplemented();
-    return 0;
-}
-
 #endif // AX_CONFIG_FS
 
 #ifdef AX_CONFIG_PIPE
@@ -1,7 +1,26 @@
 #include <stdio.h>
+#include <string.h>
 #include <sys/resource.h>
 #include <sys/wait.h>
 
+#ifdef AX_CONFIG_PROCESS
+
+pid_t wait(int *status)
+{
+    return waitpid(-1, status, 0);
+}
+
+// Resource usage is not accounted, `usage` is filled with zeros.
+pid_t wait3(int *status, int options, struct rusage *usage)
+{
+    pid_t pid = waitpid(-1, status, options);
+    if (pid > 0 && usage)
+        memset(usage, 0, sizeof(*usage));
+    return pid;
+}
+
+#else
+
 // TODO
 pid_t waitpid(pid_t pid, int *status, int options)
 {
@@ -15,3 +34,5 @@ pid_t wait3(int *status, int _options, struct rusage *usage)
     unimplemented();
     return 0;
 }
+
+#endif // AX_CONFIG_PROCESS
@@ -30,6 +30,8 @@ int readdir_r(DIR *__restrict, struct dirent *__restrict, struct dirent **__rest
 void rewinddir(DIR *);
 int dirfd(DIR *);
 
+ssize_t getdents64(int, void *, size_t);
+
 #define DT_UNKNOWN 0
 #define DT_FIFO    1
 #define DT_CHR     2
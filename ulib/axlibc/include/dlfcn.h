@@ -24,6 +24,29 @@ typedef struct {
     void *dli_saddr;
 } Dl_info;
 
+/*
+ * An entry of the symbol registry generated from `dl_exports.txt` of the app
+ * at build time. There is no runtime loading, `dlopen` only finds libraries
+ * in the registry. `size` is 0 if the symbol is not defined by the app.
+ */
+struct __dl_symbol {
+    const char *lib;
+    const char *name;
+    void *addr;
+    unsigned long size;
+};
+
+/*
+ * An entry of the table of the functions in the kernel image, generated from
+ * the linked image at build time and sorted by address. `size` is 0 if the
+ * function extends to the next one.
+ */
+struct __ksym {
+    unsigned long addr;
+    unsigned long size;
+    const char *name;
+};
+
 int dladdr(const void *, Dl_info *);
 int dlclose(void *);
 char *dlerror(void);
@@ -0,0 +1,67 @@
+#ifndef _SPAWN_H
+#define _SPAWN_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#include <features.h>
+#include <signal.h>
+#include <sys/types.h>
+
+#define POSIX_SPAWN_RESETIDS      1
+#define POSIX_SPAWN_SETPGROUP     2
+#define POSIX_SPAWN_SETSIGDEF     4
+#define POSIX_SPAWN_SETSIGMASK    8
+#define POSIX_SPAWN_SETSCHEDPARAM 16
+#define POSIX_SPAWN_SETSCHEDULER  32
+#define POSIX_SPAWN_USEVFORK      64
+#define POSIX_SPAWN_SETSID        128
+
+typedef struct {
+    int __flags;
+    pid_t __pgrp;
+    sigset_t __def, __mask;
+} posix_spawnattr_t;
+
+typedef struct {
+    void *__actions;
+} posix_spawn_file_actions_t;
+
+/*
+ * Programs are not loaded from files, `path` names a program registered in
+ * `dl_exports.txt` of the app, whose `main` is exported as "main".
+ */
+int posix_spawn(pid_t *__restrict, const char *__restrict, const posix_spawn_file_actions_t *,
+                const posix_spawnattr_t *__restrict, char *const *__restrict,
+                char *const *__restrict);
+int posix_spawnp(pid_t *__restrict, const char *__restrict, const posix_spawn_file_actions_t *,
+                 const posix_spawnattr_t *__restrict, char *const *__restrict,
+                 char *const *__restrict);
+
+int posix_spawnattr_init(posix_spawnattr_t *);
+int posix_spawnattr_destroy(posix_spawnattr_t *);
+int posix_spawnattr_setflags(posix_spawnattr_t *, short);
+int posix_spawnattr_getflags(const posix_spawnattr_t *__restrict, short *__restrict);
+int posix_spawnattr_setpgroup(posix_spawnattr_t *, pid_t);
+int posix_spawnattr_getpgroup(const posix_spawnattr_t *__restrict, pid_t *__restrict);
+int posix_spawnattr_setsigmask(posix_spawnattr_t *__restrict, const sigset_t *__restrict);
+int posix_spawnattr_getsigmask(const posix_spawnattr_t *__restrict, sigset_t *__restrict);
+int posix_spawnattr_setsigdefault(posix_spawnattr_t *__restrict, const sigset_t *__restrict);
+int posix_spawnattr_getsigdefault(const posix_spawnattr_t *__restrict, sigset_t *__restrict);
+
+int posix_spawn_file_actions_init(posix_spawn_file_actions_t *);
+int posix_spawn_file_actions_destroy(posix_spawn_file_actions_t *);
+int posix_spawn_file_actions_addopen(posix_spawn_file_actions_t *__restrict, int,
+                                     const char *__restrict, int, mode_t);
+int posix_spawn_file_actions_addclose(posix_spawn_file_actions_t *, int);
+int posix_spawn_file_actions_adddup2(posix_spawn_file_actions_t *, int, int);
+int posix_spawn_file_actions_addchdir_np(posix_spawn_file_actions_t *__restrict,
+                                         const char *__restrict);
+int posix_spawn_file_actions_addfchdir_np(posix_spawn_file_actions_t *, int);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // _SPAWN_H
@@ -25,6 +25,7 @@ long long strtoll(const char *nptr, char **endptr, int base);
 unsigned long long strtoull(const char *nptr, char **endptr, int base);
 
 int rand(void);
+int rand_r(unsigned *);
 void srand(unsigned);
 long random(void);
 void srandom(unsigned int);
@@ -26,6 +26,9 @@ size_t strcspn(const char *s1, const char *s2);
 size_t strspn(const char *s, const char *c);
 char *strpbrk(const char *, const char *);
 
+char *strtok(char *restrict s, const char *restrict sep);
+char *strtok_r(char *restrict s, const char *restrict sep, char **restrict p);
+
 char *strchrnul(const char *, int);
 
 char *strrchr(const char *str, int c);
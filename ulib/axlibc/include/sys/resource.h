@@ -5,6 +5,10 @@
 
 typedef unsigned long long rlim_t;
 
+#define RLIM_INFINITY  (~0ULL)
+#define RLIM_SAVED_CUR RLIM_INFINITY
+#define RLIM_SAVED_MAX RLIM_INFINITY
+
 struct rlimit {
     rlim_t rlim_cur;
     rlim_t rlim_max;
@@ -31,7 +35,8 @@ struct rlimit {
 #define RLIMIT_NLIMITS    16
 
 #define RUSAGE_SELF     0
-#define RUSAGE_CHILDREN -1
+#define RUSAGE_CHILDREN (-1)
+#define RUSAGE_THREAD   1
 
 struct rusage {
     struct timeval ru_utime;
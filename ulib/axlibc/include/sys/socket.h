@@ -42,6 +42,11 @@ struct cmsghdr {
     int cmsg_type;
 };
 
+struct linger {
+    int l_onoff;
+    int l_linger;
+};
+
 struct sockaddr {
     sa_family_t sa_family;
     char sa_data[14];
@@ -54,6 +59,7 @@ struct sockaddr_storage {
 };
 
 int socket(int, int, int);
+int socketpair(int, int, int, int[2]);
 int shutdown(int, int);
 
 int bind(int, const struct sockaddr *, socklen_t);
@@ -68,6 +74,7 @@ ssize_t sendto(int, const void *, size_t, int, const struct sockaddr *, socklen_
 ssize_t recvfrom(int, void *__restrict, size_t, int, struct sockaddr *__restrict,
                  socklen_t *__restrict);
 ssize_t sendmsg(int, const struct msghdr *, int);
+ssize_t recvmsg(int, struct msghdr *, int);
 
 int getsockopt(int, int, int, void *__restrict, socklen_t *__restrict);
 int setsockopt(int, int, int, const void *, socklen_t);
@@ -114,7 +121,15 @@ int getpeername(int sockfd, struct sockaddr *restrict addr, socklen_t *restrict
 #define SO_PREFER_BUSY_POLL        69
 #define SO_BUSY_POLL_BUDGET        70
 
-#define MSG_NOSIGNAL 0x4000
+#define MSG_OOB       0x0001
+#define MSG_PEEK      0x0002
+#define MSG_DONTROUTE 0x0004
+#define MSG_CTRUNC    0x0008
+#define MSG_TRUNC     0x0020
+#define MSG_DONTWAIT  0x0040
+#define MSG_EOR       0x0080
+#define MSG_WAITALL   0x0100
+#define MSG_NOSIGNAL  0x4000
 
 #define SHUT_RD   0
 #define SHUT_WR   1
@@ -4,8 +4,17 @@
 #include <sys/resource.h>
 #include <sys/types.h>
 
-#define WNOHANG 1
+#define WNOHANG   1
+#define WUNTRACED 2
 
+#define WEXITSTATUS(s) (((s)&0xff00) >> 8)
+#define WTERMSIG(s)    ((s)&0x7f)
+#define WSTOPSIG(s)    WEXITSTATUS(s)
+#define WIFEXITED(s)   (!WTERMSIG(s))
+#define WIFSTOPPED(s)  ((short)((((s)&0xffff) * 0x10001) >> 8) > 0x7f00)
+#define WIFSIGNALED(s) (((s)&0xffff) - 1U < 0xffu)
+
+pid_t wait(int *status);
 pid_t waitpid(pid_t pid, int *status, int options);
 pid_t wait3(int *, int, struct rusage *);
 
@@ -4,9 +4,16 @@
 #include <stddef.h>
 #include <sys/time.h>
 
-#define CLOCK_REALTIME  0
-#define CLOCK_MONOTONIC 1
-#define CLOCKS_PER_SEC  1000000L
+#define CLOCK_REALTIME           0
+#define CLOCK_MONOTONIC          1
+#define CLOCK_PROCESS_CPUTIME_ID 2
+#define CLOCK_THREAD_CPUTIME_ID  3
+#define CLOCK_MONOTONIC_RAW      4
+#define CLOCK_REALTIME_COARSE    5
+#define CLOCK_MONOTONIC_COARSE   6
+#define CLOCK_BOOTTIME           7
+
+#define CLOCKS_PER_SEC 1000000L
 
 struct tm {
     int tm_sec;   /* seconds of minute */
@@ -22,6 +29,11 @@ struct tm {
     const char *__tm_zone;
 };
 
+struct itimerspec {
+    struct timespec it_interval;
+    struct timespec it_value;
+};
+
 clock_t clock(void);
 time_t time(time_t *);
 double difftime(time_t, time_t);
@@ -32,12 +44,20 @@ struct tm *localtime(const time_t *);
 
 struct tm *gmtime_r(const time_t *__restrict, struct tm *__restrict);
 struct tm *localtime_r(const time_t *__restrict, struct tm *__restrict);
+char *asctime(const struct tm *);
+char *ctime(const time_t *);
 char *asctime_r(const struct tm *__restrict, char *__restrict);
 char *ctime_r(const time_t *, char *);
 
 void tzset(void);
 
+extern char *tzname[2];
+extern int daylight;
+extern long timezone;
+
 int nanosleep(const struct timespec *requested_time, struct timespec *remaining);
 int clock_gettime(clockid_t _clk, struct timespec *ts);
+int clock_settime(clockid_t _clk, const struct timespec *ts);
+int clock_getres(clockid_t _clk, struct timespec *res);
 
 #endif // __TIME_H__
@@ -1,7 +1,10 @@
 use axerrno::LinuxError;
 use core::ffi::{c_char, c_int};
 
-/// The global errno variable.
+/// The errno variable, which is thread-local with the `tls` feature.
+///
+/// The `multitask` feature implies `tls`, so that threads do not clobber the
+/// errno of each other.
 #[cfg_attr(feature = "tls", thread_local)]
 #[no_mangle]
 #[allow(non_upper_case_globals)]
@@ -13,27 +16,60 @@ pub fn set_errno(code: i32) {
     }
 }
 
-/// Returns a pointer to the global errno variable.
+/// Returns a pointer to the errno variable of the current thread.
 #[no_mangle]
 pub unsafe extern "C" fn __errno_location() -> *mut c_int {
     &mut errno
 }
 
-/// Returns a pointer to the string representation of the given error code.
-#[no_mangle]
-pub unsafe extern "C" fn strerror(e: c_int) -> *mut c_char {
-    #[allow(non_upper_case_globals)]
-    static mut strerror_buf: [u8; 256] = [0; 256]; // TODO: thread safe
-
-    let err_str = if e == 0 {
+fn err_str(e: c_int) -> &'static str {
+    if e == 0 {
         "Success"
     } else {
         LinuxError::try_from(e)
             .map(|e| e.as_str())
             .unwrap_or("Unknown error")
+    }
+}
+
+/// Copies `s` to `buf` with the terminating NUL, truncating it if `buf` is
+/// too short. Returns whether it is truncated.
+fn copy_str(s: &str, buf: &mut [u8]) -> bool {
+    let Some(max) = buf.len().checked_sub(1) else {
+        return true;
     };
-    unsafe {
-        strerror_buf[..err_str.len()].copy_from_slice(err_str.as_bytes());
-        strerror_buf.as_mut_ptr() as *mut c_char
+    let len = s.len().min(max);
+    buf[..len].copy_from_slice(&s.as_bytes()[..len]);
+    buf[len] = 0;
+    len < s.len()
+}
+
+/// Returns a pointer to the string representation of the given error code.
+///
+/// The string is overwritten by the next call in the same thread.
+#[no_mangle]
+pub unsafe extern "C" fn strerror(e: c_int) -> *mut c_char {
+    #[cfg_attr(feature = "tls", thread_local)]
+    #[allow(non_upper_case_globals)]
+    static mut strerror_buf: [u8; 256] = [0; 256];
+
+    let buf = &mut *core::ptr::addr_of_mut!(strerror_buf);
+    copy_str(err_str(e), buf);
+    buf.as_mut_ptr() as *mut c_char
+}
+
+/// Copies the string representation of the given error code to `buf`.
+///
+/// Returns `ERANGE` if `buf` is too short.
+#[no_mangle]
+pub unsafe extern "C" fn strerror_r(e: c_int, buf: *mut c_char, buflen: usize) -> c_int {
+    if buflen == 0 {
+        return LinuxError::ERANGE.code();
+    }
+    let buf = core::slice::from_raw_parts_mut(buf as *mut u8, buflen);
+    if copy_str(err_str(e), buf) {
+        LinuxError::ERANGE.code()
+    } else {
+        0
     }
 }
@@ -0,0 +1,11 @@
+use core::ffi::{c_int, c_uint};
+
+use arceos_posix_api::sys_eventfd;
+
+use crate::utils::e;
+
+/// Create a file descriptor for event notification.
+#[no_mangle]
+pub unsafe extern "C" fn eventfd(initval: c_uint, flags: c_int) -> c_int {
+    e(sys_eventfd(initval, flags))
+}
@@ -1,7 +1,11 @@
-use core::ffi::{c_char, c_int};
+use core::ffi::{c_char, c_int, c_void};
 
 use arceos_posix_api::{
-    sys_fstat, sys_getcwd, sys_lseek, sys_lstat, sys_open, sys_rename, sys_stat,
+    sys_access, sys_chdir, sys_faccessat, sys_fchdir, sys_fdatasync, sys_flock, sys_fstat,
+    sys_fstatat, sys_fsync, sys_ftruncate, sys_getcwd, sys_getdents64, sys_lseek, sys_lstat,
+    sys_mkdir, sys_mkdirat, sys_open, sys_openat, sys_pread64, sys_preadv, sys_pwrite64,
+    sys_pwritev, sys_rename, sys_renameat, sys_rmdir, sys_stat, sys_truncate, sys_unlink,
+    sys_unlinkat,
 };
 
 use crate::{ctypes, utils::e};
@@ -19,6 +23,21 @@ pub unsafe extern "C" fn ax_open(
     e(sys_open(filename, flags, mode))
 }
 
+/// Like `ax_open`, but a relative `filename` is resolved relative to the
+/// directory `dirfd`.
+///
+/// `openat` itself is variadic, and is implemented in C by calling this
+/// function.
+#[no_mangle]
+pub unsafe extern "C" fn ax_openat(
+    dirfd: c_int,
+    filename: *const c_char,
+    flags: c_int,
+    mode: ctypes::mode_t,
+) -> c_int {
+    e(sys_openat(dirfd, filename, flags, mode))
+}
+
 /// Set the position of the file indicated by `fd`.
 ///
 /// Return its position after seek.
@@ -27,6 +46,54 @@ pub unsafe extern "C" fn lseek(fd: c_int, offset: ctypes::off_t, whence: c_int)
     e(sys_lseek(fd, offset, whence) as _) as _
 }
 
+/// Read from the file `fd` at `offset`, the file position is not changed.
+///
+/// Return the read size if success.
+#[no_mangle]
+pub unsafe extern "C" fn pread(
+    fd: c_int,
+    buf: *mut c_void,
+    count: usize,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    e(sys_pread64(fd, buf, count, offset) as _) as _
+}
+
+/// Write to the file `fd` at `offset`, the file position is not changed.
+///
+/// Return the written size if success.
+#[no_mangle]
+pub unsafe extern "C" fn pwrite(
+    fd: c_int,
+    buf: *const c_void,
+    count: usize,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    e(sys_pwrite64(fd, buf, count, offset) as _) as _
+}
+
+/// Like `pread`, but reads into multiple buffers.
+#[no_mangle]
+pub unsafe extern "C" fn preadv(
+    fd: c_int,
+    iov: *const ctypes::iovec,
+    iocnt: c_int,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    e(sys_preadv(fd, iov, iocnt, offset) as _) as _
+}
+
+/// Like `pwrite`, but writes from multiple buffers.
+#[no_mangle]
+pub unsafe extern "C" fn pwritev(
+    fd: c_int,
+    iov: *const ctypes::iovec,
+    iocnt: c_int,
+    offset: ctypes::off_t,
+) -> ctypes::ssize_t {
+    e(sys_pwritev(fd, iov, iocnt, offset) as _) as _
+}
+
 /// Get the file metadata by `path` and write into `buf`.
 ///
 /// Return 0 if success.
@@ -43,6 +110,18 @@ pub unsafe extern "C" fn fstat(fd: c_int, buf: *mut ctypes::stat) -> c_int {
     e(sys_fstat(fd, buf))
 }
 
+/// Get the file metadata by `path` relative to the directory `dirfd`, and
+/// write into `buf`.
+#[no_mangle]
+pub unsafe extern "C" fn fstatat(
+    dirfd: c_int,
+    path: *const c_char,
+    buf: *mut ctypes::stat,
+    flags: c_int,
+) -> c_int {
+    e(sys_fstatat(dirfd, path, buf, flags))
+}
+
 /// Get the metadata of the symbolic link and write into `buf`.
 ///
 /// Return 0 if success.
@@ -65,3 +144,114 @@ pub unsafe extern "C" fn getcwd(buf: *mut c_char, size: usize) -> *mut c_char {
 pub unsafe extern "C" fn rename(old: *const c_char, new: *const c_char) -> c_int {
     e(sys_rename(old, new))
 }
+
+/// Like `rename`, but relative paths are resolved relative to the directories
+/// `olddirfd` and `newdirfd` respectively.
+#[no_mangle]
+pub unsafe extern "C" fn renameat(
+    olddirfd: c_int,
+    old: *const c_char,
+    newdirfd: c_int,
+    new: *const c_char,
+) -> c_int {
+    e(sys_renameat(olddirfd, old, newdirfd, new))
+}
+
+/// Remove a file.
+#[no_mangle]
+pub unsafe extern "C" fn unlink(path: *const c_char) -> c_int {
+    e(sys_unlink(path))
+}
+
+/// Remove a file, or an empty directory if `flags` contains `AT_REMOVEDIR`.
+#[no_mangle]
+pub unsafe extern "C" fn unlinkat(dirfd: c_int, path: *const c_char, flags: c_int) -> c_int {
+    e(sys_unlinkat(dirfd, path, flags))
+}
+
+/// Remove an empty directory.
+#[no_mangle]
+pub unsafe extern "C" fn rmdir(path: *const c_char) -> c_int {
+    e(sys_rmdir(path))
+}
+
+/// Create a directory.
+#[no_mangle]
+pub unsafe extern "C" fn mkdir(path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    e(sys_mkdir(path, mode))
+}
+
+/// Create a directory at `path` relative to the directory `dirfd`.
+#[no_mangle]
+pub unsafe extern "C" fn mkdirat(dirfd: c_int, path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    e(sys_mkdirat(dirfd, path, mode))
+}
+
+/// Truncate or extend the file indicated by `fd` to `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ftruncate(fd: c_int, length: ctypes::off_t) -> c_int {
+    e(sys_ftruncate(fd, length))
+}
+
+/// Truncate or extend the file at `path` to `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn truncate(path: *const c_char, length: ctypes::off_t) -> c_int {
+    e(sys_truncate(path, length))
+}
+
+/// Write the buffered data of the file indicated by `fd` to the device.
+#[no_mangle]
+pub unsafe extern "C" fn fsync(fd: c_int) -> c_int {
+    e(sys_fsync(fd))
+}
+
+/// Write the buffered data of the file indicated by `fd` to the device.
+#[no_mangle]
+pub unsafe extern "C" fn fdatasync(fd: c_int) -> c_int {
+    e(sys_fdatasync(fd))
+}
+
+/// Change the current directory.
+#[no_mangle]
+pub unsafe extern "C" fn chdir(path: *const c_char) -> c_int {
+    e(sys_chdir(path))
+}
+
+/// Apply or remove an advisory lock on the whole file `fd`.
+#[no_mangle]
+pub unsafe extern "C" fn flock(fd: c_int, operation: c_int) -> c_int {
+    e(sys_flock(fd, operation))
+}
+
+/// Change the current directory to the directory `fd`.
+#[no_mangle]
+pub unsafe extern "C" fn fchdir(fd: c_int) -> c_int {
+    e(sys_fchdir(fd))
+}
+
+/// Read entries of the directory `fd` into `dirp`, as `struct dirent`
+/// records.
+///
+/// Return the number of bytes read, or 0 at the end of the directory.
+#[no_mangle]
+pub unsafe extern "C" fn getdents64(fd: c_int, dirp: *mut c_void, count: usize) -> ctypes::ssize_t {
+    e(sys_getdents64(fd, dirp, count) as _) as _
+}
+
+/// Check whether the file at `path` can be accessed with the given `mode`.
+#[no_mangle]
+pub unsafe extern "C" fn access(path: *const c_char, mode: c_int) -> c_int {
+    e(sys_access(path, mode))
+}
+
+/// Like `access`, but a relative `path` is resolved relative to the directory
+/// `dirfd`.
+#[no_mangle]
+pub unsafe extern "C" fn faccessat(
+    dirfd: c_int,
+    path: *const c_char,
+    mode: c_int,
+    flags: c_int,
+) -> c_int {
+    e(sys_faccessat(dirfd, path, mode, flags))
+}
@@ -1,6 +1,6 @@
 use core::ffi::{c_int, c_void};
 
-use arceos_posix_api::{sys_read, sys_write, sys_writev};
+use arceos_posix_api::{sys_read, sys_readv, sys_write, sys_writev};
 
 use crate::{ctypes, utils::e};
 
@@ -30,3 +30,13 @@ pub unsafe extern "C" fn writev(
 ) -> ctypes::ssize_t {
     e(sys_writev(fd, iov, iocnt) as _) as _
 }
+
+/// Read into multiple buffers.
+#[no_mangle]
+pub unsafe extern "C" fn readv(
+    fd: c_int,
+    iov: *const ctypes::iovec,
+    iocnt: c_int,
+) -> ctypes::ssize_t {
+    e(sys_readv(fd, iov, iocnt) as _) as _
+}
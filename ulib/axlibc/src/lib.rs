@@ -9,9 +9,11 @@
 //!     - `irq`: Enable interrupt handling support.
 //! - Memory
 //!     - `alloc`: Enable dynamic memory allocation.
-//!     - `tls`: Enable thread-local storage.
+//!     - `tls`: Enable thread-local storage, which keeps `errno` and the state of
+//!       functions like `strtok` per thread.
+//!     - `mmap`: Enable memory mappings ([mmap]) in the kernel page table.
 //! - Task management
-//!     - `multitask`: Enable multi-threading support.
+//!     - `multitask`: Enable multi-threading support (implies `tls`).
 //! - Upperlayer stacks
 //!     - `fs`: Enable file system support.
 //!     - `net`: Enable networking support.
@@ -19,11 +21,22 @@
 //!     - `fd`: Enable file descriptor table.
 //!     - `pipe`: Enable pipe support.
 //!     - `select`: Enable synchronous I/O multiplexing ([select]) support.
+//!     - `poll`: Enable synchronous I/O multiplexing ([poll]) support.
 //!     - `epoll`: Enable event polling ([epoll]) support.
+//!     - `eventfd`: Enable file descriptors for event notification ([eventfd]).
+//!     - `timerfd`: Enable timers that notify via file descriptors ([timerfd]).
+//!     - `signal`: Enable signal delivery ([signal]), `alarm` and `setitimer`.
+//!     - `process`: Enable emulated processes ([posix_spawn] and `waitpid`).
 //!
 //! [ArceOS]: https://github.com/rcore-os/arceos
+//! [mmap]: https://man7.org/linux/man-pages/man2/mmap.2.html
 //! [select]: https://man7.org/linux/man-pages/man2/select.2.html
+//! [poll]: https://man7.org/linux/man-pages/man2/poll.2.html
 //! [epoll]: https://man7.org/linux/man-pages/man7/epoll.7.html
+//! [eventfd]: https://man7.org/linux/man-pages/man2/eventfd.2.html
+//! [timerfd]: https://man7.org/linux/man-pages/man2/timerfd_create.2.html
+//! [signal]: https://man7.org/linux/man-pages/man7/signal.7.html
+//! [posix_spawn]: https://man7.org/linux/man-pages/man3/posix_spawn.3.html
 
 #![cfg_attr(all(not(test), not(doc)), no_std)]
 #![feature(doc_cfg)]
@@ -49,24 +62,34 @@ mod ctypes {
 #[macro_use]
 mod utils;
 
+#[cfg(feature = "eventfd")]
+mod eventfd;
 #[cfg(feature = "fd")]
 mod fd_ops;
 #[cfg(feature = "fs")]
 mod fs;
-#[cfg(any(feature = "select", feature = "epoll"))]
+#[cfg(any(feature = "select", feature = "poll", feature = "epoll"))]
 mod io_mpx;
 #[cfg(feature = "alloc")]
 mod malloc;
+#[cfg(feature = "mmap")]
+mod mmap;
 #[cfg(feature = "net")]
 mod net;
 #[cfg(feature = "pipe")]
 mod pipe;
+#[cfg(feature = "process")]
+mod process;
 #[cfg(feature = "multitask")]
 mod pthread;
+#[cfg(feature = "signal")]
+mod signal;
 #[cfg(feature = "alloc")]
 mod strftime;
 #[cfg(feature = "fp_simd")]
 mod strtod;
+#[cfg(feature = "timerfd")]
+mod timerfd;
 
 mod errno;
 mod io;
@@ -80,38 +103,76 @@ mod unistd;
 
 #[cfg(not(test))]
 pub use self::io::write;
-pub use self::io::{read, writev};
+pub use self::io::{read, readv, writev};
 
-pub use self::errno::strerror;
+pub use self::errno::{strerror, strerror_r};
 pub use self::mktime::mktime;
-pub use self::rand::{rand, random, srand};
-pub use self::resource::{getrlimit, setrlimit};
+pub use self::rand::{rand, rand_r, random, srand};
+pub use self::resource::{getrlimit, getrusage, setrlimit};
 pub use self::setjmp::{longjmp, setjmp};
-pub use self::sys::sysconf;
-pub use self::time::{clock_gettime, nanosleep};
-pub use self::unistd::{abort, exit, getpid};
+pub use self::sys::{getentropy, getrandom, sysconf};
+pub use self::time::{clock_getres, clock_gettime, clock_settime, nanosleep};
+pub use self::unistd::{abort, exit, getpid, sched_getcpu, sched_yield};
 
 #[cfg(feature = "alloc")]
 pub use self::malloc::{free, malloc};
 #[cfg(feature = "alloc")]
 pub use self::strftime::strftime;
 
+#[cfg(feature = "mmap")]
+pub use self::mmap::{ax_mremap, mmap, mprotect, munmap};
+
 #[cfg(feature = "fd")]
-pub use self::fd_ops::{ax_fcntl, close, dup, dup2, dup3};
+pub use self::fd_ops::{ax_fcntl, ax_ioctl, close, dup, dup2, dup3};
 
 #[cfg(feature = "fs")]
-pub use self::fs::{ax_open, fstat, getcwd, lseek, lstat, rename, stat};
+pub use self::fs::{
+    access, ax_open, ax_openat, chdir, faccessat, fchdir, fdatasync, flock, fstat, fstatat, fsync,
+    ftruncate, getcwd, getdents64, lseek, lstat, mkdir, mkdirat, pread, preadv, pwrite, pwritev,
+    rename, renameat, rmdir, stat, truncate, unlink, unlinkat,
+};
 
 #[cfg(feature = "net")]
 pub use self::net::{
-    accept, bind, connect, freeaddrinfo, getaddrinfo, getpeername, getsockname, listen, recv,
-    recvfrom, send, sendto, shutdown, socket,
+    accept, bind, connect, freeaddrinfo, getaddrinfo, getpeername, getsockname, getsockopt, listen,
+    recv, recvfrom, recvmsg, send, sendmsg, sendto, setsockopt, shutdown, socket, socketpair,
 };
 
+#[cfg(feature = "multitask")]
+pub use self::pthread::{pthread_barrier_destroy, pthread_barrier_init, pthread_barrier_wait};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_cond_broadcast, pthread_cond_destroy, pthread_cond_init, pthread_cond_signal,
+    pthread_cond_timedwait, pthread_cond_wait,
+};
 #[cfg(feature = "multitask")]
 pub use self::pthread::{pthread_create, pthread_exit, pthread_join, pthread_self};
 #[cfg(feature = "multitask")]
-pub use self::pthread::{pthread_mutex_init, pthread_mutex_lock, pthread_mutex_unlock};
+pub use self::pthread::{pthread_getaffinity_np, pthread_setaffinity_np};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_mutex_init, pthread_mutex_lock, pthread_mutex_trylock, pthread_mutex_unlock,
+};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_rwlock_destroy, pthread_rwlock_init, pthread_rwlock_rdlock, pthread_rwlock_tryrdlock,
+    pthread_rwlock_trywrlock, pthread_rwlock_unlock, pthread_rwlock_wrlock,
+};
+#[cfg(feature = "multitask")]
+pub use self::unistd::{sched_get_priority_max, sched_get_priority_min};
+#[cfg(feature = "multitask")]
+pub use self::unistd::{sched_getaffinity, sched_setaffinity};
+#[cfg(feature = "multitask")]
+pub use self::unistd::{sched_getparam, sched_getscheduler, sched_setscheduler};
+
+#[cfg(feature = "process")]
+pub use self::process::{ax_spawn, getppid, waitpid};
+
+#[cfg(feature = "signal")]
+pub use self::signal::{
+    alarm, getitimer, kill, pause, pthread_kill, pthread_sigmask, raise, setitimer, sigaction,
+    sigprocmask,
+};
 
 #[cfg(feature = "pipe")]
 pub use self::pipe::pipe;
@@ -120,6 +181,13 @@ pub use self::pipe::pipe;
 pub use self::io_mpx::select;
 #[cfg(feature = "epoll")]
 pub use self::io_mpx::{epoll_create, epoll_ctl, epoll_wait};
+#[cfg(feature = "poll")]
+pub use self::io_mpx::{poll, ppoll};
+
+#[cfg(feature = "eventfd")]
+pub use self::eventfd::eventfd;
+#[cfg(feature = "timerfd")]
+pub use self::timerfd::{timerfd_create, timerfd_gettime, timerfd_settime};
 
 #[cfg(feature = "fp_simd")]
 pub use self::strtod::{strtod, strtof};
@@ -1,7 +1,12 @@
-use core::ffi::c_int;
+use core::ffi::{c_int, c_long, c_longlong};
 
 use crate::ctypes;
 
+extern "C" {
+    /// Defined in `time.c`, with the time zone from `TZ`.
+    fn __tz_offset(local: c_longlong, isdst: c_int) -> c_long;
+}
+
 const MONTH_DAYS: [[c_int; 12]; 2] = [
     // Non-leap years:
     [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
@@ -14,9 +19,14 @@ fn leap_year(year: c_int) -> bool {
     year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
 }
 
-/// Convert broken-down time into time since the Epoch.
+/// Convert broken-down local time into time since the Epoch.
 #[no_mangle]
 pub unsafe extern "C" fn mktime(t: *mut ctypes::tm) -> ctypes::time_t {
+    let local = local_secs(t);
+    local + __tz_offset(local as _, (*t).tm_isdst) as ctypes::time_t
+}
+
+unsafe fn local_secs(t: *mut ctypes::tm) -> ctypes::time_t {
     let mut year = (*t).tm_year + 1900;
     let mut month = (*t).tm_mon;
     let mut day = (*t).tm_mday as i64 - 1;
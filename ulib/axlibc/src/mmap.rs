@@ -0,0 +1,56 @@
+use crate::{ctypes, utils::e};
+use arceos_posix_api::{sys_mmap, sys_mprotect, sys_mremap, sys_munmap};
+use core::ffi::{c_int, c_void};
+
+/// Converts the return value of `sys_mmap` and `sys_mremap`, which is either
+/// an address or a negative error number, to a C return value.
+fn map_result(ret: *mut c_void) -> *mut c_void {
+    let ret = ret as isize;
+    // Mappings live in the higher half, so only small negative values are errors.
+    if (-4095..0).contains(&ret) {
+        e(ret as c_int);
+        usize::MAX as *mut c_void // MAP_FAILED
+    } else {
+        ret as *mut c_void
+    }
+}
+
+/// Map files or anonymous memory into the address space.
+#[no_mangle]
+pub unsafe extern "C" fn mmap(
+    addr: *mut c_void,
+    len: ctypes::size_t,
+    prot: c_int,
+    flags: c_int,
+    fd: c_int,
+    off: ctypes::off_t,
+) -> *mut c_void {
+    map_result(sys_mmap(addr, len, prot, flags, fd, off))
+}
+
+/// Unmap the pages in the given range.
+#[no_mangle]
+pub unsafe extern "C" fn munmap(addr: *mut c_void, len: ctypes::size_t) -> c_int {
+    e(sys_munmap(addr, len))
+}
+
+/// Set the access protections of the pages in the given range.
+#[no_mangle]
+pub unsafe extern "C" fn mprotect(addr: *mut c_void, len: ctypes::size_t, prot: c_int) -> c_int {
+    e(sys_mprotect(addr, len, prot))
+}
+
+/// Expand or shrink an existing mapping, possibly moving it.
+///
+/// `mremap` itself is variadic, and is implemented in C by calling this
+/// function.
+#[no_mangle]
+pub unsafe extern "C" fn ax_mremap(
+    old_addr: *mut c_void,
+    old_size: ctypes::size_t,
+    new_size: ctypes::size_t,
+    flags: c_int,
+    new_addr: *mut c_void,
+) -> *mut c_void {
+    map_result(sys_mremap(old_addr, old_size, new_size, flags, new_addr))
+}
@@ -1,7 +1,7 @@
 use arceos_posix_api::{
     sys_accept, sys_bind, sys_connect, sys_freeaddrinfo, sys_getaddrinfo, sys_getpeername,
-    sys_getsockname, sys_listen, sys_recv, sys_recvfrom, sys_send, sys_sendto, sys_shutdown,
-    sys_socket,
+    sys_getsockname, sys_getsockopt, sys_listen, sys_recv, sys_recvfrom, sys_recvmsg, sys_send,
+    sys_sendmsg, sys_sendto, sys_setsockopt, sys_shutdown, sys_socket, sys_socketpair,
 };
 use core::ffi::{c_char, c_int, c_void};
 
@@ -15,6 +15,20 @@ pub unsafe extern "C" fn socket(domain: c_int, socktype: c_int, protocol: c_int)
     e(sys_socket(domain, socktype, protocol))
 }
 
+/// Create a pair of connected sockets.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn socketpair(
+    domain: c_int,
+    socktype: c_int,
+    protocol: c_int,
+    sv: *mut c_int,
+) -> c_int {
+    let fds = unsafe { core::slice::from_raw_parts_mut(sv, 2) };
+    e(sys_socketpair(domain, socktype, protocol, fds))
+}
+
 /// Bind a address to a socket.
 ///
 /// Return 0 if success.
@@ -88,6 +102,30 @@ pub unsafe extern "C" fn recvfrom(
     e(sys_recvfrom(socket_fd, buf_ptr, len, flag, socket_addr, addrlen) as _) as _
 }
 
+/// Send a message gathered from multiple buffers on a socket.
+///
+/// Return the number of bytes sent if success.
+#[no_mangle]
+pub unsafe extern "C" fn sendmsg(
+    socket_fd: c_int,
+    msg: *const ctypes::msghdr,
+    flags: c_int,
+) -> ctypes::ssize_t {
+    e(sys_sendmsg(socket_fd, msg, flags) as _) as _
+}
+
+/// Receive a message on a socket into multiple buffers.
+///
+/// Return the number of bytes received if success.
+#[no_mangle]
+pub unsafe extern "C" fn recvmsg(
+    socket_fd: c_int,
+    msg: *mut ctypes::msghdr,
+    flags: c_int,
+) -> ctypes::ssize_t {
+    e(sys_recvmsg(socket_fd, msg, flags) as _) as _
+}
+
 /// Receive a message on a socket.
 ///
 /// Return the number of bytes received if success.
@@ -178,3 +216,31 @@ pub unsafe extern "C" fn getpeername(
 ) -> c_int {
     e(sys_getpeername(sock_fd, addr, addrlen))
 }
+
+/// Set options on a socket.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn setsockopt(
+    socket_fd: c_int,
+    level: c_int,
+    optname: c_int,
+    optval: *const c_void,
+    optlen: ctypes::socklen_t,
+) -> c_int {
+    e(sys_setsockopt(socket_fd, level, optname, optval, optlen))
+}
+
+/// Get options on a socket.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn getsockopt(
+    socket_fd: c_int,
+    level: c_int,
+    optname: c_int,
+    optval: *mut c_void,
+    optlen: *mut ctypes::socklen_t,
+) -> c_int {
+    e(sys_getsockopt(socket_fd, level, optname, optval, optlen))
+}
@@ -0,0 +1,28 @@
+use core::ffi::{c_int, c_void};
+
+use arceos_posix_api::{sys_getppid, sys_spawn, sys_waitpid};
+
+use crate::utils::e;
+
+/// Create a new process that runs `entry(arg)`, used by `posix_spawn`.
+///
+/// Return the PID of the new process.
+#[no_mangle]
+pub unsafe extern "C" fn ax_spawn(
+    entry: extern "C" fn(arg: *mut c_void) -> c_int,
+    arg: *mut c_void,
+) -> c_int {
+    e(sys_spawn(entry, arg))
+}
+
+/// Wait for a child process to exit.
+#[no_mangle]
+pub unsafe extern "C" fn waitpid(pid: c_int, status: *mut c_int, options: c_int) -> c_int {
+    e(sys_waitpid(pid, status, options))
+}
+
+/// Get the process ID of the parent process.
+#[no_mangle]
+pub unsafe extern "C" fn getppid() -> c_int {
+    e(sys_getppid())
+}
@@ -1,6 +1,7 @@
-use crate::{ctypes, utils::e};
+use crate::ctypes;
+use crate::utils::{e, pe};
 use arceos_posix_api as api;
-use core::ffi::{c_int, c_void};
+use core::ffi::{c_int, c_uint, c_void};
 
 /// Returns the `pthread` struct of current thread.
 #[no_mangle]
@@ -37,6 +38,26 @@ pub unsafe extern "C" fn pthread_join(
     e(api::sys_pthread_join(thread, retval))
 }
 
+/// Set the CPU affinity mask of the given thread.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_setaffinity_np(
+    thread: ctypes::pthread_t,
+    cpusetsize: usize,
+    cpuset: *const ctypes::cpu_set_t,
+) -> c_int {
+    pe(api::sys_pthread_setaffinity_np(thread, cpusetsize, cpuset))
+}
+
+/// Get the CPU affinity mask of the given thread.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_getaffinity_np(
+    thread: ctypes::pthread_t,
+    cpusetsize: usize,
+    cpuset: *mut ctypes::cpu_set_t,
+) -> c_int {
+    pe(api::sys_pthread_getaffinity_np(thread, cpusetsize, cpuset))
+}
+
 /// Initialize a mutex.
 #[no_mangle]
 pub unsafe extern "C" fn pthread_mutex_init(
@@ -52,8 +73,138 @@ pub unsafe extern "C" fn pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t)
     e(api::sys_pthread_mutex_lock(mutex))
 }
 
+/// Try to lock the given mutex, returns `EBUSY` if it is already locked.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_mutex_trylock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+    pe(api::sys_pthread_mutex_trylock(mutex))
+}
+
 /// Unlock the given mutex.
 #[no_mangle]
 pub unsafe extern "C" fn pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     e(api::sys_pthread_mutex_unlock(mutex))
 }
+
+/// Initialize a condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    pe(api::sys_pthread_cond_init(cond, attr))
+}
+
+/// Destroy a condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    pe(api::sys_pthread_cond_destroy(cond))
+}
+
+/// Unlock the mutex and wait on the condition variable, the mutex is locked
+/// again before returning.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    pe(api::sys_pthread_cond_wait(cond, mutex))
+}
+
+/// Like `pthread_cond_wait`, but returns `ETIMEDOUT` if the condition variable
+/// is not signaled before the absolute time `abstime`.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    pe(api::sys_pthread_cond_timedwait(cond, mutex, abstime))
+}
+
+/// Wake up at least one thread waiting on the condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    pe(api::sys_pthread_cond_signal(cond))
+}
+
+/// Wake up all threads waiting on the condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    pe(api::sys_pthread_cond_broadcast(cond))
+}
+
+/// Initialize a read-write lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    pe(api::sys_pthread_rwlock_init(rwlock, attr))
+}
+
+/// Destroy a read-write lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_destroy(rwlock))
+}
+
+/// Lock the given read-write lock for reading.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_rdlock(rwlock))
+}
+
+/// Try to lock the given read-write lock for reading, returns `EBUSY` if it is
+/// locked by a writer.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_tryrdlock(rwlock))
+}
+
+/// Lock the given read-write lock for writing.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_wrlock(rwlock))
+}
+
+/// Try to lock the given read-write lock for writing, returns `EBUSY` if it is
+/// already locked.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_trywrlock(rwlock))
+}
+
+/// Unlock the given read-write lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_unlock(rwlock))
+}
+
+/// Initialize a barrier that `count` threads must wait on before any of them
+/// can proceed.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_barrier_init(
+    barrier: *mut ctypes::pthread_barrier_t,
+    attr: *const ctypes::pthread_barrierattr_t,
+    count: c_uint,
+) -> c_int {
+    pe(api::sys_pthread_barrier_init(barrier, attr, count))
+}
+
+/// Destroy a barrier.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_barrier_destroy(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    pe(api::sys_pthread_barrier_destroy(barrier))
+}
+
+/// Wait until enough threads have reached the barrier.
+///
+/// Returns `PTHREAD_BARRIER_SERIAL_THREAD` in one of the threads and 0 in the
+/// others.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_barrier_wait(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    match api::sys_pthread_barrier_wait(barrier) {
+        1 => ctypes::PTHREAD_BARRIER_SERIAL_THREAD,
+        ret => pe(ret),
+    }
+}
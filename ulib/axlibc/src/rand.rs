@@ -7,6 +7,19 @@ use core::{
 
 static SEED: AtomicU64 = AtomicU64::new(0xa2ce_a2ce);
 
+fn next_seed(seed: u64) -> u64 {
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1)
+}
+
+/// Advances the shared state atomically, so that concurrent callers never
+/// get the same number.
+fn next() -> u64 {
+    let prev = SEED
+        .fetch_update(SeqCst, SeqCst, |seed| Some(next_seed(seed)))
+        .unwrap();
+    next_seed(prev)
+}
+
 /// Sets the seed for the random number generator.
 #[no_mangle]
 pub unsafe extern "C" fn srand(seed: c_uint) {
@@ -16,15 +29,20 @@ pub unsafe extern "C" fn srand(seed: c_uint) {
 /// Returns a 32-bit unsigned pseudo random interger.
 #[no_mangle]
 pub unsafe extern "C" fn rand() -> c_int {
-    let new_seed = SEED.load(SeqCst).wrapping_mul(6364136223846793005) + 1;
-    SEED.store(new_seed, SeqCst);
+    (next() >> 33) as c_int
+}
+
+/// Returns a pseudo random interger with the state in `seedp`, which is
+/// reentrant.
+#[no_mangle]
+pub unsafe extern "C" fn rand_r(seedp: *mut c_uint) -> c_int {
+    let new_seed = next_seed(*seedp as u64);
+    *seedp = new_seed as c_uint;
     (new_seed >> 33) as c_int
 }
 
 /// Returns a 64-bit unsigned pseudo random number.
 #[no_mangle]
 pub unsafe extern "C" fn random() -> c_long {
-    let new_seed = SEED.load(SeqCst).wrapping_mul(6364136223846793005) + 1;
-    SEED.store(new_seed, SeqCst);
-    new_seed as c_long
+    next() as c_long
 }
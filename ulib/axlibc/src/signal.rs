@@ -0,0 +1,81 @@
+use crate::ctypes;
+use crate::utils::{e, pe};
+use arceos_posix_api as api;
+use core::ffi::{c_int, c_uint};
+
+/// Examine and change the action of a signal.
+#[no_mangle]
+pub unsafe extern "C" fn sigaction(
+    sig: c_int,
+    act: *const ctypes::sigaction,
+    oldact: *mut ctypes::sigaction,
+) -> c_int {
+    e(api::sys_sigaction(sig, act, oldact))
+}
+
+/// Examine and change the blocked signals of the current thread.
+#[no_mangle]
+pub unsafe extern "C" fn sigprocmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    e(api::sys_sigprocmask(how, set, oldset))
+}
+
+/// Like `sigprocmask`, but returns the error number instead of setting `errno`.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_sigmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    pe(api::sys_sigprocmask(how, set, oldset))
+}
+
+/// Send a signal to a thread, `pid` is the thread ID returned by `getpid`, or
+/// the process ID with the `process` feature.
+#[no_mangle]
+pub unsafe extern "C" fn kill(pid: c_int, sig: c_int) -> c_int {
+    e(api::sys_kill(pid, sig))
+}
+
+/// Send a signal to the current thread.
+#[no_mangle]
+pub unsafe extern "C" fn raise(sig: c_int) -> c_int {
+    e(api::sys_raise(sig))
+}
+
+/// Send a signal to the given thread.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_kill(thread: ctypes::pthread_t, sig: c_int) -> c_int {
+    pe(api::sys_pthread_kill(thread, sig))
+}
+
+/// Wait until a signal is caught.
+#[no_mangle]
+pub unsafe extern "C" fn pause() -> c_int {
+    e(api::sys_pause())
+}
+
+/// Send `SIGALRM` to the current thread after `seconds` seconds.
+#[no_mangle]
+pub unsafe extern "C" fn alarm(seconds: c_uint) -> c_uint {
+    api::sys_alarm(seconds)
+}
+
+/// Get the value of an interval timer.
+#[no_mangle]
+pub unsafe extern "C" fn getitimer(which: c_int, curr_value: *mut ctypes::itimerval) -> c_int {
+    e(api::sys_getitimer(which, curr_value))
+}
+
+/// Set the value of an interval timer.
+#[no_mangle]
+pub unsafe extern "C" fn setitimer(
+    which: c_int,
+    new_value: *const ctypes::itimerval,
+    old_value: *mut ctypes::itimerval,
+) -> c_int {
+    e(api::sys_setitimer(which, new_value, old_value))
+}
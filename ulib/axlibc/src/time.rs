@@ -1,17 +1,33 @@
-use arceos_posix_api::{sys_clock_gettime, sys_nanosleep};
+use arceos_posix_api::{sys_clock_getres, sys_clock_gettime, sys_clock_settime, sys_nanosleep};
 use core::ffi::c_int;
 
 use crate::{ctypes, utils::e};
 
-/// Get clock time since booting
+/// Get the time of the clock `clk`.
 #[no_mangle]
 pub unsafe extern "C" fn clock_gettime(clk: ctypes::clockid_t, ts: *mut ctypes::timespec) -> c_int {
     e(sys_clock_gettime(clk, ts))
 }
 
+/// Set the time of the clock `clk`, only `CLOCK_REALTIME` can be set.
+#[no_mangle]
+pub unsafe extern "C" fn clock_settime(
+    clk: ctypes::clockid_t,
+    ts: *const ctypes::timespec,
+) -> c_int {
+    e(sys_clock_settime(clk, ts))
+}
+
+/// Get the resolution of the clock `clk`.
+#[no_mangle]
+pub unsafe extern "C" fn clock_getres(clk: ctypes::clockid_t, res: *mut ctypes::timespec) -> c_int {
+    e(sys_clock_getres(clk, res))
+}
+
 /// Sleep some nanoseconds
 ///
-/// TODO: should be woken by signals, and set errno
+/// If the sleep is interrupted (e.g. by a signal), the remaining time is
+/// stored in `rem` and `EINTR` is returned.
 #[no_mangle]
 pub unsafe extern "C" fn nanosleep(
     req: *const ctypes::timespec,
@@ -1,12 +1,58 @@
-use arceos_posix_api::{sys_exit, sys_getpid};
+use arceos_posix_api::{sys_exit, sys_getpid, sys_sched_getcpu, sys_sched_yield};
 use core::ffi::c_int;
 
-/// Get current thread ID.
+#[cfg(feature = "multitask")]
+use crate::{ctypes, utils::e};
+#[cfg(feature = "multitask")]
+use arceos_posix_api::{sys_sched_get_priority_max, sys_sched_get_priority_min};
+#[cfg(feature = "multitask")]
+use arceos_posix_api::{sys_sched_getaffinity, sys_sched_setaffinity};
+#[cfg(feature = "multitask")]
+use arceos_posix_api::{sys_sched_getparam, sys_sched_getscheduler, sys_sched_setscheduler};
+
+/// Get current thread ID, or the process ID if the `process` feature is
+/// enabled.
 #[no_mangle]
 pub unsafe extern "C" fn getpid() -> c_int {
     sys_getpid()
 }
 
+/// Relinquish the CPU to other threads.
+#[no_mangle]
+pub unsafe extern "C" fn sched_yield() -> c_int {
+    sys_sched_yield()
+}
+
+/// Get the CPU that the current thread is running on.
+#[no_mangle]
+pub unsafe extern "C" fn sched_getcpu() -> c_int {
+    sys_sched_getcpu()
+}
+
+/// Set the CPU affinity mask of the thread `pid` (the current thread if it
+/// is zero).
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn sched_setaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *const ctypes::cpu_set_t,
+) -> c_int {
+    e(sys_sched_setaffinity(pid, cpusetsize, mask))
+}
+
+/// Get the CPU affinity mask of the thread `pid` (the current thread if it
+/// is zero).
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn sched_getaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *mut ctypes::cpu_set_t,
+) -> c_int {
+    e(sys_sched_getaffinity(pid, cpusetsize, mask))
+}
+
 /// Abort the current process.
 #[no_mangle]
 pub unsafe extern "C" fn abort() -> ! {
@@ -18,3 +64,45 @@ pub unsafe extern "C" fn abort() -> ! {
 pub unsafe extern "C" fn exit(exit_code: c_int) -> ! {
     sys_exit(exit_code)
 }
+
+/// Set the scheduling policy and parameters of the thread `pid` (the current
+/// thread if it is zero).
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn sched_setscheduler(
+    pid: c_int,
+    policy: c_int,
+    param: *const ctypes::sched_param,
+) -> c_int {
+    e(sys_sched_setscheduler(pid, policy, param))
+}
+
+/// Get the scheduling policy of the thread `pid` (the current thread if it is
+/// zero).
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn sched_getscheduler(pid: c_int) -> c_int {
+    e(sys_sched_getscheduler(pid))
+}
+
+/// Get the scheduling parameters of the thread `pid` (the current thread if
+/// it is zero).
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn sched_getparam(pid: c_int, param: *mut ctypes::sched_param) -> c_int {
+    e(sys_sched_getparam(pid, param))
+}
+
+/// Get the maximum priority of the scheduling policy `policy`.
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn sched_get_priority_max(policy: c_int) -> c_int {
+    e(sys_sched_get_priority_max(policy))
+}
+
+/// Get the minimum priority of the scheduling policy `policy`.
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn sched_get_priority_min(policy: c_int) -> c_int {
+    e(sys_sched_get_priority_min(policy))
+}
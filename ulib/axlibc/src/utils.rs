@@ -8,3 +8,14 @@ pub fn e(ret: c_int) -> c_int {
         ret as _
     }
 }
+
+/// Like [`e`], but returns the error number instead of setting `errno`, as
+/// pthread functions do.
+#[cfg(feature = "multitask")]
+pub fn pe(ret: c_int) -> c_int {
+    if ret < 0 {
+        ret.abs()
+    } else {
+        ret
+    }
+}
@@ -6,22 +6,31 @@
 //! # Organization
 //!
 //! * [`TcpListener`] and [`TcpStream`] provide functionality for communication over TCP
-//! * [`UdpSocket`] provides functionality for communication over UDP
+//! * [`UdpSocket`] provides functionality for communication over UDP, and [`UdpRecvBuf`]
+//!   lends received datagrams without copying
 //! * [`IpAddr`] represents IP addresses of either IPv4 or IPv6; [`Ipv4Addr`] and
 //!   [`Ipv6Addr`] are respectively IPv4 and IPv6 addresses
 //! * [`SocketAddr`] represents socket addresses of either IPv4 or IPv6; [`SocketAddrV4`]
 //!   and [`SocketAddrV6`] are respectively IPv4 and IPv6 socket addresses
 //! * [`ToSocketAddrs`] is a trait that is used for generic address resolution when interacting
 //!   with networking objects like [`TcpListener`], [`TcpStream`] or [`UdpSocket`]
+//! * [`TlsConnector`] and [`TlsAcceptor`] establish [`TlsStream`]s over [`TcpStream`]s
+//!   (requires the `net-tls` feature)
 
 mod socket_addr;
 mod tcp;
 mod udp;
 
+#[cfg(feature = "net-tls")]
+mod tls;
+
 pub use self::socket_addr::{IpAddr, Ipv4Addr, Ipv6Addr};
 pub use self::socket_addr::{SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
 pub use self::tcp::{TcpListener, TcpStream};
-pub use self::udp::UdpSocket;
+pub use self::udp::{UdpRecvBuf, UdpSocket};
+
+#[cfg(feature = "net-tls")]
+pub use self::tls::{TlsAcceptor, TlsConnector, TlsStream};
 
 use crate::io;
 
@@ -1,4 +1,5 @@
 use core::fmt::Debug;
+use core::time::Duration;
 
 use super::{SocketAddr, ToSocketAddrs};
 use crate::io::{self, prelude::*};
@@ -45,6 +46,83 @@ impl TcpStream {
     pub fn shutdown(&self) -> io::Result<()> {
         api::ax_tcp_shutdown(&self.0)
     }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// If set, this option disables the Nagle algorithm. This means that
+    /// segments are always sent as soon as possible, even if there is only a
+    /// small amount of data.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        api::ax_tcp_set_nodelay(&self.0, nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    pub fn nodelay(&self) -> io::Result<bool> {
+        api::ax_tcp_nodelay(&self.0)
+    }
+
+    /// Sets the read timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then [`read`] calls will block
+    /// indefinitely. An [`Err`] is returned if the zero [`Duration`] is
+    /// passed to this method.
+    ///
+    /// [`read`]: Read::read
+    pub fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        check_timeout(dur)?;
+        api::ax_tcp_set_recv_timeout(&self.0, dur)
+    }
+
+    /// Returns the read timeout of this socket.
+    pub fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_tcp_recv_timeout(&self.0)
+    }
+
+    /// Sets the write timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then [`write`] calls will block
+    /// indefinitely. An [`Err`] is returned if the zero [`Duration`] is
+    /// passed to this method.
+    ///
+    /// [`write`]: Write::write
+    pub fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        check_timeout(dur)?;
+        api::ax_tcp_set_send_timeout(&self.0, dur)
+    }
+
+    /// Returns the write timeout of this socket.
+    pub fn write_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_tcp_send_timeout(&self.0)
+    }
+
+    /// Sets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// This value controls how the socket is closed when data remains to be
+    /// sent. If `SO_LINGER` is set, the socket will remain open for the
+    /// specified duration as the system attempts to send pending data.
+    /// Otherwise, the system may close the socket immediately, or wait for a
+    /// default timeout.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        api::ax_tcp_set_linger(&self.0, linger)
+    }
+
+    /// Gets the value of the `SO_LINGER` option on this socket.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        api::ax_tcp_linger(&self.0)
+    }
+
+    /// Sets the interval of TCP keep-alive packets on this socket.
+    ///
+    /// [`None`] disables keep-alive packets.
+    pub fn set_keepalive(&self, interval: Option<Duration>) -> io::Result<()> {
+        api::ax_tcp_set_keep_alive(&self.0, interval)
+    }
+
+    /// Returns the interval of TCP keep-alive packets on this socket, or
+    /// [`None`] if keep-alive is disabled.
+    pub fn keepalive(&self) -> io::Result<Option<Duration>> {
+        api::ax_tcp_keep_alive(&self.0)
+    }
 }
 
 impl Read for TcpStream {
@@ -114,3 +192,10 @@ impl TcpListener {
         api::ax_tcp_accept(&self.0).map(|(a, b)| (TcpStream(a), b))
     }
 }
+
+fn check_timeout(dur: Option<Duration>) -> io::Result<()> {
+    if dur == Some(Duration::ZERO) {
+        return axerrno::ax_err!(InvalidInput, "cannot set a 0 duration timeout");
+    }
+    Ok(())
+}
@@ -0,0 +1,372 @@
+//! TLS 1.3 streams over [`TcpStream`], based on [rustls].
+//!
+//! [`TlsConnector`] and [`TlsAcceptor`] hold the configurations of the client
+//! and server roles, and are usually created once and shared by all
+//! connections. Certificates and private keys are read from PEM files in the
+//! file system.
+//!
+//! The cryptography is provided by [ring], which draws its random bytes from
+//! the kernel random number generator. Certificate validity periods are
+//! checked against the wall clock.
+//!
+//! [rustls]: https://github.com/rustls/rustls
+//! [ring]: https://github.com/briansmith/ring
+
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use rustls::client::{ClientConnectionData, UnbufferedClientConnection};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::{ServerConnectionData, UnbufferedServerConnection};
+use rustls::time_provider::TimeProvider;
+use rustls::unbuffered::{
+    AppDataRecord, ConnectionState, EncodeError, EncryptError, UnbufferedStatus,
+};
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+
+use super::TcpStream;
+use crate::io::{self, prelude::*};
+
+const TLS_BUF_LEN: usize = 16 * 1024 + 512;
+
+getrandom::register_custom_getrandom!(kernel_getrandom);
+
+fn kernel_getrandom(buf: &mut [u8]) -> Result<(), getrandom::Error> {
+    arceos_api::sys::ax_fill_random(buf);
+    Ok(())
+}
+
+#[derive(Debug)]
+struct WallClockProvider;
+
+impl TimeProvider for WallClockProvider {
+    fn current_time(&self) -> Option<UnixTime> {
+        Some(UnixTime::since_unix_epoch(arceos_api::time::ax_wall_time()))
+    }
+}
+
+fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn tls_err(err: rustls::Error) -> io::Error {
+    axerrno::ax_err_type!(InvalidData, err)
+}
+
+fn load_certs(path: &str) -> io::Result<Vec<CertificateDer<'static>>> {
+    let pem = crate::fs::read(path)?;
+    CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| axerrno::ax_err_type!(InvalidData, "invalid certificate file"))
+}
+
+fn load_private_key(path: &str) -> io::Result<PrivateKeyDer<'static>> {
+    let pem = crate::fs::read(path)?;
+    PrivateKeyDer::from_pem_slice(&pem)
+        .map_err(|_| axerrno::ax_err_type!(InvalidData, "invalid private key file"))
+}
+
+/// The client role of TLS connections.
+#[derive(Clone)]
+pub struct TlsConnector(Arc<ClientConfig>);
+
+impl TlsConnector {
+    /// Creates a connector that trusts the CA certificates in the PEM file at
+    /// `ca_path`.
+    pub fn new(ca_path: &str) -> io::Result<Self> {
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(ca_path)? {
+            roots.add(cert).map_err(tls_err)?;
+        }
+        let config =
+            ClientConfig::builder_with_details(crypto_provider(), Arc::new(WallClockProvider))
+                .with_protocol_versions(&[&rustls::version::TLS13])
+                .map_err(tls_err)?
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        Ok(Self(Arc::new(config)))
+    }
+
+    /// Performs the TLS handshake over `stream` as a client, verifying that the
+    /// server certificate is valid for `server_name`.
+    pub fn connect(&self, server_name: &str, stream: TcpStream) -> io::Result<TlsStream> {
+        let name = ServerName::try_from(server_name)
+            .map_err(|_| axerrno::ax_err_type!(InvalidInput, "invalid server name"))?
+            .to_owned();
+        let conn = UnbufferedClientConnection::new(self.0.clone(), name).map_err(tls_err)?;
+        TlsStream::handshake(Connection::Client(conn), stream)
+    }
+}
+
+/// The server role of TLS connections.
+#[derive(Clone)]
+pub struct TlsAcceptor(Arc<ServerConfig>);
+
+impl TlsAcceptor {
+    /// Creates an acceptor that presents the certificate chain in the PEM file
+    /// at `cert_path`, whose private key is in the PEM file at `key_path`.
+    pub fn new(cert_path: &str, key_path: &str) -> io::Result<Self> {
+        let certs = load_certs(cert_path)?;
+        let key = load_private_key(key_path)?;
+        let config =
+            ServerConfig::builder_with_details(crypto_provider(), Arc::new(WallClockProvider))
+                .with_protocol_versions(&[&rustls::version::TLS13])
+                .map_err(tls_err)?
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .map_err(tls_err)?;
+        Ok(Self(Arc::new(config)))
+    }
+
+    /// Performs the TLS handshake over `stream` as a server.
+    pub fn accept(&self, stream: TcpStream) -> io::Result<TlsStream> {
+        let conn = UnbufferedServerConnection::new(self.0.clone()).map_err(tls_err)?;
+        TlsStream::handshake(Connection::Server(conn), stream)
+    }
+}
+
+enum Connection {
+    Client(UnbufferedClientConnection),
+    Server(UnbufferedServerConnection),
+}
+
+/// `process_tls_records` of both roles, which are not generic in rustls.
+trait ProcessRecords {
+    type Data;
+
+    fn process_records<'c, 'i>(
+        &'c mut self,
+        incoming: &'i mut [u8],
+    ) -> UnbufferedStatus<'c, 'i, Self::Data>;
+}
+
+impl ProcessRecords for UnbufferedClientConnection {
+    type Data = ClientConnectionData;
+
+    fn process_records<'c, 'i>(
+        &'c mut self,
+        incoming: &'i mut [u8],
+    ) -> UnbufferedStatus<'c, 'i, Self::Data> {
+        self.process_tls_records(incoming)
+    }
+}
+
+impl ProcessRecords for UnbufferedServerConnection {
+    type Data = ServerConnectionData;
+
+    fn process_records<'c, 'i>(
+        &'c mut self,
+        incoming: &'i mut [u8],
+    ) -> UnbufferedStatus<'c, 'i, Self::Data> {
+        self.process_tls_records(incoming)
+    }
+}
+
+/// What the caller of [`TlsIo::process`] is waiting for.
+enum Goal<'a> {
+    Handshake,
+    Read,
+    Write(&'a [u8]),
+    Close,
+}
+
+/// What to do after the state of the connection is handled.
+enum Next {
+    Continue,
+    ReadSocket,
+    Done(usize),
+}
+
+/// Buffers and the underlying socket of a TLS stream.
+struct TlsIo {
+    sock: TcpStream,
+    incoming: Vec<u8>,
+    incoming_used: usize,
+    outgoing: Vec<u8>,
+    outgoing_used: usize,
+    plaintext: VecDeque<u8>,
+    peer_closed: bool,
+}
+
+impl TlsIo {
+    /// Drives the connection until `goal` is reached.
+    fn process<C: ProcessRecords>(&mut self, conn: &mut C, goal: Goal) -> io::Result<usize> {
+        loop {
+            let UnbufferedStatus { mut discard, state } =
+                conn.process_records(&mut self.incoming[..self.incoming_used]);
+            let next = match state.map_err(tls_err)? {
+                ConnectionState::ReadTraffic(mut state) => {
+                    while let Some(record) = state.next_record() {
+                        let AppDataRecord {
+                            discard: consumed,
+                            payload,
+                        } = record.map_err(tls_err)?;
+                        discard += consumed;
+                        self.plaintext.extend(payload);
+                    }
+                    Next::Continue
+                }
+                ConnectionState::EncodeTlsData(mut state) => {
+                    loop {
+                        match state.encode(&mut self.outgoing[self.outgoing_used..]) {
+                            Ok(len) => {
+                                self.outgoing_used += len;
+                                break;
+                            }
+                            Err(EncodeError::InsufficientSize(e)) => self
+                                .outgoing
+                                .resize(self.outgoing_used + e.required_size, 0),
+                            Err(_) => return Err(io::Error::BadState),
+                        }
+                    }
+                    Next::Continue
+                }
+                ConnectionState::TransmitTlsData(state) => {
+                    self.sock.write_all(&self.outgoing[..self.outgoing_used])?;
+                    self.outgoing_used = 0;
+                    state.done();
+                    Next::Continue
+                }
+                ConnectionState::BlockedHandshake => Next::ReadSocket,
+                ConnectionState::WriteTraffic(mut state) => match goal {
+                    Goal::Handshake => Next::Done(0),
+                    Goal::Read if !self.plaintext.is_empty() || self.peer_closed => Next::Done(0),
+                    Goal::Read => Next::ReadSocket,
+                    Goal::Write(data) => {
+                        let len = encrypt_with(&mut self.outgoing, |buf| state.encrypt(data, buf))?;
+                        self.sock.write_all(&self.outgoing[..len])?;
+                        Next::Done(data.len())
+                    }
+                    Goal::Close => {
+                        let len =
+                            encrypt_with(&mut self.outgoing, |buf| state.queue_close_notify(buf))?;
+                        self.sock.write_all(&self.outgoing[..len])?;
+                        Next::Done(0)
+                    }
+                },
+                ConnectionState::PeerClosed | ConnectionState::Closed => {
+                    self.peer_closed = true;
+                    match goal {
+                        Goal::Write(_) => return Err(io::Error::ConnectionReset),
+                        _ => Next::Done(0),
+                    }
+                }
+                _ => return Err(io::Error::Unsupported),
+            };
+
+            if discard != 0 {
+                self.incoming.copy_within(discard..self.incoming_used, 0);
+                self.incoming_used -= discard;
+            }
+            match next {
+                Next::Continue => {}
+                Next::ReadSocket => {
+                    if self.incoming_used == self.incoming.len() {
+                        self.incoming.resize(self.incoming.len() + TLS_BUF_LEN, 0);
+                    }
+                    let n = self.sock.read(&mut self.incoming[self.incoming_used..])?;
+                    if n == 0 {
+                        // closed without `close_notify`
+                        self.peer_closed = true;
+                        return match goal {
+                            Goal::Read => Ok(0),
+                            _ => Err(io::Error::UnexpectedEof),
+                        };
+                    }
+                    self.incoming_used += n;
+                }
+                Next::Done(n) => return Ok(n),
+            }
+        }
+    }
+}
+
+/// Encrypts into `outgoing` with `f`, growing it if it is too small.
+fn encrypt_with<F>(outgoing: &mut Vec<u8>, mut f: F) -> io::Result<usize>
+where
+    F: FnMut(&mut [u8]) -> Result<usize, EncryptError>,
+{
+    loop {
+        match f(outgoing) {
+            Ok(len) => return Ok(len),
+            Err(EncryptError::InsufficientSize(e)) => outgoing.resize(e.required_size, 0),
+            Err(_) => return Err(io::Error::BadState),
+        }
+    }
+}
+
+/// A TLS stream over a [`TcpStream`].
+///
+/// Created by [`TlsConnector::connect`] or [`TlsAcceptor::accept`].
+pub struct TlsStream {
+    conn: Connection,
+    io: TlsIo,
+}
+
+impl TlsStream {
+    fn handshake(conn: Connection, sock: TcpStream) -> io::Result<Self> {
+        let mut stream = Self {
+            conn,
+            io: TlsIo {
+                sock,
+                incoming: vec![0; TLS_BUF_LEN],
+                incoming_used: 0,
+                outgoing: vec![0; TLS_BUF_LEN],
+                outgoing_used: 0,
+                plaintext: VecDeque::new(),
+                peer_closed: false,
+            },
+        };
+        stream.process(Goal::Handshake)?;
+        Ok(stream)
+    }
+
+    fn process(&mut self, goal: Goal) -> io::Result<usize> {
+        match &mut self.conn {
+            Connection::Client(conn) => self.io.process(conn, goal),
+            Connection::Server(conn) => self.io.process(conn, goal),
+        }
+    }
+
+    /// Returns a reference to the underlying TCP stream.
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.io.sock
+    }
+
+    /// Sends a `close_notify` alert to the peer, then shuts down the
+    /// underlying TCP stream.
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        self.process(Goal::Close)?;
+        self.io.sock.shutdown()
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.io.plaintext.is_empty() && !self.io.peer_closed {
+            self.process(Goal::Read)?;
+        }
+        let len = buf.len().min(self.io.plaintext.len());
+        for (dst, src) in buf.iter_mut().zip(self.io.plaintext.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.process(Goal::Write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
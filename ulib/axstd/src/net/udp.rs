@@ -3,6 +3,8 @@ use crate::io;
 
 use arceos_api::net::{self as api, AxUdpSocketHandle};
 
+pub use arceos_api::net::AxUdpRecvBuf as UdpRecvBuf;
+
 /// A UDP socket.
 pub struct UdpSocket(AxUdpSocketHandle);
 
@@ -41,6 +43,16 @@ impl UdpSocket {
         api::ax_udp_recv_from(&self.0, buf)
     }
 
+    /// Receives a single datagram message on the socket without copying it.
+    /// On success, returns the datagram and the origin.
+    ///
+    /// Datagrams from the NIC are lent in place in its receive buffer, which
+    /// is given back when the returned [`UdpRecvBuf`] is dropped, so it should
+    /// not be held for long.
+    pub fn recv_buf_from(&self) -> io::Result<(UdpRecvBuf, SocketAddr)> {
+        api::ax_udp_recv_buf_from(&self.0)
+    }
+
     /// Receives a single datagram message on the socket, without removing it from
     /// the queue. On success, returns the number of bytes read and the origin.
     pub fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
@@ -93,4 +105,12 @@ impl UdpSocket {
     pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
         api::ax_udp_recv(&self.0, buf)
     }
+
+    /// Receives a single datagram message on the socket from the remote address
+    /// to which it is connected, without copying it.
+    ///
+    /// See [`recv_buf_from`](Self::recv_buf_from) for how the datagram is lent.
+    pub fn recv_buf(&self) -> io::Result<UdpRecvBuf> {
+        api::ax_udp_recv_buf(&self.0)
+    }
 }
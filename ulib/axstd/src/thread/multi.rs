@@ -49,6 +49,8 @@ pub struct Builder {
     name: Option<String>,
     // The size of the stack for the spawned thread in bytes
     stack_size: Option<usize>,
+    // The CPUs that the spawned thread can run on, bit `i` for CPU `i`
+    affinity: Option<usize>,
 }
 
 impl Builder {
@@ -58,6 +60,7 @@ impl Builder {
         Builder {
             name: None,
             stack_size: None,
+            affinity: None,
         }
     }
 
@@ -73,6 +76,16 @@ impl Builder {
         self
     }
 
+    /// Sets the CPUs that the new thread can run on, bit `i` of `cpumask`
+    /// stands for CPU `i`.
+    ///
+    /// Spawning fails with [`io::Error::InvalidInput`] if the mask
+    /// contains no CPU of the system.
+    pub fn affinity(mut self, cpumask: usize) -> Builder {
+        self.affinity = Some(cpumask);
+        self
+    }
+
     /// Spawns a new thread by taking ownership of the `Builder`, and returns an
     /// [`io::Result`] to its [`JoinHandle`].
     ///
@@ -115,7 +128,10 @@ impl Builder {
             drop(their_packet);
         };
 
-        let task = api::ax_spawn(main, name, stack_size);
+        let task = match self.affinity {
+            Some(cpumask) => api::ax_spawn_with_affinity(main, name, stack_size, cpumask)?,
+            None => api::ax_spawn(main, name, stack_size),
+        };
         Ok(JoinHandle {
             thread: Thread::from_id(task.id()),
             native: task,